@@ -0,0 +1,253 @@
+// Benchmarks del pipeline: rasterizador, vertex shader, fragment shaders
+// y un frame completo de la escena por defecto.
+//
+// cargo bench --bench pipeline
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use nalgebra_glm::{Vec2, Vec3, Mat4};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use shaders_lab::{
+    Uniforms,
+    ShadingMode,
+    ProjectionSettings,
+    LightningSettings,
+    create_noise,
+    create_model_matrix,
+    create_view_matrix,
+    create_perspective_matrix,
+    create_viewport_matrix,
+    render,
+    RenderContext,
+    default_system,
+};
+use shaders_lab::lighting::DEFAULT_AMBIENT;
+use shaders_lab::color::Color;
+use shaders_lab::fragment::Fragment;
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::obj::Obj;
+use shaders_lab::assets::Assets;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::shaders::{vertex_shader, fragment_shader};
+use shaders_lab::tonemap::ToneMapSettings;
+use shaders_lab::triangle::triangle;
+use shaders_lab::vertex::Vertex;
+
+const WIDTH: usize = 800;
+const HEIGHT: usize = 600;
+const FRAGMENT_BATCH: usize = 100_000;
+const BATCH_SEED: u64 = 1337;
+// Esfera UV de 224x224: 100352 triángulos
+const OBJ_SPHERE_SEGMENTS: usize = 224;
+
+fn default_uniforms() -> Uniforms {
+    Uniforms {
+        model_matrix: Mat4::identity(),
+        view_matrix: create_view_matrix(
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ),
+        projection_matrix: create_perspective_matrix(WIDTH as f32, HEIGHT as f32),
+        viewport_matrix: create_viewport_matrix(WIDTH as f32, HEIGHT as f32),
+        time: 0.0,
+        noise: create_noise(0),
+        seed: 0,
+        displacement: 0.0,
+        depth_bias: 0.0,
+        previous_mvp: None,
+        depth_write: true,
+        gas_giant: None,
+        terrestrial: None,
+        rings: None,
+        season: 0.0,
+        terrain_octaves: 1,
+        shading: ShadingMode::Smooth,
+        sun_position: Vec3::new(0.0, 0.0, 0.0),
+        lights: Vec::new(),
+        ambient: DEFAULT_AMBIENT,
+        fill_light: false,
+        camera_position: Vec3::new(0.0, 0.0, 5.0),
+        cloud_shadow_strength: 0.35,
+        noise_cache: None,
+        clip_plane: None,
+        projection: ProjectionSettings::default(),
+        lightning: LightningSettings::default(),
+    }
+}
+
+// Vértice ya en espacio de pantalla, como lo entrega el vertex shader
+fn screen_vertex(x: f32, y: f32) -> Vertex {
+    let mut vertex = Vertex::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec2::new(0.0, 0.0));
+    vertex.transformed_position = Vec3::new(x, y, 0.5);
+    vertex
+}
+
+fn load_sphere() -> Vec<Vertex> {
+    Obj::load(Assets::locate(None).path("models/esfera.obj"))
+        .unwrap_or_else(|error| panic!("{}", error))
+        .get_vertex_array()
+}
+
+// Lote determinista de fragmentos sobre la esfera unitaria
+fn fragment_batch() -> Vec<Fragment> {
+    let mut rng = StdRng::seed_from_u64(BATCH_SEED);
+    (0..FRAGMENT_BATCH).map(|_| {
+        let position = Vec3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        ).normalize();
+        Fragment::new(
+            Vec2::new(rng.gen_range(0.0..WIDTH as f32), rng.gen_range(0.0..HEIGHT as f32)),
+            Color::black(),
+            rng.gen_range(0.0..1.0),
+            position,
+            position,
+            position,
+            1.0,
+        )
+    }).collect()
+}
+
+// OBJ de una esfera UV con `v/vt/vn` por esquina, como los que exporta Blender
+fn sphere_obj(segments: usize) -> String {
+    let mut obj = String::new();
+    let grid = |f: &mut dyn FnMut(f32, f32)| {
+        for i in 0..=segments {
+            for j in 0..=segments {
+                f(i as f32 / segments as f32, j as f32 / segments as f32);
+            }
+        }
+    };
+    let direction = |v: f32, u: f32| {
+        let (theta, phi) = (v * std::f32::consts::PI, u * std::f32::consts::TAU);
+        Vec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin())
+    };
+    grid(&mut |v, u| {
+        let p = direction(v, u);
+        obj += &format!("v {:.6} {:.6} {:.6}\n", p.x, p.y, p.z);
+    });
+    grid(&mut |v, u| obj += &format!("vt {:.6} {:.6}\n", u, 1.0 - v));
+    grid(&mut |v, u| {
+        let n = direction(v, u);
+        obj += &format!("vn {:.4} {:.4} {:.4}\n", n.x, n.y, n.z);
+    });
+    for i in 0..segments {
+        for j in 0..segments {
+            let a = i * (segments + 1) + j + 1;
+            let (b, c, d) = (a + 1, a + segments + 1, a + segments + 2);
+            obj += &format!("f {a}/{a}/{a} {c}/{c}/{c} {b}/{b}/{b}\n");
+            obj += &format!("f {b}/{b}/{b} {c}/{c}/{c} {d}/{d}/{d}\n");
+        }
+    }
+    obj
+}
+
+fn bench_triangle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("triangle");
+    let sizes = [("small", 8.0), ("medium", 120.0), ("screen_filling", 1200.0)];
+
+    for (name, size) in sizes {
+        let v1 = screen_vertex(10.0, 10.0);
+        let v2 = screen_vertex(10.0 + size, 10.0);
+        let v3 = screen_vertex(10.0, 10.0 + size * 0.75);
+        let mut fragments = Vec::new();
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                fragments.clear();
+                triangle(black_box(&v1), black_box(&v2), black_box(&v3), None, WIDTH, HEIGHT, &mut fragments);
+                black_box(fragments.len())
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_vertex_shader(c: &mut Criterion) {
+    let vertices = load_sphere();
+    let uniforms = default_uniforms();
+
+    c.bench_function("vertex_shader/sphere", |b| {
+        b.iter(|| {
+            for vertex in &vertices {
+                black_box(vertex_shader(black_box(vertex), &uniforms));
+            }
+        })
+    });
+}
+
+fn bench_fragment_shaders(c: &mut Criterion) {
+    let fragments = fragment_batch();
+    let uniforms = default_uniforms();
+    let mut group = c.benchmark_group("fragment_shader");
+    group.sample_size(10);
+
+    for planet_type in PlanetType::ALL {
+        group.bench_function(BenchmarkId::from_parameter(format!("{:?}", planet_type)), |b| {
+            b.iter(|| {
+                for fragment in &fragments {
+                    black_box(fragment_shader(fragment, &uniforms, &planet_type));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+// Carga de un modelo grande con tobj y con el lector rápido de `Obj::load`
+fn bench_obj_load(c: &mut Criterion) {
+    let path = std::env::temp_dir().join("shaders_lab_bench_sphere.obj");
+    std::fs::write(&path, sphere_obj(OBJ_SPHERE_SEGMENTS)).unwrap();
+
+    let mut group = c.benchmark_group("obj_load");
+    group.sample_size(20);
+    group.bench_function("tobj", |b| {
+        b.iter(|| black_box(Obj::load_with_tobj(&path).unwrap()))
+    });
+    group.bench_function("fast", |b| {
+        b.iter(|| black_box(Obj::load(&path).unwrap()))
+    });
+    group.finish();
+
+    std::fs::remove_file(&path).ok();
+}
+
+fn bench_frame(c: &mut Criterion) {
+    let vertices = load_sphere();
+    let bodies = default_system();
+    let mut uniforms = default_uniforms();
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_background_color(0x333355);
+
+    // Cámara alejada para que todo el sistema quede en pantalla
+    uniforms.view_matrix = create_view_matrix(
+        Vec3::new(25.0, 20.0, 60.0),
+        Vec3::new(25.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+
+    let tone_mapping = ToneMapSettings::default();
+    let mut context = RenderContext::new();
+
+    let mut group = c.benchmark_group("render");
+    group.sample_size(10);
+    group.bench_function("default_scene", |b| {
+        b.iter(|| {
+            framebuffer.clear();
+            for (index, body) in bodies.iter().enumerate() {
+                uniforms.model_matrix = create_model_matrix(body.position, body.scale, body.rotation);
+                uniforms.displacement = body.displacement;
+                render(&mut framebuffer, &mut context, &uniforms, &vertices, &body.shader_type, &bodies, index);
+            }
+            framebuffer.resolve(&tone_mapping);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_triangle, bench_vertex_shader, bench_fragment_shaders, bench_obj_load, bench_frame);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+// Un mundo de lava púrpura definido con `PlanetType::Custom`, sin tocar los
+// shaders, dibujado sin ventana y guardado como PNG.
+//
+// cargo run --release --example custom_planet [salida.png]
+
+use std::path::Path;
+use nalgebra_glm::Vec3;
+
+use shaders_lab::{CelestialBody, create_uniforms};
+use shaders_lab::assets::Assets;
+use shaders_lab::camera::Camera;
+use shaders_lab::color::Color;
+use shaders_lab::custom_planet::{NoiseFractal, PlanetParams, PlanetStyle};
+use shaders_lab::golden::GoldenImage;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::scene::{Scene, SceneMeshes, SPHERE_MODEL};
+use shaders_lab::tonemap::ToneMapSettings;
+
+const WIDTH: usize = 640;
+const HEIGHT: usize = 480;
+
+fn main() {
+    let output = std::env::args().nth(1).unwrap_or_else(|| "custom_planet.png".to_string());
+
+    // Corteza violeta casi negra partida por grietas magenta
+    let purple_lava = PlanetParams {
+        primary: Color::new(30, 8, 45),
+        secondary: Color::new(85, 35, 110),
+        accent: Some(Color::new(255, 70, 230)),
+        noise_frequency: 180.0,
+        fractal: NoiseFractal::FBm,
+        style: PlanetStyle::Lava,
+    };
+    let bodies = vec![
+        CelestialBody {
+            scale: 2.0,
+            shader_type: PlanetType::Sun,
+            rotation_speed: 0.0,
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(12.0, 0.0, 0.0),
+            scale: 1.5,
+            shader_type: PlanetType::Custom(purple_lava),
+            rotation_speed: 0.0,
+            ..Default::default()
+        },
+    ];
+
+    let assets = Assets::locate(None);
+    let meshes = match SceneMeshes::load(&assets, &assets.path(SPHERE_MODEL), &bodies) {
+        Ok(meshes) => meshes,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    // De costado respecto al Sol: se ve el día y las grietas del lado nocturno
+    let camera = Camera::new(Vec3::new(12.0, 1.5, 6.0), Vec3::new(12.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    let mut scene = Scene::new(bodies, camera, create_uniforms(WIDTH, HEIGHT, 0), meshes);
+
+    let camera = scene.camera.clone();
+    let mut framebuffer = scene.render_still(WIDTH, HEIGHT, &camera, 0.0);
+    framebuffer.resolve(&ToneMapSettings::default());
+    match GoldenImage::save(&framebuffer, Path::new(&output)) {
+        Ok(()) => println!("Guardado en {}", output),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
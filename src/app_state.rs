@@ -0,0 +1,123 @@
+use std::fs;
+use nalgebra_glm::Vec4;
+use serde::{Deserialize, Serialize};
+use crate::{
+    CelestialBody,
+    Uniforms,
+    ShadingMode,
+    ProjectionSettings,
+    LightningSettings,
+    set_reversed_z,
+};
+use crate::camera::Camera;
+use crate::framebuffer::Framebuffer;
+use crate::noise_utils::{NoiseCache, NOISE_CACHE_EXTENT, NOISE_CACHE_CELLS};
+use crate::scene::Scene;
+use crate::tonemap::ToneMapSettings;
+use crate::error::Error;
+
+// Parámetros de los shaders que se ajustan con el teclado. Lo que sale de
+// ellos (ruido, caché, matriz de proyección) no se guarda: se reconstruye.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShaderParams {
+    pub seed: u64,
+    pub terrain_octaves: i32,
+    pub shading: ShadingMode,
+    pub cloud_shadow_strength: f32,
+    pub ambient: f32,
+    pub fill_light: bool,
+    pub noise_cache: bool,
+    pub clip_plane: Option<Vec4>,
+    pub projection: ProjectionSettings,
+    pub lightning: LightningSettings,
+}
+
+impl ShaderParams {
+    pub fn from_uniforms(uniforms: &Uniforms) -> Self {
+        ShaderParams {
+            seed: uniforms.seed,
+            terrain_octaves: uniforms.terrain_octaves,
+            shading: uniforms.shading,
+            cloud_shadow_strength: uniforms.cloud_shadow_strength,
+            ambient: uniforms.ambient,
+            fill_light: uniforms.fill_light,
+            noise_cache: uniforms.noise_cache.is_some(),
+            clip_plane: uniforms.clip_plane,
+            projection: uniforms.projection,
+            lightning: uniforms.lightning,
+        }
+    }
+}
+
+// Todo lo necesario para volver a donde se estaba (F5 guarda, F9 carga):
+// cámara, selección, tiempo de simulación, cuerpos con sus fases de órbita
+// y parámetros de los shaders y de la exposición
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppState {
+    pub camera: Camera,
+    pub selected_body: Option<usize>,
+    pub sim_time: f32,
+    pub bodies: Vec<CelestialBody>,
+    pub shader_params: ShaderParams,
+    pub tone_mapping: ToneMapSettings,
+}
+
+impl AppState {
+    pub fn capture(scene: &Scene, selected_body: Option<usize>, tone_mapping: &ToneMapSettings) -> Self {
+        AppState {
+            camera: scene.camera.clone(),
+            selected_body,
+            sim_time: scene.sim_time(),
+            bodies: scene.bodies.clone(),
+            shader_params: ShaderParams::from_uniforms(&scene.uniforms),
+            tone_mapping: *tone_mapping,
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|source| Error::State { path: path.into(), source })?;
+        fs::write(path, text).map_err(|source| Error::Write { path: path.into(), source })
+    }
+
+    // Lee un estado guardado para una escena de `body_count` cuerpos. Si se
+    // guardó con otro sistema (otra --seed u otra --scene) sus índices no
+    // corresponden a los cuerpos actuales y se rechaza.
+    pub fn load(path: &str, body_count: usize) -> Result<Self, Error> {
+        let text = fs::read_to_string(path).map_err(|source| Error::Read { path: path.into(), source })?;
+        let mut state: AppState = ron::from_str(&text)
+            .map_err(|error| Error::State { path: path.into(), source: error.into() })?;
+
+        if state.bodies.len() != body_count {
+            return Err(Error::StateMismatch { path: path.into(), saved: state.bodies.len(), current: body_count });
+        }
+        // Un archivo editado a mano puede apuntar fuera de la lista
+        state.selected_body = state.selected_body.filter(|&index| index < body_count);
+        Ok(state)
+    }
+
+    // Reemplaza cuerpos, cámara y parámetros de la escena y reconstruye lo
+    // derivado: posiciones y giros en `sim_time`, ruido de la semilla, caché
+    // y proyección. La selección y la exposición las aplica quien llama.
+    pub fn restore(self, scene: &mut Scene, framebuffer: &mut Framebuffer) {
+        let params = self.shader_params;
+        scene.camera = self.camera;
+        scene.bodies = self.bodies;
+        scene.set_sim_time(self.sim_time);
+
+        scene.set_seed(params.seed);
+        let uniforms = &mut scene.uniforms;
+        uniforms.terrain_octaves = params.terrain_octaves;
+        uniforms.shading = params.shading;
+        uniforms.cloud_shadow_strength = params.cloud_shadow_strength;
+        uniforms.ambient = params.ambient;
+        uniforms.fill_light = params.fill_light;
+        uniforms.clip_plane = params.clip_plane;
+        uniforms.lightning = params.lightning;
+        uniforms.noise_cache = params.noise_cache
+            .then(|| NoiseCache::new(&uniforms.noise, NOISE_CACHE_EXTENT, NOISE_CACHE_CELLS));
+        uniforms.projection = params.projection;
+        // También recalcula la matriz de proyección
+        set_reversed_z(framebuffer, uniforms, params.projection.reversed_z);
+    }
+}
@@ -0,0 +1,52 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+// Variable de entorno con la carpeta de assets; `--assets` tiene prioridad
+pub const ASSETS_ENV_VAR: &str = "SHADERS_LAB_ASSETS";
+const ASSETS_DIR_NAME: &str = "assets";
+
+// Carpeta de la que se leen modelos y escenas de ejemplo
+#[derive(Debug, Clone)]
+pub struct Assets {
+    root: PathBuf,
+}
+
+impl Assets {
+    // Un directorio explícito (argumento o variable de entorno) se usa tal cual.
+    // Si no, se busca `assets/` en el directorio actual, junto al ejecutable
+    // (y dos niveles arriba, para `target/release`) y en la raíz del crate.
+    // Si no aparece en ninguno se usa el del directorio actual, y el error
+    // de carga mostrará esa ruta.
+    pub fn locate(override_dir: Option<&str>) -> Self {
+        let explicit = override_dir
+            .map(PathBuf::from)
+            .or_else(|| env::var_os(ASSETS_ENV_VAR).map(PathBuf::from));
+        if let Some(root) = explicit {
+            return Assets { root };
+        }
+
+        let mut candidates = vec![PathBuf::from(ASSETS_DIR_NAME)];
+        if let Some(exe_dir) = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+            candidates.push(exe_dir.join(ASSETS_DIR_NAME));
+            if let Some(project_dir) = exe_dir.parent().and_then(Path::parent) {
+                candidates.push(project_dir.join(ASSETS_DIR_NAME));
+            }
+        }
+        candidates.push(Path::new(env!("CARGO_MANIFEST_DIR")).join(ASSETS_DIR_NAME));
+
+        let root = candidates.iter()
+            .find(|candidate| candidate.is_dir())
+            .unwrap_or(&candidates[0])
+            .clone();
+        Assets { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // Ruta de un archivo relativo a la carpeta de assets, p. ej. "models/esfera.obj"
+    pub fn path(&self, relative: &str) -> PathBuf {
+        self.root.join(relative)
+    }
+}
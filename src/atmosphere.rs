@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use crate::color::Color;
+use crate::planet_type::PlanetType;
+
+// Cáscara atmosférica de un cuerpo: una esfera algo más grande que la
+// superficie que se suma sobre lo ya dibujado (ver `render_atmosphere`). En
+// un archivo de escena los campos que falten toman el valor de la Tierra.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AtmosphereParams {
+    pub color: Color,
+    // Cuánto sobresale la cáscara, en radios del planeta (0.05 = un 5 % más grande)
+    pub thickness: f32,
+    // Opacidad del halo: 1 es la de la Tierra. Más densa brilla más y se
+    // adentra más en el disco; más tenue queda pegada al borde.
+    pub density: f32,
+}
+
+impl Default for AtmosphereParams {
+    fn default() -> Self {
+        AtmosphereParams::earth()
+    }
+}
+
+impl AtmosphereParams {
+    pub fn earth() -> Self {
+        AtmosphereParams { color: Color::new(110, 170, 255), thickness: 0.05, density: 1.0 }
+    }
+
+    // Espesa y blanco amarillenta: cubre buena parte del disco
+    pub fn venus() -> Self {
+        AtmosphereParams { color: Color::new(255, 240, 195), thickness: 0.05, density: 1.6 }
+    }
+
+    // Tenue y rosada por el polvo
+    pub fn mars() -> Self {
+        AtmosphereParams { color: Color::new(230, 160, 140), thickness: 0.03, density: 0.45 }
+    }
+
+    // Bruma suave del color de las nubes altas de cada gigante
+    pub fn haze(color: Color) -> Self {
+        AtmosphereParams { color, thickness: 0.04, density: 0.35 }
+    }
+
+    pub fn jupiter() -> Self {
+        AtmosphereParams::haze(Color::new(235, 205, 165))
+    }
+
+    pub fn saturn() -> Self {
+        AtmosphereParams::haze(Color::new(240, 220, 165))
+    }
+
+    pub fn uranus() -> Self {
+        AtmosphereParams::haze(Color::new(170, 235, 240))
+    }
+
+    pub fn neptune() -> Self {
+        AtmosphereParams::haze(Color::new(90, 140, 255))
+    }
+
+    // Atmósfera por defecto de cada tipo; None si no tiene
+    pub fn preset(planet_type: &PlanetType) -> Option<Self> {
+        match planet_type {
+            PlanetType::Earth | PlanetType::Terrestrial => Some(AtmosphereParams::earth()),
+            PlanetType::Venus => Some(AtmosphereParams::venus()),
+            PlanetType::Mars => Some(AtmosphereParams::mars()),
+            PlanetType::Jupiter | PlanetType::GasGiant => Some(AtmosphereParams::jupiter()),
+            PlanetType::Saturn => Some(AtmosphereParams::saturn()),
+            PlanetType::Uranus => Some(AtmosphereParams::uranus()),
+            PlanetType::Neptune => Some(AtmosphereParams::neptune()),
+            _ => None,
+        }
+    }
+
+    // Radio de la cáscara respecto al del planeta
+    pub fn shell_scale(&self) -> f32 {
+        1.0 + self.thickness.max(0.0)
+    }
+}
@@ -0,0 +1,233 @@
+use std::f32::consts::TAU;
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::Vec3;
+use once_cell::sync::Lazy;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use crate::camera::Camera;
+use crate::framebuffer::Framebuffer;
+use crate::noise_utils::{fbm, hash_unit};
+use crate::ProjectionSettings;
+use crate::viewport::Viewport;
+
+// Capas de estrellas: cantidad, brillo HDR y si se dibujan en cruz. Las
+// lejanas son muchas y tenues; las cercanas pocas y brillantes.
+const STAR_LAYERS: [(usize, f32, bool); 3] = [
+    (6000, 0.18, false),
+    (1500, 0.45, false),
+    (250, 1.0, true),
+];
+const STAR_SEED: u64 = 0x5354_4152;
+// Brillo de los brazos de la cruz respecto al centro
+const STAR_CROSS: f32 = 0.35;
+// Cuánto baja el brillo en el punto más oscuro del titileo y su velocidad en rad/s
+const TWINKLE_DEPTH: f32 = 0.45;
+const TWINKLE_SPEED: f32 = 3.0;
+
+// La nebulosa se evalúa en una rejilla de 1/NEBULA_STEP de resolución y se
+// interpola al resto de píxeles
+const NEBULA_STEP: usize = 4;
+const NEBULA_SCALE: f32 = 200.0;
+const NEBULA_OCTAVES: i32 = 3;
+const NEBULA_INTENSITY: f32 = 0.2;
+const NEBULA_PURPLE: Vec3 = Vec3::new(0.35, 0.1, 0.5);
+const NEBULA_TEAL: Vec3 = Vec3::new(0.05, 0.4, 0.45);
+
+struct Star {
+    direction: Vec3,
+    color: Vec3,
+    cross: bool,
+    id: u32,
+}
+
+// Direcciones fijas en el cielo: no dependen de la escena ni de --seed
+static STARS: Lazy<Vec<Star>> = Lazy::new(|| {
+    let mut rng = StdRng::seed_from_u64(STAR_SEED);
+    let warm = Vec3::new(1.0, 0.85, 0.7);
+    let cool = Vec3::new(0.75, 0.85, 1.0);
+    let mut stars = Vec::new();
+    for (count, brightness, cross) in STAR_LAYERS {
+        for _ in 0..count {
+            let z: f32 = rng.gen_range(-1.0..1.0);
+            let angle: f32 = rng.gen_range(0.0..TAU);
+            let ring = (1.0 - z * z).sqrt();
+            let tint = warm.lerp(&cool, rng.gen::<f32>());
+            stars.push(Star {
+                direction: Vec3::new(ring * angle.cos(), ring * angle.sin(), z),
+                color: tint * brightness * rng.gen_range(0.5..1.0),
+                cross,
+                id: stars.len() as u32,
+            });
+        }
+    }
+    stars
+});
+
+// Vista con la que se calculó la rejilla de la nebulosa
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct NebulaView {
+    forward: Vec3,
+    right: Vec3,
+    up: Vec3,
+    width: usize,
+    height: usize,
+    background: Vec3,
+}
+
+// Fondo a distancia infinita: nebulosa y capas de estrellas que giran con la
+// cámara pero no se desplazan al moverla. Guarda la rejilla de la nebulosa
+// para no recalcularla mientras la vista no gire.
+#[derive(Default)]
+pub struct Background {
+    nebula: Vec<Vec3>,
+    view: Option<NebulaView>,
+}
+
+impl Background {
+    // Va justo después de `clear`: reemplaza todo el buffer HDR sin tocar la
+    // profundidad. `time` en segundos, para el titileo.
+    pub fn render(
+        &mut self,
+        framebuffer: &mut Framebuffer,
+        camera: &Camera,
+        projection: &ProjectionSettings,
+        noise: &FastNoiseLite,
+        time: f32,
+    ) {
+        let viewport = Viewport::full(framebuffer.width, framebuffer.height);
+        self.render_viewport(framebuffer, &viewport, camera, projection, noise, time);
+    }
+
+    // Como `render`, pero sólo dentro de `viewport` y con su aspecto. Va
+    // después de `clear_region`. Cada vista necesita su propio `Background`,
+    // o la rejilla de la nebulosa se recalcularía en cada una.
+    pub fn render_viewport(
+        &mut self,
+        framebuffer: &mut Framebuffer,
+        viewport: &Viewport,
+        camera: &Camera,
+        projection: &ProjectionSettings,
+        noise: &FastNoiseLite,
+        time: f32,
+    ) {
+        let (width, height) = (viewport.width, viewport.height);
+        if width == 0 || height == 0 || viewport.x + width > framebuffer.width || viewport.y + height > framebuffer.height {
+            return;
+        }
+
+        let (forward, right, up) = view_basis(camera, projection, width, height);
+        let view = NebulaView {
+            forward,
+            right,
+            up,
+            width,
+            height,
+            background: framebuffer.background_linear(),
+        };
+
+        if self.view != Some(view) {
+            self.update_nebula(&view, noise);
+            self.view = Some(view);
+        }
+        self.upsample_nebula(framebuffer, viewport);
+        draw_stars(framebuffer, viewport, &view, time);
+        framebuffer.mark_background_drawn();
+    }
+
+    // FBm sobre la dirección de vista con una rampa púrpura a verde azulado,
+    // un punto cada NEBULA_STEP píxeles y uno más para cerrar el borde
+    fn update_nebula(&mut self, view: &NebulaView, noise: &FastNoiseLite) {
+        let grid_width = view.width / NEBULA_STEP + 2;
+        let grid_height = view.height / NEBULA_STEP + 2;
+        self.nebula.clear();
+        for row in 0..grid_height {
+            let ndc_y = 1.0 - ((row * NEBULA_STEP) as f32 + 0.5) / view.height as f32 * 2.0;
+            for column in 0..grid_width {
+                let ndc_x = ((column * NEBULA_STEP) as f32 + 0.5) / view.width as f32 * 2.0 - 1.0;
+                let direction = (view.forward + view.right * ndc_x + view.up * ndc_y).normalize();
+                let t = fbm(noise, &(direction * NEBULA_SCALE), NEBULA_OCTAVES, 2.0, 0.5) * 0.5 + 0.5;
+                let density = smoothstep(0.45, 0.85, t);
+                let hue = smoothstep(0.55, 0.8, t);
+                self.nebula.push(view.background + NEBULA_PURPLE.lerp(&NEBULA_TEAL, hue) * (density * NEBULA_INTENSITY));
+            }
+        }
+    }
+
+    // Interpolación bilineal de la rejilla a todos los píxeles del rectángulo
+    fn upsample_nebula(&self, framebuffer: &mut Framebuffer, viewport: &Viewport) {
+        let grid_width = viewport.width / NEBULA_STEP + 2;
+        let step = 1.0 / NEBULA_STEP as f32;
+        let rows = framebuffer.hdr_buffer.chunks_exact_mut(framebuffer.width).skip(viewport.y).take(viewport.height);
+        for (y, row_pixels) in rows.enumerate() {
+            let pixels = &mut row_pixels[viewport.x..viewport.x + viewport.width];
+            let row = y / NEBULA_STEP;
+            let ty = (y % NEBULA_STEP) as f32 * step;
+            let top = &self.nebula[row * grid_width..];
+            let bottom = &self.nebula[(row + 1) * grid_width..];
+            for (x, pixel) in pixels.iter_mut().enumerate() {
+                let column = x / NEBULA_STEP;
+                let tx = (x % NEBULA_STEP) as f32 * step;
+                let upper = top[column].lerp(&top[column + 1], tx);
+                let lower = bottom[column].lerp(&bottom[column + 1], tx);
+                *pixel = upper.lerp(&lower, ty);
+            }
+        }
+    }
+}
+
+// Base de la vista sin traslación para una imagen de `width` x `height`:
+// sólo importa hacia dónde se mira. `right` y `up` van escalados por la
+// apertura del campo de visión, así la dirección del píxel en NDC (x, y) es
+// `forward + right * x + up * y`.
+pub(crate) fn view_basis(camera: &Camera, projection: &ProjectionSettings, width: usize, height: usize) -> (Vec3, Vec3, Vec3) {
+    let forward = (camera.center - camera.eye).normalize();
+    let side = forward.cross(&camera.up).normalize();
+    let tan_y = (projection.fov_degrees.to_radians() / 2.0).tan();
+    let tan_x = tan_y * width as f32 / height as f32;
+    (forward, side * tan_x, side.cross(&forward) * tan_y)
+}
+
+// Las estrellas se suman sobre la nebulosa; las de la capa cercana en cruz.
+// Los brazos que caen fuera del rectángulo no se dibujan.
+fn draw_stars(framebuffer: &mut Framebuffer, viewport: &Viewport, view: &NebulaView, time: f32) {
+    let (width, height) = (viewport.width, viewport.height);
+    let right = view.right / view.right.norm_squared();
+    let up = view.up / view.up.norm_squared();
+
+    for star in STARS.iter() {
+        let depth = star.direction.dot(&view.forward);
+        if depth <= f32::EPSILON {
+            continue;
+        }
+        let ndc_x = star.direction.dot(&right) / depth;
+        let ndc_y = star.direction.dot(&up) / depth;
+        let x = ((ndc_x + 1.0) * 0.5 * width as f32).floor();
+        let y = ((1.0 - ndc_y) * 0.5 * height as f32).floor();
+        if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+            continue;
+        }
+
+        let phase = hash_unit(star.id, 0) * TAU;
+        let speed = TWINKLE_SPEED * (0.5 + hash_unit(star.id, 1));
+        let twinkle = 1.0 - TWINKLE_DEPTH * (0.5 + 0.5 * (time * speed + phase).sin());
+        let color = star.color * twinkle;
+
+        let (x, y) = (x as usize, y as usize);
+        let index = |x: usize, y: usize| (viewport.y + y) * framebuffer.width + viewport.x + x;
+        framebuffer.hdr_buffer[index(x, y)] += color;
+        if star.cross {
+            let arms = [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)];
+            for (arm_x, arm_y) in arms {
+                if arm_x < width && arm_y < height {
+                    framebuffer.hdr_buffer[index(arm_x, arm_y)] += color * STAR_CROSS;
+                }
+            }
+        }
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
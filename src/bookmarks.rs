@@ -0,0 +1,128 @@
+use std::fs;
+use nalgebra_glm::Vec3;
+use serde::{Deserialize, Serialize};
+use crate::camera::CameraState;
+use crate::simulation::UPDATES_PER_SECOND;
+use crate::vertex::any_perpendicular;
+use crate::error::Error;
+
+pub const BOOKMARK_SLOTS: usize = 5;
+
+// La transición dura ~20 frames de 1/60 s, sin importar los FPS reales
+const TRANSITION_FRAMES: f32 = 20.0;
+// Si las distancias al centro difieren menos que esta proporción, el ojo
+// rodea el centro en lugar de ir en línea recta (y atravesar planetas)
+const SPHERICAL_DISTANCE_RATIO: f32 = 1.5;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CameraBookmarks {
+    slots: [Option<CameraState>; BOOKMARK_SLOTS],
+}
+
+impl CameraBookmarks {
+    // Un archivo inexistente o inválido deja todas las ranuras vacías
+    pub fn load(path: &str) -> Self {
+        let Ok(text) = fs::read_to_string(path) else {
+            return CameraBookmarks::default();
+        };
+        ron::from_str(&text).unwrap_or_else(|error| {
+            eprintln!("Marcadores de cámara inválidos en {}: {}", path, error);
+            CameraBookmarks::default()
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|source| Error::Bookmarks { path: path.into(), source })?;
+        fs::write(path, text).map_err(|source| Error::Write { path: path.into(), source })
+    }
+
+    pub fn store(&mut self, slot: usize, state: CameraState) {
+        if let Some(entry) = self.slots.get_mut(slot) {
+            *entry = Some(state);
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<CameraState> {
+        self.slots.get(slot).copied().flatten()
+    }
+}
+
+// Interpolación suave entre dos estados de cámara
+pub struct CameraTransition {
+    from: CameraState,
+    to: CameraState,
+    elapsed: f32,
+    duration: f32,
+}
+
+impl CameraTransition {
+    pub fn new(from: CameraState, to: CameraState) -> Self {
+        CameraTransition {
+            from,
+            to,
+            elapsed: 0.0,
+            duration: TRANSITION_FRAMES / UPDATES_PER_SECOND,
+        }
+    }
+
+    // Avanza `dt` segundos y devuelve el estado que debe tener la cámara
+    pub fn update(&mut self, dt: f32) -> CameraState {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+        let t = self.elapsed / self.duration;
+        // Arranca y frena suavemente
+        let eased = t * t * (3.0 - 2.0 * t);
+        interpolate_state(&self.from, &self.to, eased)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+// Mezcla dos estados; si las distancias al centro se parecen, el ojo rodea el
+// centro en lugar de ir en línea recta. También la usa el recorrido automático.
+pub(crate) fn interpolate_state(from: &CameraState, to: &CameraState, t: f32) -> CameraState {
+    let center = from.center.lerp(&to.center, t);
+
+    let from_offset = from.eye - from.center;
+    let to_offset = to.eye - to.center;
+    let from_distance = from_offset.norm();
+    let to_distance = to_offset.norm();
+
+    let similar_distance = from_distance > f32::EPSILON
+        && to_distance > f32::EPSILON
+        && from_distance.max(to_distance) / from_distance.min(to_distance) < SPHERICAL_DISTANCE_RATIO;
+
+    let eye = if similar_distance {
+        let direction = slerp(&(from_offset / from_distance), &(to_offset / to_distance), t);
+        center + direction * (from_distance + (to_distance - from_distance) * t)
+    } else {
+        from.eye.lerp(&to.eye, t)
+    };
+
+    let up = from.up.lerp(&to.up, t);
+    let up = if up.norm() > f32::EPSILON { up.normalize() } else { to.up };
+
+    CameraState { eye, center, up }
+}
+
+// Interpolación esférica entre dos direcciones unitarias
+fn slerp(a: &Vec3, b: &Vec3, t: f32) -> Vec3 {
+    let cos_angle = a.dot(b).clamp(-1.0, 1.0);
+    let angle = cos_angle.acos();
+
+    if angle < 1e-4 {
+        return a.lerp(b, t).normalize();
+    }
+
+    if std::f32::consts::PI - angle < 1e-3 {
+        // Direcciones opuestas: cualquier eje perpendicular sirve para girar
+        let axis = any_perpendicular(a);
+        let rotated = angle * t;
+        return a * rotated.cos() + axis.cross(a) * rotated.sin();
+    }
+
+    let sin_angle = angle.sin();
+    (a * ((1.0 - t) * angle).sin() + b * (t * angle).sin()) / sin_angle
+}
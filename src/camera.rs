@@ -1,10 +1,26 @@
 use nalgebra_glm::Vec3;
+use serde::{Deserialize, Serialize};
 use std::f32::consts::PI;
 
+// How quickly the camera catches up with its target (higher is snappier)
+const DEFAULT_DAMPING: f32 = 10.0;
+
+// Snapshot of where the camera is and what it looks at
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraState {
+  pub eye: Vec3,
+  pub center: Vec3,
+  pub up: Vec3,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Camera {
   pub eye: Vec3,
   pub center: Vec3,
   pub up: Vec3,
+  pub damping: f32,
+  target_eye: Vec3,
+  target_center: Vec3,
   pitch: f32,
   yaw: f32,
 }
@@ -15,13 +31,64 @@ impl Camera {
       eye,
       center,
       up,
+      damping: DEFAULT_DAMPING,
+      target_eye: eye,
+      target_center: center,
       pitch: 0.0,
       yaw: 0.0,
     }
   }
 
+  // Moves the actual eye/center toward the targets. Exponential smoothing
+  // never overshoots and converges at the same rate for any frame rate.
+  pub fn update(&mut self, dt: f32) {
+    let t = 1.0 - (-self.damping * dt).exp();
+    self.eye += (self.target_eye - self.eye) * t;
+    self.center += (self.target_center - self.center) * t;
+  }
+
+  // Keeps the eye where it is and turns the camera toward `target`
+  pub fn focus_on(&mut self, target: Vec3) {
+    self.target_center = target;
+  }
+
+  // Sets where the camera should end up; `update` eases toward it, so a
+  // target that moves every frame (a scripted path) is smoothed too
+  pub fn look_at(&mut self, eye: Vec3, center: Vec3) {
+    self.target_eye = eye;
+    self.target_center = center;
+    self.sync_angles(center - eye);
+  }
+
+  pub fn save_state(&self) -> CameraState {
+    CameraState {
+      eye: self.eye,
+      center: self.center,
+      up: self.up,
+    }
+  }
+
+  // Jumps straight to `state`, skipping the smoothing in `update`
+  pub fn restore_state(&mut self, state: &CameraState) {
+    self.eye = state.eye;
+    self.center = state.center;
+    self.up = state.up;
+    self.target_eye = state.eye;
+    self.target_center = state.center;
+    self.sync_angles(state.center - state.eye);
+  }
+
+  // Keep pitch/yaw in sync so the next rotate_pitch starts from this view
+  fn sync_angles(&mut self, forward: Vec3) {
+    if forward.norm() > f32::EPSILON {
+      let forward = forward.normalize();
+      self.pitch = forward.y.clamp(-1.0, 1.0).asin();
+      self.yaw = forward.z.atan2(forward.x);
+    }
+  }
+
   pub fn get_forward(&self) -> Vec3 {
-    (self.center - self.eye).normalize()
+    (self.target_center - self.target_eye).normalize()
   }
 
   pub fn get_right(&self) -> Vec3 {
@@ -30,19 +97,19 @@ impl Camera {
 
   pub fn move_forward(&mut self, amount: f32) {
     let forward = self.get_forward();
-    self.eye += forward * amount;
-    self.center += forward * amount;
+    self.target_eye += forward * amount;
+    self.target_center += forward * amount;
   }
 
   pub fn move_right(&mut self, amount: f32) {
     let right = self.get_right();
-    self.eye += right * amount;
-    self.center += right * amount;
+    self.target_eye += right * amount;
+    self.target_center += right * amount;
   }
 
   pub fn move_up(&mut self, amount: f32) {
-    self.eye += self.up * amount;
-    self.center += self.up * amount;
+    self.target_eye += self.up * amount;
+    self.target_center += self.up * amount;
   }
 
   pub fn rotate_pitch(&mut self, angle: f32) {
@@ -57,12 +124,12 @@ impl Camera {
       self.pitch.sin(),
       self.yaw.sin() * self.pitch.cos()
     ).normalize();
-    
-    self.center = self.eye + forward;
+
+    self.target_center = self.target_eye + forward;
   }
 
   pub fn zoom(&mut self, amount: f32) {
     let forward = self.get_forward();
-    self.eye += forward * amount;
+    self.target_eye += forward * amount;
   }
 }
@@ -0,0 +1,122 @@
+use std::f32::consts::PI;
+use nalgebra_glm::Vec3;
+use crate::camera::{Camera, CameraState};
+use crate::simulation::interpolated_state;
+use crate::CelestialBody;
+
+// Segundos que tarda la cámara en alcanzar al cuerpo, aproximadamente
+const DEFAULT_TIME_CONSTANT: f32 = 0.6;
+// Posición preferida: detrás del cuerpo respecto a su movimiento y algo por
+// encima, a esta distancia en radios del cuerpo
+const DEFAULT_DISTANCE: f32 = 5.0;
+const DEFAULT_PITCH: f32 = 0.35;
+const MIN_DISTANCE: f32 = 1.5;
+const MAX_DISTANCE: f32 = 200.0;
+const MAX_PITCH: f32 = PI / 2.0 - 0.1;
+
+// Cámara de persecución: el centro y el ojo siguen al cuerpo con un resorte
+// críticamente amortiguado, así las órbitas no sacuden el fondo. El ojo se
+// coloca en el marco orbital del cuerpo (detrás y arriba según su velocidad)
+// y el giro y zoom manuales se guardan como parte de ese desplazamiento.
+pub struct ChaseCamera {
+    pub enabled: bool,
+    pub time_constant: f32,
+    // Desplazamiento del ojo en el marco orbital: distancia en radios del
+    // cuerpo, giro alrededor de él y elevación
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+    // Estado de los resortes; `None` mientras no se sigue a nadie
+    smoothed: Option<Smoothed>,
+    forward: Vec3,
+}
+
+struct Smoothed {
+    eye: Vec3,
+    eye_velocity: Vec3,
+    center: Vec3,
+    center_velocity: Vec3,
+}
+
+impl Default for ChaseCamera {
+    fn default() -> Self {
+        ChaseCamera {
+            enabled: false,
+            time_constant: DEFAULT_TIME_CONSTANT,
+            distance: DEFAULT_DISTANCE,
+            yaw: 0.0,
+            pitch: DEFAULT_PITCH,
+            smoothed: None,
+            forward: Vec3::new(0.0, 0.0, -1.0),
+        }
+    }
+}
+
+impl ChaseCamera {
+    // Gira el desplazamiento alrededor del cuerpo seguido
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = (self.yaw + yaw).rem_euclid(2.0 * PI);
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    // `factor` < 1 acerca, > 1 aleja
+    pub fn zoom(&mut self, factor: f32) {
+        self.distance = (self.distance * factor).clamp(MIN_DISTANCE, MAX_DISTANCE);
+    }
+
+    // Deja de seguir; la cámara se queda donde está y la próxima
+    // persecución arranca desde ahí sin saltos
+    pub fn release(&mut self) {
+        self.smoothed = None;
+    }
+
+    // Acerca la cámara un paso de `dt` segundos hacia su sitio junto a
+    // `body`. Cambiar de cuerpo no reinicia los resortes: la cámara viaja
+    // suavemente del anterior al nuevo.
+    pub fn update(&mut self, camera: &mut Camera, body: &CelestialBody, alpha: f32, dt: f32) {
+        let (position, _) = interpolated_state(body, alpha);
+
+        // Marco orbital: hacia donde se mueve el cuerpo y la vertical del mundo.
+        // Quieto (el Sol) conserva la última dirección.
+        let velocity = body.position - body.previous_position;
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let horizontal = velocity - up * velocity.dot(&up);
+        if horizontal.norm() > f32::EPSILON {
+            self.forward = horizontal.normalize();
+        }
+        let side = self.forward.cross(&up);
+
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let direction = -self.forward * cos_pitch * cos_yaw + side * cos_pitch * sin_yaw + up * sin_pitch;
+        let target_eye = position + direction * self.distance * body.scale;
+
+        let smoothed = self.smoothed.get_or_insert(Smoothed {
+            eye: camera.eye,
+            eye_velocity: Vec3::zeros(),
+            center: camera.center,
+            center_velocity: Vec3::zeros(),
+        });
+        smooth_damp(&mut smoothed.center, &mut smoothed.center_velocity, position, self.time_constant, dt);
+        smooth_damp(&mut smoothed.eye, &mut smoothed.eye_velocity, target_eye, self.time_constant, dt);
+
+        camera.restore_state(&CameraState {
+            eye: smoothed.eye,
+            center: smoothed.center,
+            up: camera.up,
+        });
+    }
+}
+
+// Resorte críticamente amortiguado (aproximación de "Game Programming Gems 4"):
+// llega a `target` sin pasarse en unos `time_constant` segundos, es estable
+// con cualquier `dt` y persigue bien a un objetivo que se mueve
+fn smooth_damp(value: &mut Vec3, velocity: &mut Vec3, target: Vec3, time_constant: f32, dt: f32) {
+    let omega = 2.0 / time_constant.max(1e-3);
+    let x = omega * dt;
+    let decay = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = *value - target;
+    let temp = (*velocity + change * omega) * dt;
+    *velocity = (*velocity - temp * omega) * decay;
+    *value = target + (change + temp) * decay;
+}
@@ -0,0 +1,168 @@
+use clap::error::ErrorKind;
+use clap::{ArgGroup, CommandFactory, Parser};
+use std::path::PathBuf;
+use shaders_lab::assets::Assets;
+use shaders_lab::mesh::{SphereMesh, DEFAULT_ICOSPHERE_SUBDIVISIONS, MAX_ICOSPHERE_SUBDIVISIONS};
+use shaders_lab::progressive::{DEFAULT_PROGRESSIVE_PASSES, MAX_PROGRESSIVE_PASSES};
+use shaders_lab::scene::SPHERE_MODEL;
+use shaders_lab::turntable::DEFAULT_TURNTABLE_FRAMES;
+
+// Resoluciones internas de renderizado admitidas por --resolution
+pub const RESOLUTION_PRESETS: [(usize, usize); 4] = [
+    (640, 480),
+    (800, 600),
+    (1280, 720),
+    (1920, 1080),
+];
+
+// Sin argumentos todo queda como siempre: sistema por defecto en una
+// ventana de 800x600 limitada a 60 FPS
+#[derive(Debug, Parser)]
+#[command(name = "ShadersLab", about = "Sistema solar con un rasterizador por software")]
+#[command(group(ArgGroup::new("frame_mode").args(["headless", "turntable"])))]
+pub struct Args {
+    /// Genera un sistema con esta semilla, que también siembra los ruidos de los shaders
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Planetas del sistema generado con --seed
+    #[arg(long = "planets", default_value_t = 8)]
+    pub planet_count: usize,
+    /// Resolución interna fija (640x480, 800x600, 1280x720 o 1920x1080); minifb la escala a la ventana
+    #[arg(long, value_parser = parse_resolution)]
+    pub resolution: Option<(usize, usize)>,
+    /// Ancho inicial de la ventana
+    #[arg(long, default_value_t = 800)]
+    pub width: usize,
+    /// Alto inicial de la ventana
+    #[arg(long, default_value_t = 600)]
+    pub height: usize,
+    /// Modelo OBJ de la esfera; sin él se usa el de la carpeta de assets
+    #[arg(long)]
+    pub model: Option<String>,
+    /// Esfera de los cuerpos sin malla propia: obj (el modelo de assets o --model), uv o ico
+    #[arg(long, value_parser = ["obj", "uv", "ico"], default_value = "obj")]
+    pub sphere: String,
+    /// Subdivisiones de la icosfera de --sphere ico
+    #[arg(long, default_value_t = DEFAULT_ICOSPHERE_SUBDIVISIONS,
+        value_parser = clap::value_parser!(u32).range(0..=MAX_ICOSPHERE_SUBDIVISIONS as i64))]
+    pub subdivisions: u32,
+    /// Escena exportada con P para cargar al inicio
+    #[arg(long)]
+    pub scene: Option<String>,
+    /// Arranca con la caché de ruido activada (N la alterna en ejecución)
+    #[arg(long)]
+    pub noise_cache: bool,
+    /// Baja la resolución interna cuando los FPS caen
+    #[arg(long = "adaptive")]
+    pub adaptive_quality: bool,
+    /// Dibuja sin abrir ventana (requiere --frames)
+    #[arg(long, requires = "frames")]
+    pub headless: bool,
+    /// Frames que dibuja --headless antes de imprimir estadísticas y salir, o los de la vuelta de --turntable (120 por defecto)
+    #[arg(long, requires = "frame_mode")]
+    pub frames: Option<usize>,
+    /// Atajo de --headless --frames N
+    #[arg(long, value_name = "N", conflicts_with_all = ["headless", "turntable"])]
+    pub bench_frames: Option<usize>,
+    /// Guarda una vuelta de la cámara alrededor de este cuerpo (nombre o índice) como PNG numerados, sin ventana
+    #[arg(long, value_name = "CUERPO")]
+    pub turntable: Option<String>,
+    /// Carpeta de los frame_NNNN.png de --turntable
+    #[arg(long, value_name = "DIR", default_value = "turntable", requires = "turntable")]
+    pub out: PathBuf,
+    /// Con --turntable oculta los demás cuerpos; las estrellas siguen iluminando
+    #[arg(long, requires = "turntable")]
+    pub isolate: bool,
+    /// Carpeta de assets; sin ella se busca automáticamente
+    #[arg(long)]
+    pub assets: Option<String>,
+    /// Z-buffer invertido (near -> 1, far -> 0) para más precisión a lo lejos
+    #[arg(long)]
+    pub reversed_z: bool,
+    /// Pasa cada fragmento por el shader, sin la rejilla gruesa de los cuerpos grandes
+    #[arg(long)]
+    pub full_shading: bool,
+    /// Límite de frames por segundo de la ventana; 0 lo desactiva
+    #[arg(long, visible_alias = "fps-cap", default_value_t = 60)]
+    pub fps: usize,
+    /// Dibuja cada imagen en pasadas de filas intercaladas (4 si no se indica), de lo grueso a lo fino; mover la cámara vuelve a empezar
+    #[arg(long, value_name = "PASADAS", num_args = 0..=1, value_parser = parse_passes)]
+    pub progressive: Option<Option<usize>>,
+    /// Imagen equirectangular del cielo (p. ej. una nebulosa) que reemplaza el fondo de estrellas
+    #[arg(long, value_name = "RUTA")]
+    pub skybox: Option<PathBuf>,
+    /// Tamaño de la imagen que guarda F12, como ANCHOxALTO; no depende de la ventana
+    #[arg(long, value_name = "ANCHOxALTO", value_parser = parse_size, default_value = "3840x2160")]
+    pub still_size: (usize, usize),
+}
+
+impl Args {
+    // Frames a dibujar sin ventana, con --headless --frames o --bench-frames
+    pub fn headless_frames(&self) -> Option<usize> {
+        self.frames.filter(|_| self.headless).or(self.bench_frames)
+    }
+
+    // Frames de la vuelta de --turntable
+    pub fn turntable_frames(&self) -> usize {
+        self.frames.unwrap_or(DEFAULT_TURNTABLE_FRAMES)
+    }
+
+    // Pasadas de --progressive, si se pidió
+    pub fn progressive_passes(&self) -> Option<usize> {
+        self.progressive.map(|passes| passes.unwrap_or(DEFAULT_PROGRESSIVE_PASSES))
+    }
+
+    // Esfera elegida con --sphere; la de assets si no hay --model
+    pub fn sphere_mesh(&self, assets: &Assets) -> SphereMesh {
+        match self.sphere.as_str() {
+            "uv" => SphereMesh::UvSphere,
+            "ico" => SphereMesh::Icosphere(self.subdivisions),
+            _ => SphereMesh::Obj(self.model.as_ref().map(PathBuf::from).unwrap_or_else(|| assets.path(SPHERE_MODEL))),
+        }
+    }
+}
+
+// Los errores de clap ya explican el problema y salen con código 2
+pub fn parse_args() -> Args {
+    let args = Args::parse();
+    if args.width == 0 || args.height == 0 {
+        Args::command()
+            .error(ErrorKind::InvalidValue, "--width y --height tienen que ser mayores que 0")
+            .exit();
+    }
+    if args.model.is_some() && args.sphere != "obj" {
+        Args::command()
+            .error(ErrorKind::ArgumentConflict, "--model sólo se usa con --sphere obj")
+            .exit();
+    }
+    args
+}
+
+fn parse_resolution(value: &str) -> Result<(usize, usize), String> {
+    let parsed = value.split_once('x')
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+
+    match parsed {
+        Some(resolution) if RESOLUTION_PRESETS.contains(&resolution) => Ok(resolution),
+        _ => {
+            let presets: Vec<String> = RESOLUTION_PRESETS.iter()
+                .map(|(w, h)| format!("{}x{}", w, h))
+                .collect();
+            Err(format!("resolución inválida (opciones: {})", presets.join(", ")))
+        }
+    }
+}
+
+fn parse_size(value: &str) -> Result<(usize, usize), String> {
+    match value.split_once('x').and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?))) {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err("tamaño inválido (por ejemplo 3840x2160)".to_string()),
+    }
+}
+
+fn parse_passes(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(passes) if (1..=MAX_PROGRESSIVE_PASSES).contains(&passes) => Ok(passes),
+        _ => Err(format!("número de pasadas inválido (de 1 a {})", MAX_PROGRESSIVE_PASSES)),
+    }
+}
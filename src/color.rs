@@ -1,6 +1,7 @@
 use std::fmt;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Color {
   pub r: u8,
   pub g: u8,
@@ -18,15 +19,55 @@ impl Color {
     Color { r: 0, g: 0, b: 0 }
   }
 
+  // Relative luminance with Rec. 709 weights, from 0.0 (black) to 1.0 (white)
+  pub fn luminance(&self) -> f32 {
+    (0.2126 * self.r as f32 + 0.7152 * self.g as f32 + 0.0722 * self.b as f32) / 255.0
+  }
+
+  // Pushes the color away from (amount > 1) or toward (amount < 1) the gray of
+  // the same luminance: 0 gives that gray and 1 the color itself
+  pub fn with_saturation(&self, amount: f32) -> Self {
+    let gray = self.luminance() * 255.0;
+    let channel = |value: u8| saturate((gray + (value as f32 - gray) * amount).round());
+    Color { r: channel(self.r), g: channel(self.g), b: channel(self.b) }
+  }
+
   // New constructor to initialize the color using r, g, b values as f32 (0.0 to 1.0)
   pub fn from_float(r: f32, g: f32, b: f32) -> Self {
     Color {
-      r: (r.clamp(0.0, 1.0) * 255.0) as u8,
-      g: (g.clamp(0.0, 1.0) * 255.0) as u8,
-      b: (b.clamp(0.0, 1.0) * 255.0) as u8,
+      r: saturate(r * 255.0),
+      g: saturate(g * 255.0),
+      b: saturate(b * 255.0),
     }
   }
 
+  // Approximate color of a blackbody at `temp` kelvin (Tanner Helland's fit,
+  // valid from 1000 K to 40000 K): ~3000 K is orange-red, ~6500 K white
+  // and ~10000 K blue-white
+  pub fn from_kelvin(temp: f32) -> Self {
+    let t = temp.clamp(1000.0, 40000.0) / 100.0;
+
+    let r = if t <= 66.0 {
+      255.0
+    } else {
+      329.698_73 * (t - 60.0).powf(-0.133_204_76)
+    };
+    let g = if t <= 66.0 {
+      99.470_8 * t.ln() - 161.119_57
+    } else {
+      288.122_17 * (t - 60.0).powf(-0.075_514_85)
+    };
+    let b = if t >= 66.0 {
+      255.0
+    } else if t <= 19.0 {
+      0.0
+    } else {
+      138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+
+    Color { r: saturate(r), g: saturate(g), b: saturate(b) }
+  }
+
   // Function to create a color from a hex value
   pub fn from_hex(hex: u32) -> Self {
     let r = ((hex >> 16) & 0xFF) as u8;
@@ -44,9 +85,9 @@ impl Color {
   pub fn lerp(&self, other: &Color, t: f32) -> Self {
     let t = t.clamp(0.0, 1.0);
     Color {
-      r: (self.r as f32 + (other.r as f32 - self.r as f32) * t).round() as u8,
-      g: (self.g as f32 + (other.g as f32 - self.g as f32) * t).round() as u8,
-      b: (self.b as f32 + (other.b as f32 - self.b as f32) * t).round() as u8,
+      r: saturate((self.r as f32 + (other.r as f32 - self.r as f32) * t).round()),
+      g: saturate((self.g as f32 + (other.g as f32 - self.g as f32) * t).round()),
+      b: saturate((self.b as f32 + (other.b as f32 - self.b as f32) * t).round()),
     }
   }
 
@@ -61,33 +102,35 @@ impl Color {
 
   pub fn blend_multiply(&self, blend: &Color) -> Color {
     Color::new(
-      ((self.r as f32 * blend.r as f32) / 255.0) as u8,
-      ((self.g as f32 * blend.g as f32) / 255.0) as u8,
-      ((self.b as f32 * blend.b as f32) / 255.0) as u8
+      saturate((self.r as f32 * blend.r as f32) / 255.0),
+      saturate((self.g as f32 * blend.g as f32) / 255.0),
+      saturate((self.b as f32 * blend.b as f32) / 255.0)
     )
   }
 
   pub fn blend_add(&self, blend: &Color) -> Color {
     Color::new(
-      (self.r as u16 + blend.r as u16).min(255) as u8,
-      (self.g as u16 + blend.g as u16).min(255) as u8,
-      (self.b as u16 + blend.b as u16).min(255) as u8
+      self.r.saturating_add(blend.r),
+      self.g.saturating_add(blend.g),
+      self.b.saturating_add(blend.b)
     )
   }
 
   pub fn blend_subtract(&self, blend: &Color) -> Color {
-    let r = (self.r as i16 - blend.r as i16).max(0).min(255) as u8;
-    let g = (self.g as i16 - blend.g as i16).max(0).min(255) as u8;
-    let b = (self.b as i16 - blend.b as i16).max(0).min(255) as u8;
-
-    Color::new(r, g, b)
+    Color::new(
+      self.r.saturating_sub(blend.r),
+      self.g.saturating_sub(blend.g),
+      self.b.saturating_sub(blend.b)
+    )
   }
 
   pub fn blend_screen(&self, blend: &Color) -> Color {
+    // 1 - (1 - a)(1 - b): the product never exceeds 255, so this can't wrap
+    let screen = |a: u8, b: u8| 255 - ((255 - a as u16) * (255 - b as u16) / 255) as u8;
     Color::new(
-      255 - ((255 - self.r as u16) * (255 - blend.r as u16) / 255) as u8,
-      255 - ((255 - self.g as u16) * (255 - blend.g as u16) / 255) as u8,
-      255 - ((255 - self.b as u16) * (255 - blend.b as u16) / 255) as u8
+      screen(self.r, blend.r),
+      screen(self.g, blend.g),
+      screen(self.b, blend.b)
     )
   }
 
@@ -106,6 +149,17 @@ impl Color {
 
 }
 
+// Float-to-channel conversion used by every Color operation: clamps to
+// 0..=255 first so values outside the range saturate instead of wrapping
+// (NaN maps to 0)
+fn saturate(value: f32) -> u8 {
+  if value.is_nan() {
+    0
+  } else {
+    value.clamp(0.0, 255.0) as u8
+  }
+}
+
 // Implement addition for Color
 use std::ops::Add;
 
@@ -129,9 +183,9 @@ impl Mul<f32> for Color {
 
   fn mul(self, scalar: f32) -> Color {
     Color {
-      r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
-      g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
-      b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
+      r: saturate(self.r as f32 * scalar),
+      g: saturate(self.g as f32 * scalar),
+      b: saturate(self.b as f32 * scalar),
     }
   }
 }
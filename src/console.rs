@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+use thiserror::Error;
+use crate::CelestialBody;
+use crate::gas_giant::GasGiantParams;
+use crate::scene::{Scene, MAX_TIME_SCALE};
+use crate::terrestrial::TerrestrialParams;
+use crate::turntable::find_body;
+
+// Líneas de la consola que quedan a la vista, entre comandos y respuestas
+pub const CONSOLE_SCROLLBACK: usize = 5;
+
+const HELP: &str = "comandos: set CUERPO.PARAMETRO VALOR, goto CUERPO, timescale N, seed N, screenshot, lod on|off";
+
+// Un comando ya interpretado de la consola (ver `parse_command`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    // `set tierra.cloud_coverage 0.3`: un parámetro de un cuerpo (ver `set_param`)
+    Set { body: String, param: String, value: f32 },
+    // `goto marte`: selecciona el cuerpo, y la cámara lo sigue
+    Goto(String),
+    TimeScale(f32),
+    Seed(u64),
+    Screenshot,
+    Lod(bool),
+    Help,
+}
+
+// Lo que la consola no puede hacer sola y le queda al bucle principal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleEffect {
+    Select(usize),
+    Screenshot,
+    // La semilla cambió: el fondo, que sale del mismo ruido, se regenera
+    Reseeded,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CommandError {
+    #[error("comando desconocido: {0} (help muestra la lista)")]
+    Unknown(String),
+
+    #[error("uso: {0}")]
+    Usage(&'static str),
+
+    #[error("\"{0}\" no es un número")]
+    NotANumber(String),
+
+    #[error("no hay ningún cuerpo \"{0}\"")]
+    UnknownBody(String),
+
+    #[error("{body} no tiene el parámetro \"{param}\"")]
+    UnknownParam { body: String, param: String },
+
+    #[error("{param} va de {min} a {max}")]
+    OutOfRange { param: String, min: f32, max: f32 },
+}
+
+// Convierte una línea en un comando. Los nombres de comando no distinguen
+// mayúsculas; los de cuerpo se buscan como en --turntable.
+pub fn parse_command(line: &str) -> Result<Command, CommandError> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err(CommandError::Usage(HELP));
+    };
+    let arguments: Vec<&str> = words.collect();
+
+    let command = match (name.to_lowercase().as_str(), arguments.as_slice()) {
+        ("set", [target, value]) => {
+            let (body, param) = target.split_once('.').ok_or(CommandError::Usage("set CUERPO.PARAMETRO VALOR"))?;
+            Command::Set { body: body.to_string(), param: param.to_lowercase(), value: parse_number(value)? }
+        }
+        ("set", _) => return Err(CommandError::Usage("set CUERPO.PARAMETRO VALOR")),
+        ("goto", [body]) => Command::Goto(body.to_string()),
+        ("goto", _) => return Err(CommandError::Usage("goto CUERPO")),
+        ("timescale", [value]) => Command::TimeScale(parse_number(value)?),
+        ("timescale", _) => return Err(CommandError::Usage("timescale N")),
+        ("seed", [value]) => Command::Seed(value.parse().map_err(|_| CommandError::NotANumber(value.to_string()))?),
+        ("seed", _) => return Err(CommandError::Usage("seed N")),
+        ("screenshot", []) => Command::Screenshot,
+        ("lod", [state]) => match state.to_lowercase().as_str() {
+            "on" => Command::Lod(true),
+            "off" => Command::Lod(false),
+            _ => return Err(CommandError::Usage("lod on|off")),
+        },
+        ("lod", _) => return Err(CommandError::Usage("lod on|off")),
+        ("help", []) => Command::Help,
+        (other, _) => return Err(CommandError::Unknown(other.to_string())),
+    };
+    Ok(command)
+}
+
+fn parse_number(word: &str) -> Result<f32, CommandError> {
+    word.parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| CommandError::NotANumber(word.to_string()))
+}
+
+fn check_range(param: &str, value: f32, min: f32, max: f32) -> Result<f32, CommandError> {
+    if (min..=max).contains(&value) {
+        Ok(value)
+    } else {
+        Err(CommandError::OutOfRange { param: param.to_string(), min, max })
+    }
+}
+
+// Cambia un parámetro de `body`. Los de la paleta terrestre o de gigante
+// gaseoso parten de los del tipo si el cuerpo aún no tenía la suya.
+//
+// - scale, rotation_speed, orbit_speed, displacement y axial_tilt (en grados)
+// - cloud_coverage (0 a 1) o cloud_threshold (-1 a 1, el umbral del ruido
+//   de nubes), en la Tierra y los planetas terrestres
+// - band_count, turbulence, storm_probability y drift_speed en los gigantes
+// - atmosphere_thickness y atmosphere_density si tiene atmósfera
+pub fn set_param(body: &mut CelestialBody, param: &str, value: f32) -> Result<(), CommandError> {
+    match param {
+        "scale" => body.scale = check_range(param, value, 0.01, 100.0)?,
+        "rotation_speed" => body.rotation_speed = value,
+        "orbit_speed" => body.orbit_speed = value,
+        "displacement" => body.displacement = check_range(param, value, 0.0, 0.5)?,
+        "axial_tilt" => body.axial_tilt = check_range(param, value, -180.0, 180.0)?.to_radians(),
+        "cloud_coverage" | "cloud_threshold" => {
+            let coverage = if param == "cloud_coverage" {
+                check_range(param, value, 0.0, 1.0)?
+            } else {
+                (1.0 - check_range(param, value, -1.0, 1.0)?) * 0.5
+            };
+            let preset = TerrestrialParams::preset(&body.shader_type).ok_or_else(|| unknown_param(body, param))?;
+            body.terrestrial.get_or_insert_with(|| preset.clone()).cloud_coverage = coverage;
+        }
+        "band_count" | "turbulence" | "storm_probability" | "drift_speed" => {
+            let preset = GasGiantParams::preset(&body.shader_type).ok_or_else(|| unknown_param(body, param))?;
+            let params = body.gas_giant.get_or_insert_with(|| preset.clone());
+            match param {
+                "band_count" => params.band_count = check_range(param, value, 0.0, 100.0)?,
+                "turbulence" => params.turbulence = check_range(param, value, 0.0, 1.0)?,
+                "storm_probability" => params.storm_probability = check_range(param, value, 0.0, 1.0)?,
+                _ => params.drift_speed = value,
+            }
+        }
+        "atmosphere_thickness" | "atmosphere_density" => {
+            let Some(atmosphere) = body.atmosphere.as_mut() else {
+                return Err(unknown_param(body, param));
+            };
+            if param == "atmosphere_thickness" {
+                atmosphere.thickness = check_range(param, value, 0.0, 1.0)?;
+            } else {
+                atmosphere.density = check_range(param, value, 0.0, 4.0)?;
+            }
+        }
+        _ => return Err(unknown_param(body, param)),
+    }
+    Ok(())
+}
+
+fn unknown_param(body: &CelestialBody, param: &str) -> CommandError {
+    CommandError::UnknownParam { body: body.shader_type.name().to_string(), param: param.to_string() }
+}
+
+// Ejecuta `command` sobre la escena. Devuelve el mensaje para la consola y,
+// si hace falta, lo que le toca hacer al bucle principal.
+pub fn execute(command: &Command, scene: &mut Scene) -> Result<(String, Option<ConsoleEffect>), CommandError> {
+    let find = |query: &str| find_body(&scene.bodies, query).ok_or_else(|| CommandError::UnknownBody(query.to_string()));
+    let result = match command {
+        Command::Set { body, param, value } => {
+            let index = find(body)?;
+            let body = &mut scene.bodies[index];
+            set_param(body, param, *value)?;
+            (format!("{}.{} = {}", body.shader_type.name(), param, value), None)
+        }
+        Command::Goto(query) => {
+            let index = find(query)?;
+            if !scene.bodies[index].visible {
+                return Err(CommandError::UnknownBody(query.to_string()));
+            }
+            (format!("Siguiendo a {}", scene.bodies[index].shader_type.name()), Some(ConsoleEffect::Select(index)))
+        }
+        Command::TimeScale(scale) => {
+            scene.time_scale = check_range("timescale", *scale, 0.0, MAX_TIME_SCALE)?;
+            (format!("Escala de tiempo: {}", scene.time_scale), None)
+        }
+        Command::Seed(seed) => {
+            scene.set_seed(*seed);
+            (format!("Semilla del ruido: {}", seed), Some(ConsoleEffect::Reseeded))
+        }
+        Command::Screenshot => (String::new(), Some(ConsoleEffect::Screenshot)),
+        Command::Lod(enabled) => {
+            scene.context.shader_lod.enabled = *enabled;
+            (format!("Nivel de detalle de los shaders: {}", if *enabled { "activado" } else { "desactivado" }), None)
+        }
+        Command::Help => (HELP.to_string(), None),
+    };
+    Ok(result)
+}
+
+// Consola de comandos de la ventana: se abre y cierra con la tecla de acento
+// grave. Mientras está abierta, las teclas escriben la línea en lugar de
+// mover la cámara; Enter la ejecuta y las respuestas y errores quedan en las
+// últimas `CONSOLE_SCROLLBACK` líneas.
+#[derive(Debug, Default)]
+pub struct Console {
+    open: bool,
+    line: String,
+    scrollback: VecDeque<String>,
+}
+
+impl Console {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    // Al cerrarla se descarta lo escrito; lo ya ejecutado se conserva
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.line.clear();
+    }
+
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    pub fn scrollback(&self) -> impl Iterator<Item = &str> {
+        self.scrollback.iter().map(String::as_str)
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.line.push(c);
+    }
+
+    pub fn backspace(&mut self) {
+        self.line.pop();
+    }
+
+    pub fn print(&mut self, message: impl Into<String>) {
+        if self.scrollback.len() == CONSOLE_SCROLLBACK {
+            self.scrollback.pop_front();
+        }
+        self.scrollback.push_back(message.into());
+    }
+
+    // Ejecuta la línea escrita y la deja vacía. Un error queda en la consola
+    // y no cambia nada.
+    pub fn submit(&mut self, scene: &mut Scene) -> Option<ConsoleEffect> {
+        let line = std::mem::take(&mut self.line);
+        if line.trim().is_empty() {
+            return None;
+        }
+        self.print(format!("> {}", line.trim()));
+        match parse_command(&line).and_then(|command| execute(&command, scene)) {
+            Ok((message, effect)) => {
+                if !message.is_empty() {
+                    self.print(message);
+                }
+                effect
+            }
+            Err(error) => {
+                self.print(format!("error: {}", error));
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use once_cell::sync::Lazy;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use nalgebra_glm::Vec3;
+
+// Dónde acaba el fondo plano y dónde está la cresta del borde, en radios del cráter
+const FLOOR_EDGE: f32 = 0.7;
+const RIM_POSITION: f32 = 1.0;
+// Semiancho del borde levantado, en radios del cráter
+const RIM_WIDTH: f32 = 0.25;
+// Hasta dónde llega la influencia de un cráter, en radios
+const REACH: f32 = RIM_POSITION + RIM_WIDTH;
+
+pub static MERCURY_CRATERS: Lazy<CraterField> = Lazy::new(|| CraterField::new(0x4D45_5243, 160, 0.02, 0.16));
+pub static MARS_CRATERS: Lazy<CraterField> = Lazy::new(|| CraterField::new(0x4D41_5253, 70, 0.03, 0.14));
+// Calisto está saturada de cráteres parecidos, sin mares que los borren
+pub static CALLISTO_CRATERS: Lazy<CraterField> = Lazy::new(|| CraterField::new(0x4341_4C4C, 420, 0.03, 0.09));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crater {
+    // Dirección del centro sobre la esfera unitaria
+    pub center: Vec3,
+    // Radio angular, en radianes
+    pub radius: f32,
+    // Coseno del ángulo hasta el que llega su borde, para descartar rápido
+    reach_cos: f32,
+}
+
+// Cuánto hay de fondo de cráter y de borde levantado en un punto, de 0 a 1
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CraterProfile {
+    pub floor: f32,
+    pub rim: f32,
+}
+
+impl CraterProfile {
+    // Altura relativa para el relieve: el fondo se hunde y el borde sube
+    pub fn height(&self) -> f32 {
+        self.rim - self.floor
+    }
+}
+
+// Cráteres fijos sobre la esfera unitaria, generados una vez con una semilla
+// para que no cambien entre frames
+#[derive(Debug, Clone, PartialEq)]
+pub struct CraterField {
+    craters: Vec<Crater>,
+}
+
+impl CraterField {
+    // Centros uniformes sobre la esfera; los radios se reparten entre
+    // `min_radius` y `max_radius` con muchos más pequeños que grandes
+    pub fn new(seed: u64, count: usize, min_radius: f32, max_radius: f32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let craters = (0..count).map(|_| {
+            let z: f32 = rng.gen_range(-1.0..1.0);
+            let angle: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
+            let ring = (1.0 - z * z).sqrt();
+            let radius = min_radius + (max_radius - min_radius) * rng.gen::<f32>().powi(3);
+            Crater {
+                center: Vec3::new(ring * angle.cos(), ring * angle.sin(), z),
+                radius,
+                reach_cos: (radius * REACH).min(std::f32::consts::PI).cos(),
+            }
+        }).collect();
+        CraterField { craters }
+    }
+
+    pub fn craters(&self) -> &[Crater] {
+        &self.craters
+    }
+
+    // Perfil del cráter más cercano (en radios) a la dirección `direction`.
+    // Fuera de todos los cráteres devuelve un perfil vacío.
+    pub fn profile(&self, direction: &Vec3) -> CraterProfile {
+        let norm = direction.norm();
+        if norm <= f32::EPSILON {
+            return CraterProfile::default();
+        }
+        let direction = direction / norm;
+
+        let nearest = self.craters.iter()
+            .filter_map(|crater| {
+                let cos = crater.center.dot(&direction);
+                (cos >= crater.reach_cos).then(|| cos.clamp(-1.0, 1.0).acos() / crater.radius)
+            })
+            .fold(f32::INFINITY, f32::min);
+        if nearest >= REACH {
+            return CraterProfile::default();
+        }
+
+        CraterProfile {
+            floor: 1.0 - smoothstep(FLOOR_EDGE, RIM_POSITION, nearest),
+            rim: smoothstep(0.0, 1.0, 1.0 - (nearest - RIM_POSITION).abs() / RIM_WIDTH),
+        }
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
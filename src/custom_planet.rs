@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use crate::color::Color;
+
+// Familia de superficie que dibuja el shader genérico de `PlanetType::Custom`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlanetStyle {
+    // Manchas de terreno con relieve: `primary` en lo bajo, `secondary` en
+    // lo alto y `accent` en las cumbres
+    Terrestrial,
+    // Bandas por latitud deformadas por el ruido, de `primary` a `secondary`,
+    // con `accent` en las turbulencias más fuertes
+    Gas,
+    // Corteza oscura de `primary` a `secondary` partida por grietas de
+    // `accent` que brillan también en el lado nocturno
+    Lava,
+}
+
+// Cómo se suman las octavas del ruido (las de `Uniforms::terrain_octaves`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NoiseFractal {
+    // Una sola octava: formas grandes y blandas
+    None,
+    #[default]
+    FBm,
+    // Crestas finas donde el fbm cruza el cero
+    Ridged,
+}
+
+// Parámetros de un planeta definido sin tocar los shaders. Es `Copy` para
+// que `PlanetType` lo siga siendo. En un archivo de escena los campos que
+// falten toman el valor de `DEFAULT_CUSTOM_PLANET`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlanetParams {
+    pub primary: Color,
+    pub secondary: Color,
+    // Tercer color opcional; sin él, el estilo usa `secondary` aclarado
+    pub accent: Option<Color>,
+    // Zoom del ruido sobre la esfera unitaria: más alto, detalles más chicos
+    pub noise_frequency: f32,
+    pub fractal: NoiseFractal,
+    pub style: PlanetStyle,
+}
+
+// Un mundo rocoso pardo, el que agrega Tab
+pub const DEFAULT_CUSTOM_PLANET: PlanetParams = PlanetParams {
+    primary: Color { r: 95, g: 80, b: 65 },
+    secondary: Color { r: 170, g: 150, b: 120 },
+    accent: None,
+    noise_frequency: 200.0,
+    fractal: NoiseFractal::FBm,
+    style: PlanetStyle::Terrestrial,
+};
+
+impl Default for PlanetParams {
+    fn default() -> Self {
+        DEFAULT_CUSTOM_PLANET
+    }
+}
+
+impl PlanetParams {
+    // El tercer color, o `secondary` a medio camino del blanco
+    pub fn accent_color(&self) -> Color {
+        self.accent.unwrap_or_else(|| self.secondary.lerp(&Color::new(255, 255, 255), 0.5))
+    }
+}
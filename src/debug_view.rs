@@ -0,0 +1,160 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+use crate::{ProjectionSettings, RenderStats};
+
+// Color de las aristas cercanas en los modos de alambre (HDR lineal); las
+// lejanas se apagan hacia WIREFRAME_FAR_COLOR
+pub const WIREFRAME_COLOR: Vec3 = Vec3::new(0.1, 1.0, 0.3);
+pub const WIREFRAME_FAR_COLOR: Vec3 = Vec3::new(0.05, 0.15, 0.5);
+// Distancia a la cámara a la que una arista queda a mitad de camino
+const WIREFRAME_FADE_DISTANCE: f32 = 20.0;
+
+// Rampa del mapa de sobredibujado: índice = intentos de test de profundidad
+// en el píxel; del último en adelante se satura en blanco
+const OVERDRAW_RAMP: [u32; 9] = [
+    0x000000, 0x0000A0, 0x0060FF, 0x00C0C0, 0x00C000, 0xC0C000, 0xFF8000, 0xFF0000, 0xFFFFFF,
+];
+
+// Cómo dibuja `render` la superficie de cada cuerpo, en el orden en que los
+// recorre F2. A diferencia de `DebugView`, actúa durante el render, así que
+// el resultado pasa después por el posprocesado como cualquier imagen.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RenderMode {
+    #[default]
+    Shaded,
+    // Sólo las aristas de los triángulos; la superficie escribe profundidad
+    // con el color de fondo, así tapa las aristas del lado de atrás
+    Wireframe,
+    ShadedWithWireframe,
+    // La normal interpolada de cada fragmento, de [-1, 1] a RGB, sin luz
+    Normals,
+}
+
+impl RenderMode {
+    pub fn next(self) -> Self {
+        match self {
+            RenderMode::Shaded => RenderMode::Wireframe,
+            RenderMode::Wireframe => RenderMode::ShadedWithWireframe,
+            RenderMode::ShadedWithWireframe => RenderMode::Normals,
+            RenderMode::Normals => RenderMode::Shaded,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Shaded => "Sombreado",
+            RenderMode::Wireframe => "Alambre",
+            RenderMode::ShadedWithWireframe => "Sombreado con alambre",
+            RenderMode::Normals => "Normales",
+        }
+    }
+
+    // Si pasa los fragmentos por el fragment shader y la iluminación
+    pub fn shades(self) -> bool {
+        matches!(self, RenderMode::Shaded | RenderMode::ShadedWithWireframe)
+    }
+
+    pub fn draws_edges(self) -> bool {
+        matches!(self, RenderMode::Wireframe | RenderMode::ShadedWithWireframe)
+    }
+}
+
+// Color de una arista a `distance` de la cámara
+pub fn wireframe_color(distance: f32) -> Vec3 {
+    let t = distance.max(0.0) / (distance.max(0.0) + WIREFRAME_FADE_DISTANCE);
+    WIREFRAME_COLOR.lerp(&WIREFRAME_FAR_COLOR, t)
+}
+
+// Color de un fragmento en el modo de normales
+pub fn normal_color(normal: &Vec3) -> Vec3 {
+    normal.map(|component| component.clamp(-1.0, 1.0) * 0.5 + 0.5)
+}
+
+// Vistas de depuración del pipeline, en el orden en que las recorre F4
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugView {
+    Shaded,
+    Depth,
+    Overdraw,
+}
+
+impl DebugView {
+    pub fn next(self) -> Self {
+        match self {
+            DebugView::Shaded => DebugView::Depth,
+            DebugView::Depth => DebugView::Overdraw,
+            DebugView::Overdraw => DebugView::Shaded,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugView::Shaded => "Normal",
+            DebugView::Depth => "Profundidad",
+            DebugView::Overdraw => "Sobredibujado",
+        }
+    }
+
+    // Reemplaza el buffer ya resuelto por la vista elegida; la normal no toca nada
+    pub fn apply(self, framebuffer: &mut Framebuffer, projection: &ProjectionSettings) {
+        match self {
+            DebugView::Depth => show_depth(framebuffer, projection),
+            DebugView::Overdraw => show_overdraw(framebuffer),
+            DebugView::Shaded => {}
+        }
+    }
+}
+
+// Distancia a la cámara en grises: lo más cercano de la escena en blanco, lo
+// más lejano en gris oscuro y el fondo en negro. Se normaliza con el rango
+// de este frame para que se lea igual desde cerca que desde lejos.
+fn show_depth(framebuffer: &mut Framebuffer, projection: &ProjectionSettings) {
+    let mut nearest = f32::INFINITY;
+    let mut farthest = f32::NEG_INFINITY;
+    for &depth in &framebuffer.zbuffer {
+        if framebuffer.is_surface(depth) {
+            let distance = projection.linear_depth(depth);
+            nearest = nearest.min(distance);
+            farthest = farthest.max(distance);
+        }
+    }
+    let range = (farthest - nearest).max(f32::EPSILON);
+
+    for index in 0..framebuffer.buffer.len() {
+        let depth = framebuffer.zbuffer[index];
+        framebuffer.buffer[index] = if framebuffer.is_surface(depth) {
+            let t = (projection.linear_depth(depth) - nearest) / range;
+            let gray = (255.0 - t.clamp(0.0, 1.0) * 215.0) as u32;
+            (gray << 16) | (gray << 8) | gray
+        } else {
+            0x000000
+        };
+    }
+    framebuffer.mark_all_dirty();
+}
+
+fn show_overdraw(framebuffer: &mut Framebuffer) {
+    for (pixel, &count) in framebuffer.buffer.iter_mut().zip(&framebuffer.overdraw_buffer) {
+        *pixel = OVERDRAW_RAMP[(count as usize).min(OVERDRAW_RAMP.len() - 1)];
+    }
+    framebuffer.mark_all_dirty();
+}
+
+// Estadísticas del frame como líneas de texto para dibujar en pantalla, con
+// la resolución interna y su escala respecto a la ventana (--adaptive)
+pub fn stats_text(view: DebugView, stats: &RenderStats, resolution: (usize, usize), scale: f32) -> String {
+    format!(
+        "Vista: {}\nResolucion: {}x{} ({:.0}%)\nVertices: {}\nTriangulos: {} ({} descartados)\nFragmentos: {} ({} sombreados)\nPasan profundidad: {}\nPixeles escritos: {}",
+        view.name(),
+        resolution.0,
+        resolution.1,
+        scale * 100.0,
+        stats.vertices,
+        stats.triangles,
+        stats.culled_triangles,
+        stats.fragments,
+        stats.shaded_fragments,
+        stats.depth_passed,
+        stats.pixels_written,
+    )
+}
@@ -0,0 +1,134 @@
+use crate::framebuffer::Framebuffer;
+use crate::ProjectionSettings;
+
+// Disco de Poisson clásico de 16 muestras (radio hasta ~1.3)
+const POISSON_DISK: [(f32, f32); 16] = [
+    (-0.942_016_24, -0.399_062_16),
+    (0.945_586_1, -0.768_907_25),
+    (-0.094_184_1, -0.929_388_7),
+    (0.344_959_38, 0.293_877_6),
+    (-0.915_885_8, 0.457_714_32),
+    (-0.815_442_3, -0.879_124_64),
+    (-0.382_775_43, 0.276_768_45),
+    (0.974_844, 0.756_483_8),
+    (0.443_233_25, -0.975_115_54),
+    (0.537_429_8, -0.473_734_2),
+    (-0.264_969_1, -0.418_930_23),
+    (0.791_975_14, 0.190_901_88),
+    (-0.241_888_4, 0.997_065_07),
+    (-0.814_099_55, 0.914_375_9),
+    (0.199_841_26, 0.786_413_7),
+    (0.143_831_61, -0.141_007_9),
+];
+// Por debajo de este círculo de confusión (en píxeles) el píxel queda nítido
+const SHARP_RADIUS: f32 = 0.5;
+
+// Profundidad de campo como post-proceso sobre el buffer ya convertido a 8 bits
+pub struct DepthOfField {
+    pub enabled: bool,
+    // Píxeles de desenfoque para un objeto infinitamente lejos del plano focal
+    pub aperture: f32,
+    // Tope del radio de desenfoque, en píxeles
+    pub max_radius: f32,
+    // Copias de trabajo reutilizadas entre frames
+    source: Vec<u32>,
+    coc: Vec<f32>,
+    distance: Vec<f32>,
+}
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        DepthOfField {
+            enabled: false,
+            aperture: 12.0,
+            max_radius: 8.0,
+            source: Vec::new(),
+            coc: Vec::new(),
+            distance: Vec::new(),
+        }
+    }
+}
+
+impl DepthOfField {
+    // Desenfoca cada píxel según lo lejos que esté de `focal_distance`.
+    // Con el efecto desactivado no hace nada.
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer, projection: &ProjectionSettings, focal_distance: f32) {
+        if !self.enabled || focal_distance <= 0.0 {
+            return;
+        }
+
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        self.source.clear();
+        self.source.extend_from_slice(&framebuffer.buffer);
+
+        // El fondo (sin geometría) cuenta como infinitamente lejos
+        self.distance.clear();
+        self.distance.extend(framebuffer.zbuffer.iter().map(|&depth| {
+            if framebuffer.is_surface(depth) { projection.linear_depth(depth) } else { f32::INFINITY }
+        }));
+        self.coc.clear();
+        self.coc.extend(self.distance.iter().map(|&distance| {
+            let defocus = if distance.is_finite() {
+                (distance - focal_distance).abs() / distance
+            } else {
+                1.0
+            };
+            (self.aperture * defocus).min(self.max_radius)
+        }));
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                let radius = self.coc[index];
+                if radius < SHARP_RADIUS {
+                    continue;
+                }
+
+                let center = self.source[index];
+                let mut sum = unpack(center);
+                let mut weight = 1.0;
+                for &(dx, dy) in POISSON_DISK.iter() {
+                    let sx = (x as f32 + dx * radius).round();
+                    let sy = (y as f32 + dy * radius).round();
+                    if sx < 0.0 || sy < 0.0 || sx >= width as f32 || sy >= height as f32 {
+                        continue;
+                    }
+                    let sample = sy as usize * width + sx as usize;
+
+                    // Un objeto nítido más cercano no se corre sobre el fondo
+                    // desenfocado: sólo entra si está detrás o si su propio
+                    // desenfoque alcanza a este píxel
+                    let offset = (dx * dx + dy * dy).sqrt() * radius;
+                    if self.distance[sample] < self.distance[index] && self.coc[sample] < offset {
+                        continue;
+                    }
+
+                    let color = unpack(self.source[sample]);
+                    for channel in 0..3 {
+                        sum[channel] += color[channel];
+                    }
+                    weight += 1.0;
+                }
+
+                framebuffer.buffer[index] = pack(sum.map(|c| c / weight));
+            }
+        }
+
+        // El desenfoque toca píxeles fuera de la región dibujada
+        framebuffer.mark_all_dirty();
+    }
+}
+
+fn unpack(color: u32) -> [f32; 3] {
+    [
+        ((color >> 16) & 0xFF) as f32,
+        ((color >> 8) & 0xFF) as f32,
+        (color & 0xFF) as f32,
+    ]
+}
+
+fn pack(color: [f32; 3]) -> u32 {
+    let channel = |c: f32| c.round().clamp(0.0, 255.0) as u32;
+    (channel(color[0]) << 16) | (channel(color[1]) << 8) | channel(color[2])
+}
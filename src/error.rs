@@ -0,0 +1,46 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+// Errores de carga y guardado. Cada variante lleva la ruta implicada para
+// que el mensaje diga qué archivo falló y por qué.
+#[derive(Debug, Error)]
+pub enum Error {
+    // El archivo no está donde se buscó; el mensaje muestra esa ruta
+    #[error("no se encontró {}: se esperaba en esa ruta", .path.display())]
+    MissingAsset { path: PathBuf },
+
+    #[error("no se pudo cargar el modelo {}: {source}", .path.display())]
+    Obj { path: PathBuf, source: tobj::LoadError },
+
+    #[error("no se pudo crear la ventana: {0}")]
+    Window(#[from] minifb::Error),
+
+    #[error("no se pudo leer {}: {source}", .path.display())]
+    Read { path: PathBuf, source: io::Error },
+
+    #[error("no se pudo escribir {}: {source}", .path.display())]
+    Write { path: PathBuf, source: io::Error },
+
+    // Escena JSON mal formada, o que no se pudo serializar
+    #[error("escena inválida en {}: {source}", .path.display())]
+    Scene { path: PathBuf, source: serde_json::Error },
+
+    #[error("marcadores de cámara inválidos en {}: {source}", .path.display())]
+    Bookmarks { path: PathBuf, source: ron::Error },
+
+    // Estado de la aplicación (F5/F9) mal formado, o que no se pudo serializar
+    #[error("estado guardado inválido en {}: {source}", .path.display())]
+    State { path: PathBuf, source: ron::Error },
+
+    // El estado se guardó con otro sistema: sus cuerpos no son los de la escena
+    #[error("el estado de {} tiene {saved} cuerpos y la escena actual {current}", .path.display())]
+    StateMismatch { path: PathBuf, saved: usize, current: usize },
+
+    // Un cuerpo pedido por nombre o índice (p. ej. con --turntable) que no está
+    #[error("no hay ningún cuerpo \"{query}\" en la escena (cuerpos: {available})")]
+    UnknownBody { query: String, available: String },
+
+    #[error("no se pudo leer o guardar la imagen {}: {source}", .path.display())]
+    Image { path: PathBuf, source: image::ImageError },
+}
@@ -0,0 +1,187 @@
+use nalgebra_glm::{Vec3, Vec4};
+use crate::framebuffer::Framebuffer;
+use crate::Uniforms;
+
+// Reflejo circular de la lente sobre la línea Sol → centro de la pantalla
+pub struct FlareGhost {
+    // 0 = sobre el Sol, 1 = centro de la pantalla, 2 = reflejado al otro lado
+    pub offset: f32,
+    // Radio como fracción de la altura del framebuffer
+    pub radius: f32,
+    // Color lineal ya multiplicado por su opacidad
+    pub color: [f32; 3],
+}
+
+// Todo lo que define el aspecto del destello
+pub struct LensFlareSettings {
+    pub ghosts: &'static [FlareGhost],
+    // Semilongitud de la raya horizontal, como fracción del ancho
+    pub streak_length: f32,
+    // Semigrosor de la raya, en fracción de la altura
+    pub streak_width: f32,
+    pub streak_color: [f32; 3],
+    // Fracción de pantalla (en NDC) sobre la que se apaga al acercarse al borde
+    pub edge_fade: f32,
+    // Muestras del disco solar usadas para estimar cuánto queda tapado
+    pub occlusion_samples: usize,
+}
+
+pub const LENS_FLARE: LensFlareSettings = LensFlareSettings {
+    ghosts: &[
+        FlareGhost { offset: 0.35, radius: 0.020, color: [0.10, 0.08, 0.04] },
+        FlareGhost { offset: 0.70, radius: 0.045, color: [0.05, 0.09, 0.05] },
+        FlareGhost { offset: 1.10, radius: 0.030, color: [0.04, 0.06, 0.12] },
+        FlareGhost { offset: 1.45, radius: 0.070, color: [0.07, 0.04, 0.08] },
+        FlareGhost { offset: 1.80, radius: 0.015, color: [0.12, 0.10, 0.06] },
+        FlareGhost { offset: 2.20, radius: 0.100, color: [0.03, 0.05, 0.08] },
+    ],
+    streak_length: 0.35,
+    streak_width: 0.004,
+    streak_color: [0.35, 0.30, 0.25],
+    edge_fade: 0.25,
+    occlusion_samples: 16,
+};
+
+// Dibuja el destello de una estrella de radio `radius` en `position`, sumándolo
+// al buffer de 8 bits. Va después de resolver el frame y antes de las etiquetas.
+// No dibuja nada si la estrella está fuera de pantalla, detrás de la cámara
+// o completamente tapada.
+pub fn draw_lens_flare(
+    framebuffer: &mut Framebuffer,
+    settings: &LensFlareSettings,
+    uniforms: &Uniforms,
+    position: &Vec3,
+    radius: f32,
+    light_color: &Vec3,
+) {
+    let width = framebuffer.width as f32;
+    let height = framebuffer.height as f32;
+    let Some(center) = project(position, uniforms) else {
+        return;
+    };
+
+    // Se apaga al acercarse al borde y desaparece al salir
+    let ndc_x = center.x / width * 2.0 - 1.0;
+    let ndc_y = center.y / height * 2.0 - 1.0;
+    let edge_distance = 1.0 - ndc_x.abs().max(ndc_y.abs());
+    if edge_distance <= 0.0 {
+        return;
+    }
+    let edge = smoothstep(edge_distance / settings.edge_fade);
+
+    let visibility = visible_fraction(framebuffer, settings, uniforms, position, radius, &center);
+    let intensity = edge * visibility;
+    if intensity <= 0.0 {
+        return;
+    }
+
+    let sun = (center.x, center.y);
+    let screen_center = (width * 0.5, height * 0.5);
+    let tint = |color: &[f32; 3]| Vec3::new(color[0], color[1], color[2]).component_mul(light_color) * intensity;
+
+    draw_streak(framebuffer, sun, settings.streak_length * width, settings.streak_width * height, &tint(&settings.streak_color));
+
+    for ghost in settings.ghosts {
+        let ghost_center = (
+            sun.0 + (screen_center.0 - sun.0) * ghost.offset,
+            sun.1 + (screen_center.1 - sun.1) * ghost.offset,
+        );
+        draw_ghost(framebuffer, ghost_center, ghost.radius * height, &tint(&ghost.color));
+    }
+}
+
+// Punto del mundo en píxeles, con la misma profundidad que escribe el rasterizador
+fn project(point: &Vec3, uniforms: &Uniforms) -> Option<Vec3> {
+    let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= f32::EPSILON {
+        return None;
+    }
+    let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    Some((uniforms.viewport_matrix * ndc).xyz())
+}
+
+// Fracción del disco visible. Cada muestra compara el z-buffer con la
+// profundidad del punto de la estrella más cercano a la cámara: lo que esté
+// delante de ese punto sólo puede ser otro cuerpo.
+fn visible_fraction(
+    framebuffer: &Framebuffer,
+    settings: &LensFlareSettings,
+    uniforms: &Uniforms,
+    position: &Vec3,
+    radius: f32,
+    center: &Vec3,
+) -> f32 {
+    let to_camera = uniforms.camera_position - position;
+    let distance = to_camera.norm();
+    if distance <= radius {
+        return 0.0;
+    }
+    let Some(nearest) = project(&(position + to_camera / distance * radius), uniforms) else {
+        return 0.0;
+    };
+
+    // Radio del disco en pantalla a partir de un punto del limbo
+    let side = to_camera.cross(&Vec3::new(0.0, 1.0, 0.0));
+    let side = if side.norm() > f32::EPSILON { side.normalize() } else { Vec3::new(1.0, 0.0, 0.0) };
+    let screen_radius = project(&(position + side * radius), uniforms)
+        .map(|limb| (limb.xy() - center.xy()).norm())
+        .unwrap_or(0.0);
+
+    // Espiral de Vogel: muestras repartidas uniformemente por el disco
+    let samples = settings.occlusion_samples.max(1);
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0_f32.sqrt());
+    let mut visible = 0;
+    for i in 0..samples {
+        let r = ((i as f32 + 0.5) / samples as f32).sqrt() * screen_radius * 0.9;
+        let angle = i as f32 * golden_angle;
+        let x = center.x + r * angle.cos();
+        let y = center.y + r * angle.sin();
+        if x < 0.0 || y < 0.0 || x >= framebuffer.width as f32 || y >= framebuffer.height as f32 {
+            continue;
+        }
+        let depth = framebuffer.zbuffer[y as usize * framebuffer.width + x as usize];
+        if !framebuffer.is_nearer(depth, nearest.z) {
+            visible += 1;
+        }
+    }
+    visible as f32 / samples as f32
+}
+
+// Raya horizontal que se desvanece hacia los extremos
+fn draw_streak(framebuffer: &mut Framebuffer, center: (f32, f32), length: f32, width: f32, color: &Vec3) {
+    let half_height = (width * 3.0).ceil().max(1.0);
+    for dy in -(half_height as i32)..=half_height as i32 {
+        let vertical = (-(dy as f32 / width.max(0.5)).powi(2)).exp();
+        for dx in -(length as i32)..=length as i32 {
+            let horizontal = 1.0 - dx.abs() as f32 / length;
+            add_pixel(framebuffer, center.0 + dx as f32, center.1 + dy as f32, &(color * (horizontal * horizontal * vertical)));
+        }
+    }
+}
+
+// Círculo translúcido con el borde suavizado
+fn draw_ghost(framebuffer: &mut Framebuffer, center: (f32, f32), radius: f32, color: &Vec3) {
+    let extent = radius.ceil() as i32 + 1;
+    for dy in -extent..=extent {
+        for dx in -extent..=extent {
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+            let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                // Un poco más brillante hacia el borde, como los reflejos reales
+                let rim = 0.6 + 0.4 * (distance / radius.max(1.0)).min(1.0);
+                add_pixel(framebuffer, center.0 + dx as f32, center.1 + dy as f32, &(color * (coverage * rim)));
+            }
+        }
+    }
+}
+
+fn add_pixel(framebuffer: &mut Framebuffer, x: f32, y: f32, color: &Vec3) {
+    if x >= 0.0 && y >= 0.0 {
+        framebuffer.add_color(x as usize, y as usize, color);
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
@@ -5,9 +5,19 @@ pub struct Fragment {
     pub position: Vec2,
     pub color: Color,
     pub depth: f32,
+    // World space, interpolated from the vertices (or the face normal with
+    // flat shading). Lighting happens in the shaders, see `lighting`.
     pub normal: Vec3,
-    pub intensity: f32,
+    // Object space: the mesh position before the model matrix (and before
+    // displacement). Surface patterns sample noise here so they stay glued
+    // to the body while it moves and spins.
     pub vertex_position: Vec3,
+    // World space: the displaced position after the model matrix, for
+    // lighting, shadows, clipping and anything measured against the camera
+    pub world_position: Vec3,
+    // Fraction of the pixel covered by the triangle: 1.0 inside, less on
+    // the anti-aliased fringe just outside its edges
+    pub coverage: f32,
 }
 
 impl Fragment {
@@ -16,16 +26,18 @@ impl Fragment {
         color: Color,
         depth: f32,
         normal: Vec3,
-        intensity: f32,
         vertex_position: Vec3,
+        world_position: Vec3,
+        coverage: f32,
     ) -> Self {  
         Fragment {
             position,
             color,
             depth,
             normal,
-            intensity,
             vertex_position,
+            world_position,
+            coverage,
         }
     }
 }
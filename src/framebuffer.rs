@@ -1,50 +1,592 @@
 // framebuffer.rs
 
+use nalgebra_glm::{Vec2, Vec3};
+use crate::tonemap::{compress_highlights, luminance, ToneMapOperator, ToneMapSettings};
+use crate::color::Color;
+use crate::font::{glyph, GLYPH_WIDTH, GLYPH_HEIGHT};
+use crate::line::clip_to_screen;
+use crate::progressive::Interlace;
+use crate::viewport::Viewport;
+
+// Padding around text drawn with a background box, in unscaled pixels
+const TEXT_BOX_PADDING: usize = 2;
+
+// Inclusive pixel rectangle: (min_x, min_y, max_x, max_y)
+type Region = (usize, usize, usize, usize);
+
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
     pub zbuffer: Vec<f32>,
+    // Linear RGB accumulated by `point_hdr`; `resolve` tone maps it into `buffer`
+    pub hdr_buffer: Vec<Vec3>,
+    // Screen-space motion in pixels per frame, written by `set_velocity`
+    pub velocity_buffer: Vec<Vec2>,
+    // Depth tests attempted per pixel this frame, for the overdraw view
+    pub overdraw_buffer: Vec<u16>,
     background_color: u32,
     current_color: u32,
+    // Reversed-Z: depth goes from 1 at the near plane to 0 at the far plane,
+    // and nearer means larger. Must match `ProjectionSettings::reversed_z`.
+    reversed_z: bool,
+    // Set by `mark_background_drawn`: `resolve` then converts every pixel,
+    // not only those with geometry. Reset by `clear`.
+    background_drawn: bool,
+    // Area touched since the last clear; `clear` only resets this part
+    dirty: Option<Region>,
+    // When set, depth-tested and additive writes outside it are dropped, so
+    // a view drawn into part of the buffer can't spill into its neighbour
+    scissor: Option<Viewport>,
+    // When set, the same writes are also dropped outside these rows, for
+    // one pass of a progressive render
+    interlace: Option<Interlace>,
+    // Frames resolved so far, to move the temporal dither pattern
+    resolved_frames: u32,
 }
 
 impl Framebuffer {
     pub fn new(width: usize, height: usize) -> Self {
-        Framebuffer {
+        let mut framebuffer = Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
             zbuffer: vec![f32::INFINITY; width * height],
+            hdr_buffer: vec![Vec3::zeros(); width * height],
+            velocity_buffer: vec![Vec2::zeros(); width * height],
+            overdraw_buffer: vec![0; width * height],
             background_color: 0x000000,
             current_color: 0xFFFFFF,
-        }
+            reversed_z: false,
+            background_drawn: false,
+            dirty: None,
+            scissor: None,
+            interlace: None,
+            resolved_frames: 0,
+        };
+        framebuffer.mark_all_dirty();
+        framebuffer
     }
 
+    // Reallocates the color and depth planes for a new size
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.buffer = vec![self.background_color; width * height];
+        self.zbuffer = vec![self.clear_depth(); width * height];
+        self.hdr_buffer = vec![self.background_linear(); width * height];
+        self.velocity_buffer = vec![Vec2::zeros(); width * height];
+        self.overdraw_buffer = vec![0; width * height];
+        self.dirty = None;
+    }
+
+    // Resets color and depth. Both planes must be cleared every frame,
+    // otherwise last frame's depths hide this frame's geometry.
     pub fn clear(&mut self) {
-        for pixel in self.buffer.iter_mut() {
-            *pixel = self.background_color;
+        self.background_drawn = false;
+        let Some((min_x, min_y, max_x, max_y)) = self.dirty.take() else {
+            return;
+        };
+        let background = self.background_linear();
+        let clear_depth = self.clear_depth();
+
+        if min_x == 0 && max_x + 1 == self.width {
+            // Whole rows: one contiguous fill
+            let start = min_y * self.width;
+            let end = (max_y + 1) * self.width;
+            self.buffer[start..end].fill(self.background_color);
+            self.zbuffer[start..end].fill(clear_depth);
+            self.hdr_buffer[start..end].fill(background);
+            self.velocity_buffer[start..end].fill(Vec2::zeros());
+            self.overdraw_buffer[start..end].fill(0);
+        } else {
+            for y in min_y..=max_y {
+                let start = y * self.width + min_x;
+                let end = y * self.width + max_x + 1;
+                self.buffer[start..end].fill(self.background_color);
+                self.zbuffer[start..end].fill(clear_depth);
+                self.hdr_buffer[start..end].fill(background);
+                self.velocity_buffer[start..end].fill(Vec2::zeros());
+                self.overdraw_buffer[start..end].fill(0);
+            }
+        }
+    }
+
+    // Resets color and depth inside `viewport` only, for a view that shares
+    // the buffer with others. Unlike `clear` it always touches the whole
+    // rectangle, and a background drawn by another view stays drawn.
+    pub fn clear_region(&mut self, viewport: &Viewport) {
+        let background = self.background_linear();
+        let clear_depth = self.clear_depth();
+        let end_x = (viewport.x + viewport.width).min(self.width);
+        let end_y = (viewport.y + viewport.height).min(self.height);
+        if viewport.x >= end_x || viewport.y >= end_y {
+            return;
+        }
+
+        for y in viewport.y..end_y {
+            let start = y * self.width + viewport.x;
+            let end = y * self.width + end_x;
+            self.buffer[start..end].fill(self.background_color);
+            self.zbuffer[start..end].fill(clear_depth);
+            self.hdr_buffer[start..end].fill(background);
+            self.velocity_buffer[start..end].fill(Vec2::zeros());
+            self.overdraw_buffer[start..end].fill(0);
+        }
+        self.mark_dirty(viewport.x, viewport.y);
+        self.mark_dirty(end_x - 1, end_y - 1);
+    }
+
+    // Restricts surface writes to `viewport`; None lifts the restriction
+    pub fn set_scissor(&mut self, viewport: Option<Viewport>) {
+        self.scissor = viewport;
+    }
+
+    // Restricts surface writes to the rows of one progressive pass; None
+    // lifts the restriction
+    pub fn set_interlace(&mut self, interlace: Option<Interlace>) {
+        self.interlace = interlace;
+    }
+
+    // Inside the buffer, the scissor rectangle and the interlaced rows.
+    // Rasterizers check it before shading, not only before writing.
+    pub fn writable(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+            && self.scissor.is_none_or(|scissor| scissor.contains(x as f32, y as f32))
+            && self.interlace.is_none_or(|interlace| interlace.contains_row(y))
+    }
+
+    // Converts this frame's HDR pixels to `buffer`, dithered as `settings`
+    // says. Only pixels with geometry are touched, so the background keeps
+    // its exact color, unless a background was painted into the HDR buffer
+    // this frame. Overlays that write `buffer` directly must be drawn after this.
+    pub fn resolve(&mut self, settings: &ToneMapSettings) {
+        let frame = self.resolved_frames;
+        self.resolved_frames = self.resolved_frames.wrapping_add(1);
+        let Some((min_x, min_y, max_x, max_y)) = self.dirty else {
+            return;
+        };
+
+        // The highlight curve runs on the HDR buffer itself, so all that is
+        // left here is quantizing
+        let tonemapped = settings.operator == ToneMapOperator::Highlights;
+        if tonemapped {
+            self.tonemap(settings.exposure);
         }
-        for depth in self.zbuffer.iter_mut() {
-            *depth = f32::INFINITY;
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let index = y * self.width + x;
+                if self.background_drawn || self.zbuffer[index].is_finite() {
+                    let color = &self.hdr_buffer[index];
+                    self.buffer[index] = if tonemapped {
+                        settings.quantize_dithered(color, x, y, frame)
+                    } else {
+                        settings.apply_dithered(color, x, y, frame)
+                    };
+                }
+            }
         }
     }
 
+    // Scales this frame's HDR pixels by `exposure` and rolls them off with
+    // `compress_highlights`, in place and still in linear space. Values up
+    // to `HIGHLIGHT_KNEE` pass through; the result stays in [0, 1] until the
+    // next clear.
+    pub fn tonemap(&mut self, exposure: f32) {
+        let Some((min_x, min_y, max_x, max_y)) = self.dirty else {
+            return;
+        };
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let index = y * self.width + x;
+                if self.background_drawn || self.zbuffer[index].is_finite() {
+                    self.hdr_buffer[index] = compress_highlights(&(self.hdr_buffer[index] * exposure));
+                }
+            }
+        }
+    }
+
+    // Log-average luminance of the pixels drawn this frame, for auto exposure
+    pub fn average_luminance(&self) -> Option<f32> {
+        let (min_x, min_y, max_x, max_y) = self.dirty?;
+
+        let mut log_sum = 0.0;
+        let mut count = 0usize;
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let index = y * self.width + x;
+                if self.zbuffer[index].is_finite() {
+                    log_sum += (luminance(&self.hdr_buffer[index]) + 1e-4).ln();
+                    count += 1;
+                }
+            }
+        }
+
+        if count > 0 { Some((log_sum / count as f32).exp()) } else { None }
+    }
+
+    pub fn reversed_z(&self) -> bool {
+        self.reversed_z
+    }
+
+    // Switches the depth convention and resets the depth plane to match
+    pub fn set_reversed_z(&mut self, enabled: bool) {
+        self.reversed_z = enabled;
+        let clear_depth = self.clear_depth();
+        self.zbuffer.fill(clear_depth);
+        self.mark_all_dirty();
+    }
+
+    // Depth of an empty pixel: farther than anything
+    pub fn clear_depth(&self) -> f32 {
+        if self.reversed_z { f32::NEG_INFINITY } else { f32::INFINITY }
+    }
+
+    // Depth test: whether `depth` is in front of `than`. Reversed-Z keeps
+    // the later of two equal depths.
+    pub fn is_nearer(&self, depth: f32, than: f32) -> bool {
+        if self.reversed_z { depth >= than } else { depth < than }
+    }
+
+    // Moves `depth` toward the camera by `amount` (away if negative)
+    pub fn toward_camera(&self, depth: f32, amount: f32) -> f32 {
+        if self.reversed_z { depth + amount } else { depth - amount }
+    }
+
+    // Whether a stored depth belongs to a surface, as opposed to an empty
+    // pixel or one that only holds glow (see `add_hdr`)
+    pub fn is_surface(&self, depth: f32) -> bool {
+        depth.is_finite() && depth != self.glow_depth()
+    }
+
+    // Finite sentinel for pixels with color but no surface, so `resolve`
+    // converts them while any real surface still wins the depth test
+    fn glow_depth(&self) -> f32 {
+        if self.reversed_z { f32::MIN } else { f32::MAX }
+    }
+
+    // Anything writing to `buffer`/`zbuffer` directly must call this
+    pub fn mark_all_dirty(&mut self) {
+        if self.width > 0 && self.height > 0 {
+            self.dirty = Some((0, 0, self.width - 1, self.height - 1));
+        }
+    }
+
+    fn mark_dirty(&mut self, x: usize, y: usize) {
+        self.dirty = Some(match self.dirty {
+            Some((min_x, min_y, max_x, max_y)) => (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)),
+            None => (x, y, x, y),
+        });
+    }
+
     pub fn point(&mut self, x: usize, y: usize, depth: f32) {
-        if x < self.width && y < self.height {
+        if self.writable(x, y) {
             let index = y * self.width + x;
-            if self.zbuffer[index] > depth {
+            self.overdraw_buffer[index] = self.overdraw_buffer[index].saturating_add(1);
+            if self.is_nearer(depth, self.zbuffer[index]) {
                 self.buffer[index] = self.current_color;
                 self.zbuffer[index] = depth;
+                self.mark_dirty(x, y);
+            }
+        }
+    }
+
+    // Depth-tested write of a linear HDR color (components may exceed 1.0).
+    // Returns whether the pixel was written.
+    pub fn point_hdr(&mut self, x: usize, y: usize, depth: f32, color: Vec3) -> bool {
+        if self.writable(x, y) {
+            let index = y * self.width + x;
+            self.overdraw_buffer[index] = self.overdraw_buffer[index].saturating_add(1);
+            if self.is_nearer(depth, self.zbuffer[index]) {
+                self.hdr_buffer[index] = color;
+                self.zbuffer[index] = depth;
+                self.mark_dirty(x, y);
+                return true;
             }
         }
+        false
+    }
+
+    // Velocity of the surface that owns the pixel; call after a successful `point_hdr`
+    pub fn set_velocity(&mut self, x: usize, y: usize, velocity: Vec2) {
+        if self.writable(x, y) {
+            self.velocity_buffer[y * self.width + x] = velocity;
+        }
+    }
+
+    // Draws `text` at 1x with a darkened box behind it. Call after `resolve`.
+    pub fn draw_text(&mut self, x: isize, y: isize, text: &str, color: u32) {
+        self.draw_text_scaled(x, y, text, color, 1, true);
+    }
+
+    // Blits glyphs straight into `buffer` with its top-left corner at (x, y).
+    // `\n` starts a new line; pixels outside the framebuffer are skipped.
+    pub fn draw_text_scaled(&mut self, x: isize, y: isize, text: &str, color: u32, scale: usize, background: bool) {
+        let scale = scale.max(1);
+        let columns = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let rows = text.lines().count();
+        if columns == 0 {
+            return;
+        }
+
+        let padding = if background { (TEXT_BOX_PADDING * scale) as isize } else { 0 };
+        let box_min_x = x - padding;
+        let box_min_y = y - padding;
+        let box_max_x = x + (columns * GLYPH_WIDTH * scale) as isize + padding - 1;
+        let box_max_y = y + (rows * GLYPH_HEIGHT * scale) as isize + padding - 1;
+
+        // Nothing visible: leave the dirty region alone
+        if box_max_x < 0 || box_max_y < 0
+            || box_min_x >= self.width as isize || box_min_y >= self.height as isize
+        {
+            return;
+        }
+        let min_x = box_min_x.max(0) as usize;
+        let min_y = box_min_y.max(0) as usize;
+        let max_x = box_max_x.min(self.width as isize - 1) as usize;
+        let max_y = box_max_y.min(self.height as isize - 1) as usize;
+
+        if background {
+            // Halve every channel so the text reads on bright planets too
+            for py in min_y..=max_y {
+                for px in min_x..=max_x {
+                    let index = py * self.width + px;
+                    self.buffer[index] = (self.buffer[index] >> 1) & 0x7F7F7F;
+                }
+            }
+        }
+
+        for (row, line) in text.lines().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                let glyph_x = x + (column * GLYPH_WIDTH * scale) as isize;
+                let glyph_y = y + (row * GLYPH_HEIGHT * scale) as isize;
+                for (gy, bits) in glyph(c).iter().enumerate() {
+                    for gx in 0..GLYPH_WIDTH {
+                        if bits & (1 << gx) == 0 {
+                            continue;
+                        }
+                        for sy in 0..scale {
+                            for sx in 0..scale {
+                                let px = glyph_x + (gx * scale + sx) as isize;
+                                let py = glyph_y + (gy * scale + sy) as isize;
+                                if px >= 0 && py >= 0 && (px as usize) < self.width && (py as usize) < self.height {
+                                    self.buffer[py as usize * self.width + px as usize] = color;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // The next `clear` has to wipe the text as well
+        self.mark_dirty(min_x, min_y);
+        self.mark_dirty(max_x, max_y);
+    }
+
+    // Depth-tested blend of a partially covered pixel over what is already
+    // there. It doesn't write depth, so the fully covered pixel that owns
+    // this spot (if any) can still replace it later. Returns whether it blended.
+    pub fn blend_hdr(&mut self, x: usize, y: usize, depth: f32, color: Vec3, coverage: f32) -> bool {
+        if self.writable(x, y) {
+            let index = y * self.width + x;
+            self.overdraw_buffer[index] = self.overdraw_buffer[index].saturating_add(1);
+            if self.is_nearer(depth, self.zbuffer[index]) {
+                let existing = self.hdr_buffer[index];
+                self.hdr_buffer[index] = existing.lerp(&color, coverage.clamp(0.0, 1.0));
+                if !self.zbuffer[index].is_finite() {
+                    self.zbuffer[index] = self.glow_depth();
+                }
+                self.mark_dirty(x, y);
+                return true;
+            }
+        }
+        false
+    }
+
+    // Additive HDR write for glow layers. It never writes depth; background
+    // pixels get a finite sentinel so `resolve` still converts them.
+    pub fn add_hdr(&mut self, x: usize, y: usize, color: Vec3) {
+        if self.writable(x, y) {
+            let index = y * self.width + x;
+            self.hdr_buffer[index] += color;
+            if !self.zbuffer[index].is_finite() {
+                self.zbuffer[index] = self.glow_depth();
+            }
+            self.mark_dirty(x, y);
+        }
+    }
+
+    // Adds a color (0..1 per channel) to the already resolved `buffer`,
+    // saturating at white. For overlays drawn after `resolve`.
+    pub fn add_color(&mut self, x: usize, y: usize, color: &Vec3) {
+        if x < self.width && y < self.height {
+            let index = y * self.width + x;
+            let pixel = self.buffer[index];
+            let channel = |shift: u32, value: f32| {
+                let existing = (pixel >> shift) & 0xFF;
+                (existing + (value.max(0.0) * 255.0).round() as u32).min(0xFF) << shift
+            };
+            self.buffer[index] = channel(16, color.x) | channel(8, color.y) | channel(0, color.z);
+            self.mark_dirty(x, y);
+        }
+    }
+
+    // Anti-aliased line (Xiaolin Wu) blended into the resolved `buffer`.
+    // Coordinates are in pixels with centers at +0.5, so a horizontal line
+    // through pixel centers covers one row fully, like a plain line would.
+    // With `depth` (at `from` and `to`) pixels behind the z-buffer are
+    // skipped, without writing depth; HUD elements pass None.
+    pub fn draw_line_aa(&mut self, from: Vec2, to: Vec2, color: u32, depth: Option<(f32, f32)>) {
+        let (from_depth, to_depth) = depth.unwrap_or((0.0, 0.0));
+        let from = Vec3::new(from.x, from.y, from_depth);
+        let to = Vec3::new(to.x, to.y, to_depth);
+        // One pixel of slack so the fringe of a line along an edge survives
+        let Some((from, to)) = clip_to_screen(&(from.add_scalar(1.0)), &(to.add_scalar(1.0)), self.width as f32 + 2.0, self.height as f32 + 2.0) else {
+            return;
+        };
+        // Back to pixel-center coordinates: pixel i is centered at i
+        let shift = Vec3::new(1.5, 1.5, 1.0);
+        let (mut a, mut b) = (from - shift, to - shift);
+
+        let steep = (b.y - a.y).abs() > (b.x - a.x).abs();
+        if steep {
+            a = Vec3::new(a.y, a.x, a.z);
+            b = Vec3::new(b.y, b.x, b.z);
+        }
+        if a.x > b.x {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        let dx = b.x - a.x;
+        let gradient = if dx.abs() > f32::EPSILON { (b.y - a.y) / dx } else { 0.0 };
+        let depth_gradient = if dx.abs() > f32::EPSILON { (b.z - a.z) / dx } else { 0.0 };
+        let start = a.x.round() as i64;
+        let end = b.x.round() as i64;
+
+        for major in start..=end {
+            let minor = a.y + gradient * (major as f32 - a.x);
+            let z = a.z + depth_gradient * (major as f32 - a.x);
+            let depth = depth.map(|_| z);
+            let row = minor.floor();
+            let fraction = minor - row;
+            for (offset, coverage) in [(0, 1.0 - fraction), (1, fraction)] {
+                let other = row as i64 + offset;
+                let (x, y) = if steep { (other, major) } else { (major, other) };
+                self.blend_pixel(x, y, color, coverage, depth);
+            }
+        }
+    }
+
+    // Anti-aliased circle outline centered at (cx, cy) in pixels, blended
+    // into the resolved `buffer` for HUD use. Coverage falls off over one
+    // pixel on both sides of the `thickness`-wide ring.
+    pub fn draw_circle_aa(&mut self, cx: f32, cy: f32, r: f32, color: u32, thickness: f32) {
+        let half = thickness.max(0.0) / 2.0;
+        let outer = r + half + 1.0;
+        let min_x = (cx - outer).floor().max(0.0);
+        let min_y = (cy - outer).floor().max(0.0);
+        let max_x = (cx + outer).ceil().min(self.width as f32 - 1.0);
+        let max_y = (cy + outer).ceil().min(self.height as f32 - 1.0);
+        if max_x < min_x || max_y < min_y {
+            return;
+        }
+
+        for y in min_y as i64..=max_y as i64 {
+            for x in min_x as i64..=max_x as i64 {
+                let dx = x as f32 + 0.5 - cx;
+                let dy = y as f32 + 0.5 - cy;
+                let distance = ((dx * dx + dy * dy).sqrt() - r).abs();
+                // Thinner than a pixel: fainter instead of narrower
+                let coverage = (half + 0.5 - distance).clamp(0.0, 1.0) * thickness.min(1.0);
+                self.blend_pixel(x, y, color, coverage, None);
+            }
+        }
+    }
+
+    // Lerps `color` over the resolved pixel by `coverage`, behind the
+    // z-buffer test when a depth is given. Off-screen pixels are ignored.
+    fn blend_pixel(&mut self, x: i64, y: i64, color: u32, coverage: f32, depth: Option<f32>) {
+        if coverage <= 0.0 || x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let index = y * self.width + x;
+        if depth.is_some_and(|depth| !self.is_nearer(depth, self.zbuffer[index])) {
+            return;
+        }
+        self.buffer[index] = mix_rgb(self.buffer[index], color, coverage.min(1.0));
+        self.mark_dirty(x, y);
+    }
+
+    // Background color in linear RGB, the value `clear` fills the HDR buffer with
+    pub fn background_linear(&self) -> Vec3 {
+        let c = self.background_color;
+        Vec3::new(
+            ((c >> 16) & 0xFF) as f32 / 255.0,
+            ((c >> 8) & 0xFF) as f32 / 255.0,
+            (c & 0xFF) as f32 / 255.0,
+        )
+    }
+
+    // Replaces each resolved pixel with a gray of the same luminance. For
+    // the monochrome mode, after `resolve` and the post effects.
+    pub fn to_grayscale_in_place(&mut self) {
+        for pixel in self.buffer.iter_mut() {
+            let gray = (Color::from_hex(*pixel).luminance() * 255.0).round() as u32;
+            *pixel = (gray << 16) | (gray << 8) | gray;
+        }
+        self.mark_all_dirty();
+    }
+
+    // Called after painting a sky over the whole HDR buffer (no depth), so
+    // this frame's `resolve` tone maps the background pixels too
+    pub fn mark_background_drawn(&mut self) {
+        self.background_drawn = true;
+        self.mark_all_dirty();
+    }
+
+    pub fn background_drawn(&self) -> bool {
+        self.background_drawn
+    }
+
+    // Resolved 0xRRGGBB color at (x, y). Panics outside the framebuffer.
+    pub fn get_pixel(&self, x: usize, y: usize) -> u32 {
+        assert!(x < self.width && y < self.height, "pixel ({}, {}) outside {}x{}", x, y, self.width, self.height);
+        self.buffer[y * self.width + x]
+    }
+
+    // FNV-1a hash of the size and the resolved pixels. Identical frames hash
+    // the same on any machine; any change, even one bit, changes the hash.
+    pub fn checksum(&self) -> u64 {
+        const OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01B3;
+        let words = [self.width as u32, self.height as u32].into_iter().chain(self.buffer.iter().copied());
+        words.flat_map(u32::to_le_bytes).fold(OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+    }
+
+    pub fn background_color(&self) -> u32 {
+        self.background_color
     }
 
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
+        self.mark_all_dirty();
     }
 
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
 }
+
+// Per-channel lerp between two 0xRRGGBB colors
+pub(crate) fn mix_rgb(a: u32, b: u32, t: f32) -> u32 {
+    let channel = |shift: u32| {
+        let ca = ((a >> shift) & 0xFF) as f32;
+        let cb = ((b >> shift) & 0xFF) as f32;
+        ((ca + (cb - ca) * t).round() as u32) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
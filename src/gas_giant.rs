@@ -0,0 +1,180 @@
+use once_cell::sync::Lazy;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use crate::color::Color;
+use crate::planet_type::PlanetType;
+
+// Parámetros del shader común de los gigantes gaseosos. En un archivo de
+// escena los campos que falten toman el valor de Júpiter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GasGiantParams {
+    // Rampa de colores de las bandas, del valor de ruido 0 al 1
+    pub band_colors: Vec<Color>,
+    // Densidad de bandas en latitud (0 = sin bandas; Júpiter usa 10)
+    pub band_count: f32,
+    // Cuánto empujan las bandas hacia el color de tormenta
+    pub band_storm_mix: f32,
+    pub storm_color: Color,
+    // Cuánto empuja la turbulencia hacia el color de tormenta
+    pub turbulence: f32,
+    // Zoom del ruido de la turbulencia
+    pub turbulence_scale: f32,
+    // Probabilidad de que cada celda de la rejilla de tormentas tenga un óvalo
+    pub storm_probability: f32,
+    // Semilla de la posición de los óvalos
+    pub storm_seed: u32,
+    // Velocidad a la que se desplazan bandas y turbulencia
+    pub drift_speed: f32,
+    // Tormenta fija en la esfera. No toma el valor de Júpiter si falta:
+    // sin ella, una paleta propia no trae la Gran Mancha Roja.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub great_spot: Option<GreatSpot>,
+}
+
+// Un óvalo de `storm_color` en una latitud y longitud fijas del planeta, como
+// la Gran Mancha Roja. Está en espacio de modelo, así gira con el cuerpo.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GreatSpot {
+    // Centro en grados; latitud positiva al norte
+    pub latitude: f32,
+    pub longitude: f32,
+    // Semiejes del óvalo en grados, a lo largo del paralelo y del meridiano
+    pub half_width: f32,
+    pub half_height: f32,
+    // Grados de longitud que deriva por segundo
+    pub drift: f32,
+}
+
+impl GreatSpot {
+    pub fn jupiter() -> Self {
+        GreatSpot {
+            latitude: -22.0,
+            longitude: 20.0,
+            half_width: 15.0,
+            half_height: 8.0,
+            drift: 0.5,
+        }
+    }
+}
+
+impl Default for GasGiantParams {
+    fn default() -> Self {
+        GasGiantParams::jupiter()
+    }
+}
+
+pub static JUPITER: Lazy<GasGiantParams> = Lazy::new(GasGiantParams::jupiter);
+pub static SATURN: Lazy<GasGiantParams> = Lazy::new(GasGiantParams::saturn);
+pub static URANUS: Lazy<GasGiantParams> = Lazy::new(GasGiantParams::uranus);
+pub static NEPTUNE: Lazy<GasGiantParams> = Lazy::new(GasGiantParams::neptune);
+
+impl GasGiantParams {
+    pub fn jupiter() -> Self {
+        GasGiantParams {
+            band_colors: vec![Color::new(180, 140, 100), Color::new(255, 220, 180)],
+            band_count: 10.0,
+            band_storm_mix: 0.0,
+            storm_color: Color::new(255, 160, 120),
+            turbulence: 0.3,
+            turbulence_scale: 300.0,
+            storm_probability: 0.0,
+            storm_seed: 0,
+            drift_speed: 0.1,
+            great_spot: Some(GreatSpot::jupiter()),
+        }
+    }
+
+    pub fn saturn() -> Self {
+        GasGiantParams {
+            band_colors: vec![Color::new(255, 240, 200), Color::new(200, 180, 140)],
+            band_count: 12.0,
+            turbulence: 0.0,
+            drift_speed: 0.08,
+            great_spot: None,
+            ..GasGiantParams::jupiter()
+        }
+    }
+
+    pub fn uranus() -> Self {
+        GasGiantParams {
+            band_colors: vec![Color::new(150, 210, 230)],
+            band_count: 0.0,
+            storm_color: Color::new(180, 230, 255),
+            turbulence: 0.4,
+            turbulence_scale: 200.0,
+            drift_speed: 0.03,
+            great_spot: None,
+            ..GasGiantParams::jupiter()
+        }
+    }
+
+    pub fn neptune() -> Self {
+        GasGiantParams {
+            band_colors: vec![Color::new(30, 100, 200)],
+            band_count: 15.0,
+            band_storm_mix: 0.2,
+            storm_color: Color::new(100, 160, 255),
+            turbulence: 0.4,
+            turbulence_scale: 250.0,
+            drift_speed: 0.06,
+            great_spot: None,
+            ..GasGiantParams::jupiter()
+        }
+    }
+
+    // Parámetros por defecto de cada tipo; None si no es un gigante gaseoso
+    pub fn preset(planet_type: &PlanetType) -> Option<&'static GasGiantParams> {
+        match planet_type {
+            PlanetType::Jupiter | PlanetType::GasGiant => Some(&JUPITER),
+            PlanetType::Saturn => Some(&SATURN),
+            PlanetType::Uranus => Some(&URANUS),
+            PlanetType::Neptune => Some(&NEPTUNE),
+            _ => None,
+        }
+    }
+
+    // Gigante gaseoso inventado: la misma semilla siempre da el mismo.
+    // Los colores de la rampa son variaciones de un mismo tono base.
+    pub fn random(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let base = [rng.gen_range(60.0..230.0), rng.gen_range(60.0..230.0), rng.gen_range(60.0..230.0)];
+        let stops = rng.gen_range(2..=4);
+        let band_colors = (0..stops)
+            .map(|_| {
+                let shift: f32 = rng.gen_range(-50.0..50.0);
+                let channel = |c: f32, rng: &mut StdRng| (c + shift + rng.gen_range(-15.0..15.0)).clamp(0.0, 255.0) / 255.0;
+                Color::from_float(channel(base[0], &mut rng), channel(base[1], &mut rng), channel(base[2], &mut rng))
+            })
+            .collect();
+
+        GasGiantParams {
+            band_colors,
+            band_count: rng.gen_range(6.0..16.0),
+            band_storm_mix: rng.gen_range(0.0..0.2),
+            storm_color: Color::new(rng.gen_range(120..=255), rng.gen_range(120..=255), rng.gen_range(120..=255)),
+            turbulence: rng.gen_range(0.1..0.5),
+            turbulence_scale: rng.gen_range(150.0..350.0),
+            storm_probability: rng.gen_range(0.0..0.3),
+            storm_seed: rng.gen(),
+            drift_speed: rng.gen_range(0.02..0.12),
+            great_spot: None,
+        }
+    }
+
+    // Color de la rampa para un valor en [0, 1]
+    pub fn band_color(&self, value: f32) -> Color {
+        match self.band_colors.len() {
+            0 => Color::black(),
+            1 => self.band_colors[0],
+            len => {
+                let scaled = value.clamp(0.0, 1.0) * (len - 1) as f32;
+                let index = (scaled.floor() as usize).min(len - 2);
+                self.band_colors[index].lerp(&self.band_colors[index + 1], scaled - index as f32)
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use nalgebra_glm::Vec3;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::f32::consts::PI;
+use crate::CelestialBody;
+use crate::planet_type::PlanetType;
+use crate::gas_giant::GasGiantParams;
+use crate::rings::RingSystem;
+use crate::atmosphere::AtmosphereParams;
+
+const SUN_SCALE: f32 = 2.0;
+const MIN_ORBIT_GAP: f32 = 2.0;
+
+const ROCKY_TYPES: [PlanetType; 4] = [
+    PlanetType::Mercury,
+    PlanetType::Venus,
+    PlanetType::Earth,
+    PlanetType::Mars,
+];
+
+// GasGiant recibe una paleta inventada a partir de la semilla
+const GAS_GIANT_TYPES: [PlanetType; 5] = [
+    PlanetType::Jupiter,
+    PlanetType::Saturn,
+    PlanetType::Uranus,
+    PlanetType::Neptune,
+    PlanetType::GasGiant,
+];
+
+// Genera un sistema solar a partir de una semilla: la misma semilla siempre
+// produce el mismo sistema. El Sol va primero y los planetas se colocan en
+// órbitas crecientes separadas lo suficiente para que nunca se toquen.
+pub fn generate_system(seed: u64, count: usize) -> Vec<CelestialBody> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut bodies = Vec::with_capacity(count + 1);
+
+    bodies.push(CelestialBody {
+        position: Vec3::new(0.0, 0.0, 0.0),
+        scale: SUN_SCALE,
+        rotation: Vec3::new(0.0, 0.0, 0.0),
+        shader_type: PlanetType::Sun,
+        rotation_speed: 0.6,
+        orbit_radius: 0.0,
+        orbit_speed: 0.0,
+        orbit_phase: 0.0,
+        ..Default::default()
+    });
+
+    let mut previous_edge = SUN_SCALE;
+
+    for i in 0..count {
+        // Probabilidad de gigante gaseoso crece con la distancia al Sol
+        let distance_factor = if count > 1 { i as f32 / (count - 1) as f32 } else { 0.0 };
+        let is_gas_giant = rng.gen::<f32>() < distance_factor;
+
+        // Solo los planetas rocosos tienen relieve
+        let (shader_type, scale, displacement) = if is_gas_giant {
+            (GAS_GIANT_TYPES[rng.gen_range(0..GAS_GIANT_TYPES.len())], rng.gen_range(0.9..1.6), 0.0)
+        } else {
+            (ROCKY_TYPES[rng.gen_range(0..ROCKY_TYPES.len())], rng.gen_range(0.3..0.8), rng.gen_range(0.01..0.04))
+        };
+
+        // El radio de la órbita deja un hueco mínimo respecto a la anterior
+        let orbit_radius = previous_edge + MIN_ORBIT_GAP + scale + rng.gen_range(0.0..4.0);
+        previous_edge = orbit_radius + scale;
+
+        // Órbitas más lejanas son más lentas (aproximación de Kepler)
+        let orbit_speed = 3.0 / orbit_radius.powf(1.5) * rng.gen_range(0.8..1.2);
+        let orbit_phase = rng.gen_range(0.0..2.0 * PI);
+        let rotation_speed = rng.gen_range(0.3..1.8);
+        let axial_tilt = rng.gen_range(0.0..30.0_f32).to_radians();
+        let gas_giant = matches!(shader_type, PlanetType::GasGiant).then(|| GasGiantParams::random(rng.gen()));
+
+        bodies.push(CelestialBody {
+            position: Vec3::new(
+                orbit_radius * orbit_phase.cos(),
+                0.0,
+                orbit_radius * orbit_phase.sin(),
+            ),
+            scale,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type,
+            rotation_speed,
+            orbit_radius,
+            orbit_speed,
+            orbit_phase,
+            displacement,
+            axial_tilt,
+            gas_giant,
+            rings: RingSystem::preset(&shader_type),
+            atmosphere: AtmosphereParams::preset(&shader_type),
+            ..Default::default()
+        });
+    }
+
+    bodies
+}
@@ -0,0 +1,77 @@
+use std::path::Path;
+use image::{ImageBuffer, Rgb, RgbImage};
+use crate::error::Error;
+use crate::framebuffer::Framebuffer;
+
+// Imagen de referencia guardada como PNG, para comparar frames en las
+// pruebas de regresión de los shaders
+pub struct GoldenImage {
+    pub width: usize,
+    pub height: usize,
+    // 0xRRGGBB por píxel, fila a fila como `Framebuffer::buffer`
+    pub pixels: Vec<u32>,
+}
+
+// Resultado de comparar un frame con su imagen de referencia
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDiff {
+    // Si los tamaños no coinciden no se compara ningún píxel
+    pub same_size: bool,
+    // Píxeles con algún canal más allá de la tolerancia
+    pub differing: usize,
+    // Mayor diferencia en un canal, de 0 a 255
+    pub max_difference: u8,
+    // Primer píxel distinto, para empezar a buscar
+    pub first_difference: Option<(usize, usize)>,
+}
+
+impl ImageDiff {
+    pub fn matches(&self) -> bool {
+        self.same_size && self.differing == 0
+    }
+}
+
+impl GoldenImage {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let image = image::open(path)
+            .map_err(|source| Error::Image { path: path.to_path_buf(), source })?
+            .to_rgb8();
+        let pixels = image.pixels()
+            .map(|Rgb([r, g, b])| ((*r as u32) << 16) | ((*g as u32) << 8) | *b as u32)
+            .collect();
+        Ok(GoldenImage { width: image.width() as usize, height: image.height() as usize, pixels })
+    }
+
+    // Guarda el `buffer` ya resuelto del framebuffer como nueva referencia
+    pub fn save(framebuffer: &Framebuffer, path: &Path) -> Result<(), Error> {
+        let image: RgbImage = ImageBuffer::from_fn(framebuffer.width as u32, framebuffer.height as u32, |x, y| {
+            let pixel = framebuffer.get_pixel(x as usize, y as usize);
+            Rgb([(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+        });
+        image.save(path).map_err(|source| Error::Image { path: path.to_path_buf(), source })
+    }
+
+    // Compara canal por canal: diferencias de hasta `tolerance` (de 0 a 255)
+    // no cuentan, para absorber el redondeo de otra máquina o compilador
+    pub fn compare(&self, framebuffer: &Framebuffer, tolerance: u8) -> ImageDiff {
+        let mut diff = ImageDiff { same_size: true, differing: 0, max_difference: 0, first_difference: None };
+        if self.width != framebuffer.width || self.height != framebuffer.height {
+            diff.same_size = false;
+            return diff;
+        }
+
+        for (index, (&expected, &actual)) in self.pixels.iter().zip(&framebuffer.buffer).enumerate() {
+            let difference = [16, 8, 0]
+                .map(|shift| ((expected >> shift) & 0xFF).abs_diff((actual >> shift) & 0xFF) as u8)
+                .into_iter()
+                .max()
+                .unwrap_or(0);
+            diff.max_difference = diff.max_difference.max(difference);
+            if difference > tolerance {
+                diff.differing += 1;
+                diff.first_difference.get_or_insert((index % self.width, index / self.width));
+            }
+        }
+        diff
+    }
+}
@@ -0,0 +1,101 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::{mix_rgb, Framebuffer};
+use crate::line::project_point;
+use crate::Uniforms;
+
+// Radio de la zona deformada, en radios del horizonte (espacio de mundo)
+const LENS_RADIUS: f32 = 4.0;
+// Radio del anillo de Einstein, en radios del horizonte en pantalla
+const EINSTEIN_RADIUS: f32 = 1.5;
+// Semiancho de la banda del anillo de Einstein, en radios del horizonte en pantalla
+const EINSTEIN_BAND: f32 = 0.12;
+// Cuánto pesa la imagen reflejada en el centro de la banda
+const EINSTEIN_MIX: f32 = 0.8;
+
+// Aproximación en pantalla de la lente gravitacional de un agujero negro.
+// Se aplica sobre el buffer de 8 bits ya resuelto, después de dibujar todos
+// los cuerpos, y lee de una copia para no deformar lo ya deformado.
+#[derive(Default)]
+pub struct GravitationalLens {
+    source: Vec<u32>,
+}
+
+impl GravitationalLens {
+    // Cada píxel alrededor del horizonte toma el color de un punto más alejado
+    // del centro, desplazado en proporción a 1/d; cerca del radio de Einstein
+    // se mezcla además la imagen del lado opuesto. Lo que está delante del
+    // agujero negro no se deforma.
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, position: &Vec3, scale: f32) {
+        let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+        let Some(center) = project_point(position, &view_projection, &uniforms.viewport_matrix) else {
+            return;
+        };
+
+        // Radio del horizonte en pantalla con un punto de su contorno
+        let view = &uniforms.view_matrix;
+        let right = Vec3::new(view[(0, 0)], view[(0, 1)], view[(0, 2)]);
+        let Some(edge) = project_point(&(position + right * scale), &view_projection, &uniforms.viewport_matrix) else {
+            return;
+        };
+        let horizon = (edge.xy() - center.xy()).norm();
+        if horizon < 0.5 {
+            return;
+        }
+        let lens_radius = horizon * LENS_RADIUS;
+        let einstein_radius = horizon * EINSTEIN_RADIUS;
+        // Desplazamiento k / d, con k tal que el anillo de Einstein cae en d = sqrt(k)
+        let strength = einstein_radius * einstein_radius;
+
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        let min_x = (center.x - lens_radius).floor().max(0.0) as usize;
+        let min_y = (center.y - lens_radius).floor().max(0.0) as usize;
+        let max_x = (center.x + lens_radius).ceil().min(width as f32 - 1.0);
+        let max_y = (center.y + lens_radius).ceil().min(height as f32 - 1.0);
+        if max_x < 0.0 || max_y < 0.0 || min_x >= width || min_y >= height {
+            return;
+        }
+        let (max_x, max_y) = (max_x as usize, max_y as usize);
+
+        self.source.clear();
+        self.source.extend_from_slice(&framebuffer.buffer);
+
+        let sample = |x: f32, y: f32| -> usize {
+            let x = x.round().clamp(0.0, (width - 1) as f32) as usize;
+            let y = y.round().clamp(0.0, (height - 1) as f32) as usize;
+            y * width + x
+        };
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let dx = x as f32 + 0.5 - center.x;
+                let dy = y as f32 + 0.5 - center.y;
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance < horizon || distance >= lens_radius {
+                    continue;
+                }
+                let index = y * width + x;
+                if framebuffer.is_nearer(framebuffer.zbuffer[index], center.z) {
+                    continue;
+                }
+
+                // Se apaga hacia el borde de la lente para que no haya corte
+                let fade = (1.0 - distance / lens_radius).powi(2);
+                let offset = strength / distance * fade;
+                let (dir_x, dir_y) = (dx / distance, dy / distance);
+                let reach = distance + offset;
+                let mut color = self.source[sample(center.x + dir_x * reach, center.y + dir_y * reach)];
+
+                let band = 1.0 - (distance - einstein_radius).abs() / (horizon * EINSTEIN_BAND);
+                if band > 0.0 {
+                    let mirrored = self.source[sample(center.x - dir_x * reach, center.y - dir_y * reach)];
+                    color = mix_rgb(color, mirrored, band * EINSTEIN_MIX);
+                }
+                framebuffer.buffer[index] = color;
+            }
+        }
+
+        // Píxeles del fondo cambiaron: el próximo `clear` tiene que alcanzarlos
+        framebuffer.mark_all_dirty();
+    }
+}
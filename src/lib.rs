@@ -0,0 +1,1054 @@
+use nalgebra_glm::{Vec3, Vec4, Mat4, look_at, perspective, ortho};
+use std::f32::consts::PI;
+use serde::{Deserialize, Serialize};
+
+pub mod framebuffer;
+pub mod triangle;
+pub mod vertex;
+pub mod obj;
+pub mod mesh;
+pub mod color;
+pub mod fragment;
+pub mod shaders;
+pub mod lighting;
+pub mod camera;
+pub mod planet_type;
+pub mod generator;
+pub mod simulation;
+pub mod picking;
+pub mod noise_utils;
+pub mod scene;
+pub mod bookmarks;
+pub mod shadows;
+pub mod tonemap;
+pub mod rings;
+pub mod quality;
+pub mod font;
+pub mod dof;
+pub mod gas_giant;
+pub mod terrestrial;
+pub mod custom_planet;
+pub mod flare;
+pub mod assets;
+pub mod motion_blur;
+pub mod line;
+pub mod tour;
+pub mod lensing;
+pub mod debug_view;
+pub mod chase;
+pub mod craters;
+pub mod background;
+pub mod skybox;
+pub mod error;
+pub mod prominences;
+pub mod outline;
+pub mod shader_lod;
+pub mod golden;
+pub mod app_state;
+pub mod visibility;
+pub mod scene_watch;
+pub mod viewport;
+pub mod turntable;
+pub mod progressive;
+pub mod atmosphere;
+pub mod console;
+
+use framebuffer::Framebuffer;
+use vertex::Vertex;
+use triangle::triangle;
+use fragment::Fragment;
+use shaders::{vertex_shader, fragment_shader, fragment_opacity, emission, atmosphere_shader, prominence_shader};
+use lighting::{Light, DEFAULT_AMBIENT, light_color};
+use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
+use planet_type::PlanetType;
+use shadows::eclipse_light;
+use noise_utils::NoiseCache;
+use gas_giant::GasGiantParams;
+use terrestrial::TerrestrialParams;
+use rings::RingSystem;
+use atmosphere::AtmosphereParams;
+use picking::intersect_sphere;
+use motion_blur::screen_velocity;
+use line::draw_line;
+use shader_lod::ShaderLod;
+use debug_view::{normal_color, wireframe_color, RenderMode};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CelestialBody {
+    pub position: Vec3,
+    pub scale: f32,
+    pub rotation: Vec3,
+    pub shader_type: PlanetType,
+    pub rotation_speed: f32,
+    pub orbit_radius: f32,
+    pub orbit_speed: f32,
+    pub orbit_phase: f32,
+    // Giro y estado anterior: salen del tiempo de simulación, no se guardan
+    #[serde(skip)]
+    pub spin: f32,
+    #[serde(skip)]
+    pub previous_spin: f32,
+    #[serde(skip)]
+    pub previous_position: Vec3,
+    pub displacement: f32,
+    // Inclinación fija del eje de rotación, en radianes (rotación en Z)
+    pub axial_tilt: f32,
+    // Paleta propia de gigante gaseoso; sin ella se usa la del tipo
+    pub gas_giant: Option<GasGiantParams>,
+    // Paleta propia de planeta terrestre (Tierra o `Terrestrial`); sin ella,
+    // la de la Tierra
+    pub terrestrial: Option<TerrestrialParams>,
+    // OBJ propio, relativo a la carpeta de assets (p. ej. "models/asteroide.obj");
+    // sin él se usa la esfera
+    pub mesh: Option<String>,
+    // Anillos que se dibujan alrededor del cuerpo, con su misma matriz de modelo
+    pub rings: Option<RingSystem>,
+    // Cáscara atmosférica que se suma alrededor del cuerpo (ver `render_atmosphere`)
+    pub atmosphere: Option<AtmosphereParams>,
+    // Se dibuja en el pase transparente (ver `Scene::render`) aunque su
+    // shader sea opaco, p. ej. una nube de gas. Los anillos y las
+    // atmósferas van ahí siempre.
+    #[serde(default)]
+    pub is_transparent: bool,
+    // Con false no se dibuja, no proyecta sombra y no se puede seleccionar.
+    // Una estrella oculta sigue iluminando (ver `visibility`).
+    #[serde(default = "visible_by_default")]
+    pub visible: bool,
+}
+
+// Las sesiones guardadas antes de que existiera `visible` lo muestran todo
+fn visible_by_default() -> bool {
+    true
+}
+
+impl Default for CelestialBody {
+    fn default() -> Self {
+        CelestialBody {
+            position: Vec3::new(0.0, 0.0, 0.0),
+            scale: 1.0,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Sun,
+            rotation_speed: 0.6,
+            orbit_radius: 0.0,
+            orbit_speed: 0.0,
+            orbit_phase: 0.0,
+            spin: 0.0,
+            previous_spin: 0.0,
+            previous_position: Vec3::new(0.0, 0.0, 0.0),
+            displacement: 0.0,
+            axial_tilt: 0.0,
+            gas_giant: None,
+            terrestrial: None,
+            mesh: None,
+            rings: None,
+            atmosphere: None,
+            is_transparent: false,
+            visible: true,
+        }
+    }
+}
+
+pub struct Uniforms {
+    pub model_matrix: Mat4,
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    pub time: f32,
+    pub noise: FastNoiseLite,
+    // Semilla de --seed; cambia también los shaders aleatorios. Con 0 los
+    // ruidos son los de siempre
+    pub seed: u64,
+    pub displacement: f32,
+    // Se resta a la profundidad de cada fragmento antes del test de profundidad:
+    // con un valor positivo la superficie gana frente a otra coplanar (anillos, calcas)
+    pub depth_bias: f32,
+    // Modelo-vista-proyección del cuerpo en el frame anterior; con Some el
+    // rasterizador escribe la velocidad de cada píxel para el desenfoque de movimiento
+    pub previous_mvp: Option<Mat4>,
+    // Con false todos los fragmentos se mezclan con lo que hay debajo sin
+    // escribir profundidad, como en el pase transparente
+    pub depth_write: bool,
+    // Paleta del gigante gaseoso que se está dibujando, si trae una propia
+    pub gas_giant: Option<GasGiantParams>,
+    // Paleta del planeta terrestre que se está dibujando, si trae una propia
+    pub terrestrial: Option<TerrestrialParams>,
+    // Anillos del cuerpo que se está dibujando; el shader de anillos lee sus bandas
+    pub rings: Option<RingSystem>,
+    // Estación del cuerpo que se está dibujando según `season`: 1 en pleno
+    // verano del hemisferio norte, -1 en pleno invierno
+    pub season: f32,
+    pub terrain_octaves: i32,
+    pub shading: ShadingMode,
+    // Posición del Sol en espacio de mundo, para efectos que dependen de su dirección
+    pub sun_position: Vec3,
+    // Luces que iluminan los planetas: primero las estrellas de la escena y
+    // luego, si está activa, la de relleno (ver `lighting::apply_lighting`)
+    pub lights: Vec<Light>,
+    // Luz que llega a todas partes, de 0 a 1, para que el lado nocturno no
+    // quede negro del todo
+    pub ambient: f32,
+    // Con true la escena añade a `lights` una luz tenue desde la cámara
+    pub fill_light: bool,
+    // Posición del ojo en espacio de mundo, para efectos que dependen del ángulo de vista
+    pub camera_position: Vec3,
+    // Cuánto oscurecen las sombras de las nubes la superficie de la Tierra (0 = sin sombras)
+    pub cloud_shadow_strength: f32,
+    // Con Some, los shaders leen el ruido 3D de la caché en lugar de calcularlo
+    pub noise_cache: Option<NoiseCache>,
+    // Plano de corte (ax + by + cz + d) en espacio de mundo; se descarta lo que queda detrás
+    pub clip_plane: Option<Vec4>,
+    // Parámetros con los que se reconstruye `projection_matrix`
+    pub projection: ProjectionSettings,
+    pub lightning: LightningSettings,
+}
+
+// Trabajo hecho por cada etapa del pipeline; se acumula por frame para
+// --bench-frames y las vistas de depuración (F4)
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RenderStats {
+    // Pasados por el vertex shader
+    pub vertices: usize,
+    // Ensamblados, incluidos los descartados
+    pub triangles: usize,
+    // Descartados: de espaldas (atmósferas) o sin ningún fragmento, porque
+    // quedan fuera de la pantalla o son degenerados
+    pub culled_triangles: usize,
+    pub fragments: usize,
+    // Llamadas al fragment shader; menos que `fragments` con `ShaderLod`
+    pub shaded_fragments: usize,
+    // Pasaron el test de profundidad (o el de oclusión, en las atmósferas)
+    pub depth_passed: usize,
+    // Se quedaron con el píxel: color y profundidad. Los bordes
+    // antialiasados y las atmósferas sólo se mezclan con lo que hay.
+    pub pixels_written: usize,
+}
+
+impl std::ops::AddAssign for RenderStats {
+    fn add_assign(&mut self, other: RenderStats) {
+        self.vertices += other.vertices;
+        self.triangles += other.triangles;
+        self.culled_triangles += other.culled_triangles;
+        self.fragments += other.fragments;
+        self.shaded_fragments += other.shaded_fragments;
+        self.depth_passed += other.depth_passed;
+        self.pixels_written += other.pixels_written;
+    }
+}
+
+// Relámpagos en el lado nocturno de Venus
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LightningSettings {
+    // Destellos por segundo en todo el planeta
+    pub frequency: f32,
+    // Multiplicador del color del destello
+    pub brightness: f32,
+    // Cuánto dura cada destello, en frames de 1/60 s
+    pub duration_frames: f32,
+}
+
+impl Default for LightningSettings {
+    fn default() -> Self {
+        LightningSettings {
+            frequency: 1.5,
+            brightness: 1.0,
+            duration_frames: 6.0,
+        }
+    }
+}
+
+// Límites del campo de visión vertical ajustable con , y .
+pub const MIN_FOV_DEGREES: f32 = 10.0;
+pub const MAX_FOV_DEGREES: f32 = 120.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ProjectionMode {
+    Perspective,
+    Orthographic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProjectionSettings {
+    pub fov_degrees: f32,
+    pub near: f32,
+    pub far: f32,
+    pub mode: ProjectionMode,
+    // Mitad de la altura visible en modo ortográfico, en unidades de mundo
+    pub ortho_half_height: f32,
+    // Profundidad invertida: near -> 1 y far -> 0. Con floats la precisión se
+    // concentra cerca de 0, justo donde el mapeo normal la pierde (lo lejano).
+    // Debe coincidir con `Framebuffer::reversed_z`; ver `set_reversed_z`.
+    pub reversed_z: bool,
+}
+
+impl Default for ProjectionSettings {
+    fn default() -> Self {
+        ProjectionSettings {
+            fov_degrees: 45.0,
+            near: 0.1,
+            far: 1000.0,
+            mode: ProjectionMode::Perspective,
+            ortho_half_height: 30.0,
+            reversed_z: false,
+        }
+    }
+}
+
+impl ProjectionSettings {
+    pub fn matrix(&self, width: f32, height: f32) -> Mat4 {
+        let aspect_ratio = width / height;
+        match self.mode {
+            ProjectionMode::Perspective => {
+                // nalgebra-glm recibe el aspecto antes que el FOV vertical
+                let mut matrix = perspective(aspect_ratio, self.fov_degrees * PI / 180.0, self.near, self.far);
+                if self.reversed_z {
+                    // z_ndc = near / (far - near) * (far / d - 1), con d = -z de vista
+                    let (near, far) = (self.near, self.far);
+                    matrix[(2, 2)] = near / (far - near);
+                    matrix[(2, 3)] = near * far / (far - near);
+                }
+                matrix
+            }
+            ProjectionMode::Orthographic => {
+                // Profundidad simétrica alrededor de la cámara: lo que queda
+                // detrás del ojo también entra en el z-buffer
+                let half_width = self.ortho_half_height * aspect_ratio;
+                let mut matrix = ortho(
+                    -half_width,
+                    half_width,
+                    -self.ortho_half_height,
+                    self.ortho_half_height,
+                    -self.far,
+                    self.far,
+                );
+                if self.reversed_z {
+                    // d = -far -> 1 y d = far -> 0
+                    matrix[(2, 2)] = 0.5 / self.far;
+                    matrix[(2, 3)] = 0.5;
+                }
+                matrix
+            }
+        }
+    }
+
+    // Distancia a la cámara a partir de la profundidad del z-buffer (z en NDC)
+    pub fn linear_depth(&self, depth: f32) -> f32 {
+        let (near, far) = (self.near, self.far);
+        match (self.mode, self.reversed_z) {
+            (ProjectionMode::Perspective, false) => 2.0 * far * near / ((far + near) - depth * (far - near)),
+            (ProjectionMode::Perspective, true) => far * near / (depth * (far - near) + near),
+            // Con profundidad simétrica en [-far, far] la relación es lineal
+            (ProjectionMode::Orthographic, false) => depth * far,
+            (ProjectionMode::Orthographic, true) => far * (1.0 - 2.0 * depth),
+        }
+    }
+
+    pub fn adjust_fov(&mut self, delta_degrees: f32) {
+        self.fov_degrees = (self.fov_degrees + delta_degrees).clamp(MIN_FOV_DEGREES, MAX_FOV_DEGREES);
+    }
+}
+
+// Mitad de la altura ortográfica para que quepa todo el sistema (con margen)
+pub fn system_extent(bodies: &[CelestialBody]) -> f32 {
+    let extent = bodies.iter()
+        .map(|body| body.position.norm().max(body.orbit_radius) + body.scale)
+        .fold(0.0, f32::max);
+    extent * 1.1
+}
+
+// Estación del hemisferio norte según hacia dónde apunta el eje de rotación
+// (el +Y de `model_matrix`) respecto al Sol: 1 cuando el polo norte mira al
+// Sol, -1 cuando le da la espalda y 0 en los equinoccios. Sale de un producto
+// escalar, así que varía sin saltos a lo largo de la órbita.
+pub fn season(model_matrix: &Mat4, axial_tilt: f32, sun_position: &Vec3) -> f32 {
+    let max_declination = axial_tilt.sin().abs();
+    let position = (model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+    let north = (model_matrix * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz();
+    let to_sun = sun_position - position;
+    if max_declination <= f32::EPSILON || north.norm() <= f32::EPSILON || to_sun.norm() <= f32::EPSILON {
+        return 0.0;
+    }
+    (north.normalize().dot(&to_sun.normalize()) / max_declination).clamp(-1.0, 1.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ShadingMode {
+    Smooth,
+    Flat,
+}
+
+// Semilla de cada ruido: con `seed` 0 la de siempre; con otra una mezcla de
+// las dos, para que los ruidos sigan siendo distintos entre sí
+fn noise_seed(base: i32, seed: u64) -> i32 {
+    if seed == 0 {
+        base
+    } else {
+        ((seed ^ base as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as i32
+    }
+}
+
+pub fn create_noise(seed: u64) -> FastNoiseLite {
+    create_cloud_noise(seed)
+}
+
+pub fn create_cloud_noise(seed: u64) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(noise_seed(1337, seed));
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise
+}
+
+pub fn create_cell_noise(seed: u64) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(noise_seed(1337, seed));
+    noise.set_noise_type(Some(NoiseType::Cellular));
+    noise.set_frequency(Some(0.1));
+    noise
+}
+
+pub fn create_ground_noise(seed: u64) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(noise_seed(1337, seed));
+    
+   
+    noise.set_noise_type(Some(NoiseType::Cellular)); 
+    noise.set_fractal_type(Some(FractalType::FBm)); 
+    noise.set_fractal_octaves(Some(5));              
+    noise.set_fractal_lacunarity(Some(2.0));         
+    noise.set_fractal_gain(Some(0.5));               
+    noise.set_frequency(Some(0.05));                 
+
+    noise
+}
+
+pub fn create_lava_noise(seed: u64) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(noise_seed(42, seed));
+    
+ 
+    noise.set_noise_type(Some(NoiseType::Perlin));  
+    noise.set_fractal_type(Some(FractalType::FBm)); 
+    noise.set_fractal_octaves(Some(6));            
+    noise.set_fractal_lacunarity(Some(2.0));       
+    noise.set_fractal_gain(Some(0.5));              
+    noise.set_frequency(Some(0.002));                
+    
+    noise
+}
+
+pub fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+    let (sin_x, cos_x) = rotation.x.sin_cos();
+    let (sin_y, cos_y) = rotation.y.sin_cos();
+    let (sin_z, cos_z) = rotation.z.sin_cos();
+
+    let rotation_matrix_x = Mat4::new(
+        1.0,  0.0,    0.0,   0.0,
+        0.0,  cos_x, -sin_x, 0.0,
+        0.0,  sin_x,  cos_x, 0.0,
+        0.0,  0.0,    0.0,   1.0,
+    );
+
+    let rotation_matrix_y = Mat4::new(
+        cos_y,  0.0,  sin_y, 0.0,
+        0.0,    1.0,  0.0,   0.0,
+        -sin_y, 0.0,  cos_y, 0.0,
+        0.0,    0.0,  0.0,   1.0,
+    );
+
+    let rotation_matrix_z = Mat4::new(
+        cos_z, -sin_z, 0.0, 0.0,
+        sin_z,  cos_z, 0.0, 0.0,
+        0.0,    0.0,  1.0, 0.0,
+        0.0,    0.0,  0.0, 1.0,
+    );
+
+    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+
+    let transform_matrix = Mat4::new(
+        scale, 0.0,   0.0,   translation.x,
+        0.0,   scale, 0.0,   translation.y,
+        0.0,   0.0,   scale, translation.z,
+        0.0,   0.0,   0.0,   1.0,
+    );
+
+    transform_matrix * rotation_matrix
+}
+
+
+pub fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
+    look_at(&eye, &center, &up)
+}
+
+pub fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
+    ProjectionSettings::default().matrix(window_width, window_height)
+}
+
+pub fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        width / 2.0, 0.0, 0.0, width / 2.0,
+        0.0, -height / 2.0, 0.0, height / 2.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0
+    )
+}
+
+// Uniforms iniciales para un framebuffer de `width` x `height`; la cámara
+// y el tiempo los pone cada frame la escena
+pub fn create_uniforms(width: usize, height: usize, seed: u64) -> Uniforms {
+    let projection_matrix = ProjectionSettings::default().matrix(width as f32, height as f32);
+    let viewport_matrix = create_viewport_matrix(width as f32, height as f32);
+    Uniforms {
+        model_matrix: Mat4::identity(),
+        view_matrix: Mat4::identity(),
+        projection_matrix,
+        viewport_matrix,
+        time: 0.0,
+        noise: create_noise(seed),
+        seed,
+        displacement: 0.0,
+        depth_bias: 0.0,
+        previous_mvp: None,
+        depth_write: true,
+        gas_giant: None,
+        terrestrial: None,
+        rings: None,
+        season: 0.0,
+        terrain_octaves: 1,
+        shading: ShadingMode::Smooth,
+        sun_position: Vec3::new(0.0, 0.0, 0.0),
+        lights: Vec::new(),
+        ambient: DEFAULT_AMBIENT,
+        fill_light: false,
+        camera_position: Vec3::new(0.0, 0.0, 0.0),
+        cloud_shadow_strength: 0.35,
+        noise_cache: None,
+        clip_plane: None,
+        projection: ProjectionSettings::default(),
+        lightning: LightningSettings::default(),
+    }
+}
+
+// Ajusta framebuffer, proyección y viewport a un nuevo tamaño
+pub fn resize_viewport(framebuffer: &mut Framebuffer, uniforms: &mut Uniforms, width: usize, height: usize) {
+    framebuffer.resize(width, height);
+    update_projection_matrix(uniforms, width, height);
+    uniforms.viewport_matrix = create_viewport_matrix(width as f32, height as f32);
+}
+
+// Activa o desactiva reversed-Z en el framebuffer y la proyección a la vez;
+// por separado el test de profundidad quedaría al revés
+pub fn set_reversed_z(framebuffer: &mut Framebuffer, uniforms: &mut Uniforms, enabled: bool) {
+    framebuffer.set_reversed_z(enabled);
+    uniforms.projection.reversed_z = enabled;
+    update_projection_matrix(uniforms, framebuffer.width, framebuffer.height);
+}
+
+// Reconstruye la proyección tras cambiar `uniforms.projection`
+pub fn update_projection_matrix(uniforms: &mut Uniforms, width: usize, height: usize) {
+    uniforms.projection_matrix = uniforms.projection.matrix(width as f32, height as f32);
+}
+
+// Normal de la cara a partir de las posiciones en espacio de mundo,
+// orientada hacia el mismo lado que las normales de los vértices
+pub fn face_normal(tri: &[Vertex], model_matrix: &Mat4) -> Vec3 {
+    let world = |v: &Vertex| (model_matrix * Vec4::new(v.position.x, v.position.y, v.position.z, 1.0)).xyz();
+    let (a, b, c) = (world(&tri[0]), world(&tri[1]), world(&tri[2]));
+
+    let normal = (b - a).cross(&(c - a));
+    let vertex_normals = tri[0].transformed_normal + tri[1].transformed_normal + tri[2].transformed_normal;
+    let normal = if normal.dot(&vertex_normals) < 0.0 { -normal } else { normal };
+
+    if normal.norm() > f32::EPSILON { normal.normalize() } else { vertex_normals.normalize() }
+}
+
+// Las caras interiores que deja ver el corte se ven apagadas y un poco
+// más lejos que las exteriores, para que nunca peleen en el z-buffer
+const CUTAWAY_INTERIOR_LIGHT: f32 = 0.35;
+const CUTAWAY_DEPTH_BIAS: f32 = 1e-6;
+// Las aristas del modo alambre se acercan un poco para ganarle a su propia cara
+const WIREFRAME_DEPTH_BIAS: f32 = 1e-5;
+
+// Buffers intermedios del pipeline. Se crea uno solo y se reutiliza para
+// todos los cuerpos y frames: tras el primer frame ya no hace falta reservar memoria.
+#[derive(Default)]
+pub struct RenderContext {
+    transformed_vertices: Vec<Vertex>,
+    fragments: Vec<Fragment>,
+    // Paralelo a `fragments`: si el fragmento viene de una cara vista por dentro
+    back_facing: Vec<bool>,
+    // Cobertura ya sumada en cada píxel por la cáscara atmosférica que se
+    // dibuja; fuera de `render_atmosphere` queda en cero
+    coverage: Vec<f32>,
+    // Superficie sombreada, aristas o normales (F2)
+    pub render_mode: RenderMode,
+    pub shader_lod: ShaderLod,
+}
+
+impl RenderContext {
+    pub fn new() -> Self {
+        RenderContext::default()
+    }
+
+    // Vacía los buffers conservando su capacidad
+    pub fn clear(&mut self) {
+        self.transformed_vertices.clear();
+        self.fragments.clear();
+        self.back_facing.clear();
+    }
+
+    // Dirección y capacidad de cada buffer, para ver que un frame no los
+    // vuelve a reservar
+    pub fn allocations(&self) -> [(usize, usize); 4] {
+        [
+            (self.transformed_vertices.as_ptr() as usize, self.transformed_vertices.capacity()),
+            (self.fragments.as_ptr() as usize, self.fragments.capacity()),
+            (self.back_facing.as_ptr() as usize, self.back_facing.capacity()),
+            (self.coverage.as_ptr() as usize, self.coverage.capacity()),
+        ]
+    }
+}
+
+pub fn render(
+    framebuffer: &mut Framebuffer,
+    context: &mut RenderContext,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    planet_type: &PlanetType,
+    // Cuerpos que pueden eclipsar al que se dibuja (`self_index` se ignora)
+    occluders: &[CelestialBody],
+    self_index: usize,
+) -> RenderStats {
+    context.clear();
+    let RenderContext { transformed_vertices, fragments, back_facing, render_mode, shader_lod, .. } = context;
+    let render_mode = *render_mode;
+    let mut stats = RenderStats { vertices: vertex_array.len(), ..Default::default() };
+
+    // Vertex Shader Stage
+    transformed_vertices.extend(vertex_array.iter().map(|vertex| vertex_shader(vertex, uniforms)));
+
+    // Con plano de corte hace falta saber qué triángulos miran hacia dentro
+    let eye = uniforms.clip_plane.and_then(|_| {
+        uniforms.view_matrix.try_inverse().map(|inverse| (inverse * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz())
+    });
+
+    // Primitive Assembly Stage: cada tres vértices forman un triángulo
+    let triangles = transformed_vertices.chunks_exact(3);
+    stats.triangles = triangles.len();
+    for tri in triangles {
+        let flat_normal = match uniforms.shading {
+            ShadingMode::Smooth => None,
+            ShadingMode::Flat => Some(face_normal(tri, &uniforms.model_matrix)),
+        };
+        let faces_inward = eye.is_some_and(|eye| {
+            let centroid = (tri[0].position + tri[1].position + tri[2].position) / 3.0;
+            let centroid = (uniforms.model_matrix * Vec4::new(centroid.x, centroid.y, centroid.z, 1.0)).xyz();
+            face_normal(tri, &uniforms.model_matrix).dot(&(eye - centroid)) < 0.0
+        });
+        let generated = fragments.len();
+        triangle(&tri[0], &tri[1], &tri[2], flat_normal, framebuffer.width, framebuffer.height, fragments);
+        if fragments.len() == generated {
+            stats.culled_triangles += 1;
+        }
+        back_facing.resize(fragments.len(), faces_inward);
+    }
+
+    stats.fragments = fragments.len();
+
+    let motion = uniforms.previous_mvp.map(|previous| {
+        (uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix, previous)
+    });
+
+    // Interpolar mezclaría las caras interiores del corte y las del sombreado plano
+    let lod_allowed = planet_type.allows_shader_lod()
+        && uniforms.clip_plane.is_none()
+        && uniforms.shading == ShadingMode::Smooth;
+    shader_lod.prepare(fragments, framebuffer, lod_allowed);
+
+    for (index, (fragment, &back_facing)) in fragments.iter().zip(back_facing.iter()).enumerate() {
+        // Un cast a usize satura los negativos a 0: descartarlos antes
+        if fragment.position.x < 0.0 || fragment.position.y < 0.0 {
+            continue;
+        }
+        let x = fragment.position.x as usize;
+        let y = fragment.position.y as usize;
+        if framebuffer.writable(x, y) {
+            let world_position = fragment.world_position;
+
+            if let Some(plane) = uniforms.clip_plane {
+                if plane.xyz().dot(&world_position) + plane.w < 0.0 {
+                    continue;
+                }
+            }
+
+            // Los huecos de los anillos no se dibujan; lo translúcido se mezcla
+            let opacity = fragment_opacity(fragment, uniforms, planet_type);
+            if opacity <= 0.0 {
+                continue;
+            }
+
+            let mut depth = framebuffer.toward_camera(fragment.depth, uniforms.depth_bias);
+            if back_facing {
+                depth = framebuffer.toward_camera(depth, -CUTAWAY_DEPTH_BIAS);
+            }
+
+            let linear = match render_mode {
+                RenderMode::Normals => normal_color(&fragment.normal),
+                // Sin color propio: la superficie sólo tapa lo que hay detrás
+                RenderMode::Wireframe => framebuffer.background_linear(),
+                RenderMode::Shaded | RenderMode::ShadedWithWireframe => {
+                    let mut shaded_color = shader_lod.shade(fragments, index, |fragment| fragment_shader(fragment, uniforms, planet_type));
+
+                    // Las estrellas emiten su propia luz; el resto puede quedar eclipsado
+                    let self_lit = planet_type.is_self_lit();
+                    if !self_lit {
+                        let light = eclipse_light(world_position, uniforms.sun_position, occluders, self_index);
+                        if light < 1.0 {
+                            shaded_color = shaded_color * light;
+                        }
+                    }
+                    if back_facing {
+                        shaded_color = shaded_color * CUTAWAY_INTERIOR_LIGHT;
+                    }
+
+                    // A HDR: las superficies emisivas pueden pasar de 1.0
+                    let mut linear = Vec3::new(
+                        shaded_color.r as f32 / 255.0,
+                        shaded_color.g as f32 / 255.0,
+                        shaded_color.b as f32 / 255.0,
+                    ) * emission(planet_type);
+                    if !self_lit {
+                        linear.component_mul_assign(&light_color(&world_position, &fragment.normal, &uniforms.lights));
+                    }
+                    linear
+                }
+            };
+            // Los bordes antialiasados, lo translúcido y todo el pase
+            // transparente se mezclan con lo que ya hay debajo, sin escribir
+            // profundidad
+            let alpha = fragment.coverage * opacity;
+            if alpha < 1.0 || !uniforms.depth_write {
+                if framebuffer.blend_hdr(x, y, depth, linear, alpha) {
+                    stats.depth_passed += 1;
+                }
+            } else {
+                let written = framebuffer.point_hdr(x, y, depth, linear);
+                if written {
+                    stats.depth_passed += 1;
+                    stats.pixels_written += 1;
+                }
+                if let (true, Some((current, previous))) = (written, &motion) {
+                    let velocity = screen_velocity(&uniforms.viewport_matrix, current, previous, &fragment.vertex_position);
+                    framebuffer.set_velocity(x, y, velocity);
+                }
+            }
+        }
+    }
+    stats.shaded_fragments = shader_lod.shaded();
+
+    // Cada arista con el color de su distancia a la cámara; la de atrás
+    // queda tapada por la superficie
+    if render_mode.draws_edges() {
+        for tri in transformed_vertices.chunks_exact(3) {
+            let corner = |vertex: &Vertex| {
+                let p = vertex.transformed_position;
+                Vec3::new(p.x, p.y, framebuffer.toward_camera(p.z, WIREFRAME_DEPTH_BIAS))
+            };
+            let distance = |vertex: &Vertex| uniforms.projection.linear_depth(vertex.transformed_position.z);
+            let corners = [corner(&tri[0]), corner(&tri[1]), corner(&tri[2])];
+            let distances = [distance(&tri[0]), distance(&tri[1]), distance(&tri[2])];
+            for (from, to) in [(0, 1), (1, 2), (2, 0)] {
+                let color = wireframe_color((distances[from] + distances[to]) * 0.5);
+                draw_line(framebuffer, &corners[from], &corners[to], color);
+            }
+        }
+    }
+
+    stats
+}
+
+// Cáscara atmosférica de un cuerpo: sólo las caras traseras, sumadas sobre lo
+// ya dibujado sin escribir profundidad, así nunca oscurece ni tapa el planeta. Debe ir después de todos los cuerpos opacos.
+// No se compara con el z-buffer (el propio planeta la taparía); en su lugar
+// se descarta lo que otro cuerpo tapa entre la cámara y el fragmento.
+pub fn render_atmosphere(
+    framebuffer: &mut Framebuffer,
+    context: &mut RenderContext,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    atmosphere: &AtmosphereParams,
+    occluders: &[CelestialBody],
+    self_index: usize,
+) -> RenderStats {
+    // Los modos de alambre y de normales muestran sólo las superficies
+    if !context.render_mode.shades() {
+        return RenderStats::default();
+    }
+    context.clear();
+    let RenderContext { transformed_vertices, fragments, coverage, .. } = context;
+    coverage.resize(framebuffer.width * framebuffer.height, 0.0);
+
+    transformed_vertices.extend(vertex_array.iter().map(|vertex| vertex_shader(vertex, uniforms)));
+    let eye = uniforms.camera_position;
+
+    let mut stats = RenderStats { vertices: vertex_array.len(), ..Default::default() };
+    for tri in transformed_vertices.chunks_exact(3) {
+        stats.triangles += 1;
+        let centroid = (tri[0].position + tri[1].position + tri[2].position) / 3.0;
+        let centroid = (uniforms.model_matrix * Vec4::new(centroid.x, centroid.y, centroid.z, 1.0)).xyz();
+        if face_normal(tri, &uniforms.model_matrix).dot(&(eye - centroid)) > 0.0 {
+            stats.culled_triangles += 1;
+            continue;
+        }
+        let generated = fragments.len();
+        triangle(&tri[0], &tri[1], &tri[2], None, framebuffer.width, framebuffer.height, fragments);
+        if fragments.len() == generated {
+            stats.culled_triangles += 1;
+        }
+    }
+    stats.fragments = fragments.len();
+
+    for fragment in fragments.iter() {
+        if fragment.position.x < 0.0 || fragment.position.y < 0.0 {
+            continue;
+        }
+        let x = fragment.position.x as usize;
+        let y = fragment.position.y as usize;
+        if !framebuffer.writable(x, y) {
+            continue;
+        }
+
+        let world_position = fragment.world_position;
+        if let Some(plane) = uniforms.clip_plane {
+            if plane.xyz().dot(&world_position) + plane.w < 0.0 {
+                continue;
+            }
+        }
+
+        let to_fragment = world_position - eye;
+        let distance = to_fragment.norm();
+        if distance <= f32::EPSILON {
+            continue;
+        }
+        let direction = to_fragment / distance;
+        let hidden = occluders.iter().enumerate().any(|(index, body)| {
+            index != self_index
+                && intersect_sphere(&eye, &direction, &body.position, body.scale)
+                    .is_some_and(|t| t < distance)
+        });
+        if hidden {
+            continue;
+        }
+
+        // Cada píxel cae en una sola cara trasera, salvo el borde
+        // antialiasado que comparten dos vecinas: sin este tope las aristas
+        // de la malla se sumarían dos veces
+        let index = y * framebuffer.width + x;
+        let weight = fragment.coverage.min(1.0 - coverage[index]);
+        if weight <= 0.0 {
+            continue;
+        }
+        coverage[index] += weight;
+
+        let color = atmosphere_shader(fragment, uniforms, atmosphere);
+        let linear = Vec3::new(color.r as f32, color.g as f32, color.b as f32) / 255.0;
+        framebuffer.add_hdr(x, y, linear * weight);
+        stats.depth_passed += 1;
+    }
+
+    for fragment in fragments.iter() {
+        if fragment.position.x >= 0.0 && fragment.position.y >= 0.0 {
+            if let Some(value) = coverage.get_mut(fragment.position.y as usize * framebuffer.width + fragment.position.x as usize) {
+                *value = 0.0;
+            }
+        }
+    }
+
+    stats
+}
+
+// Billboards emisivos ya en espacio de mundo (ver `Prominences::billboards`).
+// Como la atmósfera, se suman sin escribir profundidad y van después de los
+// cuerpos opacos, pero sí se comparan con el z-buffer para que un planeta
+// delante los tape.
+pub fn render_prominences(
+    framebuffer: &mut Framebuffer,
+    context: &mut RenderContext,
+    uniforms: &mut Uniforms,
+    vertex_array: &[Vertex],
+) -> RenderStats {
+    if !context.render_mode.shades() {
+        return RenderStats::default();
+    }
+    context.clear();
+    let RenderContext { transformed_vertices, fragments, .. } = context;
+    uniforms.model_matrix = Mat4::identity();
+    uniforms.displacement = 0.0;
+
+    transformed_vertices.extend(vertex_array.iter().map(|vertex| vertex_shader(vertex, uniforms)));
+    let mut stats = RenderStats { vertices: vertex_array.len(), ..Default::default() };
+    for tri in transformed_vertices.chunks_exact(3) {
+        stats.triangles += 1;
+        let generated = fragments.len();
+        triangle(&tri[0], &tri[1], &tri[2], None, framebuffer.width, framebuffer.height, fragments);
+        if fragments.len() == generated {
+            stats.culled_triangles += 1;
+        }
+    }
+    stats.fragments = fragments.len();
+
+    for fragment in fragments.iter() {
+        if fragment.position.x < 0.0 || fragment.position.y < 0.0 {
+            continue;
+        }
+        let x = fragment.position.x as usize;
+        let y = fragment.position.y as usize;
+        if !framebuffer.writable(x, y) {
+            continue;
+        }
+        if !framebuffer.is_nearer(fragment.depth, framebuffer.zbuffer[y * framebuffer.width + x]) {
+            continue;
+        }
+
+        framebuffer.add_hdr(x, y, prominence_shader(fragment) * fragment.coverage);
+        stats.depth_passed += 1;
+    }
+
+    stats
+}
+
+pub fn default_system() -> Vec<CelestialBody> {
+    vec![
+        CelestialBody {
+            position: Vec3::new(0.0, 0.0, 0.0),
+            scale: 2.0,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Sun,
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(6.0, 0.0, 0.0),
+            scale: 0.4,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Mercury,
+            displacement: 0.03,
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(12.0, 0.0, 0.0),
+            scale: 0.6,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Venus,
+            // Rotación retrógrada y lenta
+            rotation_speed: -0.15,
+            axial_tilt: 2.6_f32.to_radians(),
+            atmosphere: Some(AtmosphereParams::venus()),
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(18.0, 0.0, 0.0),
+            scale: 0.7,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Earth,
+            displacement: 0.02,
+            axial_tilt: 23.4_f32.to_radians(),
+            atmosphere: Some(AtmosphereParams::earth()),
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(24.0, 0.0, 0.0),
+            scale: 0.5,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Mars,
+            displacement: 0.04,
+            axial_tilt: 25.2_f32.to_radians(),
+            atmosphere: Some(AtmosphereParams::mars()),
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(32.0, 0.0, 0.0),
+            scale: 1.5,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Jupiter,
+            axial_tilt: 3.1_f32.to_radians(),
+            atmosphere: Some(AtmosphereParams::jupiter()),
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(40.0, 0.0, 0.0),
+            scale: 1.3,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Saturn,
+            axial_tilt: 26.7_f32.to_radians(),
+            rings: Some(RingSystem::saturn()),
+            atmosphere: Some(AtmosphereParams::saturn()),
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(48.0, 0.0, 0.0),
+            scale: 0.9,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Uranus,
+            axial_tilt: 97.8_f32.to_radians(),
+            rings: Some(RingSystem::uranus()),
+            atmosphere: Some(AtmosphereParams::uranus()),
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(56.0, 0.0, 0.0),
+            scale: 0.9,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Neptune,
+            axial_tilt: 28.3_f32.to_radians(),
+            rings: Some(RingSystem::neptune()),
+            atmosphere: Some(AtmosphereParams::neptune()),
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(18.0, 0.0, 2.0),
+            scale: 0.2,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::Moon,
+            displacement: 0.03,
+            axial_tilt: 6.7_f32.to_radians(),
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(-20.0, 0.0, -20.0),
+            scale: 4.0,
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            shader_type: PlanetType::BlackHole,
+            // Inclinado para que el disco de acreción no se vea de canto
+            axial_tilt: 20.0_f32.to_radians(),
+            ..Default::default()
+        },
+        // Lunas galileanas: orbitan a Júpiter, cada una más lejos y más
+        // despacio que la anterior. Son chicas para que el nivel de detalle y
+        // el descarte de triángulos trabajen con cuerpos de pocos píxeles.
+        CelestialBody {
+            scale: 0.12,
+            shader_type: PlanetType::Io,
+            orbit_radius: 2.4,
+            orbit_speed: 1.6,
+            ..Default::default()
+        },
+        CelestialBody {
+            scale: 0.1,
+            shader_type: PlanetType::Europa,
+            orbit_radius: 3.0,
+            orbit_speed: 1.1,
+            orbit_phase: 2.1,
+            ..Default::default()
+        },
+        CelestialBody {
+            scale: 0.17,
+            shader_type: PlanetType::Ganymede,
+            orbit_radius: 3.8,
+            orbit_speed: 0.75,
+            orbit_phase: 4.2,
+            ..Default::default()
+        },
+        CelestialBody {
+            scale: 0.15,
+            shader_type: PlanetType::Callisto,
+            orbit_radius: 4.9,
+            orbit_speed: 0.45,
+            orbit_phase: 1.0,
+            ..Default::default()
+        },
+    ]
+}
@@ -0,0 +1,153 @@
+use nalgebra_glm::Vec3;
+use crate::Uniforms;
+use crate::fragment::Fragment;
+
+// Luz que ilumina los planetas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    // Color lineal de la luz, con el canal más alto en 1
+    pub color: Vec3,
+    // Cuánto ilumina una superficie que la mira de frente, de 0 a 1: las
+    // estrellas 1, la de relleno bastante menos
+    pub intensity: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    // Sale de un punto del mundo; con `attenuation` pierde fuerza con la distancia
+    Point { position: Vec3, attenuation: Attenuation },
+    // Llega igual a todas partes desde `direction` (hacia la luz), como una
+    // estrella muy lejana
+    Directional { direction: Vec3 },
+}
+
+// Factor 1 / (constant + linear·d + quadratic·d²) de una luz puntual a distancia d
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Attenuation {
+    // La de las estrellas: alumbran todo el sistema por igual
+    pub const NONE: Attenuation = Attenuation { constant: 1.0, linear: 0.0, quadratic: 0.0 };
+
+    pub fn factor(&self, distance: f32) -> f32 {
+        let denominator = self.constant + distance * (self.linear + distance * self.quadratic);
+        if denominator > f32::EPSILON { (1.0 / denominator).min(1.0) } else { 1.0 }
+    }
+}
+
+// Sin ninguna luz en la escena, la luz fija de siempre, desde +Z. Las
+// estrellas, que no reciben luz, la usan para oscurecer su disco.
+pub const FALLBACK_LIGHT: Light = Light {
+    kind: LightKind::Directional { direction: Vec3::new(0.0, 0.0, 1.0) },
+    color: Vec3::new(1.0, 1.0, 1.0),
+    intensity: 1.0,
+};
+
+// Ambiente por defecto: el lado nocturno apenas se adivina
+pub const DEFAULT_AMBIENT: f32 = 0.03;
+
+impl Light {
+    // Luz puntual sin atenuación, como las estrellas
+    pub fn point(position: Vec3, color: Vec3, intensity: f32) -> Self {
+        Light { kind: LightKind::Point { position, attenuation: Attenuation::NONE }, color, intensity }
+    }
+
+    pub fn directional(direction: Vec3, color: Vec3, intensity: f32) -> Self {
+        Light { kind: LightKind::Directional { direction }, color, intensity }
+    }
+
+    // Dónde está, si es puntual
+    pub fn position(&self) -> Option<Vec3> {
+        match self.kind {
+            LightKind::Point { position, .. } => Some(position),
+            LightKind::Directional { .. } => None,
+        }
+    }
+
+    // Dirección unitaria de `point` hacia la luz y cuánta llega (la
+    // intensidad ya atenuada). None si el punto está sobre la luz.
+    pub fn incidence(&self, point: &Vec3) -> Option<(Vec3, f32)> {
+        match self.kind {
+            LightKind::Point { position, attenuation } => {
+                let to_light = position - point;
+                let distance = to_light.norm();
+                if distance <= f32::EPSILON {
+                    return None;
+                }
+                Some((to_light / distance, self.intensity * attenuation.factor(distance)))
+            }
+            LightKind::Directional { direction } => {
+                Some((direction.try_normalize(f32::EPSILON)?, self.intensity))
+            }
+        }
+    }
+
+    // Lambert de esta luz en un punto con normal `normal`, ya con la intensidad
+    pub fn diffuse(&self, point: &Vec3, normal: &Vec3) -> f32 {
+        self.incidence(point).map_or(0.0, |(direction, amount)| normal.dot(&direction).max(0.0) * amount)
+    }
+}
+
+// Cuánta luz llega a un punto, de 0 a 1: el Lambert de cada luz por su
+// intensidad, más el ambiente. Se combinan como filtros, 1 - Π(1 - aporte):
+// aportes chicos casi se suman, pero el total nunca pasa de 1, así que una
+// luz de relleno tenue aclara el lado nocturno y apenas toca el que da al Sol.
+pub fn apply_lighting(position: &Vec3, normal: &Vec3, uniforms: &Uniforms) -> f32 {
+    if uniforms.lights.is_empty() {
+        return FALLBACK_LIGHT.diffuse(position, normal);
+    }
+
+    let mut darkness = 1.0 - uniforms.ambient.clamp(0.0, 1.0);
+    for light in &uniforms.lights {
+        darkness *= 1.0 - light.diffuse(position, normal).clamp(0.0, 1.0);
+    }
+    1.0 - darkness
+}
+
+// `apply_lighting` con la posición y la normal interpoladas del fragmento
+pub fn fragment_lighting(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    apply_lighting(&fragment.world_position, &fragment.normal, uniforms)
+}
+
+// Brillo especular de Blinn-Phong de todas las luces visto desde
+// `uniforms.camera_position`: 1 en el reflejo de una luz de intensidad 1,
+// más concentrado cuanto más alto `shininess`. Sólo lo que mira a la luz brilla.
+pub fn specular(position: &Vec3, normal: &Vec3, uniforms: &Uniforms, shininess: f32) -> f32 {
+    let Some(to_eye) = (uniforms.camera_position - position).try_normalize(f32::EPSILON) else {
+        return 0.0;
+    };
+    uniforms.lights.iter()
+        .filter_map(|light| light.incidence(position))
+        .filter(|(direction, _)| normal.dot(direction) > 0.0)
+        .filter_map(|(direction, amount)| {
+            let half = (direction + to_eye).try_normalize(f32::EPSILON)?;
+            Some(normal.dot(&half).max(0.0).powf(shininess) * amount)
+        })
+        .sum()
+}
+
+// Tinte de la luz que llega a un punto: el color de cada luz pesado por lo
+// que aporta. El brillo lo pone `apply_lighting`.
+pub fn light_color(position: &Vec3, normal: &Vec3, lights: &[Light]) -> Vec3 {
+    let mut total = Vec3::zeros();
+    let mut total_weight = 0.0;
+    for light in lights {
+        let weight = light.diffuse(position, normal);
+        total += light.color * weight;
+        total_weight += weight;
+    }
+
+    if total_weight > f32::EPSILON {
+        total / total_weight
+    } else if !lights.is_empty() {
+        // Ninguna luz lo ve de frente (sólo le llega el ambiente): promedio de todas
+        lights.iter().map(|light| light.color).sum::<Vec3>() / lights.len() as f32
+    } else {
+        Vec3::new(1.0, 1.0, 1.0)
+    }
+}
@@ -0,0 +1,71 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4, Mat4};
+use crate::framebuffer::Framebuffer;
+
+// Proyecta un punto del mundo a (x, y) en píxeles y z = profundidad, el
+// mismo espacio en el que rasteriza `triangle`. None detrás de la cámara.
+pub fn project_point(point: &Vec3, view_projection: &Mat4, viewport: &Mat4) -> Option<Vec3> {
+    let clip = view_projection * Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= f32::EPSILON {
+        return None;
+    }
+    let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    Some((viewport * ndc).xyz())
+}
+
+// Línea con prueba de profundidad entre dos puntos de pantalla (x, y en
+// píxeles, z = profundidad), de un píxel de ancho, escrita en el buffer HDR
+// como cualquier superficie. La profundidad se interpola linealmente, lo que
+// es exacto para líneas proyectadas.
+pub fn draw_line(framebuffer: &mut Framebuffer, from: &Vec3, to: &Vec3, color: Vec3) {
+    let Some((from, to)) = clip_to_screen(from, to, framebuffer.width as f32, framebuffer.height as f32) else {
+        return;
+    };
+
+    let delta = to - from;
+    let steps = delta.x.abs().max(delta.y.abs()).ceil().max(1.0) as usize;
+    for i in 0..=steps {
+        let p = from + delta * (i as f32 / steps as f32);
+        if p.x < 0.0 || p.y < 0.0 {
+            continue;
+        }
+        let (x, y) = (p.x as usize, p.y as usize);
+        // Las líneas no se mueven con la superficie de abajo
+        if framebuffer.point_hdr(x, y, p.z, color) {
+            framebuffer.set_velocity(x, y, Vec2::zeros());
+        }
+    }
+}
+
+// Recorte de Liang-Barsky contra [0, width] x [0, height], para que las
+// líneas con extremos lejanos no recorran millones de píxeles fuera de pantalla
+pub(crate) fn clip_to_screen(from: &Vec3, to: &Vec3, width: f32, height: f32) -> Option<(Vec3, Vec3)> {
+    let delta = to - from;
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+    let edges = [
+        (-delta.x, from.x),
+        (delta.x, width - from.x),
+        (-delta.y, from.y),
+        (delta.y, height - from.y),
+    ];
+
+    for (p, q) in edges {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = q / p;
+        if p < 0.0 {
+            t0 = t0.max(t);
+        } else {
+            t1 = t1.min(t);
+        }
+        if t0 > t1 {
+            return None;
+        }
+    }
+
+    Some((from + delta * t0, from + delta * t1))
+}
@@ -1,361 +1,1230 @@
-use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
+use nalgebra_glm::{Vec3, Vec4};
+use minifb::{Key, KeyRepeat, Window, WindowOptions, MouseButton, MouseMode, ScaleMode};
+use std::path::Path;
 use std::f32::consts::PI;
+use std::time::Instant;
 
-mod framebuffer;
-mod triangle;
-mod vertex;
-mod obj;
-mod color;
-mod fragment;
-mod shaders;
-mod camera;
-mod planet_type;
-
-use framebuffer::Framebuffer;
-use vertex::Vertex;
-use obj::Obj;
-use camera::Camera;
-use triangle::triangle;
-use shaders::{vertex_shader, fragment_shader};
-use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
-use planet_type::PlanetType;
-
-pub struct CelestialBody {
-    position: Vec3,
-    scale: f32,
-    rotation: Vec3,
-    shader_type: PlanetType,
-}
+mod cli;
+
+use shaders_lab::{
+    CelestialBody,
+    Uniforms,
+    ShadingMode,
+    ProjectionSettings,
+    RenderStats,
+    ProjectionMode,
+    system_extent,
+    update_projection_matrix,
+    set_reversed_z,
+    create_uniforms,
+    create_model_matrix,
+    resize_viewport,
+    default_system,
+};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::assets::{Assets, ASSETS_ENV_VAR};
+use shaders_lab::error::Error;
+use shaders_lab::camera::Camera;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::generator::generate_system;
+use shaders_lab::simulation::{interpolated_state, FIXED_DT, MAX_FRAME_TIME};
+use shaders_lab::picking;
+use shaders_lab::quality::QualityController;
+use shaders_lab::tonemap::{ToneMapSettings, EXPOSURE_STEP};
+use shaders_lab::noise_utils::{NoiseCache, NOISE_CACHE_EXTENT, NOISE_CACHE_CELLS};
+use shaders_lab::scene::{export_scene, load_scene, Scene, SceneMeshes};
+use shaders_lab::shaders::star_light_color;
+use shaders_lab::dof::DepthOfField;
+use shaders_lab::debug_view::{stats_text, DebugView};
+use shaders_lab::lensing::GravitationalLens;
+use shaders_lab::line::{draw_line, project_point};
+use shaders_lab::tour::Tour;
+use shaders_lab::chase::ChaseCamera;
+use shaders_lab::background::Background;
+use shaders_lab::skybox::{self, SkyboxTexture};
+use shaders_lab::outline::SelectionOutline;
+use shaders_lab::flare::{draw_lens_flare, LENS_FLARE};
+use shaders_lab::font::{GLYPH_WIDTH, GLYPH_HEIGHT};
+use shaders_lab::app_state::AppState;
+use shaders_lab::bookmarks::{CameraBookmarks, CameraTransition, BOOKMARK_SLOTS};
+use shaders_lab::visibility::{self, BodyGroup};
+use shaders_lab::scene_watch::SceneWatcher;
+use shaders_lab::golden::GoldenImage;
+use shaders_lab::viewport::{SplitScreen, Viewport};
+use shaders_lab::turntable::{self, Turntable};
+use shaders_lab::progressive::RenderProgress;
+use shaders_lab::console::{Console, ConsoleEffect};
+
+const MAX_TERRAIN_OCTAVES: i32 = 8;
+// Cuánto se mueve el plano de corte por pulsación de RePág/AvPág
+const CLIP_PLANE_STEP: f32 = 0.1;
+const FOV_STEP_DEGREES: f32 = 5.0;
+// Objetivo del modo de calidad adaptativa
+const TARGET_FPS: f32 = 30.0;
+// Etiquetas de los cuerpos: color normal, del seleccionado y separación en píxeles
+const LABEL_COLOR: u32 = 0xFFFFFF;
+const LABEL_SELECTED_COLOR: u32 = 0xFFD700;
+const LABEL_MARGIN: usize = 4;
+// Línea entre las dos vistas de la pantalla dividida
+const DIVIDER_COLOR: u32 = 0x808080;
+// Distancia de las estadísticas de depuración a la esquina superior izquierda
+const STATS_MARGIN: isize = 6;
+// Ejes de rotación: cuánto sobresalen de los polos (en radios) y su color HDR
+const AXIS_EXTENT: f32 = 1.5;
+const AXIS_COLOR: (f32, f32, f32) = (0.3, 0.9, 1.0);
+// Gizmo del origen: largo de cada eje como fracción de media altura de la
+// pantalla, y colores HDR de X, Y y Z
+const GIZMO_SCREEN_FRACTION: f32 = 0.3;
+const GIZMO_COLORS: [(f32, f32, f32); 3] = [(1.0, 0.15, 0.15), (0.15, 1.0, 0.15), (0.25, 0.35, 1.0)];
+
+const WINDOW_TITLE: &str = "Rust Graphics - Renderer Example";
+const FULLSCREEN_SIZE: (usize, usize) = (1920, 1080);
+const SCENE_EXPORT_PATH: &str = "scene.json";
+const BOOKMARKS_PATH: &str = "camera_bookmarks.ron";
+const STATE_PATH: &str = "state.ron";
+const STILL_PATH: &str = "still.png";
+const BOOKMARK_KEYS: [Key; BOOKMARK_SLOTS] = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5];
+// Teclas que escriben en la consola, en el orden de los caracteres
+const LETTER_KEYS: [Key; 26] = [
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+];
+const DIGIT_KEYS: [[Key; 10]; 2] = [
+    [Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9],
+    [Key::NumPad0, Key::NumPad1, Key::NumPad2, Key::NumPad3, Key::NumPad4, Key::NumPad5, Key::NumPad6, Key::NumPad7, Key::NumPad8, Key::NumPad9],
+];
 
-pub struct Uniforms {
-    model_matrix: Mat4,
-    view_matrix: Mat4,
-    projection_matrix: Mat4,
-    viewport_matrix: Mat4,
-    time: u32,
-    noise: FastNoiseLite
+// `fps` limita la frecuencia de `window.update()` (0 = sin límite) para no
+// ocupar un núcleo entero; la animación avanza con el tiempo medido
+fn create_window(width: usize, height: usize, fullscreen: bool, fps: usize) -> Result<Window, Error> {
+    let mut window = Window::new(
+        WINDOW_TITLE,
+        width,
+        height,
+        WindowOptions {
+            borderless: fullscreen,
+            title: !fullscreen,
+            resize: !fullscreen,
+            topmost: fullscreen,
+            scale_mode: ScaleMode::AspectRatioStretch,
+            ..WindowOptions::default()
+        },
+    )?;
+    window.set_target_fps(fps);
+    Ok(window)
 }
 
-fn create_noise() -> FastNoiseLite {
-    create_cloud_noise() 
+// Los errores de carga llegan hasta aquí con la ruta y la causa; se
+// muestran y se sale con código 1
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("Error: {}", error);
+        if matches!(error, Error::MissingAsset { .. } | Error::Obj { .. }) {
+            eprintln!(
+                "Indica el modelo con --model <ruta>, o la carpeta de assets con --assets <dir> o la variable {}",
+                ASSETS_ENV_VAR,
+            );
+        }
+        std::process::exit(1);
+    }
 }
 
-fn create_cloud_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(1337);
-    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
-    noise
+fn run() -> Result<(), Error> {
+    let args = cli::parse_args();
+
+    if let Some(frames) = args.headless_frames() {
+        return run_benchmark(&args, frames);
+    }
+    if let Some(query) = &args.turntable {
+        return run_turntable(&args, query);
+    }
+
+    let window_width = args.width;
+    let window_height = args.height;
+    // Con --resolution el tamaño interno es fijo y minifb escala a la ventana
+    let (framebuffer_width, framebuffer_height) = args.resolution.unwrap_or((window_width, window_height));
+
+    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+    let mut window = create_window(window_width, window_height, false, args.fps)?;
+
+    window.set_position(500, 500);
+    window.set_title(&window_title(&ProjectionSettings::default()));
+    window.update();
+
+    let mut fullscreen = false;
+    let mut windowed_size = (window_width, window_height);
+    let mut windowed_position = window.get_position();
+
+    // Casi negro: la nebulosa y las estrellas de `Background` van encima
+    framebuffer.set_background_color(0x020208);
+
+    let mut last_frame = Instant::now();
+    let mut scene = build_scene(&args, &mut framebuffer)?;
+
+    let mut selected_body: Option<usize> = None;
+    let mut mouse_was_down = false;
+
+    let mut bookmarks = CameraBookmarks::load(BOOKMARKS_PATH);
+    let mut camera_transition: Option<CameraTransition> = None;
+    let mut tone_mapping = ToneMapSettings::default();
+    let mut quality = args.adaptive_quality.then(|| QualityController::new(TARGET_FPS));
+    // Trabajo del frame anterior, sin la espera del límite de FPS
+    let mut last_work_ms: Option<f32> = None;
+    let mut depth_of_field = DepthOfField::default();
+    let mut gravitational_lens = GravitationalLens::default();
+    let mut background = Background::default();
+    // La vista general tiene su propia rejilla de nebulosa
+    let mut overview_background = Background::default();
+    // Con --skybox la imagen reemplaza a `Background` en las dos vistas
+    let skybox = args.skybox.as_deref().map(SkyboxTexture::load).transpose()?;
+    let mut split = SplitScreen::new(&scene.bodies, framebuffer.width, framebuffer.height);
+    let mut outline = SelectionOutline::default();
+    let mut show_axes = false;
+    let mut show_gizmo = false;
+    let mut monochrome = false;
+    let mut debug_view = DebugView::Shaded;
+    let mut auto_tour = false;
+    let mut tour = Tour::around_system(&scene.bodies);
+    let mut chase = ChaseCamera::default();
+    let mut body_group = BodyGroup::default();
+    // Con --progressive la vista completa se dibuja en pasadas, una por frame
+    let mut progress = args.progressive_passes().map(RenderProgress::new);
+    // Sólo una escena de --scene se recarga al guardarla
+    let mut scene_watcher = args.scene.as_deref().map(SceneWatcher::new);
+    let mut console = Console::default();
+
+    while window.is_open() {
+        // Con la consola abierta, Escape sólo la cierra
+        if !console.is_open() && window.is_key_pressed(Key::Escape, KeyRepeat::No) {
+            break;
+        }
+
+        let now = Instant::now();
+        let frame_time = (now - last_frame).as_secs_f32().min(MAX_FRAME_TIME);
+        last_frame = now;
+
+        // ` abre y cierra la consola; abierta, el teclado escribe en ella y
+        // los demás controles no responden
+        if window.is_key_pressed(Key::Backquote, KeyRepeat::No) {
+            console.toggle();
+        }
+        let typing = console.is_open();
+        if typing {
+            match handle_console_input(&window, &mut console, &mut scene) {
+                Some(ConsoleEffect::Select(index)) => {
+                    selected_body = Some(index);
+                    camera_transition = None;
+                    auto_tour = false;
+                }
+                Some(ConsoleEffect::Screenshot) => save_still(&mut scene, args.still_size, &tone_mapping),
+                Some(ConsoleEffect::Reseeded) => {
+                    background = Background::default();
+                    overview_background = Background::default();
+                }
+                None => {}
+            }
+        }
+
+        // V alterna el recorrido automático, que ignora los controles de la cámara
+        if !typing && window.is_key_pressed(Key::V, KeyRepeat::No) {
+            auto_tour = !auto_tour;
+            if auto_tour {
+                tour = Tour::around_system(&scene.bodies);
+                selected_body = None;
+                camera_transition = None;
+            }
+            println!("Recorrido automático: {}", if auto_tour { "activado" } else { "desactivado" });
+        }
+
+        // L alterna la persecución suave del cuerpo seleccionado
+        if !typing && window.is_key_pressed(Key::L, KeyRepeat::No) {
+            chase.enabled = !chase.enabled;
+            println!("Cámara de persecución: {}", if chase.enabled { "activada" } else { "desactivada" });
+        }
+
+        // F3 divide la pantalla: vista general a la izquierda y la cámara de
+        // siempre a la derecha; Y elige cuál de las dos recibe los controles
+        if !typing && window.is_key_pressed(Key::F3, KeyRepeat::No) {
+            split.toggle(&scene.bodies, framebuffer.width, framebuffer.height);
+            println!("Pantalla dividida: {}", if split.enabled { "activada" } else { "desactivada" });
+        }
+        if !typing && split.enabled && window.is_key_pressed(Key::Y, KeyRepeat::No) {
+            split.toggle_active();
+            println!("Vista activa: {}", if split.overview_active() { "general" } else { "primer plano" });
+        }
+        let overview_input = split.overview_active();
+
+        // Persiguiendo, las flechas y el zoom giran la cámara alrededor del cuerpo
+        let chasing = chase.enabled && selected_body.is_some();
+        if typing {
+            // Lo que se escribe no mueve la cámara
+        } else if overview_input {
+            handle_input(&window, &mut split.overview_camera, frame_time);
+        } else if chasing && !auto_tour {
+            handle_chase_input(&window, &mut chase, frame_time);
+        } else if !auto_tour {
+            handle_input(&window, &mut scene.camera, frame_time);
+        }
+        if !typing {
+            handle_detail_input(&window, &mut scene.uniforms);
+            handle_exposure_input(&window, &mut tone_mapping);
+            handle_clip_input(&window, &mut scene.uniforms);
+            if handle_seed_input(&window, &mut scene) {
+                // La nebulosa del fondo sale del mismo ruido
+                background = Background::default();
+                overview_background = Background::default();
+            }
+            if overview_input {
+                // La matriz de la vista general se arma al dibujarla
+                handle_projection_input(&window, &mut split.overview_projection, &scene.bodies);
+            } else if handle_projection_input(&window, &mut scene.uniforms.projection, &scene.bodies) {
+                update_projection_matrix(&mut scene.uniforms, framebuffer.width, framebuffer.height);
+                window.set_title(&window_title(&scene.uniforms.projection));
+            }
+        }
+        scene.camera.update(frame_time);
+        split.overview_camera.update(frame_time);
+
+        // Los marcadores son de la cámara de la escena
+        let bookmark_transition = if typing || auto_tour || overview_input { None } else { handle_bookmark_input(&window, &scene.camera, &mut bookmarks) };
+        if let Some(transition) = bookmark_transition {
+            // Un marcador manda sobre el seguimiento del cuerpo seleccionado
+            selected_body = None;
+            camera_transition = Some(transition);
+        }
+        if let Some(transition) = &mut camera_transition {
+            scene.camera.restore_state(&transition.update(frame_time));
+            if transition.is_finished() {
+                camera_transition = None;
+            }
+        }
+
+        selected_body = handle_reload_input(&window, scene_watcher.as_mut(), &mut scene, selected_body, frame_time);
+
+        // Una imagen progresiva a medias espera al tiempo de simulación
+        let holds_time = !split.enabled && progress.as_ref()
+            .is_some_and(|progress| progress.holds_time(&scene.camera, &scene.uniforms.projection, &framebuffer));
+        if !holds_time {
+            scene.update(frame_time);
+        }
+        let alpha = scene.alpha();
+
+        if auto_tour {
+            if let Some(state) = tour.update(frame_time, &scene.bodies, alpha) {
+                scene.camera.look_at(state.eye, state.center);
+            }
+        }
+
+        // Selección con clic: sólo en el flanco de bajada del botón
+        let mouse_down = window.get_mouse_down(MouseButton::Left);
+        if mouse_down && !mouse_was_down && !auto_tour {
+            if let Some((mouse_x, mouse_y)) = window.get_mouse_pos(MouseMode::Discard) {
+                // Con la pantalla dividida sólo cuenta el clic en la vista activa
+                let (x, y) = window_to_framebuffer(mouse_x, mouse_y, &window, &framebuffer);
+                let (overview, close_up) = split.viewports(framebuffer.width, framebuffer.height);
+                let (viewport, camera, projection) = if !split.enabled {
+                    (Viewport::full(framebuffer.width, framebuffer.height), &scene.camera, scene.uniforms.projection)
+                } else if overview_input {
+                    (overview, &split.overview_camera, split.overview_projection)
+                } else {
+                    (close_up, &scene.camera, scene.uniforms.projection)
+                };
+                if viewport.contains(x, y) {
+                    // Las matrices de esa vista, como las pone `Scene::render_viewport`
+                    let live = (scene.uniforms.projection, scene.uniforms.projection_matrix, scene.uniforms.viewport_matrix);
+                    scene.uniforms.projection = projection;
+                    scene.uniforms.projection_matrix = viewport.projection_matrix(&projection);
+                    scene.uniforms.viewport_matrix = viewport.matrix();
+                    selected_body = handle_click(x, y, camera, &scene.bodies, &scene.uniforms);
+                    (scene.uniforms.projection, scene.uniforms.projection_matrix, scene.uniforms.viewport_matrix) = live;
+                }
+            }
+        }
+        mouse_was_down = mouse_down;
+
+        // La cámara sigue al cuerpo seleccionado: lo mira o, con L, lo persigue
+        if let Some(index) = selected_body {
+            if chase.enabled {
+                chase.update(&mut scene.camera, &scene.bodies[index], alpha, frame_time);
+            } else {
+                chase.release();
+                scene.camera.focus_on(scene.bodies[index].position);
+            }
+
+            // Tab cambia el shader del cuerpo seleccionado
+            if !typing && window.is_key_pressed(Key::Tab, KeyRepeat::No) {
+                let body = &mut scene.bodies[index];
+                body.shader_type = body.shader_type.next();
+                println!("Shader: {:?}", body.shader_type);
+            }
+        } else {
+            chase.release();
+        }
+
+        if !typing {
+            handle_visibility_input(&window, &mut scene.bodies, selected_body, &mut body_group);
+        }
+        // Lo oculto no se puede seleccionar, tampoco si venía de F9
+        if selected_body.is_some_and(|index| !scene.bodies[index].visible) {
+            selected_body = None;
+        }
+
+        // B alterna la profundidad de campo
+        if !typing && window.is_key_pressed(Key::B, KeyRepeat::No) {
+            depth_of_field.enabled = !depth_of_field.enabled;
+            println!("Profundidad de campo: {}", if depth_of_field.enabled { "activada" } else { "desactivada" });
+        }
+
+        // M alterna el desenfoque de movimiento; Shift+M cambia su número de muestras
+        if !typing && window.is_key_pressed(Key::M, KeyRepeat::No) {
+            let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+            if shift {
+                scene.motion_blur.cycle_samples();
+            } else {
+                scene.motion_blur.enabled = !scene.motion_blur.enabled;
+                scene.motion_blur.reset();
+            }
+            println!(
+                "Desenfoque de movimiento: {} ({} muestras)",
+                if scene.motion_blur.enabled { "activado" } else { "desactivado" },
+                scene.motion_blur.samples,
+            );
+        }
+
+        // G muestra los ejes de rotación de cada cuerpo
+        if !typing && window.is_key_pressed(Key::G, KeyRepeat::No) {
+            show_axes = !show_axes;
+        }
+
+        // H muestra los ejes X, Y y Z del mundo en el origen
+        if !typing && window.is_key_pressed(Key::H, KeyRepeat::No) {
+            show_gizmo = !show_gizmo;
+        }
+
+        // I alterna la luz de relleno, que aclara el lado nocturno sin quitarle protagonismo al Sol
+        if !typing && window.is_key_pressed(Key::I, KeyRepeat::No) {
+            scene.uniforms.fill_light = !scene.uniforms.fill_light;
+            println!("Luz de relleno: {}", if scene.uniforms.fill_light { "activada" } else { "desactivada" });
+        }
+
+        // K alterna el modo blanco y negro
+        if !typing && window.is_key_pressed(Key::K, KeyRepeat::No) {
+            monochrome = !monochrome;
+        }
+
+        // F4 recorre las vistas de depuración del pipeline
+        if !typing && window.is_key_pressed(Key::F4, KeyRepeat::No) {
+            debug_view = debug_view.next();
+            println!("Vista de depuración: {}", debug_view.name());
+        }
+
+        // F2 cambia cómo se dibujan las superficies: alambre, alambre encima o normales
+        if !typing && window.is_key_pressed(Key::F2, KeyRepeat::No) {
+            scene.context.render_mode = scene.context.render_mode.next();
+            println!("Modo de render: {}", scene.context.render_mode.name());
+        }
+
+        // P guarda la escena actual para poder reproducirla con --scene
+        if !typing && window.is_key_pressed(Key::P, KeyRepeat::No) {
+            match export_scene(&scene.bodies, &scene.camera, SCENE_EXPORT_PATH) {
+                Ok(()) => println!("Escena exportada a {}", SCENE_EXPORT_PATH),
+                Err(error) => eprintln!("No se pudo exportar la escena: {}", error),
+            }
+        }
+
+        // F5 guarda la sesión (cámara, selección, tiempo, cuerpos y parámetros) y F9 vuelve a ella
+        if !typing && window.is_key_pressed(Key::F5, KeyRepeat::No) {
+            match AppState::capture(&scene, selected_body, &tone_mapping).save(STATE_PATH) {
+                Ok(()) => println!("Estado guardado en {}", STATE_PATH),
+                Err(error) => eprintln!("No se pudo guardar el estado: {}", error),
+            }
+        }
+        if !typing && window.is_key_pressed(Key::F9, KeyRepeat::No) {
+            match AppState::load(STATE_PATH, scene.bodies.len()) {
+                Ok(state) => {
+                    selected_body = state.selected_body;
+                    tone_mapping = state.tone_mapping;
+                    state.restore(&mut scene, &mut framebuffer);
+                    // La nebulosa sale del ruido de la semilla cargada
+                    background = Background::default();
+                    overview_background = Background::default();
+                    camera_transition = None;
+                    auto_tour = false;
+                    window.set_title(&window_title(&scene.uniforms.projection));
+                    println!("Estado cargado de {}", STATE_PATH);
+                }
+                Err(error) => eprintln!("No se pudo cargar el estado: {}", error),
+            }
+        }
+
+        // F12 guarda el cuadro actual a --still-size, con su propia proyección
+        if !typing && window.is_key_pressed(Key::F12, KeyRepeat::No) {
+            save_still(&mut scene, args.still_size, &tone_mapping);
+        }
+
+        // F11 recrea la ventana; cámara y escena se conservan
+        if !typing && window.is_key_pressed(Key::F11, KeyRepeat::No) {
+            if fullscreen {
+                window = create_window(windowed_size.0, windowed_size.1, false, args.fps)?;
+                window.set_position(windowed_position.0, windowed_position.1);
+            } else {
+                windowed_size = window.get_size();
+                windowed_position = window.get_position();
+                window = create_window(FULLSCREEN_SIZE.0, FULLSCREEN_SIZE.1, true, args.fps)?;
+                window.set_position(0, 0);
+            }
+            fullscreen = !fullscreen;
+            window.set_title(&window_title(&scene.uniforms.projection));
+        }
+
+        // Si la ventana cambió de tamaño, todo lo que depende de él se reconstruye.
+        // En modo adaptativo la resolución interna se reduce y minifb la escala.
+        let (base_width, base_height) = args.resolution.unwrap_or(window.get_size());
+        let render_scale = match (&mut quality, last_work_ms) {
+            (Some(quality), Some(work_ms)) => quality.next_scale(work_ms),
+            (Some(quality), None) => quality.scale(),
+            (None, _) => 1.0,
+        };
+        let new_width = (base_width as f32 * render_scale) as usize;
+        let new_height = (base_height as f32 * render_scale) as usize;
+        if new_width > 0 && new_height > 0
+            && (new_width != framebuffer.width || new_height != framebuffer.height)
+        {
+            resize_viewport(&mut framebuffer, &mut scene.uniforms, new_width, new_height);
+        }
+
+        let split_views = split.enabled.then(|| split.viewports(framebuffer.width, framebuffer.height));
+        let frame_stats = if let Some((overview, close_up)) = split_views {
+            // Cada vista limpia su rectángulo; los efectos de pantalla, que
+            // suponen una sola cámara, quedan para la vista completa
+            let (camera, projection) = (scene.camera.clone(), scene.uniforms.projection);
+            let mut stats = render_split_view(&mut framebuffer, &mut scene, &mut overview_background, skybox.as_ref(), &overview, &split.overview_camera, &split.overview_projection);
+            stats += render_split_view(&mut framebuffer, &mut scene, &mut background, skybox.as_ref(), &close_up, &camera, &projection);
+            stats
+        } else {
+            let stats = if let Some(progress) = &mut progress {
+                // Cualquier tecla puede cambiar lo que se ve (shaders, visibilidad, detalle)
+                if !window.get_keys_pressed(KeyRepeat::No).is_empty() {
+                    progress.restart();
+                }
+                render_progressive_pass(&mut framebuffer, &mut scene, progress, &mut background, skybox.as_ref())
+            } else {
+                framebuffer.clear();
+                if skybox.is_none() {
+                    background.render(&mut framebuffer, &scene.camera, &scene.uniforms.projection, &scene.uniforms.noise, scene.render_time());
+                }
+                let stats = scene.render(&mut framebuffer);
+                // El cielo sólo va donde no quedó nada dibujado
+                if let Some(texture) = &skybox {
+                    skybox::render_skybox(&mut framebuffer, &scene.camera, &scene.uniforms.projection, texture);
+                }
+                stats
+            };
+            if let Some(index) = selected_body {
+                scene.render_outline_mask(&framebuffer, &mut outline, index);
+            }
+            if show_axes {
+                draw_rotation_axes(&mut framebuffer, &scene.uniforms, &scene.bodies, alpha);
+            }
+            if show_gizmo {
+                render_gizmo(&mut framebuffer, &scene.uniforms);
+            }
+            scene.motion_blur.apply(&mut framebuffer);
+            stats
+        };
+
+        // Exposición y tone mapping del buffer HDR al buffer que muestra minifb
+        if let Some(luminance) = framebuffer.average_luminance() {
+            tone_mapping.update_auto_exposure(luminance, frame_time);
+        }
+        framebuffer.resolve(&tone_mapping);
+
+        if !split.enabled {
+            // La lente curva todo lo que quedó detrás de cada agujero negro
+            for body in scene.bodies.iter().filter(|body| body.visible && matches!(body.shader_type, PlanetType::BlackHole)) {
+                let (position, _) = interpolated_state(body, alpha);
+                gravitational_lens.apply(&mut framebuffer, &scene.uniforms, &position, body.scale);
+            }
+
+            // Enfoca el cuerpo seleccionado o, sin selección, el punto que mira la cámara
+            let focus_point = match selected_body {
+                Some(index) => interpolated_state(&scene.bodies[index], alpha).0,
+                None => scene.camera.center,
+            };
+            depth_of_field.apply(&mut framebuffer, &scene.uniforms.projection, (focus_point - scene.camera.eye).norm());
+
+            // Destello de la primera estrella, tapado por lo que quedó en el z-buffer
+            if let Some(sun) = scene.bodies.iter().find(|body| body.shader_type.is_star()).filter(|sun| sun.visible) {
+                let (position, _) = interpolated_state(sun, alpha);
+                draw_lens_flare(&mut framebuffer, &LENS_FLARE, &scene.uniforms, &position, sun.scale, &star_light_color(&sun.shader_type));
+            }
+        }
+
+        // Profundidad y sobredibujado reemplazan la imagen, efectos incluidos
+        debug_view.apply(&mut framebuffer, &scene.uniforms.projection);
+        if monochrome {
+            framebuffer.to_grayscale_in_place();
+        }
+
+        // El contorno y las etiquetas van sobre el buffer ya convertido a 8 bits y no pierden su color
+        if let Some((overview, close_up)) = split_views {
+            draw_split_overlay(&mut framebuffer, &overview, &close_up, split.overview_active());
+        } else {
+            if selected_body.is_some() {
+                outline.apply(&mut framebuffer);
+            }
+            draw_body_labels(&mut framebuffer, &scene.uniforms, &scene.camera, &scene.bodies, selected_body, alpha);
+        }
+        if debug_view != DebugView::Shaded {
+            let resolution = (framebuffer.width, framebuffer.height);
+            framebuffer.draw_text(STATS_MARGIN, STATS_MARGIN, &stats_text(debug_view, &frame_stats, resolution, render_scale), LABEL_COLOR);
+        }
+        if console.is_open() {
+            draw_console(&mut framebuffer, &console);
+        }
+
+        // `update_with_buffer` espera al límite de FPS: se mide antes
+        last_work_ms = Some(now.elapsed().as_secs_f32() * 1000.0);
+        window
+            .update_with_buffer(&framebuffer.buffer, framebuffer.width, framebuffer.height)?;
+    }
+
+    if let Err(error) = bookmarks.save(BOOKMARKS_PATH) {
+        eprintln!("No se pudieron guardar los marcadores de cámara: {}", error);
+    }
+    Ok(())
 }
 
-fn create_cell_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(1337);
-    noise.set_noise_type(Some(NoiseType::Cellular));
-    noise.set_frequency(Some(0.1));
-    noise
+// Ctrl+1..5 guarda la vista actual; 1..5 vuelve a ella con una transición
+fn handle_bookmark_input(
+    window: &Window,
+    camera: &Camera,
+    bookmarks: &mut CameraBookmarks,
+) -> Option<CameraTransition> {
+    let ctrl = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+
+    for (slot, key) in BOOKMARK_KEYS.iter().enumerate() {
+        if !window.is_key_pressed(*key, KeyRepeat::No) {
+            continue;
+        }
+
+        if ctrl {
+            bookmarks.store(slot, camera.save_state());
+            println!("Vista guardada en el marcador {}", slot + 1);
+        } else if let Some(state) = bookmarks.get(slot) {
+            return Some(CameraTransition::new(camera.save_state(), state));
+        }
+    }
+
+    None
 }
 
-fn create_ground_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(1337);
-    
-   
-    noise.set_noise_type(Some(NoiseType::Cellular)); 
-    noise.set_fractal_type(Some(FractalType::FBm)); 
-    noise.set_fractal_octaves(Some(5));              
-    noise.set_fractal_lacunarity(Some(2.0));         
-    noise.set_fractal_gain(Some(0.5));               
-    noise.set_frequency(Some(0.05));                 
-
-    noise
+// Cuerpos y cámara iniciales según --seed / --scene
+fn load_system(args: &cli::Args) -> Result<(Vec<CelestialBody>, Camera), Error> {
+    let mut camera = Camera::new(
+        Vec3::new(0.0, 0.0, 5.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0)
+    );
+
+    let mut celestial_bodies = match args.seed {
+        Some(seed) => generate_system(seed, args.planet_count),
+        None => default_system(),
+    };
+
+    // Una escena exportada reemplaza tanto los cuerpos como la cámara
+    if let Some(path) = &args.scene {
+        (celestial_bodies, camera) = load_scene(path)?;
+    }
+
+    Ok((celestial_bodies, camera))
 }
 
-fn create_lava_noise() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::with_seed(42);
-    
- 
-    noise.set_noise_type(Some(NoiseType::Perlin));  
-    noise.set_fractal_type(Some(FractalType::FBm)); 
-    noise.set_fractal_octaves(Some(6));            
-    noise.set_fractal_lacunarity(Some(2.0));       
-    noise.set_fractal_gain(Some(0.5));              
-    noise.set_frequency(Some(0.002));                
-    
-    noise
+// Uniforms, sistema y mallas según los argumentos. Va después de crear el
+// framebuffer porque --reversed-z cambia también su test de profundidad.
+fn build_scene(args: &cli::Args, framebuffer: &mut Framebuffer) -> Result<Scene, Error> {
+    let mut uniforms = create_uniforms(framebuffer.width, framebuffer.height, args.seed.unwrap_or(0));
+    if args.noise_cache {
+        toggle_noise_cache(&mut uniforms);
+    }
+    if args.reversed_z {
+        set_reversed_z(framebuffer, &mut uniforms, true);
+    }
+
+    let (bodies, camera) = load_system(args)?;
+    let assets = Assets::locate(args.assets.as_deref());
+    let meshes = SceneMeshes::with_sphere(&assets, &args.sphere_mesh(&assets), &bodies)?;
+    let mut scene = Scene::new(bodies, camera, uniforms, meshes);
+    scene.context.shader_lod.enabled = !args.full_shading;
+    Ok(scene)
 }
 
-fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
-    let (sin_x, cos_x) = rotation.x.sin_cos();
-    let (sin_y, cos_y) = rotation.y.sin_cos();
-    let (sin_z, cos_z) = rotation.z.sin_cos();
+// Modo --bench-frames: sin ventana, dibuja `frames` cuadros con pasos fijos
+// de simulación e imprime el tiempo medio y el trabajo del pipeline
+fn run_benchmark(args: &cli::Args, frames: usize) -> Result<(), Error> {
+    let (width, height) = args.resolution.unwrap_or((args.width, args.height));
+    let mut framebuffer = Framebuffer::new(width, height);
+    framebuffer.set_background_color(0x333355);
 
-    let rotation_matrix_x = Mat4::new(
-        1.0,  0.0,    0.0,   0.0,
-        0.0,  cos_x, -sin_x, 0.0,
-        0.0,  sin_x,  cos_x, 0.0,
-        0.0,  0.0,    0.0,   1.0,
-    );
+    // El desenfoque de movimiento queda desactivado: se mide sólo el pipeline base
+    let mut scene = build_scene(args, &mut framebuffer)?;
+    let tone_mapping = ToneMapSettings::default();
 
-    let rotation_matrix_y = Mat4::new(
-        cos_y,  0.0,  sin_y, 0.0,
-        0.0,    1.0,  0.0,   0.0,
-        -sin_y, 0.0,  cos_y, 0.0,
-        0.0,    0.0,  0.0,   1.0,
-    );
+    let mut stats = RenderStats::default();
+    let mut total_time = 0.0;
+    let mut slowest_frame: f32 = 0.0;
+    for _ in 0..frames {
+        let start = Instant::now();
 
-    let rotation_matrix_z = Mat4::new(
-        cos_z, -sin_z, 0.0, 0.0,
-        sin_z,  cos_z, 0.0, 0.0,
-        0.0,    0.0,  1.0, 0.0,
-        0.0,    0.0,  0.0, 1.0,
+        // Un paso de simulación exacto por frame
+        scene.update(FIXED_DT);
+        framebuffer.clear();
+        stats += scene.render(&mut framebuffer);
+        framebuffer.resolve(&tone_mapping);
+
+        let elapsed = start.elapsed().as_secs_f32();
+        total_time += elapsed;
+        slowest_frame = slowest_frame.max(elapsed);
+    }
+
+    let count = frames.max(1) as f32;
+    let average_ms = total_time / count * 1000.0;
+    println!(
+        "bench: {} frames a {}x{}, {:.2} ms/frame de media ({:.1} FPS, peor {:.2} ms), {:.0} triángulos y {:.0} fragmentos ({:.0} sombreados) por frame",
+        frames,
+        width,
+        height,
+        average_ms,
+        if average_ms > 0.0 { 1000.0 / average_ms } else { 0.0 },
+        slowest_frame * 1000.0,
+        stats.triangles as f32 / count,
+        stats.fragments as f32 / count,
+        stats.shaded_fragments as f32 / count,
     );
+    Ok(())
+}
+
+// Modo --turntable: sin ventana, guarda una vuelta de la cámara alrededor de
+// un cuerpo como PNG numerados, al tamaño de --resolution o --width/--height
+fn run_turntable(args: &cli::Args, query: &str) -> Result<(), Error> {
+    let (width, height) = args.resolution.unwrap_or((args.width, args.height));
+    let mut framebuffer = Framebuffer::new(width, height);
+    let mut scene = build_scene(args, &mut framebuffer)?;
 
-    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+    let Some(body) = turntable::find_body(&scene.bodies, query) else {
+        let names: Vec<String> = scene.bodies.iter()
+            .enumerate()
+            .map(|(index, body)| format!("{} {}", index, body.shader_type.name()))
+            .collect();
+        return Err(Error::UnknownBody { query: query.to_string(), available: names.join(", ") });
+    };
+    if args.isolate {
+        visibility::isolate(&mut scene.bodies, body);
+    }
 
-    let transform_matrix = Mat4::new(
-        scale, 0.0,   0.0,   translation.x,
-        0.0,   scale, 0.0,   translation.y,
-        0.0,   0.0,   scale, translation.z,
-        0.0,   0.0,   0.0,   1.0,
+    let turntable = Turntable::new(body, args.turntable_frames());
+    let paths = turntable::render_turntable(&mut scene, &turntable, width, height, &ToneMapSettings::default(), &args.out)?;
+    println!(
+        "{}: {} frames de {}x{} guardados en {}",
+        scene.bodies[body].shader_type.name(),
+        paths.len(),
+        width,
+        height,
+        args.out.display(),
     );
+    Ok(())
+}
+
+// Una vista de la pantalla dividida: limpia su rectángulo y dibuja ahí el
+// fondo (o el cielo de --skybox, después) y la escena con su cámara y proyección
+fn render_split_view(
+    framebuffer: &mut Framebuffer,
+    scene: &mut Scene,
+    background: &mut Background,
+    skybox: Option<&SkyboxTexture>,
+    viewport: &Viewport,
+    camera: &Camera,
+    projection: &ProjectionSettings,
+) -> RenderStats {
+    framebuffer.clear_region(viewport);
+    if skybox.is_none() {
+        background.render_viewport(framebuffer, viewport, camera, projection, &scene.uniforms.noise, scene.render_time());
+    }
+    let stats = scene.render_viewport(framebuffer, viewport, camera, projection);
+    if let Some(texture) = skybox {
+        skybox::render_skybox_viewport(framebuffer, viewport, camera, projection, texture);
+    }
+    stats
+}
 
-    transform_matrix * rotation_matrix
+// La siguiente pasada de --progressive y, en `framebuffer`, la imagen que hay
+// hasta ahora con las filas que faltan rellenadas. Si la vista cambió empieza
+// de nuevo desde el fondo; completa, sólo vuelve a copiar la imagen.
+fn render_progressive_pass(
+    framebuffer: &mut Framebuffer,
+    scene: &mut Scene,
+    progress: &mut RenderProgress,
+    background: &mut Background,
+    skybox: Option<&SkyboxTexture>,
+) -> RenderStats {
+    let restarted = progress.track(&scene.camera, &scene.uniforms.projection, scene.render_time(), framebuffer);
+    if restarted && skybox.is_none() {
+        background.render(progress.target_mut(), &scene.camera, &scene.uniforms.projection, &scene.uniforms.noise, scene.render_time());
+    }
+    let drawing = !progress.is_complete();
+    let stats = scene.render_pass(progress);
+    if let (true, Some(texture)) = (drawing, skybox) {
+        skybox::render_skybox(progress.target_mut(), &scene.camera, &scene.uniforms.projection, texture);
+    }
+    progress.present(framebuffer);
+    stats
 }
 
+// Línea entre las dos vistas y el nombre de cada una arriba, el de la activa
+// resaltado. Sobre el buffer ya convertido.
+fn draw_split_overlay(framebuffer: &mut Framebuffer, overview: &Viewport, close_up: &Viewport, overview_active: bool) {
+    for y in 0..framebuffer.height {
+        let row = y * framebuffer.width;
+        framebuffer.buffer[row + overview.x + overview.width..row + close_up.x].fill(DIVIDER_COLOR);
+    }
+    framebuffer.mark_all_dirty();
 
-fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    look_at(&eye, &center, &up)
+    let views = [(overview, "General", overview_active), (close_up, "Primer plano", !overview_active)];
+    for (viewport, name, active) in views {
+        let color = if active { LABEL_SELECTED_COLOR } else { LABEL_COLOR };
+        let x = (viewport.x + viewport.width) as isize - (name.chars().count() * GLYPH_WIDTH) as isize - STATS_MARGIN;
+        framebuffer.draw_text(x, STATS_MARGIN, name, color);
+    }
 }
 
-fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 45.0 * PI / 180.0;
-    let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
+// Eje de rotación de cada cuerpo como una línea que atraviesa sus polos,
+// con test de profundidad para que el planeta tape la mitad de atrás
+fn draw_rotation_axes(framebuffer: &mut Framebuffer, uniforms: &Uniforms, bodies: &[CelestialBody], alpha: f32) {
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let color = Vec3::new(AXIS_COLOR.0, AXIS_COLOR.1, AXIS_COLOR.2);
+
+    for body in bodies.iter().filter(|body| body.visible) {
+        let (position, _) = interpolated_state(body, alpha);
+        // El giro no mueve el eje: basta la orientación con la inclinación
+        let orientation = create_model_matrix(Vec3::zeros(), 1.0, body.rotation + Vec3::new(0.0, 0.0, body.axial_tilt));
+        let axis = (orientation * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz().normalize() * body.scale * AXIS_EXTENT;
 
-    perspective(fov, aspect_ratio, near, far)
+        let north = project_point(&(position + axis), &view_projection, &uniforms.viewport_matrix);
+        let south = project_point(&(position - axis), &view_projection, &uniforms.viewport_matrix);
+        if let (Some(north), Some(south)) = (north, south) {
+            draw_line(framebuffer, &south, &north, color);
+        }
+    }
 }
 
-fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
-    Mat4::new(
-        width / 2.0, 0.0, 0.0, width / 2.0,
-        0.0, -height / 2.0, 0.0, height / 2.0,
-        0.0, 0.0, 1.0, 0.0,
-        0.0, 0.0, 0.0, 1.0
-    )
+// Ejes X (rojo), Y (verde) y Z (azul) del mundo desde el origen, con test de
+// profundidad para que el Sol los tape cuando quedan detrás. El largo crece
+// con la distancia de la cámara, así ocupan siempre una parte parecida de la
+// pantalla; de cerca quedan dentro del Sol.
+fn render_gizmo(framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+    let projection = &uniforms.projection;
+    let half_height = match projection.mode {
+        ProjectionMode::Perspective => uniforms.camera_position.norm() * (projection.fov_degrees.to_radians() * 0.5).tan(),
+        ProjectionMode::Orthographic => projection.ortho_half_height,
+    };
+    let length = half_height * GIZMO_SCREEN_FRACTION;
+
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let Some(origin) = project_point(&Vec3::zeros(), &view_projection, &uniforms.viewport_matrix) else {
+        return;
+    };
+    let axes = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)];
+    for (axis, color) in axes.iter().zip(GIZMO_COLORS) {
+        if let Some(end) = project_point(&(axis * length), &view_projection, &uniforms.viewport_matrix) {
+            draw_line(framebuffer, &origin, &end, Vec3::new(color.0, color.1, color.2));
+        }
+    }
 }
 
-fn render(
+// Nombre de cada cuerpo, centrado justo encima de su borde superior en pantalla
+fn draw_body_labels(
     framebuffer: &mut Framebuffer,
     uniforms: &Uniforms,
-    vertex_array: &[Vertex],
-    planet_type: &PlanetType
+    camera: &Camera,
+    bodies: &[CelestialBody],
+    selected: Option<usize>,
+    alpha: f32,
 ) {
-    // Vertex Shader Stage
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
+    let width = framebuffer.width as f32;
+    let height = framebuffer.height as f32;
+    let up = camera.up.normalize();
+
+    for (index, body) in bodies.iter().enumerate().filter(|(_, body)| body.visible) {
+        let (position, _) = interpolated_state(body, alpha);
+        let top = position + up * body.scale;
+        let Some((x, y)) = picking::project_to_screen(&top, width, height, &uniforms.view_matrix, &uniforms.projection_matrix) else {
+            continue;
+        };
+
+        let name = body.shader_type.name();
+        let text_width = (name.chars().count() * GLYPH_WIDTH) as f32;
+        let color = if selected == Some(index) { LABEL_SELECTED_COLOR } else { LABEL_COLOR };
+        framebuffer.draw_text(
+            (x - text_width / 2.0) as isize,
+            (y - (GLYPH_HEIGHT + LABEL_MARGIN) as f32) as isize,
+            name,
+            color,
+        );
     }
+}
+
+// El ratón viene en coordenadas de ventana; pasarlo a las del framebuffer
+// teniendo en cuenta las franjas de AspectRatioStretch
+fn window_to_framebuffer(mouse_x: f32, mouse_y: f32, window: &Window, framebuffer: &Framebuffer) -> (f32, f32) {
+    let (window_width, window_height) = window.get_size();
+    let scale = (window_width as f32 / framebuffer.width as f32)
+        .min(window_height as f32 / framebuffer.height as f32);
+    let offset_x = (window_width as f32 - framebuffer.width as f32 * scale) / 2.0;
+    let offset_y = (window_height as f32 - framebuffer.height as f32 * scale) / 2.0;
+    ((mouse_x - offset_x) / scale, (mouse_y - offset_y) / scale)
+}
 
-    // Primitive Assembly Stage
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
+// `x` e `y` en píxeles del framebuffer; `uniforms` trae la proyección y el
+// viewport de la vista en la que cayó el clic
+fn handle_click(x: f32, y: f32, camera: &Camera, bodies: &[CelestialBody], uniforms: &Uniforms) -> Option<usize> {
+    let selected = picking::pick_body(x, y, camera, bodies, uniforms);
+    match selected {
+        Some(index) => {
+            let body = &bodies[index];
+            let sun_position = bodies.iter()
+                .find(|body| body.shader_type.is_star())
+                .map(|body| body.position)
+                .unwrap_or(Vec3::new(0.0, 0.0, 0.0));
+            println!(
+                "Seleccionado: {:?} (escala {:.2}, distancia al Sol {:.2})",
+                body.shader_type,
+                body.scale,
+                (body.position - sun_position).norm()
+            );
         }
+        None => println!("Selección eliminada"),
+    }
+    selected
+}
+
+fn window_title(projection: &ProjectionSettings) -> String {
+    match projection.mode {
+        ProjectionMode::Perspective => format!("{} - FOV {:.0}°", WINDOW_TITLE, projection.fov_degrees),
+        ProjectionMode::Orthographic => format!("{} - Ortográfica", WINDOW_TITLE),
+    }
+}
+
+// , y . cambian el FOV; Shift+O alterna la proyección ortográfica.
+// Devuelve true si hay que reconstruir la matriz de proyección.
+fn handle_projection_input(window: &Window, projection: &mut ProjectionSettings, bodies: &[CelestialBody]) -> bool {
+    let mut changed = false;
+
+    if window.is_key_pressed(Key::Comma, KeyRepeat::Yes) {
+        projection.adjust_fov(-FOV_STEP_DEGREES);
+        changed = true;
+    }
+    if window.is_key_pressed(Key::Period, KeyRepeat::Yes) {
+        projection.adjust_fov(FOV_STEP_DEGREES);
+        changed = true;
+    }
+
+    let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+    if shift && window.is_key_pressed(Key::O, KeyRepeat::No) {
+        projection.mode = match projection.mode {
+            ProjectionMode::Perspective => {
+                projection.ortho_half_height = system_extent(bodies);
+                ProjectionMode::Orthographic
+            }
+            ProjectionMode::Orthographic => ProjectionMode::Perspective,
+        };
+        changed = true;
     }
 
- 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+    changed
+}
+
+// C activa un corte horizontal (se descarta todo lo que está por encima
+// de la altura del plano); RePág/AvPág lo suben y bajan
+fn handle_clip_input(window: &Window, uniforms: &mut Uniforms) {
+    if window.is_key_pressed(Key::C, KeyRepeat::No) {
+        uniforms.clip_plane = match uniforms.clip_plane {
+            Some(_) => None,
+            None => Some(Vec4::new(0.0, -1.0, 0.0, 0.0)),
+        };
+        println!("Corte: {}", if uniforms.clip_plane.is_some() { "activado" } else { "desactivado" });
     }
 
-    
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-        if x < framebuffer.width && y < framebuffer.height {
-            
-            let shaded_color = fragment_shader(&fragment, &uniforms, planet_type);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+    if let Some(plane) = &mut uniforms.clip_plane {
+        let mut moved = false;
+        if window.is_key_pressed(Key::PageUp, KeyRepeat::Yes) {
+            plane.w += CLIP_PLANE_STEP;
+            moved = true;
+        }
+        if window.is_key_pressed(Key::PageDown, KeyRepeat::Yes) {
+            plane.w -= CLIP_PLANE_STEP;
+            moved = true;
+        }
+        if moved {
+            println!("Altura del corte: {:.2}", plane.w);
         }
     }
 }
 
-fn main() {
-    let window_width = 800;
-    let window_height = 600;
-    let framebuffer_width = 800;
-    let framebuffer_height = 600;
+fn handle_detail_input(window: &Window, uniforms: &mut Uniforms) {
+    // Octavas del terreno (- para menos detalle, = para más)
+    let mut octaves = uniforms.terrain_octaves;
+    if window.is_key_pressed(Key::Minus, KeyRepeat::Yes) {
+        octaves -= 1;
+    }
+    if window.is_key_pressed(Key::Equal, KeyRepeat::Yes) {
+        octaves += 1;
+    }
 
-    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
-    let mut window = Window::new(
-        "Rust Graphics - Renderer Example",
-        window_width,
-        window_height,
-        WindowOptions::default(),
-    )
-        .unwrap();
+    // F alterna entre sombreado suave y plano
+    if window.is_key_pressed(Key::F, KeyRepeat::No) {
+        uniforms.shading = match uniforms.shading {
+            ShadingMode::Smooth => ShadingMode::Flat,
+            ShadingMode::Flat => ShadingMode::Smooth,
+        };
+        println!("Sombreado: {:?}", uniforms.shading);
+    }
 
-    window.set_position(500, 500);
-    window.update();
+    // N alterna entre ruido directo y ruido cacheado, para comparar calidad
+    if window.is_key_pressed(Key::N, KeyRepeat::No) {
+        toggle_noise_cache(uniforms);
+    }
 
-    framebuffer.set_background_color(0x333355);
+    let octaves = octaves.clamp(0, MAX_TERRAIN_OCTAVES);
+    if octaves != uniforms.terrain_octaves {
+        uniforms.terrain_octaves = octaves;
+        println!("Octavas del terreno: {}", octaves);
+    }
+}
 
-    
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 0.0, 5.0),
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0)
-    );
+// [ y ] bajan/suben la exposición, T cambia el operador, \ activa la exposición automática
+// y F6 cambia el tramado
+fn handle_exposure_input(window: &Window, tone_mapping: &mut ToneMapSettings) {
+    let mut changed = false;
+    if window.is_key_pressed(Key::LeftBracket, KeyRepeat::Yes) {
+        tone_mapping.adjust_exposure(-EXPOSURE_STEP);
+        tone_mapping.auto_exposure = false;
+        changed = true;
+    }
+    if window.is_key_pressed(Key::RightBracket, KeyRepeat::Yes) {
+        tone_mapping.adjust_exposure(EXPOSURE_STEP);
+        tone_mapping.auto_exposure = false;
+        changed = true;
+    }
+    if window.is_key_pressed(Key::T, KeyRepeat::No) {
+        tone_mapping.cycle_operator();
+        changed = true;
+    }
+    if window.is_key_pressed(Key::Backslash, KeyRepeat::No) {
+        tone_mapping.auto_exposure = !tone_mapping.auto_exposure;
+        changed = true;
+    }
+    if window.is_key_pressed(Key::F6, KeyRepeat::No) {
+        tone_mapping.dither = tone_mapping.dither.next();
+        println!("Tramado: {:?}", tone_mapping.dither);
+    }
+
+    if changed {
+        println!(
+            "Exposición: {:.2} ({:?}{})",
+            tone_mapping.exposure,
+            tone_mapping.operator,
+            if tone_mapping.auto_exposure { ", automática" } else { "" }
+        );
+    }
+}
 
-    let obj = Obj::load("assets/models/esfera.obj").expect("Failed to load obj");
-    let vertex_arrays = obj.get_vertex_array(); 
-    let mut time = 0;
-
-    let noise = create_noise();
-    let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
-    let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
-    let mut uniforms = Uniforms { 
-        model_matrix: Mat4::identity(), 
-        view_matrix: Mat4::identity(), 
-        projection_matrix, 
-        viewport_matrix, 
-        time: 0, 
-        noise
+// Recarga la escena de --scene cuando cambia en disco o, con Ctrl+R, aunque
+// no haya cambiado. La cámara y el tiempo se conservan; si el archivo no se
+// puede leer se avisa y sigue la escena que había. Devuelve la selección con
+// el índice que le toca en la escena nueva.
+fn handle_reload_input(
+    window: &Window,
+    watcher: Option<&mut SceneWatcher>,
+    scene: &mut Scene,
+    selected: Option<usize>,
+    dt: f32,
+) -> Option<usize> {
+    let ctrl = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+    let forced = ctrl && window.is_key_pressed(Key::R, KeyRepeat::No);
+    let Some(watcher) = watcher else {
+        if forced {
+            println!("No hay escena que recargar: se abre con --scene");
+        }
+        return selected;
     };
+    if forced {
+        watcher.sync();
+    } else if !watcher.poll(dt) {
+        return selected;
+    }
 
-    
-    let mut celestial_bodies = vec![
-        CelestialBody {
-            position: Vec3::new(0.0, 0.0, 0.0),
-            scale: 2.0,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Sun,
-        },
-        CelestialBody {
-            position: Vec3::new(6.0, 0.0, 0.0),
-            scale: 0.4,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Mercury,
-        },
-        CelestialBody {
-            position: Vec3::new(12.0, 0.0, 0.0),
-            scale: 0.6,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Venus,
-        },
-        CelestialBody {
-            position: Vec3::new(18.0, 0.0, 0.0),
-            scale: 0.7,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Earth,
-        },
-        CelestialBody {
-            position: Vec3::new(24.0, 0.0, 0.0),
-            scale: 0.5,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Mars,
-        },
-        CelestialBody {
-            position: Vec3::new(32.0, 0.0, 0.0),
-            scale: 1.5,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Jupiter,
-        },
-        CelestialBody {
-            position: Vec3::new(40.0, 0.0, 0.0),
-            scale: 1.3,
-            rotation: Vec3::new(0.2, 0.0, 0.0),
-            shader_type: PlanetType::Saturn,
-        },
-        CelestialBody {
-            position: Vec3::new(48.0, 0.0, 0.0),
-            scale: 0.9,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Uranus,
-        },
-        CelestialBody {
-            position: Vec3::new(56.0, 0.0, 0.0),
-            scale: 0.9,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Neptune,
-        },
-        CelestialBody {
-            position: Vec3::new(18.0, 0.0, 2.0),
-            scale: 0.2,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::Moon,
-        },
-        CelestialBody {
-            position: Vec3::new(-20.0, 0.0, -20.0),
-            scale: 4.0,
-            rotation: Vec3::new(0.0, 0.0, 0.0),
-            shader_type: PlanetType::BlackHole,
-        },
-    ];
+    match load_scene(watcher.path()) {
+        Ok((bodies, _)) => {
+            let reload = scene.reload_bodies(bodies);
+            println!(
+                "Escena recargada de {}: {} cuerpos actualizados, {} nuevos, {} quitados",
+                watcher.path(),
+                reload.updated,
+                reload.added,
+                reload.removed,
+            );
+            selected.and_then(|index| reload.moved[index])
+        }
+        Err(error) => {
+            eprintln!("No se pudo recargar la escena: {}", error);
+            selected
+        }
+    }
+}
 
-    while window.is_open() {
-        if window.is_key_down(Key::Escape) {
-            break;
+// J oculta o muestra el cuerpo seleccionado; sin selección vuelve a mostrar
+// todos. U recorre los grupos de `BodyGroup`.
+fn handle_visibility_input(window: &Window, bodies: &mut [CelestialBody], selected: Option<usize>, group: &mut BodyGroup) {
+    if window.is_key_pressed(Key::J, KeyRepeat::No) {
+        match selected {
+            Some(index) => {
+                let visible = visibility::toggle_visible(bodies, index);
+                println!("{}: {}", bodies[index].shader_type.name(), if visible { "visible" } else { "oculto" });
+            }
+            None => {
+                *group = BodyGroup::All;
+                group.apply(bodies);
+                println!("Todos los cuerpos visibles");
+            }
         }
+    }
+    if window.is_key_pressed(Key::U, KeyRepeat::No) {
+        *group = group.next();
+        group.apply(bodies);
+        println!("Cuerpos visibles: {}", group.name());
+    }
+}
 
-        time += 1;
+// Escribe en la consola lo tecleado en este frame, con repetición al mantener
+// la tecla; Enter ejecuta la línea y Escape cierra la consola
+fn handle_console_input(window: &Window, console: &mut Console, scene: &mut Scene) -> Option<ConsoleEffect> {
+    let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+    for key in window.get_keys_pressed(KeyRepeat::Yes) {
+        match key {
+            Key::Enter | Key::NumPadEnter => return console.submit(scene),
+            Key::Backspace => console.backspace(),
+            Key::Escape => console.toggle(),
+            key => {
+                if let Some(c) = console_char(key, shift) {
+                    console.push_char(c);
+                }
+            }
+        }
+    }
+    None
+}
 
-        handle_input(&window, &mut camera);
+// Carácter que escribe `key` en la consola; sólo lo que usan los comandos
+fn console_char(key: Key, shift: bool) -> Option<char> {
+    if let Some(index) = LETTER_KEYS.iter().position(|&letter| letter == key) {
+        let c = (b'a' + index as u8) as char;
+        return Some(if shift { c.to_ascii_uppercase() } else { c });
+    }
+    if let Some(index) = DIGIT_KEYS.iter().find_map(|row| row.iter().position(|&digit| digit == key)) {
+        return char::from_digit(index as u32, 10);
+    }
+    match key {
+        Key::Space => Some(' '),
+        Key::Period | Key::NumPadDot => Some('.'),
+        Key::Minus if shift => Some('_'),
+        Key::Minus | Key::NumPadMinus => Some('-'),
+        _ => None,
+    }
+}
 
-        framebuffer.clear();
+// Las últimas respuestas y, debajo, la línea que se está escribiendo, abajo a la izquierda
+fn draw_console(framebuffer: &mut Framebuffer, console: &Console) {
+    let mut lines: Vec<String> = console.scrollback().map(str::to_string).collect();
+    lines.push(format!("> {}_", console.line()));
+    let y = framebuffer.height as isize - (lines.len() * GLYPH_HEIGHT) as isize - STATS_MARGIN;
+    framebuffer.draw_text(STATS_MARGIN, y, &lines.join("\n"), LABEL_COLOR);
+}
 
-        // Encontrar la posición de la Tierra
-        let earth_position = celestial_bodies.iter()
-            .find(|body| matches!(body.shader_type, PlanetType::Earth))
-            .map(|body| body.position)
-            .unwrap_or(Vec3::new(0.0, 0.0, 0.0));
-
-        // Actualizar la posición de la luna
-        let moon_orbit_radius = 2.0;
-        let moon_orbit_speed = 0.03;
-        let moon_angle = time as f32 * moon_orbit_speed;
-        
-        if let Some(moon) = celestial_bodies.iter_mut()
-            .find(|body| matches!(body.shader_type, PlanetType::Moon))
-        {
-            moon.position = earth_position + Vec3::new(
-                moon_orbit_radius * moon_angle.cos(),
-                0.0,
-                moon_orbit_radius * moon_angle.sin()
-            );
-        }
+// Guarda el cuadro actual a `size` en STILL_PATH, con su propia proyección
+fn save_still(scene: &mut Scene, size: (usize, usize), tone_mapping: &ToneMapSettings) {
+    let (width, height) = size;
+    let camera = scene.camera.clone();
+    let mut still = scene.render_still(width, height, &camera, scene.render_time());
+    still.resolve(tone_mapping);
+    match GoldenImage::save(&still, Path::new(STILL_PATH)) {
+        Ok(()) => println!("Imagen de {}x{} guardada en {}", width, height, STILL_PATH),
+        Err(error) => eprintln!("No se pudo guardar la imagen: {}", error),
+    }
+}
 
-        // Renderizar cada cuerpo celeste
-        for body in &celestial_bodies {
-            uniforms.model_matrix = create_model_matrix(
-                body.position,
-                body.scale,
-                body.rotation + Vec3::new(0.0, time as f32 * 0.01, 0.0)
-            );
-            uniforms.view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-            uniforms.time = time;
-            
-            render(&mut framebuffer, &uniforms, &vertex_arrays, &body.shader_type);
-        }
+// ; y ' bajan/suben la semilla del ruido y regeneran las superficies y las
+// protuberancias. Sin --seed se parte de 0; cada valor da siempre el mismo
+// resultado.
+fn handle_seed_input(window: &Window, scene: &mut Scene) -> bool {
+    let step: i64 = if window.is_key_pressed(Key::Semicolon, KeyRepeat::Yes) {
+        -1
+    } else if window.is_key_pressed(Key::Apostrophe, KeyRepeat::Yes) {
+        1
+    } else {
+        return false;
+    };
 
-        window
-            .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
-            .unwrap();
+    let seed = scene.uniforms.seed.wrapping_add_signed(step);
+    scene.set_seed(seed);
+    println!("Semilla del ruido: {}", seed);
+    true
+}
+
+fn toggle_noise_cache(uniforms: &mut Uniforms) {
+    uniforms.noise_cache = match uniforms.noise_cache.take() {
+        Some(_) => None,
+        None => Some(NoiseCache::new(&uniforms.noise, NOISE_CACHE_EXTENT, NOISE_CACHE_CELLS)),
+    };
+    let state = if uniforms.noise_cache.is_some() { "activada" } else { "desactivada" };
+    println!("Caché de ruido: {}", state);
+}
+
+// Controles mientras la cámara persigue un cuerpo: izquierda/derecha y
+// arriba/abajo giran alrededor de él y Z/X acercan o alejan. El giro queda
+// guardado en el marco orbital del cuerpo mientras se le siga.
+fn handle_chase_input(window: &Window, chase: &mut ChaseCamera, dt: f32) {
+    let rotation_speed = PI / 2.0 * dt;
+    let zoom_factor = (-1.5 * dt).exp();
+
+    let mut yaw = 0.0;
+    let mut pitch = 0.0;
+    if window.is_key_down(Key::Left) || window.is_key_down(Key::A) {
+        yaw -= rotation_speed;
+    }
+    if window.is_key_down(Key::Right) || window.is_key_down(Key::D) {
+        yaw += rotation_speed;
+    }
+    if window.is_key_down(Key::Up) || window.is_key_down(Key::W) {
+        pitch += rotation_speed;
+    }
+    if window.is_key_down(Key::Down) || window.is_key_down(Key::S) {
+        pitch -= rotation_speed;
+    }
+    chase.orbit(yaw, pitch);
+
+    if window.is_key_down(Key::Z) {
+        chase.zoom(zoom_factor);
+    }
+    if window.is_key_down(Key::X) {
+        chase.zoom(1.0 / zoom_factor);
     }
 }
 
-fn handle_input(window: &Window, camera: &mut Camera) {
-    let movement_speed = 0.5;
-    let rotation_speed = PI/50.0;
-    let zoom_speed = 1.0;
+fn handle_input(window: &Window, camera: &mut Camera, dt: f32) {
+    // Velocidades por segundo: los controles no dependen de los FPS
+    let movement_speed = 30.0 * dt;
+    let rotation_speed = PI / 50.0 * 60.0 * dt;
+    let zoom_speed = 60.0 * dt;
 
     // Rotación de la cámara (mirando arriba/abajo)
     if window.is_key_down(Key::Up) {
@@ -366,9 +1235,6 @@ fn handle_input(window: &Window, camera: &mut Camera) {
     }
 
     // Movimiento WASD (adelante, izquierda, atrás, derecha)
-    let forward = camera.get_forward() * movement_speed;
-    let right = camera.get_right() * movement_speed;
-    
     if window.is_key_down(Key::W) {
         camera.move_forward(movement_speed);
     }
@@ -390,11 +1256,11 @@ fn handle_input(window: &Window, camera: &mut Camera) {
         camera.move_up(-movement_speed);
     }
 
-    // Zoom (1 para acercar, 2 para alejar)
-    if window.is_key_down(Key::Key1) {
+    // Zoom (Z para acercar, X para alejar; 1..5 son marcadores)
+    if window.is_key_down(Key::Z) {
         camera.zoom(zoom_speed);
     }
-    if window.is_key_down(Key::Key2) {
+    if window.is_key_down(Key::X) {
         camera.zoom(-zoom_speed);
     }
 }
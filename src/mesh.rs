@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+use std::path::PathBuf;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::color::Color;
+use crate::error::Error;
+use crate::obj::{Obj, fix_spherical_triangle, spherical_tangent, spherical_uv};
+use crate::vertex::Vertex;
+
+// La misma resolución que `models/esfera.obj`
+pub const UV_SPHERE_SECTORS: usize = 32;
+pub const UV_SPHERE_STACKS: usize = 16;
+// El nivel 3 tiene 1280 triángulos, cerca de los 960 de la esfera UV
+pub const DEFAULT_ICOSPHERE_SUBDIVISIONS: u32 = 3;
+// Cada nivel multiplica los triángulos por cuatro; el 7 ya tiene 327680
+pub const MAX_ICOSPHERE_SUBDIVISIONS: u32 = 7;
+
+// Número áureo: las esquinas del icosaedro son las de tres rectángulos
+// áureos ortogonales
+const PHI: f32 = 1.618_034;
+
+const ICOSAHEDRON_VERTICES: [[f32; 3]; 12] = [
+    [-1.0, PHI, 0.0], [1.0, PHI, 0.0], [-1.0, -PHI, 0.0], [1.0, -PHI, 0.0],
+    [0.0, -1.0, PHI], [0.0, 1.0, PHI], [0.0, -1.0, -PHI], [0.0, 1.0, -PHI],
+    [PHI, 0.0, -1.0], [PHI, 0.0, 1.0], [-PHI, 0.0, -1.0], [-PHI, 0.0, 1.0],
+];
+
+// En sentido antihorario vistos desde fuera
+const ICOSAHEDRON_FACES: [[u32; 3]; 20] = [
+    [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+    [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+    [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+    [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+];
+
+// De dónde sale la malla de esfera que comparten los cuerpos sin malla
+// propia
+#[derive(Debug, Clone, PartialEq)]
+pub enum SphereMesh {
+    Obj(PathBuf),
+    UvSphere,
+    Icosphere(u32),
+}
+
+impl SphereMesh {
+    // Lista de triángulos lista para `render_mesh`
+    pub fn vertex_array(&self) -> Result<Vec<Vertex>, Error> {
+        Ok(match self {
+            SphereMesh::Obj(path) => Obj::load(path)?.get_vertex_array(),
+            SphereMesh::UvSphere => {
+                let (vertices, indices) = uv_sphere(UV_SPHERE_SECTORS, UV_SPHERE_STACKS);
+                triangle_list(&vertices, &indices)
+            }
+            SphereMesh::Icosphere(subdivisions) => {
+                let (vertices, indices) = icosphere(*subdivisions);
+                triangle_list(&vertices, &indices)
+            }
+        })
+    }
+}
+
+// Esfera unitaria a partir de un icosaedro cuyos triángulos se dividen en
+// cuatro `subdivisions` veces, llevando las esquinas nuevas a la esfera. A
+// diferencia de la esfera UV, todos los triángulos son de un tamaño parecido
+// y no se amontonan en los polos. Las esquinas en la costura de las UV o en
+// un polo se duplican con las coordenadas de textura que necesita cada
+// triángulo (ver `fix_spherical_triangle`), así que sólo las posiciones
+// dicen qué vértices son el mismo punto.
+pub fn icosphere(subdivisions: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let mut positions: Vec<Vec3> = ICOSAHEDRON_VERTICES.iter()
+        .map(|&[x, y, z]| Vec3::new(x, y, z).normalize())
+        .collect();
+    let mut faces = ICOSAHEDRON_FACES.to_vec();
+
+    for _ in 0..subdivisions {
+        // Cada arista se divide una vez, llegue primero el triángulo que llegue
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::with_capacity(faces.len() * 3 / 2);
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push((positions[a as usize] + positions[b as usize]).normalize());
+                positions.len() as u32 - 1
+            })
+        };
+
+        faces = faces.iter().flat_map(|&[a, b, c]| {
+            let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+            [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+        }).collect();
+    }
+
+    let mut vertices = Vec::with_capacity(positions.len());
+    let mut indices = Vec::with_capacity(faces.len() * 3);
+    // (posición, u, v) -> vértice, para compartir las esquinas con las mismas UV
+    let mut corners: HashMap<(u32, u32, u32), u32> = HashMap::with_capacity(positions.len());
+    for face in faces {
+        let mut tex_coords = face.map(|index| spherical_uv(&positions[index as usize]));
+        fix_spherical_triangle(&face.map(|index| positions[index as usize]), &mut tex_coords);
+
+        for (index, uv) in face.into_iter().zip(tex_coords) {
+            let vertex = *corners.entry((index, uv.x.to_bits(), uv.y.to_bits())).or_insert_with(|| {
+                vertices.push(sphere_vertex(positions[index as usize], uv));
+                vertices.len() as u32 - 1
+            });
+            indices.push(vertex);
+        }
+    }
+
+    (vertices, indices)
+}
+
+// Esfera unitaria de `stacks` anillos de polo a polo y `sectors` gajos
+// alrededor, con las mismas UV que `spherical_uv`. La columna de la costura
+// se repite en u = 1 y cada polo tiene un vértice por gajo, en su centro.
+pub fn uv_sphere(sectors: usize, stacks: usize) -> (Vec<Vertex>, Vec<u32>) {
+    let (sectors, stacks) = (sectors.max(3), stacks.max(2));
+    let mut vertices = Vec::with_capacity((sectors + 1) * (stacks + 1));
+
+    for stack in 0..=stacks {
+        let v = stack as f32 / stacks as f32;
+        let latitude = (0.5 - v) * PI;
+        for sector in 0..=sectors {
+            let pole = stack == 0 || stack == stacks;
+            let u = (sector as f32 + if pole { 0.5 } else { 0.0 }) / sectors as f32;
+            // La columna de la costura toma exactamente la posición de la primera
+            let longitude = (u % 1.0 - 0.5) * TAU;
+            let position = if pole {
+                Vec3::new(0.0, latitude.signum(), 0.0)
+            } else {
+                Vec3::new(longitude.sin() * latitude.cos(), latitude.sin(), longitude.cos() * latitude.cos())
+            };
+            vertices.push(sphere_vertex(position, Vec2::new(u, v)));
+        }
+    }
+
+    let row = sectors as u32 + 1;
+    let mut indices = Vec::with_capacity(sectors * (stacks - 1) * 6);
+    for stack in 0..stacks as u32 {
+        for sector in 0..sectors as u32 {
+            let (top, bottom) = (stack * row + sector, (stack + 1) * row + sector);
+            if stack != 0 {
+                indices.extend_from_slice(&[top, bottom, top + 1]);
+            }
+            if stack != stacks as u32 - 1 {
+                indices.extend_from_slice(&[top + 1, bottom, bottom + 1]);
+            }
+        }
+    }
+
+    (vertices, indices)
+}
+
+// Un vértice por índice, como el renderizador recibe las mallas
+pub fn triangle_list(vertices: &[Vertex], indices: &[u32]) -> Vec<Vertex> {
+    indices.iter().map(|&index| vertices[index as usize].clone()).collect()
+}
+
+// En una esfera unitaria la normal suave es la propia posición
+fn sphere_vertex(position: Vec3, tex_coords: Vec2) -> Vertex {
+    Vertex {
+        // El blanco deja ver sólo la iluminación, como en los OBJ sin colores
+        color: Color::new(255, 255, 255),
+        ..Vertex::new_with_tangent(position, position, spherical_tangent(&position, &position), tex_coords)
+    }
+}
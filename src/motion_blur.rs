@@ -0,0 +1,127 @@
+use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4};
+use crate::framebuffer::Framebuffer;
+
+// Cantidades de muestras entre las que alterna la calidad
+pub const SAMPLE_COUNTS: [usize; 3] = [4, 6, 8];
+// Fracción del movimiento del frame durante la que el "obturador" está abierto
+const SHUTTER: f32 = 0.5;
+// Por debajo de esta velocidad (píxeles por frame) el píxel no se toca
+const MIN_SPEED: f32 = 0.5;
+
+// Desenfoque de movimiento por objeto: cada cuerpo recuerda su matriz
+// modelo-vista-proyección del frame anterior, el rasterizador escribe la
+// velocidad en pantalla de cada píxel y `apply` lo difumina a lo largo de ella.
+pub struct MotionBlur {
+    pub enabled: bool,
+    pub samples: usize,
+    // Longitud máxima de la estela, en píxeles
+    pub max_length: f32,
+    previous_mvp: Vec<Option<Mat4>>,
+    source: Vec<Vec3>,
+}
+
+impl Default for MotionBlur {
+    fn default() -> Self {
+        MotionBlur {
+            enabled: false,
+            samples: SAMPLE_COUNTS[0],
+            max_length: 24.0,
+            previous_mvp: Vec::new(),
+            source: Vec::new(),
+        }
+    }
+}
+
+impl MotionBlur {
+    pub fn cycle_samples(&mut self) {
+        let current = SAMPLE_COUNTS.iter().position(|&count| count == self.samples).unwrap_or(0);
+        self.samples = SAMPLE_COUNTS[(current + 1) % SAMPLE_COUNTS.len()];
+    }
+
+    // Matriz del frame anterior para el cuerpo `index`. Sin el efecto activo
+    // devuelve None, y el rasterizador no calcula velocidades.
+    pub fn previous_mvp(&self, index: usize) -> Option<Mat4> {
+        if !self.enabled {
+            return None;
+        }
+        self.previous_mvp.get(index).copied().flatten()
+    }
+
+    // Guarda la matriz con la que se dibujó el cuerpo este frame
+    pub fn store_mvp(&mut self, index: usize, mvp: Mat4) {
+        if self.previous_mvp.len() <= index {
+            self.previous_mvp.resize(index + 1, None);
+        }
+        self.previous_mvp[index] = Some(mvp);
+    }
+
+    // Matrices guardadas hasta ahora, para volver a ellas con `restore_history`
+    pub fn history(&self) -> Vec<Option<Mat4>> {
+        self.previous_mvp.clone()
+    }
+
+    pub fn restore_history(&mut self, history: &[Option<Mat4>]) {
+        self.previous_mvp.clear();
+        self.previous_mvp.extend_from_slice(history);
+    }
+
+    // Olvida el historial, p. ej. al activar el efecto tras un rato apagado
+    pub fn reset(&mut self) {
+        self.previous_mvp.clear();
+    }
+
+    // Difumina el buffer HDR; va antes de `resolve`
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer) {
+        if !self.enabled {
+            return;
+        }
+
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+        self.source.clear();
+        self.source.extend_from_slice(&framebuffer.hdr_buffer);
+        let samples = self.samples.max(2);
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                let velocity = framebuffer.velocity_buffer[index];
+                let speed = velocity.norm();
+                if speed < MIN_SPEED {
+                    continue;
+                }
+
+                // La estela se recorta para que un salto enorme no cruce la pantalla
+                let length = (speed * SHUTTER).min(self.max_length);
+                let step = velocity / speed * length;
+                let mut sum = Vec3::zeros();
+                for i in 0..samples {
+                    let t = i as f32 / (samples - 1) as f32 - 0.5;
+                    let sx = (x as f32 + step.x * t).round().clamp(0.0, (width - 1) as f32) as usize;
+                    let sy = (y as f32 + step.y * t).round().clamp(0.0, (height - 1) as f32) as usize;
+                    sum += self.source[sy * width + sx];
+                }
+                framebuffer.hdr_buffer[index] = sum / samples as f32;
+            }
+        }
+    }
+}
+
+// Velocidad en pantalla (píxeles por frame) de un punto en espacio de modelo:
+// dónde cae con la matriz de este frame menos dónde caía con la anterior
+pub fn screen_velocity(viewport: &Mat4, current_mvp: &Mat4, previous_mvp: &Mat4, model_position: &Vec3) -> Vec2 {
+    let point = Vec4::new(model_position.x, model_position.y, model_position.z, 1.0);
+    match (to_screen(viewport, current_mvp, &point), to_screen(viewport, previous_mvp, &point)) {
+        (Some(current), Some(previous)) => current - previous,
+        _ => Vec2::zeros(),
+    }
+}
+
+fn to_screen(viewport: &Mat4, mvp: &Mat4, point: &Vec4) -> Option<Vec2> {
+    let clip = mvp * point;
+    if clip.w <= f32::EPSILON {
+        return None;
+    }
+    let screen = viewport * Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    Some(Vec2::new(screen.x, screen.y))
+}
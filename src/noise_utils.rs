@@ -0,0 +1,159 @@
+use fastnoise_lite::FastNoiseLite;
+use nalgebra_glm::Vec3;
+
+// Desplazamientos que descorrelacionan las muestras auxiliares del domain warp
+const WARP_OFFSET_X: f32 = 31.7;
+const WARP_OFFSET_Y: f32 = 113.5;
+const WARP_OFFSET_Z: f32 = 271.3;
+
+// Movimiento browniano fractal sobre `get_noise_3d`, normalizado a [-1, 1].
+// Con cero octavas da 0.0 en lugar de dividir por cero.
+pub fn fbm(noise: &FastNoiseLite, p: &Vec3, octaves: i32, lacunarity: f32, gain: f32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut total_amplitude = 0.0;
+
+    for _ in 0..octaves.max(0) {
+        sum += amplitude * noise.get_noise_3d(p.x * frequency, p.y * frequency, p.z * frequency);
+        total_amplitude += amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+
+    if total_amplitude > 0.0 { sum / total_amplitude } else { 0.0 }
+}
+
+// Multifractal de crestas: picos agudos donde el fbm cruza el cero. Rango [0, 1].
+pub fn ridged(noise: &FastNoiseLite, p: &Vec3, octaves: i32, lacunarity: f32, gain: f32) -> f32 {
+    let ridge = 1.0 - fbm(noise, p, octaves, lacunarity, gain).abs();
+    (ridge * ridge).clamp(0.0, 1.0)
+}
+
+// Mueve el punto de muestreo con otras muestras de ruido para que las formas se curven de forma orgánica
+pub fn domain_warp(noise: &FastNoiseLite, p: &Vec3, strength: f32) -> Vec3 {
+    let offset = Vec3::new(
+        noise.get_noise_3d(p.x + WARP_OFFSET_X, p.y, p.z),
+        noise.get_noise_3d(p.x, p.y + WARP_OFFSET_Y, p.z),
+        noise.get_noise_3d(p.x, p.y, p.z + WARP_OFFSET_Z),
+    );
+    p + offset * strength
+}
+
+// Dominio por defecto de la caché: cubre las consultas de nivel base de casi todos los shaders
+pub const NOISE_CACHE_EXTENT: f32 = 512.0;
+pub const NOISE_CACHE_CELLS: usize = 128;
+
+// `get_noise_3d` precalculado en una rejilla regular sobre [-extent, extent]^3
+// y leído con interpolación trilineal. La separación de 8 unidades por
+// defecto da unas 12 muestras por rasgo con la frecuencia por defecto del
+// ruido, así que cuesta distinguirlo del muestreo directo.
+pub struct NoiseCache {
+    min: f32,
+    cell_size: f32,
+    samples: usize,
+    values: Vec<f32>,
+}
+
+impl NoiseCache {
+    pub fn new(noise: &FastNoiseLite, extent: f32, cells: usize) -> Self {
+        let cells = cells.max(1);
+        let samples = cells + 1;
+        let min = -extent;
+        let cell_size = 2.0 * extent / cells as f32;
+
+        let mut values = Vec::with_capacity(samples * samples * samples);
+        for z in 0..samples {
+            for y in 0..samples {
+                for x in 0..samples {
+                    values.push(noise.get_noise_3d(
+                        min + x as f32 * cell_size,
+                        min + y as f32 * cell_size,
+                        min + z as f32 * cell_size,
+                    ));
+                }
+            }
+        }
+
+        NoiseCache { min, cell_size, samples, values }
+    }
+
+    // Si (x, y, z) cae dentro del dominio de la caché
+    pub fn contains(&self, x: f32, y: f32, z: f32) -> bool {
+        let max = self.min + (self.samples - 1) as f32 * self.cell_size;
+        [x, y, z].iter().all(|&v| v >= self.min && v <= max)
+    }
+
+    // Lectura trilineal; los puntos fuera del dominio se llevan a su borde
+    pub fn sample(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (x0, fx) = self.cell(x);
+        let (y0, fy) = self.cell(y);
+        let (z0, fz) = self.cell(z);
+
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        let edge = |y: usize, z: usize| lerp(self.value(x0, y, z), self.value(x0 + 1, y, z), fx);
+        let face = |z: usize| lerp(edge(y0, z), edge(y0 + 1, z), fy);
+
+        lerp(face(z0), face(z0 + 1), fz)
+    }
+
+    // Índice inferior de la rejilla en un eje y la fracción dentro de la celda
+    fn cell(&self, v: f32) -> (usize, f32) {
+        let last_cell = self.samples - 2;
+        let grid = ((v - self.min) / self.cell_size).clamp(0.0, (last_cell + 1) as f32);
+        let index = (grid.floor() as usize).min(last_cell);
+        (index, grid - index as f32)
+    }
+
+    fn value(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.values[(z * self.samples + y) * self.samples + x]
+    }
+}
+
+// Hash entero (una variante de PCG) llevado a [0, 1). `channel` elige valores
+// independientes para la misma semilla.
+pub fn hash_unit(seed: u32, channel: u32) -> f32 {
+    let mut x = seed.wrapping_mul(747796405).wrapping_add(channel.wrapping_mul(2891336453));
+    x = ((x >> ((x >> 28) + 4)) ^ x).wrapping_mul(277803737);
+    x = (x >> 22) ^ x;
+    (x >> 8) as f32 / (1u32 << 24) as f32
+}
+
+// Distancias al punto más cercano y al segundo del ruido de Worley 3D, más
+// un identificador de la celda más cercana para variar por celda
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cellular {
+    pub f1: f32,
+    pub f2: f32,
+    pub cell: u32,
+}
+
+// Ruido de Worley con un punto desplazado al azar por celda unitaria.
+// `f2 - f1` es cero en los bordes entre celdas y crece hacia sus centros.
+pub fn cellular(p: &Vec3, seed: u32) -> Cellular {
+    let base = p.map(f32::floor);
+    let mut result = Cellular { f1: f32::INFINITY, f2: f32::INFINITY, cell: 0 };
+
+    for dz in -1..=1 {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let cell = base + Vec3::new(dx as f32, dy as f32, dz as f32);
+                let id = (cell.x as i32 as u32).wrapping_mul(73856093)
+                    ^ (cell.y as i32 as u32).wrapping_mul(19349663)
+                    ^ (cell.z as i32 as u32).wrapping_mul(83492791)
+                    ^ seed;
+                let feature = cell + Vec3::new(hash_unit(id, 0), hash_unit(id, 1), hash_unit(id, 2));
+                let distance = (feature - p).norm();
+                if distance < result.f1 {
+                    result.f2 = result.f1;
+                    result.f1 = distance;
+                    result.cell = id;
+                } else if distance < result.f2 {
+                    result.f2 = distance;
+                }
+            }
+        }
+    }
+
+    result
+}
@@ -1,6 +1,13 @@
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
 use tobj;
 use nalgebra_glm::{Vec2, Vec3};
-use crate::vertex::Vertex;
+use crate::vertex::{Vertex, any_perpendicular};
+use crate::color::Color;
+use crate::error::Error;
 
 pub struct Obj {
     meshes: Vec<Mesh>,
@@ -10,53 +17,458 @@ struct Mesh {
     vertices: Vec<Vec3>,
     normals: Vec<Vec3>,
     texcoords: Vec<Vec2>,
+    tangents: Vec<Vec3>,
+    // Per-vertex colors from the extended `v x y z r g b` syntax; empty if absent
+    colors: Vec<Color>,
     indices: Vec<u32>,
+    // Whether `texcoords` came from `spherical_uv`, so triangles crossing
+    // the seam or touching a pole need fixing up
+    spherical_uvs: bool,
 }
 
 impl Obj {
-    pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
+    // Reads the file with `FastObjReader`; anything it doesn't handle the
+    // same way tobj would (materials splitting an object, malformed lines)
+    // goes through tobj instead, which also reports the errors.
+    //
+    // Unlike tobj, texture coordinates come back with V flipped (`vt u v`
+    // loads as `(u, 1 - v)`): V = 0 is the top row of an image, the
+    // convention `spherical_uv` and `compute_tangents` are built on.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        // tobj only reports that the file couldn't be opened; say where it was expected
+        if !path.is_file() {
+            return Err(Error::MissingAsset { path: path.to_path_buf() });
+        }
+        match FastObjReader::read(path) {
+            Some(meshes) => Ok(Self::from_meshes(path, meshes)),
+            None => Self::load_with_tobj(path),
+        }
+    }
+
+    // The plain tobj loader. `load` gives the same vertices faster; this is
+    // kept as its fallback and as the reference to compare it against.
+    pub fn load_with_tobj<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(Error::MissingAsset { path: path.to_path_buf() });
+        }
+        // Faces come back untriangulated so degenerate ones can be told apart
+        let (models, _) = tobj::load_obj(path, &tobj::LoadOptions {
             single_index: true,
-            triangulate: true,
+            triangulate: false,
             ..Default::default()
-        })?;
-
-        let meshes = models.into_iter().map(|model| {
-            let mesh = model.mesh;
-            Mesh {
-                vertices: mesh.positions.chunks(3)
-                    .map(|v| Vec3::new(v[0], v[1], v[2]))
-                    .collect(),
-                normals: mesh.normals.chunks(3)
-                    .map(|n| Vec3::new(n[0], n[1], n[2]))
-                    .collect(),
-                texcoords: mesh.texcoords.chunks(2)
-                    .map(|t| Vec2::new(t[0], 1.0 - t[1]))
-                    .collect(),
-                indices: mesh.indices,
+        }).map_err(|source| Error::Obj { path: path.to_path_buf(), source })?;
+
+        Ok(Self::from_meshes(path, models.into_iter().map(|model| model.mesh).collect()))
+    }
+
+    fn from_meshes(path: &Path, meshes: Vec<tobj::Mesh>) -> Self {
+        let mut skipped_faces = 0;
+        let meshes = meshes.into_iter().map(|mesh| {
+            let (indices, skipped) = triangulate_faces(&mesh.indices, &mesh.face_arities);
+            skipped_faces += skipped;
+            let vertices: Vec<Vec3> = mesh.positions.chunks(3)
+                .map(|v| Vec3::new(v[0], v[1], v[2]))
+                .collect();
+            let normals: Vec<Vec3> = mesh.normals.chunks(3)
+                .map(|n| Vec3::new(n[0], n[1], n[2]))
+                .collect();
+            // V flipped to the top-down convention, see `load`
+            let texcoords: Vec<Vec2> = mesh.texcoords.chunks(2)
+                .map(|t| Vec2::new(t[0], 1.0 - t[1]))
+                .collect();
+            let tangents = compute_tangents(&vertices, &normals, &texcoords, &indices);
+            let colors: Vec<Color> = mesh.vertex_color.chunks(3)
+                .map(|c| Color::from_float(c[0], c[1], c[2]))
+                .collect();
+
+            let mut mesh = Mesh {
+                vertices,
+                normals,
+                texcoords,
+                tangents,
+                colors,
+                indices,
+                spherical_uvs: false,
+            };
+            // Without UVs, texturing and normal mapping would see a single texel
+            if mesh.texcoords.is_empty() {
+                mesh.use_spherical_uvs();
             }
+            mesh
         }).collect();
 
-        Ok(Obj { meshes })
+        if skipped_faces > 0 {
+            eprintln!("{}: se descartaron {} caras degeneradas", path.display(), skipped_faces);
+        }
+
+        Obj { meshes }
+    }
+
+    // Replaces the UVs of every mesh with `spherical_uv` of the vertex
+    // positions, for spheres whose own UVs are missing or broken
+    pub fn use_spherical_uvs(&mut self) {
+        for mesh in &mut self.meshes {
+            mesh.use_spherical_uvs();
+        }
     }
 
     pub fn get_vertex_array(&self) -> Vec<Vertex> {
-        let mut vertices = Vec::new();
+        let corners = self.meshes.iter().map(|mesh| mesh.indices.len() / 3 * 3).sum();
+        let mut vertices = Vec::with_capacity(corners);
 
         for mesh in &self.meshes {
-            for &index in &mesh.indices {
-                let position = mesh.vertices[index as usize];
-                let normal = mesh.normals.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
-                let tex_coords = mesh.texcoords.get(index as usize)
+            for tri in mesh.indices.chunks_exact(3) {
+                let tri = [tri[0], tri[1], tri[2]];
+                let mut tex_coords = tri.map(|index| mesh.texcoords.get(index as usize)
                     .cloned()
-                    .unwrap_or(Vec2::new(0.0, 0.0));
+                    .unwrap_or(Vec2::new(0.0, 0.0)));
+                if mesh.spherical_uvs {
+                    fix_spherical_triangle(&tri.map(|index| mesh.vertices[index as usize]), &mut tex_coords);
+                }
 
-                vertices.push(Vertex::new(position, normal, tex_coords));
+                for (&index, tex_coords) in tri.iter().zip(tex_coords) {
+                    let position = mesh.vertices[index as usize];
+                    let normal = mesh.normals.get(index as usize)
+                        .cloned()
+                        .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                    let tangent = mesh.tangents[index as usize];
+                    // Without vertex colors, white leaves only the lighting visible
+                    let color = mesh.colors.get(index as usize)
+                        .cloned()
+                        .unwrap_or(Color::new(255, 255, 255));
+
+                    vertices.push(Vertex {
+                        color,
+                        ..Vertex::new_with_tangent(position, normal, tangent, tex_coords)
+                    });
+                }
             }
         }
 
         vertices
     }
 }
+
+impl Mesh {
+    fn use_spherical_uvs(&mut self) {
+        self.texcoords = self.vertices.iter().map(spherical_uv).collect();
+        // `compute_tangents` would get them wrong on the triangles that
+        // cross the seam
+        self.tangents = self.vertices.iter().enumerate().map(|(i, position)| {
+            let normal = self.normals.get(i).cloned().unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+            spherical_tangent(position, &normal)
+        }).collect();
+        self.spherical_uvs = true;
+    }
+}
+
+// Bigger than BufReader's default: meshes are read start to end in one go
+const READ_BUFFER_SIZE: usize = 1 << 16;
+// Typical length of an exported OBJ line, for the capacity guesses
+const BYTES_PER_LINE: usize = 30;
+// A smooth textured mesh has about one `v`, `vt` and `vn` line per vertex
+// and two triangles per vertex: five lines per vertex
+const LINES_PER_ELEMENT: usize = 5;
+// Index of an absent texture coordinate or normal in a face corner
+const MISSING_INDEX: usize = usize::MAX;
+
+// Loader for the part of the OBJ format the scene models use, giving the
+// same meshes as tobj with `single_index` and no triangulation. tobj
+// allocates a string per line and a list per face, grows every array as it
+// goes and hashes every face corner; this reuses one line buffer, sizes the
+// arrays from the file size and finds most vertices by position index.
+struct FastObjReader {
+    // Shared by every object in the file, flattened like tobj's
+    positions: Vec<f32>,
+    colors: Vec<f32>,
+    texcoords: Vec<f32>,
+    normals: Vec<f32>,
+    // Corners (position, texcoord, normal) of the current object's faces
+    corners: Vec<[usize; 3]>,
+    face_arities: Vec<u32>,
+    meshes: Vec<tobj::Mesh>,
+}
+
+impl FastObjReader {
+    // `None` when the file should go through tobj instead
+    fn read(path: &Path) -> Option<Vec<tobj::Mesh>> {
+        let file = File::open(path).ok()?;
+        // A first pass counting each kind of line took longer than the
+        // reallocations it saved; the file size gives a close enough guess
+        let lines = file.metadata().map_or(0, |metadata| metadata.len() as usize / BYTES_PER_LINE);
+        let elements = lines / LINES_PER_ELEMENT;
+        let mut reader = BufReader::with_capacity(READ_BUFFER_SIZE, file);
+
+        let mut obj = FastObjReader {
+            positions: Vec::with_capacity(elements * 3),
+            colors: Vec::new(),
+            texcoords: Vec::with_capacity(elements * 2),
+            normals: Vec::with_capacity(elements * 3),
+            corners: Vec::with_capacity(elements * 2 * 3),
+            face_arities: Vec::with_capacity(elements * 2),
+            meshes: Vec::new(),
+        };
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).ok()? == 0 {
+                break;
+            }
+            obj.parse_line(&line)?;
+        }
+        // Like tobj, the last object is kept even without faces
+        obj.finish_mesh()?;
+        Some(obj.meshes)
+    }
+
+    fn parse_line(&mut self, line: &str) -> Option<()> {
+        // Several times faster, and the same words unless the line has
+        // other Unicode whitespace or a vertical tab, which the ASCII split
+        // doesn't treat as one
+        if line.is_ascii() && !line.contains('\u{b}') {
+            self.parse_words(line, line.split_ascii_whitespace())
+        } else {
+            self.parse_words(line, line.split_whitespace())
+        }
+    }
+
+    fn parse_words<'a>(&mut self, line: &str, mut words: impl Iterator<Item = &'a str>) -> Option<()> {
+        match words.next() {
+            Some("v") => {
+                parse_floats(&mut words, &mut self.positions, 3)?;
+                // Optional `r g b` after the position
+                parse_floats(&mut words, &mut self.colors, 3);
+            }
+            // Only u and v; the optional w of `vt u v w` is ignored, as tobj does
+            Some("vt") => parse_floats(&mut words, &mut self.texcoords, 2)?,
+            Some("vn") => parse_floats(&mut words, &mut self.normals, 3)?,
+            Some("f") | Some("l") => {
+                let counts = [self.positions.len() / 3, self.texcoords.len() / 2, self.normals.len() / 3];
+                let start = self.corners.len();
+                for word in words {
+                    self.corners.push(parse_corner(word, counts)?);
+                }
+                self.face_arities.push((self.corners.len() - start) as u32);
+            }
+            // A new object ends the current one
+            Some("o") | Some("g") if !self.face_arities.is_empty() => self.finish_mesh()?,
+            // tobj starts a new mesh when the material changes mid-object,
+            // which depends on what the .mtl file defines, and rejects a
+            // missing name
+            Some("usemtl") if !self.face_arities.is_empty()
+                || line.split_once(' ').unwrap_or_default().1.trim().is_empty() => return None,
+            // Comments, smoothing groups, material names and the like
+            _ => {}
+        }
+        Some(())
+    }
+
+    // Turns the faces read so far into a mesh with one vertex per distinct
+    // corner, in order of first appearance
+    fn finish_mesh(&mut self) -> Option<()> {
+        let mut mesh = tobj::Mesh::default();
+        mesh.indices.reserve(self.corners.len());
+        // Most positions only ever appear with one texcoord and normal, so
+        // the first vertex made from each position is found by index; the
+        // rest (UV seams, hard edges) go through a map
+        let mut first_vertex = vec![u32::MAX; self.positions.len() / 3];
+        let mut vertex_corners: Vec<[usize; 3]> = Vec::with_capacity(first_vertex.len());
+        let mut other_vertices: HashMap<[usize; 3], u32> = HashMap::new();
+
+        for &corner in &self.corners {
+            let first = first_vertex.get_mut(corner[0])?;
+            let index = if *first == u32::MAX {
+                *first = vertex_corners.len() as u32;
+                *first
+            } else if vertex_corners[*first as usize] == corner {
+                *first
+            } else {
+                let next = vertex_corners.len() as u32;
+                *other_vertices.entry(corner).or_insert(next)
+            };
+            if index as usize == vertex_corners.len() {
+                self.push_vertex(&mut mesh, corner)?;
+                vertex_corners.push(corner);
+            }
+            mesh.indices.push(index);
+        }
+        // A mesh of only triangles has no arities, as in tobj
+        if self.face_arities.iter().any(|&arity| arity != 3) {
+            mesh.face_arities = std::mem::take(&mut self.face_arities);
+        }
+
+        self.corners.clear();
+        self.face_arities.clear();
+        self.meshes.push(mesh);
+        Some(())
+    }
+
+    fn push_vertex(&self, mesh: &mut tobj::Mesh, [position, texcoord, normal]: [usize; 3]) -> Option<()> {
+        mesh.positions.extend_from_slice(self.positions.get(position.checked_mul(3)?..)?.get(..3)?);
+        if !self.texcoords.is_empty() && texcoord != MISSING_INDEX {
+            mesh.texcoords.extend_from_slice(self.texcoords.get(texcoord.checked_mul(2)?..)?.get(..2)?);
+        }
+        if !self.normals.is_empty() && normal != MISSING_INDEX {
+            mesh.normals.extend_from_slice(self.normals.get(normal.checked_mul(3)?..)?.get(..3)?);
+        }
+        if !self.colors.is_empty() {
+            mesh.vertex_color.extend_from_slice(self.colors.get(position.checked_mul(3)?..)?.get(..3)?);
+        }
+        Some(())
+    }
+}
+
+// Appends `count` floats; `None` if there were fewer. Whatever did parse
+// is kept, as tobj does.
+fn parse_floats<'a>(words: &mut impl Iterator<Item = &'a str>, values: &mut Vec<f32>, count: usize) -> Option<()> {
+    let start = values.len();
+    for word in words.take(count) {
+        values.push(word.parse().ok()?);
+    }
+    (values.len() == start + count).then_some(())
+}
+
+// `v`, `v/vt`, `v//vn` or `v/vt/vn`, 1-based or negative (counted back from
+// the last element read so far)
+fn parse_corner(word: &str, counts: [usize; 3]) -> Option<[usize; 3]> {
+    let mut corner = [MISSING_INDEX; 3];
+    for (slot, part) in word.split('/').enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        let index: isize = part.parse().ok()?;
+        *corner.get_mut(slot)? = if index < 0 {
+            (counts[slot] as isize + index) as usize
+        } else {
+            (index - 1) as usize
+        };
+    }
+    Some(corner)
+}
+
+// Longitude/latitude mapping of a direction from the mesh center: u grows
+// eastward from the -z meridian (u = 0.5 faces +z) and v runs from 0 at the
+// north pole (+y) to 1 at the south pole, matching the flipped OBJ convention.
+// u wraps from 1 back to 0 on the -z meridian; `get_vertex_array` unwraps
+// the triangles that straddle it.
+pub fn spherical_uv(position: &Vec3) -> Vec2 {
+    let length = position.norm();
+    if length <= f32::EPSILON {
+        return Vec2::new(0.5, 0.5);
+    }
+    let direction = position / length;
+    Vec2::new(
+        0.5 + direction.x.atan2(direction.z) / TAU,
+        0.5 - direction.y.clamp(-1.0, 1.0).asin() / PI,
+    )
+}
+
+// Unit tangent along +u of `spherical_uv` (eastward), orthogonal to
+// `normal`, so normal mapping keeps working on spherical UVs
+pub fn spherical_tangent(position: &Vec3, normal: &Vec3) -> Vec3 {
+    let east = Vec3::new(position.z, 0.0, -position.x);
+    let orthogonal = east - normal * normal.dot(&east);
+    if orthogonal.norm() > f32::EPSILON {
+        orthogonal.normalize()
+    } else {
+        any_perpendicular(normal)
+    }
+}
+
+// Interpolating u straight across the seam would run backwards over the
+// whole texture in one triangle. Corners on the low side are moved past 1
+// instead, which a repeating sampler reads as the same texels. A corner on
+// a pole has no longitude of its own, so it takes the mean of the other two.
+pub(crate) fn fix_spherical_triangle(positions: &[Vec3; 3], tex_coords: &mut [Vec2; 3]) {
+    let max_u = tex_coords.iter().map(|uv| uv.x).fold(f32::MIN, f32::max);
+    for uv in tex_coords.iter_mut() {
+        if max_u - uv.x > 0.5 {
+            uv.x += 1.0;
+        }
+    }
+
+    for i in 0..3 {
+        let position = positions[i];
+        let off_axis = position.x.hypot(position.z);
+        if off_axis <= position.norm() * 1e-4 {
+            let (a, b) = (tex_coords[(i + 1) % 3].x, tex_coords[(i + 2) % 3].x);
+            tex_coords[i].x = (a + b) * 0.5;
+        }
+    }
+}
+
+// Fan-triangulates faces of any size into (v0, vi, vi+1) triangles. With
+// `single_index` each corner already carries its own position, texture and
+// normal, so every generated triangle keeps them. Points, lines and faces that
+// repeat a corner are dropped; returns the triangle indices and how many faces
+// were dropped. An empty `face_arities` means everything is a triangle already.
+fn triangulate_faces(indices: &[u32], face_arities: &[u32]) -> (Vec<u32>, usize) {
+    if face_arities.is_empty() {
+        return (indices.to_vec(), 0);
+    }
+
+    let mut triangles = Vec::with_capacity(indices.len());
+    let mut skipped = 0;
+    let mut start = 0;
+    for &arity in face_arities {
+        let end = (start + arity as usize).min(indices.len());
+        let face = &indices[start..end];
+        start = end;
+
+        let repeats_corner = face.iter().enumerate().any(|(i, index)| face[i + 1..].contains(index));
+        if face.len() < 3 || repeats_corner {
+            skipped += 1;
+            continue;
+        }
+        for i in 1..face.len() - 1 {
+            triangles.extend_from_slice(&[face[0], face[i], face[i + 1]]);
+        }
+    }
+
+    (triangles, skipped)
+}
+
+// Per-vertex tangents from positions and UVs, accumulated over the triangles
+// sharing each vertex and then orthonormalized against the normal.
+pub fn compute_tangents(vertices: &[Vec3], normals: &[Vec3], texcoords: &[Vec2], indices: &[u32]) -> Vec<Vec3> {
+    let mut accumulated = vec![Vec3::new(0.0, 0.0, 0.0); vertices.len()];
+
+    for tri in indices.chunks(3) {
+        if tri.len() < 3 {
+            continue;
+        }
+        let (i0, i1, i2) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
+        let (uv0, uv1, uv2) = match (texcoords.get(i0), texcoords.get(i1), texcoords.get(i2)) {
+            (Some(a), Some(b), Some(c)) => (a, b, c),
+            _ => continue,
+        };
+
+        let edge1 = vertices[i1] - vertices[i0];
+        let edge2 = vertices[i2] - vertices[i0];
+        let duv1 = uv1 - uv0;
+        let duv2 = uv2 - uv0;
+
+        let det = duv1.x * duv2.y - duv2.x * duv1.y;
+        if det.abs() < f32::EPSILON {
+            continue;
+        }
+
+        let tangent = (edge1 * duv2.y - edge2 * duv1.y) / det;
+        for &i in &[i0, i1, i2] {
+            accumulated[i] += tangent;
+        }
+    }
+
+    accumulated.iter().enumerate().map(|(i, tangent)| {
+        let normal = normals.get(i).cloned().unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+        // Gram-Schmidt: remove the component along the normal
+        let orthogonal = tangent - normal * normal.dot(tangent);
+        if orthogonal.norm() > f32::EPSILON {
+            orthogonal.normalize()
+        } else {
+            any_perpendicular(&normal)
+        }
+    }).collect()
+}
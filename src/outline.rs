@@ -0,0 +1,156 @@
+use crate::framebuffer::Framebuffer;
+use crate::shaders::vertex_shader;
+use crate::triangle::triangle;
+use crate::vertex::Vertex;
+use crate::{RenderContext, Uniforms};
+
+pub const OUTLINE_COLOR: u32 = 0xFFD24A;
+// Grosor en píxeles que admite la dilatación
+pub const MIN_OUTLINE_THICKNESS: usize = 1;
+pub const MAX_OUTLINE_THICKNESS: usize = 3;
+
+// Contorno del cuerpo seleccionado. Se vuelve a rasterizar el cuerpo en una
+// máscara de un bit por píxel, leyendo el z-buffer sin escribirlo, y después
+// se pintan los píxeles de fuera que quedan a `thickness` o menos de ella.
+pub struct SelectionOutline {
+    pub color: u32,
+    thickness: usize,
+    width: usize,
+    height: usize,
+    mask: Vec<u64>,
+    // Rectángulo que contiene la máscara, para no recorrer toda la pantalla
+    bounds: Option<(usize, usize, usize, usize)>,
+}
+
+impl Default for SelectionOutline {
+    fn default() -> Self {
+        SelectionOutline {
+            color: OUTLINE_COLOR,
+            thickness: 2,
+            width: 0,
+            height: 0,
+            mask: Vec::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl SelectionOutline {
+    pub fn thickness(&self) -> usize {
+        self.thickness
+    }
+
+    pub fn set_thickness(&mut self, thickness: usize) {
+        self.thickness = thickness.clamp(MIN_OUTLINE_THICKNESS, MAX_OUTLINE_THICKNESS);
+    }
+
+    // Marca los píxeles donde el cuerpo quedó visible. Hay que llamarlo
+    // justo después de dibujar la escena, con las mismas uniforms con las que
+    // se dibujó el cuerpo: sus fragmentos visibles repiten exactamente la
+    // profundidad guardada, y donde otro cuerpo pasa por delante no coinciden.
+    pub fn render_mask(
+        &mut self,
+        framebuffer: &Framebuffer,
+        context: &mut RenderContext,
+        uniforms: &Uniforms,
+        vertex_array: &[Vertex],
+    ) {
+        self.clear(framebuffer.width, framebuffer.height);
+        context.clear();
+        let RenderContext { transformed_vertices, fragments, .. } = context;
+
+        transformed_vertices.extend(vertex_array.iter().map(|vertex| vertex_shader(vertex, uniforms)));
+        for tri in transformed_vertices.chunks_exact(3) {
+            triangle(&tri[0], &tri[1], &tri[2], None, framebuffer.width, framebuffer.height, fragments);
+        }
+
+        for fragment in fragments.iter() {
+            // El borde antialiasado no escribe profundidad: no se puede comparar
+            if fragment.coverage < 1.0 || fragment.position.x < 0.0 || fragment.position.y < 0.0 {
+                continue;
+            }
+            let x = fragment.position.x as usize;
+            let y = fragment.position.y as usize;
+            if x >= self.width || y >= self.height {
+                continue;
+            }
+            let depth = framebuffer.toward_camera(fragment.depth, uniforms.depth_bias);
+            if framebuffer.zbuffer[y * self.width + x] == depth {
+                self.set(x, y);
+            }
+        }
+    }
+
+    // Pinta el contorno sobre el buffer ya resuelto; va antes de las etiquetas.
+    // No tapa lo que está delante del cuerpo, así donde otro planeta lo cruza
+    // no hay contorno.
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        let Some((min_x, min_y, max_x, max_y)) = self.bounds else {
+            return;
+        };
+        if framebuffer.width != self.width || framebuffer.height != self.height {
+            return;
+        }
+
+        let reach = self.thickness as isize;
+        let radius_squared = reach * reach + reach;
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                if !self.get(x, y) || !self.is_edge(x, y) {
+                    continue;
+                }
+                let depth = framebuffer.zbuffer[y * self.width + x];
+                // Disco de radio `thickness` alrededor de cada píxel del borde
+                for dy in -reach..=reach {
+                    for dx in -reach..=reach {
+                        if dx * dx + dy * dy > radius_squared {
+                            continue;
+                        }
+                        let (px, py) = (x as isize + dx, y as isize + dy);
+                        if px < 0 || py < 0 || px as usize >= self.width || py as usize >= self.height {
+                            continue;
+                        }
+                        let (px, py) = (px as usize, py as usize);
+                        let index = py * self.width + px;
+                        let behind = framebuffer.zbuffer[index];
+                        let occluded = framebuffer.is_surface(behind) && framebuffer.is_nearer(behind, depth);
+                        if !self.get(px, py) && !occluded {
+                            framebuffer.buffer[index] = self.color;
+                        }
+                    }
+                }
+            }
+        }
+        framebuffer.mark_all_dirty();
+    }
+
+    fn clear(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.mask.clear();
+        self.mask.resize((width * height).div_ceil(64), 0);
+        self.bounds = None;
+    }
+
+    fn set(&mut self, x: usize, y: usize) {
+        let index = y * self.width + x;
+        self.mask[index / 64] |= 1 << (index % 64);
+        self.bounds = Some(match self.bounds {
+            Some((min_x, min_y, max_x, max_y)) => (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)),
+            None => (x, y, x, y),
+        });
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        let index = y * self.width + x;
+        self.mask[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    // Algún vecino (en cruz) está fuera de la máscara
+    fn is_edge(&self, x: usize, y: usize) -> bool {
+        (x > 0 && !self.get(x - 1, y))
+            || (x + 1 < self.width && !self.get(x + 1, y))
+            || (y > 0 && !self.get(x, y - 1))
+            || (y + 1 < self.height && !self.get(x, y + 1))
+    }
+}
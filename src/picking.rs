@@ -0,0 +1,93 @@
+use nalgebra_glm::{Vec3, Vec4, Mat4};
+use crate::{CelestialBody, Uniforms, create_view_matrix};
+use crate::camera::Camera;
+
+// Índice del cuerpo visible más cercano bajo el píxel (x, y) del
+// framebuffer, visto desde `camera` con la proyección y el viewport de
+// `uniforms`. El rayo sale de esas mismas matrices, así que el aspecto y el
+// rectángulo de la vista son los del dibujo.
+pub fn pick_body(mouse_x: f32, mouse_y: f32, camera: &Camera, bodies: &[CelestialBody], uniforms: &Uniforms) -> Option<usize> {
+    let view = create_view_matrix(camera.eye, camera.center, camera.up);
+    let (origin, direction) = screen_ray(
+        mouse_x,
+        mouse_y,
+        &view,
+        &uniforms.projection_matrix,
+        &uniforms.viewport_matrix,
+        uniforms.projection.reversed_z,
+    )?;
+    nearest_body(&origin, &direction, bodies)
+}
+
+// Rayo en espacio de mundo que pasa por el píxel (x, y) del framebuffer,
+// deshaciendo viewport, proyección y vista. Sale del plano cercano y apunta
+// hacia el lejano; con `reversed_z` esos planos quedan en z = 1 y z = 0 de
+// NDC en lugar de -1 y 1.
+pub fn screen_ray(
+    x: f32,
+    y: f32,
+    view: &Mat4,
+    projection: &Mat4,
+    viewport: &Mat4,
+    reversed_z: bool,
+) -> Option<(Vec3, Vec3)> {
+    let (near_depth, far_depth) = if reversed_z { (1.0, 0.0) } else { (-1.0, 1.0) };
+    // El viewport deja z como está, así que estas profundidades son las de NDC
+    let inverse = (viewport * projection * view).try_inverse()?;
+    let unproject = |depth: f32| {
+        let point = inverse * Vec4::new(x, y, depth, 1.0);
+        point.xyz() / point.w
+    };
+
+    let near = unproject(near_depth);
+    let far = unproject(far_depth);
+    Some((near, (far - near).try_normalize(f32::EPSILON)?))
+}
+
+// Inverso de `screen_ray`: píxel del framebuffer donde cae un punto del mundo.
+// None si queda detrás de la cámara.
+pub fn project_to_screen(point: &Vec3, width: f32, height: f32, view: &Mat4, projection: &Mat4) -> Option<(f32, f32)> {
+    let clip = projection * view * Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= f32::EPSILON {
+        return None;
+    }
+
+    let ndc_x = clip.x / clip.w;
+    let ndc_y = clip.y / clip.w;
+    Some(((ndc_x + 1.0) * 0.5 * width, (1.0 - ndc_y) * 0.5 * height))
+}
+
+// Distancia a lo largo del rayo hasta la esfera, si la toca
+pub fn intersect_sphere(origin: &Vec3, direction: &Vec3, center: &Vec3, radius: f32) -> Option<f32> {
+    let oc = origin - center;
+    let b = oc.dot(direction);
+    let c = oc.dot(&oc) - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let sqrt_d = discriminant.sqrt();
+    let near_hit = -b - sqrt_d;
+    let far_hit = -b + sqrt_d;
+    if near_hit >= 0.0 {
+        Some(near_hit)
+    } else if far_hit >= 0.0 {
+        // El origen está dentro de la esfera
+        Some(far_hit)
+    } else {
+        None
+    }
+}
+
+// Índice del cuerpo visible más cercano que toca el rayo, no el primero de la lista
+pub fn nearest_body(origin: &Vec3, direction: &Vec3, bodies: &[CelestialBody]) -> Option<usize> {
+    bodies.iter()
+        .enumerate()
+        .filter(|(_, body)| body.visible)
+        .filter_map(|(i, body)| {
+            intersect_sphere(origin, direction, &body.position, body.scale).map(|t| (i, t))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
@@ -1,4 +1,9 @@
-#[derive(Debug)]
+use std::mem;
+use serde::{Deserialize, Serialize};
+use crate::custom_planet::{PlanetParams, PlanetStyle, DEFAULT_CUSTOM_PLANET};
+
+// Se serializa por nombre ("Earth"), no como número
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PlanetType {
     Sun,
     Mercury,
@@ -8,7 +13,141 @@ pub enum PlanetType {
     Mars,
     Jupiter,
     Saturn,
+    // Anillos de Saturno, dibujados con su propia malla
+    Rings,
+    // Disco brillante alrededor de un agujero negro, con la malla de los anillos
+    AccretionDisk,
     Uranus,
     Neptune,
     BlackHole,
-}
\ No newline at end of file
+    // Usa los colores por vértice del OBJ
+    VertexColor,
+    // Un color sacado de la semilla del ruido, que cambia cada segundo
+    RandomColor,
+    // Estrella genérica; su color sale de la temperatura en kelvin
+    Star { temperature: f32 },
+    // Gigante gaseoso con la paleta de `CelestialBody::gas_giant`
+    GasGiant,
+    // Planeta con océanos y continentes con la paleta de `CelestialBody::terrestrial`
+    Terrestrial,
+    // Planeta procedural con colores, ruido y estilo propios
+    Custom(PlanetParams),
+    // Lunas galileanas: orbitan a Júpiter (ver `host`)
+    Io,
+    Europa,
+    Ganymede,
+    Callisto,
+}
+
+// Temperatura de la estrella que agrega Tab, parecida a la del Sol
+pub const DEFAULT_STAR_TEMPERATURE: f32 = 5800.0;
+
+impl PlanetType {
+    // Todas las variantes, en el orden en que las recorre Tab
+    pub const ALL: [PlanetType; 23] = [
+        PlanetType::Sun,
+        PlanetType::Mercury,
+        PlanetType::Venus,
+        PlanetType::Earth,
+        PlanetType::Moon,
+        PlanetType::Mars,
+        PlanetType::Jupiter,
+        PlanetType::Saturn,
+        PlanetType::Rings,
+        PlanetType::AccretionDisk,
+        PlanetType::Uranus,
+        PlanetType::Neptune,
+        PlanetType::BlackHole,
+        PlanetType::VertexColor,
+        PlanetType::RandomColor,
+        PlanetType::Star { temperature: DEFAULT_STAR_TEMPERATURE },
+        PlanetType::GasGiant,
+        PlanetType::Terrestrial,
+        PlanetType::Custom(DEFAULT_CUSTOM_PLANET),
+        PlanetType::Io,
+        PlanetType::Europa,
+        PlanetType::Ganymede,
+        PlanetType::Callisto,
+    ];
+
+    // Siguiente variante; después de la última vuelve a la primera.
+    // Se compara sólo la variante, así una estrella de cualquier temperatura avanza igual.
+    pub fn next(self) -> PlanetType {
+        let index = PlanetType::ALL.iter()
+            .position(|t| mem::discriminant(t) == mem::discriminant(&self))
+            .unwrap_or(0);
+        PlanetType::ALL[(index + 1) % PlanetType::ALL.len()]
+    }
+
+    // Nombre que se muestra en las etiquetas de la pantalla
+    pub fn name(self) -> &'static str {
+        match self {
+            PlanetType::Sun => "Sol",
+            PlanetType::Mercury => "Mercurio",
+            PlanetType::Venus => "Venus",
+            PlanetType::Earth => "Tierra",
+            PlanetType::Moon => "Luna",
+            PlanetType::Mars => "Marte",
+            PlanetType::Jupiter => "Júpiter",
+            PlanetType::Saturn => "Saturno",
+            PlanetType::Rings => "Anillos",
+            PlanetType::AccretionDisk => "Disco de acreción",
+            PlanetType::Uranus => "Urano",
+            PlanetType::Neptune => "Neptuno",
+            PlanetType::BlackHole => "Agujero negro",
+            PlanetType::VertexColor => "Colores de vértice",
+            PlanetType::RandomColor => "Color aleatorio",
+            PlanetType::Star { .. } => "Estrella",
+            PlanetType::GasGiant => "Gigante gaseoso",
+            PlanetType::Terrestrial => "Planeta terrestre",
+            PlanetType::Custom(_) => "Planeta personalizado",
+            PlanetType::Io => "Ío",
+            PlanetType::Europa => "Europa",
+            PlanetType::Ganymede => "Ganímedes",
+            PlanetType::Callisto => "Calisto",
+        }
+    }
+
+    // Tipo del planeta alrededor del cual orbita una luna: el primer cuerpo
+    // de ese tipo en la escena (ver `simulation::host_index`)
+    pub fn host(self) -> Option<PlanetType> {
+        match self {
+            PlanetType::Moon => Some(PlanetType::Earth),
+            PlanetType::Io | PlanetType::Europa | PlanetType::Ganymede | PlanetType::Callisto => Some(PlanetType::Jupiter),
+            _ => None,
+        }
+    }
+
+    // Cuerpos que emiten luz: no quedan en sombra ni la proyectan
+    pub fn is_star(self) -> bool {
+        matches!(self, PlanetType::Sun | PlanetType::Star { .. })
+    }
+
+    // Superficies que brillan por sí mismas: ni los eclipses ni el color de
+    // las estrellas las afectan, aunque no iluminen a nadie
+    pub fn is_self_lit(self) -> bool {
+        self.is_star() || matches!(self, PlanetType::AccretionDisk)
+    }
+
+    // Los que usan el shader de bandas de `GasGiantParams` y los
+    // personalizados de estilo gaseoso
+    pub fn is_gas_giant(self) -> bool {
+        match self {
+            PlanetType::Custom(params) => params.style == PlanetStyle::Gas,
+            _ => matches!(self, PlanetType::Jupiter | PlanetType::Saturn | PlanetType::Uranus | PlanetType::Neptune | PlanetType::GasGiant),
+        }
+    }
+
+    // Mallas con huecos o partes translúcidas (ver `fragment_opacity`): van
+    // en el pase transparente
+    pub fn is_transparent(self) -> bool {
+        matches!(self, PlanetType::Rings)
+    }
+
+    // Superficies de ruido suave, que se pueden sombrear en una rejilla más
+    // gruesa e interpolar (ver `ShaderLod`). Las bandas finas de los anillos
+    // y el disco se emborronarían, y el resto cuesta poco de por sí.
+    pub fn allows_shader_lod(self) -> bool {
+        !matches!(self, PlanetType::Rings | PlanetType::AccretionDisk | PlanetType::BlackHole | PlanetType::VertexColor)
+    }
+}
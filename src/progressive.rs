@@ -0,0 +1,190 @@
+use nalgebra_glm::Mat4;
+use crate::camera::{Camera, CameraState};
+use crate::framebuffer::Framebuffer;
+use crate::ProjectionSettings;
+
+// Pasadas de `--progressive` sin número: la primera dibuja una fila de cada 4
+pub const DEFAULT_PROGRESSIVE_PASSES: usize = 4;
+// Con más, la primera pasada es casi una línea suelta
+pub const MAX_PROGRESSIVE_PASSES: usize = 32;
+
+// Filas que dibuja una pasada: las `y` con `y % passes == offset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interlace {
+    pub passes: usize,
+    pub offset: usize,
+}
+
+impl Interlace {
+    pub fn contains_row(self, y: usize) -> bool {
+        y % self.passes == self.offset
+    }
+}
+
+// Desplazamiento de las filas de cada pasada, de lo grueso a lo fino: cada
+// una cae lo más lejos posible de las ya dibujadas (0, 2, 1, 3 con cuatro)
+pub fn pass_offsets(passes: usize) -> Vec<usize> {
+    let mut offsets = vec![0];
+    while offsets.len() < passes {
+        let gap = |offset: usize| {
+            offsets.iter()
+                .map(|&drawn| offset.abs_diff(drawn).min(passes - offset.abs_diff(drawn)))
+                .min()
+                .unwrap_or(passes)
+        };
+        let next = (0..passes)
+            .filter(|offset| !offsets.contains(offset))
+            .max_by_key(|&offset| (gap(offset), std::cmp::Reverse(offset)))
+            .unwrap_or(0);
+        offsets.push(next);
+    }
+    offsets
+}
+
+// Lo que se dibuja en una progresión; si cambia, las filas ya dibujadas no
+// sirven y se empieza de nuevo
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProgressView {
+    camera: CameraState,
+    projection: ProjectionSettings,
+    time: f32,
+    width: usize,
+    height: usize,
+}
+
+// Render progresivo para escenas pesadas: cada pasada (`Scene::render_pass`)
+// dibuja una fila de cada `passes`, así la primera imagen aproximada sale
+// enseguida y las siguientes la completan. Las pasadas se acumulan en un
+// framebuffer propio, de modo que lo que se dibuje encima de la imagen
+// mostrada (contornos, ejes, posprocesado) no ensucia la siguiente. Cada fila
+// se dibuja una sola vez y con la escena completa, así que tras la última
+// pasada el buffer es idéntico al de `Scene::render`. No guarda referencias:
+// se puede mover a otro hilo junto con la escena.
+pub struct RenderProgress {
+    offsets: Vec<usize>,
+    pub(crate) completed: usize,
+    view: Option<ProgressView>,
+    target: Framebuffer,
+    // Matrices del frame anterior al de la progresión, para que todas las
+    // pasadas calculen las mismas velocidades del desenfoque de movimiento
+    pub(crate) motion_history: Vec<Option<Mat4>>,
+}
+
+impl RenderProgress {
+    pub fn new(passes: usize) -> Self {
+        RenderProgress {
+            offsets: pass_offsets(passes.max(1)),
+            completed: 0,
+            view: None,
+            target: Framebuffer::new(0, 0),
+            motion_history: Vec::new(),
+        }
+    }
+
+    pub fn passes(&self) -> usize {
+        self.offsets.len()
+    }
+
+    // Pasadas ya dibujadas de la progresión actual
+    pub fn completed(&self) -> usize {
+        self.completed
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.completed == self.passes()
+    }
+
+    // Descarta lo dibujado; la siguiente llamada a `track` empieza de cero
+    pub fn restart(&mut self) {
+        self.completed = 0;
+        self.view = None;
+    }
+
+    // Si la progresión está a medias y la vista no cambió: mientras tanto el
+    // tiempo de simulación espera, o las pasadas mostrarían instantes
+    // distintos. Si la cámara se mueve, de todos modos se empieza de nuevo.
+    pub fn holds_time(&self, camera: &Camera, projection: &ProjectionSettings, framebuffer: &Framebuffer) -> bool {
+        let refining = self.completed > 0 && !self.is_complete();
+        refining && self.view.is_some_and(|view| {
+            view.camera == camera.save_state()
+                && view.projection == *projection
+                && (view.width, view.height) == (framebuffer.width, framebuffer.height)
+        })
+    }
+
+    // Compara la cámara, la proyección, el tiempo y el tamaño de `framebuffer`
+    // con los de la progresión actual. Si algo cambió, deja el buffer de
+    // acumulación limpio, con el color, el tamaño y la profundidad de
+    // `framebuffer`, y devuelve true: hay que dibujar el fondo en `target_mut`
+    // antes de la primera pasada.
+    pub fn track(&mut self, camera: &Camera, projection: &ProjectionSettings, time: f32, framebuffer: &Framebuffer) -> bool {
+        let view = ProgressView {
+            camera: camera.save_state(),
+            projection: *projection,
+            time,
+            width: framebuffer.width,
+            height: framebuffer.height,
+        };
+        if self.view == Some(view) {
+            return false;
+        }
+
+        let target = &mut self.target;
+        if target.width != framebuffer.width || target.height != framebuffer.height {
+            target.resize(framebuffer.width, framebuffer.height);
+        }
+        if target.reversed_z() != framebuffer.reversed_z() {
+            target.set_reversed_z(framebuffer.reversed_z());
+        }
+        target.set_background_color(framebuffer.background_color());
+        target.set_interlace(None);
+        target.clear();
+        self.completed = 0;
+        self.view = Some(view);
+        true
+    }
+
+    // Filas de la siguiente pasada, o None si ya están todas
+    pub fn next_interlace(&self) -> Option<Interlace> {
+        let offset = *self.offsets.get(self.completed)?;
+        Some(Interlace { passes: self.passes(), offset })
+    }
+
+    // Buffer donde se acumulan las pasadas. Tras `Scene::render_pass` sigue
+    // limitado a las filas de esa pasada, así lo que se dibuje después (el
+    // cielo de `--skybox`) cae en las mismas filas.
+    pub fn target(&self) -> &Framebuffer {
+        &self.target
+    }
+
+    pub fn target_mut(&mut self) -> &mut Framebuffer {
+        &mut self.target
+    }
+
+    // Copia lo acumulado a `framebuffer`, listo para el posprocesado y
+    // `resolve`. Cada fila que falta toma la más cercana ya dibujada por
+    // encima en su bloque. Con todas las pasadas la copia es exacta.
+    pub fn present(&self, framebuffer: &mut Framebuffer) {
+        let source = &self.target;
+        if framebuffer.width != source.width || framebuffer.height != source.height {
+            framebuffer.resize(source.width, source.height);
+        }
+        framebuffer.clear();
+        let passes = self.passes();
+        let drawn = &self.offsets[..self.completed.max(1)];
+        let width = source.width;
+        for y in 0..source.height {
+            let block = y - y % passes;
+            let offset = drawn.iter().copied().filter(|&offset| offset <= y % passes).max().unwrap_or(0);
+            let (to, from) = (y * width..(y + 1) * width, (block + offset) * width..(block + offset + 1) * width);
+            framebuffer.hdr_buffer[to.clone()].copy_from_slice(&source.hdr_buffer[from.clone()]);
+            framebuffer.zbuffer[to.clone()].copy_from_slice(&source.zbuffer[from.clone()]);
+            framebuffer.velocity_buffer[to.clone()].copy_from_slice(&source.velocity_buffer[from.clone()]);
+            framebuffer.overdraw_buffer[to].copy_from_slice(&source.overdraw_buffer[from]);
+        }
+        framebuffer.mark_all_dirty();
+        if source.background_drawn() {
+            framebuffer.mark_background_drawn();
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::f32::consts::{PI, TAU};
+use nalgebra_glm::Vec3;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use crate::color::Color;
+use crate::vertex::{Vertex, any_perpendicular};
+
+// Cuántas protuberancias puede haber a la vez y la probabilidad de que
+// nazca una en cada paso de simulación
+const MAX_PROMINENCES: usize = 4;
+const SPAWN_CHANCE: f32 = 0.012;
+// Vida en pasos de simulación (a 60 por segundo)
+const LIFETIME: (u32, u32) = (240, 480);
+// Medio ángulo entre los dos pies del arco, en radianes
+const HALF_SPAN: (f32, f32) = (0.12, 0.3);
+// Altura máxima del arco sobre la superficie, en radios de la estrella
+const HEIGHT: (f32, f32) = (0.2, 0.55);
+// Fracción de la vida en la que el arco crece y en la que se apaga
+const GROWTH: f32 = 0.35;
+const FADE: f32 = 0.3;
+// Billboards por arco y su semilado, en radios de la estrella
+const QUADS_PER_ARC: usize = 16;
+const QUAD_SIZE: f32 = 0.11;
+const PROMINENCE_COLOR: (f32, f32, f32) = (1.0, 0.45, 0.15);
+const PROMINENCE_SEED: u64 = 0x5052_4F4D;
+
+// Un arco de plasma anclado en dos puntos de la superficie
+#[derive(Debug, Clone, PartialEq)]
+struct Prominence {
+    // Dirección del punto medio entre los pies, sobre la esfera unitaria
+    anchor: Vec3,
+    // Dirección tangente hacia la que se separan los pies
+    tangent: Vec3,
+    half_span: f32,
+    height: f32,
+    age: u32,
+    lifetime: u32,
+}
+
+impl Prominence {
+    // Punto del arco en `s` (0 y 1 son los pies) relativo al centro de una
+    // estrella de radio 1: una parábola que sale hacia fuera de la superficie
+    fn point(&self, s: f32, growth: f32) -> Vec3 {
+        let (sin, cos) = self.half_span.sin_cos();
+        let start = self.anchor * cos - self.tangent * sin;
+        let end = self.anchor * cos + self.tangent * sin;
+        let direction = start.lerp(&end, s).normalize();
+        direction * (1.0 + self.height * growth * 4.0 * s * (1.0 - s))
+    }
+}
+
+// Protuberancias del Sol. Nacen en puntos pseudoaleatorios de la superficie,
+// crecen hacia fuera y se apagan. Avanzan con los pasos fijos de simulación y
+// salen de un generador sembrado, así la secuencia depende sólo de la semilla.
+pub struct Prominences {
+    rng: StdRng,
+    active: Vec<Prominence>,
+}
+
+impl Prominences {
+    pub fn new(seed: u64) -> Self {
+        Prominences {
+            rng: StdRng::seed_from_u64(seed ^ PROMINENCE_SEED),
+            active: Vec::new(),
+        }
+    }
+
+    // Un paso de simulación: envejece las activas y quizá agrega otra
+    pub fn update(&mut self) {
+        for prominence in &mut self.active {
+            prominence.age += 1;
+        }
+        self.active.retain(|prominence| prominence.age < prominence.lifetime);
+
+        if self.active.len() < MAX_PROMINENCES && self.rng.gen::<f32>() < SPAWN_CHANCE {
+            let z: f32 = self.rng.gen_range(-1.0..1.0);
+            let angle: f32 = self.rng.gen_range(0.0..TAU);
+            let ring = (1.0 - z * z).sqrt();
+            let anchor = Vec3::new(ring * angle.cos(), ring * angle.sin(), z);
+            let side = any_perpendicular(&anchor);
+            let turn: f32 = self.rng.gen_range(0.0..PI);
+            let tangent = side * turn.cos() + anchor.cross(&side) * turn.sin();
+            self.active.push(Prominence {
+                anchor,
+                tangent,
+                half_span: self.rng.gen_range(HALF_SPAN.0..HALF_SPAN.1),
+                height: self.rng.gen_range(HEIGHT.0..HEIGHT.1),
+                age: 0,
+                lifetime: self.rng.gen_range(LIFETIME.0..LIFETIME.1),
+            });
+        }
+    }
+
+    // Triángulos en espacio de mundo para una estrella en `center` de radio
+    // `radius`, vistos desde `eye`. Cada billboard es un abanico de cuatro
+    // triángulos con el color en el centro y negro en las esquinas, para que
+    // la suma aditiva se desvanezca hacia los bordes. `alpha` interpola
+    // entre pasos de simulación.
+    pub fn billboards(&self, center: &Vec3, radius: f32, eye: &Vec3, alpha: f32) -> Vec<Vertex> {
+        let mut vertices = Vec::with_capacity(self.active.len() * QUADS_PER_ARC * 12);
+        let base = Vec3::new(PROMINENCE_COLOR.0, PROMINENCE_COLOR.1, PROMINENCE_COLOR.2);
+
+        for prominence in &self.active {
+            let progress = (prominence.age as f32 + alpha) / prominence.lifetime as f32;
+            let growth = smoothstep(0.0, GROWTH, progress);
+            let brightness = growth * (1.0 - smoothstep(1.0 - FADE, 1.0, progress));
+            if brightness <= 0.0 {
+                continue;
+            }
+            let tint = base * brightness;
+            let color = Color::from_float(tint.x, tint.y, tint.z);
+
+            for quad in 0..QUADS_PER_ARC {
+                let s = (quad as f32 + 0.5) / QUADS_PER_ARC as f32;
+                let position = center + prominence.point(s, growth) * radius;
+
+                let Some(view) = (eye - position).try_normalize(f32::EPSILON) else {
+                    continue;
+                };
+                let right = any_perpendicular(&view);
+                let up = view.cross(&right);
+                let size = QUAD_SIZE * radius;
+                let corners = [
+                    position + (-right - up) * size,
+                    position + (right - up) * size,
+                    position + (right + up) * size,
+                    position + (-right + up) * size,
+                ];
+
+                for edge in 0..4 {
+                    let next = (edge + 1) % 4;
+                    vertices.push(Vertex::new_with_color(position, color));
+                    vertices.push(Vertex::new_with_color(corners[edge], Color::black()));
+                    vertices.push(Vertex::new_with_color(corners[next], Color::black()));
+                }
+            }
+        }
+
+        vertices
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
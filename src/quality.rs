@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+
+// Escalas de resolución interna, de mejor a peor calidad
+const SCALE_LEVELS: [f32; 4] = [1.0, 0.85, 0.7, 0.5];
+// Frames cuyo tiempo se promedia antes de decidir. Tras un cambio se vacía:
+// los tiempos anteriores corresponden a otra resolución.
+const FRAME_WINDOW: usize = 30;
+// Por encima de target * DOWNGRADE_RATIO se baja la escala. Se sube sólo si
+// el tiempo previsto en el nivel de arriba (el costo crece con los píxeles,
+// el cuadrado de la escala) queda por debajo de target * UPGRADE_RATIO: entre
+// las dos queda una banda en la que un cambio no provoca el contrario.
+const DOWNGRADE_RATIO: f32 = 1.2;
+const UPGRADE_RATIO: f32 = 0.85;
+
+// Decide la escala de render según el tiempo de frame, con histéresis
+pub struct QualityController {
+    target_ms: f32,
+    level: usize,
+    // Últimos tiempos de frame en este nivel, en milisegundos
+    frame_times: VecDeque<f32>,
+}
+
+impl QualityController {
+    pub fn new(target_fps: f32) -> Self {
+        QualityController {
+            target_ms: 1000.0 / target_fps,
+            level: 0,
+            frame_times: VecDeque::with_capacity(FRAME_WINDOW),
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        SCALE_LEVELS[self.level]
+    }
+
+    // `frame_ms` es el trabajo del frame, sin la espera del límite de FPS:
+    // con ella nunca parecería haber margen para subir
+    pub fn next_scale(&mut self, frame_ms: f32) -> f32 {
+        if self.frame_times.len() == FRAME_WINDOW {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_ms);
+        if self.frame_times.len() < FRAME_WINDOW {
+            return self.scale();
+        }
+        let average = self.frame_times.iter().sum::<f32>() / FRAME_WINDOW as f32;
+
+        if average > self.target_ms * DOWNGRADE_RATIO && self.level + 1 < SCALE_LEVELS.len() {
+            self.change_level(self.level + 1);
+        } else if self.level > 0 {
+            let upgrade_cost = (SCALE_LEVELS[self.level - 1] / self.scale()).powi(2);
+            if average * upgrade_cost < self.target_ms * UPGRADE_RATIO {
+                self.change_level(self.level - 1);
+            }
+        }
+
+        self.scale()
+    }
+
+    fn change_level(&mut self, level: usize) {
+        self.level = level;
+        self.frame_times.clear();
+    }
+}
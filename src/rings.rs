@@ -0,0 +1,204 @@
+use std::f32::consts::PI;
+use nalgebra_glm::{Vec2, Vec3, Mat4};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::color::Color;
+use crate::planet_type::PlanetType;
+use crate::vertex::Vertex;
+
+pub const RING_SEGMENTS: usize = 128;
+// Sesgo de profundidad de los anillos (en z de NDC) para que ganen frente a
+// superficies casi coplanares en lugar de parpadear
+pub const RING_DEPTH_BIAS: f32 = 1e-6;
+// Disco de acreción de los agujeros negros (el horizonte mide 1)
+pub const ACCRETION_DISK_INNER: f32 = 1.3;
+pub const ACCRETION_DISK_OUTER: f32 = 3.0;
+// Ancho máximo del borde suave de cada banda, en radios del planeta. Las
+// bandas más angostas lo reducen a un cuarto de su ancho.
+const BAND_EDGE_SOFTNESS: f32 = 0.004;
+
+pub static SATURN_RINGS: Lazy<RingSystem> = Lazy::new(RingSystem::saturn);
+
+// Una banda de anillo entre dos radios (en radios del planeta). Lo que no
+// cubre ninguna banda es transparente.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RingBand {
+    pub inner: f32,
+    pub outer: f32,
+    pub color: Color,
+    // 0 = hueco, 1 = tapa por completo lo que hay detrás
+    pub opacity: f32,
+}
+
+// Anillos de un planeta: la corona de `inner` a `outer` que se rasteriza y
+// las bandas que el shader lee para colorearla. En un archivo de escena los
+// campos que falten toman el valor de Saturno.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RingSystem {
+    pub inner: f32,
+    pub outer: f32,
+    // Inclinación respecto al ecuador del planeta, en radianes (rotación en Z);
+    // la inclinación del eje del planeta ya la heredan
+    pub tilt: f32,
+    pub segments: usize,
+    pub bands: Vec<RingBand>,
+}
+
+impl Default for RingSystem {
+    fn default() -> Self {
+        RingSystem::saturn()
+    }
+}
+
+impl RingSystem {
+    // Anillos C, B, A y F, con la división de Cassini y el hueco de Encke
+    pub fn saturn() -> Self {
+        RingSystem {
+            inner: 1.2,
+            outer: 2.35,
+            tilt: 0.0,
+            segments: RING_SEGMENTS,
+            bands: vec![
+                band(1.239, 1.527, Color::new(150, 135, 120), 0.25),
+                band(1.527, 1.951, Color::new(215, 195, 170), 0.9),
+                band(1.951, 2.027, Color::new(120, 110, 100), 0.08),
+                band(2.027, 2.205, Color::new(195, 178, 155), 0.65),
+                band(2.225, 2.269, Color::new(190, 172, 150), 0.55),
+                band(2.315, 2.335, Color::new(200, 190, 175), 0.4),
+            ],
+        }
+    }
+
+    // Nueve anillos angostos y oscuros; el épsilon, el de fuera, es el más
+    // ancho. Los anchos reales no llegan a un píxel: aquí van exagerados.
+    pub fn uranus() -> Self {
+        let dark = Color::new(70, 70, 75);
+        RingSystem {
+            inner: 1.6,
+            outer: 2.03,
+            tilt: 0.0,
+            segments: RING_SEGMENTS,
+            bands: vec![
+                band(1.634, 1.640, dark, 0.3),
+                band(1.649, 1.655, dark, 0.3),
+                band(1.663, 1.669, dark, 0.3),
+                band(1.746, 1.754, dark, 0.35),
+                band(1.783, 1.791, dark, 0.35),
+                band(1.843, 1.849, dark, 0.25),
+                band(1.861, 1.867, dark, 0.35),
+                band(1.886, 1.894, dark, 0.4),
+                band(1.993, 2.009, Color::new(90, 90, 95), 0.6),
+            ],
+        }
+    }
+
+    // Galle y Lassell son anchos y difusos; Le Verrier y Adams, angostos
+    pub fn neptune() -> Self {
+        RingSystem {
+            inner: 1.6,
+            outer: 2.6,
+            tilt: 0.0,
+            segments: RING_SEGMENTS,
+            bands: vec![
+                band(1.652, 1.732, Color::new(80, 80, 90), 0.12),
+                band(2.142, 2.154, Color::new(90, 88, 95), 0.35),
+                band(2.156, 2.310, Color::new(80, 80, 90), 0.08),
+                band(2.535, 2.547, Color::new(95, 88, 92), 0.4),
+            ],
+        }
+    }
+
+    // Los anillos con los que nace un cuerpo de este tipo, si tiene
+    pub fn preset(planet_type: &PlanetType) -> Option<Self> {
+        match planet_type {
+            PlanetType::Saturn => Some(RingSystem::saturn()),
+            PlanetType::Uranus => Some(RingSystem::uranus()),
+            PlanetType::Neptune => Some(RingSystem::neptune()),
+            _ => None,
+        }
+    }
+
+    pub fn mesh(&self) -> Vec<Vertex> {
+        ring_mesh(self.inner, self.outer, self.segments)
+    }
+
+    // Lleva del espacio de modelo del planeta al de los anillos: se multiplica
+    // por la derecha a la matriz de modelo del planeta
+    pub fn tilt_matrix(&self) -> Mat4 {
+        nalgebra_glm::rotation(self.tilt, &Vec3::new(0.0, 0.0, 1.0))
+    }
+
+    // Un vector del espacio de modelo del planeta visto desde los anillos,
+    // donde el plano de la corona es y = 0
+    pub fn to_ring_plane(&self, vector: &Vec3) -> Vec3 {
+        let (sin, cos) = self.tilt.sin_cos();
+        Vec3::new(vector.x * cos + vector.y * sin, -vector.x * sin + vector.y * cos, vector.z)
+    }
+
+    // Color y opacidad a `radius` del centro. Las bandas tienen bordes suaves
+    // y, si se solapan, se suman sus opacidades.
+    pub fn sample(&self, radius: f32) -> (Color, f32) {
+        let (mut color, mut opacity) = ((0.0, 0.0, 0.0), 0.0);
+        for band in &self.bands {
+            let softness = BAND_EDGE_SOFTNESS.min((band.outer - band.inner) * 0.25);
+            let weight = smoothstep(band.inner - softness, band.inner + softness, radius)
+                * (1.0 - smoothstep(band.outer - softness, band.outer + softness, radius))
+                * band.opacity;
+            if weight <= 0.0 {
+                continue;
+            }
+            color.0 += band.color.r as f32 * weight;
+            color.1 += band.color.g as f32 * weight;
+            color.2 += band.color.b as f32 * weight;
+            opacity += weight;
+        }
+        if opacity <= 0.0 {
+            return (Color::black(), 0.0);
+        }
+        let scale = 1.0 / (opacity * 255.0);
+        (Color::from_float(color.0 * scale, color.1 * scale, color.2 * scale), opacity.min(1.0))
+    }
+}
+
+fn band(inner: f32, outer: f32, color: Color, opacity: f32) -> RingBand {
+    RingBand { inner, outer, color, opacity }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Corona plana en el plano ecuatorial (y = 0), como lista de triángulos.
+// Las coordenadas de textura van (radio normalizado, ángulo normalizado).
+pub fn ring_mesh(inner: f32, outer: f32, segments: usize) -> Vec<Vertex> {
+    let segments = segments.max(3);
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+
+    let vertex = |radius: f32, angle: f32| {
+        let (sin, cos) = angle.sin_cos();
+        Vertex::new_with_tangent(
+            Vec3::new(radius * cos, 0.0, radius * sin),
+            normal,
+            Vec3::new(cos, 0.0, sin),
+            Vec2::new((radius - inner) / (outer - inner), angle / (2.0 * PI)),
+        )
+    };
+
+    let mut vertices = Vec::with_capacity(segments * 6);
+    for i in 0..segments {
+        let a0 = i as f32 / segments as f32 * 2.0 * PI;
+        let a1 = (i + 1) as f32 / segments as f32 * 2.0 * PI;
+
+        let inner0 = vertex(inner, a0);
+        let outer0 = vertex(outer, a0);
+        let inner1 = vertex(inner, a1);
+        let outer1 = vertex(outer, a1);
+
+        vertices.extend([inner0, outer0.clone(), inner1.clone()]);
+        vertices.extend([inner1, outer0, outer1]);
+    }
+
+    vertices
+}
@@ -0,0 +1,713 @@
+use std::collections::HashMap;
+use std::fs;
+use std::mem;
+use std::path::Path;
+use nalgebra_glm::{Vec3, Vec4, Mat4};
+use serde::{Deserialize, Serialize};
+use crate::{
+    CelestialBody,
+    Uniforms,
+    RenderContext,
+    RenderStats,
+    ProjectionSettings,
+    create_model_matrix,
+    create_noise,
+    create_view_matrix,
+    render,
+    render_atmosphere,
+    render_prominences,
+    season,
+};
+use crate::assets::Assets;
+use crate::camera::{Camera, CameraState};
+use crate::framebuffer::Framebuffer;
+use crate::lighting::Light;
+use crate::mesh::SphereMesh;
+use crate::motion_blur::MotionBlur;
+use crate::noise_utils::{NoiseCache, NOISE_CACHE_EXTENT, NOISE_CACHE_CELLS};
+use crate::obj::Obj;
+use crate::outline::SelectionOutline;
+use crate::planet_type::PlanetType;
+use crate::progressive::RenderProgress;
+use crate::prominences::Prominences;
+use crate::rings::{
+    ring_mesh, RingSystem, RING_SEGMENTS, RING_DEPTH_BIAS, ACCRETION_DISK_INNER, ACCRETION_DISK_OUTER,
+};
+use crate::shaders::star_light_color;
+use crate::simulation::{update_simulation, interpolated_state, host_index, FIXED_DT, UPDATES_PER_SECOND};
+use crate::vertex::Vertex;
+use crate::viewport::Viewport;
+use crate::gas_giant::GasGiantParams;
+use crate::terrestrial::TerrestrialParams;
+use crate::atmosphere::AtmosphereParams;
+use crate::error::Error;
+
+// Modelo de la esfera, relativo a la carpeta de assets
+pub const SPHERE_MODEL: &str = "models/esfera.obj";
+// Luz de relleno (I): tenue y algo fría, para que el Sol siga mandando.
+// La posición la pone cada frame la cámara.
+const FILL_LIGHT_COLOR: Vec3 = Vec3::new(0.75, 0.82, 1.0);
+const FILL_LIGHT_INTENSITY: f32 = 0.35;
+// Tope de `Scene::time_scale`: más rápido, un frame lento pediría miles de pasos
+pub const MAX_TIME_SCALE: f32 = 50.0;
+
+// Estado de un cuerpo tal como se guarda en disco; los vectores quedan
+// como arreglos [x, y, z], fáciles de leer y editar a mano
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BodyState {
+    pub shader_type: PlanetType,
+    pub position: Vec3,
+    pub scale: f32,
+    pub rotation: Vec3,
+    pub rotation_speed: f32,
+    pub orbit_radius: f32,
+    pub orbit_speed: f32,
+    pub orbit_phase: f32,
+    pub displacement: f32,
+    pub axial_tilt: f32,
+    // Paleta propia de gigante gaseoso; las escenas viejas no la traen
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_giant: Option<GasGiantParams>,
+    // Paleta de planeta terrestre; los campos que falten toman los de la Tierra
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terrestrial: Option<TerrestrialParams>,
+    // Malla propia, relativa a la carpeta de assets; sin ella, la esfera
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<String>,
+    // Anillos propios; sin ellos, los del tipo (Saturno, Urano y Neptuno)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rings: Option<RingSystem>,
+    // Atmósfera propia; sin ella, la del tipo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atmosphere: Option<AtmosphereParams>,
+    // Ver `CelestialBody::is_transparent`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_transparent: bool,
+    // Ver `CelestialBody::visible`
+    #[serde(default = "crate::visible_by_default")]
+    pub visible: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SceneFile {
+    pub camera: CameraState,
+    pub bodies: Vec<BodyState>,
+}
+
+// Guarda la escena tal como se ve ahora. El giro y el ángulo de órbita
+// actuales se pliegan en `rotation` y `orbit_phase`, así al cargarla la
+// simulación arranca en t = 0 exactamente desde este estado.
+pub fn export_scene(bodies: &[CelestialBody], camera: &Camera, path: &str) -> Result<(), Error> {
+    let scene = SceneFile {
+        camera: camera.save_state(),
+        bodies: bodies.iter().enumerate().map(|(index, body)| {
+            // Las lunas orbitan alrededor de su planeta, el resto alrededor del origen
+            let relative = match host_index(bodies, index) {
+                Some(host) => body.position - bodies[host].position,
+                None => body.position,
+            };
+            let orbit_phase = if body.orbit_speed != 0.0 || body.shader_type.host().is_some() {
+                relative.z.atan2(relative.x)
+            } else {
+                body.orbit_phase
+            };
+
+            BodyState {
+                shader_type: body.shader_type,
+                position: body.position,
+                scale: body.scale,
+                rotation: body.rotation + Vec3::new(0.0, body.spin, 0.0),
+                rotation_speed: body.rotation_speed,
+                orbit_radius: body.orbit_radius,
+                orbit_speed: body.orbit_speed,
+                orbit_phase,
+                displacement: body.displacement,
+                axial_tilt: body.axial_tilt,
+                gas_giant: body.gas_giant.clone(),
+                terrestrial: body.terrestrial.clone(),
+                mesh: body.mesh.clone(),
+                rings: body.rings.clone(),
+                atmosphere: body.atmosphere.clone(),
+                is_transparent: body.is_transparent,
+                visible: body.visible,
+            }
+        }).collect(),
+    };
+
+    let json = serde_json::to_string_pretty(&scene)
+        .map_err(|source| Error::Scene { path: path.into(), source })?;
+    fs::write(path, json).map_err(|source| Error::Write { path: path.into(), source })
+}
+
+// Lee una escena exportada con `export_scene`
+pub fn load_scene(path: &str) -> Result<(Vec<CelestialBody>, Camera), Error> {
+    let json = fs::read_to_string(path).map_err(|source| Error::Read { path: path.into(), source })?;
+    let scene: SceneFile = serde_json::from_str(&json)
+        .map_err(|source| Error::Scene { path: path.into(), source })?;
+
+    let camera = Camera::new(scene.camera.eye, scene.camera.center, scene.camera.up);
+
+    let bodies = scene.bodies.into_iter().map(|state| {
+        CelestialBody {
+            position: state.position,
+            previous_position: state.position,
+            scale: state.scale,
+            rotation: state.rotation,
+            shader_type: state.shader_type,
+            rotation_speed: state.rotation_speed,
+            orbit_radius: state.orbit_radius,
+            orbit_speed: state.orbit_speed,
+            orbit_phase: state.orbit_phase,
+            displacement: state.displacement,
+            axial_tilt: state.axial_tilt,
+            gas_giant: state.gas_giant,
+            terrestrial: state.terrestrial,
+            mesh: state.mesh,
+            rings: state.rings.or_else(|| RingSystem::preset(&state.shader_type)),
+            atmosphere: state.atmosphere.or_else(|| AtmosphereParams::preset(&state.shader_type)),
+            is_transparent: state.is_transparent,
+            visible: state.visible,
+            ..Default::default()
+        }
+    }).collect();
+
+    Ok((bodies, camera))
+}
+
+// Mallas de la escena. Cada OBJ distinto se carga una sola vez en `models`,
+// indexado por su ruta dentro de assets, aunque lo usen muchos cuerpos. Las
+// coronas de los anillos se indexan por sus radios y segmentos.
+pub struct SceneMeshes {
+    assets: Assets,
+    models: HashMap<String, Vec<Vertex>>,
+    rings: HashMap<RingKey, Vec<Vertex>>,
+    accretion_disk: Vec<Vertex>,
+}
+
+type RingKey = (u32, u32, usize);
+
+fn ring_key(rings: &RingSystem) -> RingKey {
+    (rings.inner.to_bits(), rings.outer.to_bits(), rings.segments)
+}
+
+impl SceneMeshes {
+    // Sin la esfera (`sphere_path`) no hay nada que dibujar y se devuelve el
+    // error. Si falta la malla propia de un cuerpo, se avisa y usa la esfera.
+    pub fn load(assets: &Assets, sphere_path: &Path, bodies: &[CelestialBody]) -> Result<Self, Error> {
+        Self::with_sphere(assets, &SphereMesh::Obj(sphere_path.to_path_buf()), bodies)
+    }
+
+    // Como `load`, con la esfera de un OBJ o generada (esfera UV o icosfera)
+    pub fn with_sphere(assets: &Assets, sphere: &SphereMesh, bodies: &[CelestialBody]) -> Result<Self, Error> {
+        let mut meshes = SceneMeshes {
+            assets: assets.clone(),
+            models: HashMap::from([(SPHERE_MODEL.to_string(), sphere.vertex_array()?)]),
+            rings: HashMap::new(),
+            accretion_disk: ring_mesh(ACCRETION_DISK_INNER, ACCRETION_DISK_OUTER, RING_SEGMENTS),
+        };
+        meshes.add_bodies(bodies);
+        Ok(meshes)
+    }
+
+    // Carga las mallas y anillos de `bodies` que todavía no estén, p. ej. los
+    // de cuerpos nuevos al recargar la escena
+    pub fn add_bodies(&mut self, bodies: &[CelestialBody]) {
+        for body in bodies {
+            let Some(mesh) = &body.mesh else {
+                continue;
+            };
+            if self.models.contains_key(mesh) {
+                continue;
+            }
+            match Obj::load(self.assets.path(mesh)) {
+                Ok(obj) => {
+                    self.models.insert(mesh.clone(), obj.get_vertex_array());
+                }
+                Err(error) => {
+                    // Se guarda la esfera con esta clave para avisar una sola vez
+                    eprintln!("No se pudo cargar la malla de {}: {}; se usa la esfera", body.shader_type.name(), error);
+                    let fallback = self.models[SPHERE_MODEL].clone();
+                    self.models.insert(mesh.clone(), fallback);
+                }
+            }
+        }
+
+        for rings in bodies.iter().filter_map(|body| body.rings.as_ref()) {
+            self.rings.entry(ring_key(rings)).or_insert_with(|| rings.mesh());
+        }
+    }
+
+    pub fn sphere(&self) -> &[Vertex] {
+        &self.models[SPHERE_MODEL]
+    }
+
+    // La malla propia del cuerpo o, sin ella, la esfera
+    pub fn body_mesh(&self, body: &CelestialBody) -> &[Vertex] {
+        body.mesh.as_ref()
+            .and_then(|mesh| self.models.get(mesh))
+            .map_or(self.sphere(), Vec::as_slice)
+    }
+
+    // La corona de estos anillos. Sólo existen las de los cuerpos que había
+    // al cargar las mallas o que se agregaron con `add_bodies`.
+    pub fn ring_mesh(&self, rings: &RingSystem) -> Option<&[Vertex]> {
+        self.rings.get(&ring_key(rings)).map(Vec::as_slice)
+    }
+}
+
+// Lo que cambió al recargar la escena (ver `Scene::reload_bodies`)
+#[derive(Debug, Default)]
+pub struct SceneReload {
+    // Para cada cuerpo anterior, su índice en la escena nueva; None si se quitó
+    pub moved: Vec<Option<usize>>,
+    pub updated: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+// Un sistema listo para simular y dibujar, sin ventana ni entrada: los
+// cuerpos, la cámara, las uniforms y lo que el render necesita conservar
+// entre frames. La ventana, los controles y el posprocesado quedan fuera.
+pub struct Scene {
+    pub bodies: Vec<CelestialBody>,
+    pub camera: Camera,
+    pub uniforms: Uniforms,
+    pub meshes: SceneMeshes,
+    // Guarda las matrices de cada cuerpo del frame anterior; `apply` va aparte
+    pub motion_blur: MotionBlur,
+    pub context: RenderContext,
+    // Segundos simulados por segundo real (0 detiene la simulación)
+    pub time_scale: f32,
+    prominences: Prominences,
+    sim_time: f32,
+    accumulator: f32,
+}
+
+impl Scene {
+    pub fn new(bodies: Vec<CelestialBody>, camera: Camera, uniforms: Uniforms, meshes: SceneMeshes) -> Self {
+        let mut scene = Scene {
+            bodies,
+            camera,
+            prominences: Prominences::new(uniforms.seed),
+            uniforms,
+            meshes,
+            motion_blur: MotionBlur::default(),
+            context: RenderContext::new(),
+            time_scale: 1.0,
+            sim_time: 0.0,
+            accumulator: 0.0,
+        };
+        // Estado inicial: el paso anterior y el actual coinciden en t = 0
+        update_simulation(&mut scene.bodies, scene.sim_time);
+        update_simulation(&mut scene.bodies, scene.sim_time);
+        scene
+    }
+
+    // Avanza `dt` segundos en pasos fijos de simulación, independientes de
+    // la velocidad de renderizado; lo que sobra queda para el siguiente.
+    // `time_scale` acelera o frena el paso del tiempo.
+    pub fn update(&mut self, dt: f32) {
+        self.accumulator += dt * self.time_scale.clamp(0.0, MAX_TIME_SCALE);
+        while self.accumulator >= FIXED_DT {
+            self.sim_time += FIXED_DT;
+            update_simulation(&mut self.bodies, self.sim_time);
+            self.prominences.update();
+            self.accumulator -= FIXED_DT;
+        }
+    }
+
+    // Fracción del paso actual ya transcurrida, para interpolar posiciones
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / FIXED_DT
+    }
+
+    // Tiempo de simulación que corresponde a lo que se dibuja, en segundos
+    pub fn render_time(&self) -> f32 {
+        self.sim_time - FIXED_DT + self.alpha() * FIXED_DT
+    }
+
+    // Segundos de simulación transcurridos
+    pub fn sim_time(&self) -> f32 {
+        self.sim_time
+    }
+
+    // Salta a `sim_time` sin simular los pasos intermedios: órbitas y giros
+    // salen del tiempo, así que basta recalcularlos como en `new`
+    pub fn set_sim_time(&mut self, sim_time: f32) {
+        self.sim_time = sim_time;
+        self.accumulator = 0.0;
+        update_simulation(&mut self.bodies, sim_time);
+        update_simulation(&mut self.bodies, sim_time);
+        // Las matrices del frame anterior ya no corresponden
+        self.motion_blur.reset();
+    }
+
+    // Reemplaza los cuerpos por los de una escena recargada sin tocar la
+    // cámara ni el tiempo de simulación. Cada cuerpo nuevo se empareja con
+    // uno actual del mismo tipo y en el mismo puesto entre los de su tipo (la
+    // segunda luna con la segunda luna), así agregar o quitar uno no cambia
+    // la identidad del resto. Los emparejados toman todo lo del archivo salvo
+    // la visibilidad, que es de la sesión; los que sobran se quitan.
+    pub fn reload_bodies(&mut self, bodies: Vec<CelestialBody>) -> SceneReload {
+        let mut current: Vec<Option<CelestialBody>> = mem::take(&mut self.bodies).into_iter().map(Some).collect();
+        let mut reload = SceneReload { moved: vec![None; current.len()], ..Default::default() };
+
+        for (index, body) in bodies.into_iter().enumerate() {
+            let kind = mem::discriminant(&body.shader_type);
+            let matched = current.iter_mut()
+                .enumerate()
+                .find(|(_, old)| old.as_ref().is_some_and(|old| mem::discriminant(&old.shader_type) == kind))
+                .and_then(|(old_index, old)| Some((old_index, old.take()?)));
+            match matched {
+                Some((old_index, old)) => {
+                    reload.moved[old_index] = Some(index);
+                    reload.updated += 1;
+                    self.bodies.push(CelestialBody { visible: old.visible, ..body });
+                }
+                None => {
+                    reload.added += 1;
+                    self.bodies.push(body);
+                }
+            }
+        }
+        reload.removed = current.iter().filter(|old| old.is_some()).count();
+
+        self.meshes.add_bodies(&self.bodies);
+        // Órbitas y giros salen del tiempo: el paso anterior y el actual se
+        // recalculan para que la interpolación siga donde estaba
+        update_simulation(&mut self.bodies, self.sim_time - FIXED_DT);
+        update_simulation(&mut self.bodies, self.sim_time);
+        // Los índices pueden haber cambiado
+        self.motion_blur.reset();
+        reload
+    }
+
+    // Cambia la semilla del ruido y regenera todo lo que sale de ella
+    pub fn set_seed(&mut self, seed: u64) {
+        self.uniforms.seed = seed;
+        self.uniforms.noise = create_noise(seed);
+        // La caché se muestreó del ruido anterior
+        if self.uniforms.noise_cache.is_some() {
+            self.uniforms.noise_cache = Some(NoiseCache::new(&self.uniforms.noise, NOISE_CACHE_EXTENT, NOISE_CACHE_CELLS));
+        }
+        self.prominences = Prominences::new(seed);
+    }
+
+    // Dibuja todos los cuerpos, los anillos, el disco de acreción, las
+    // atmósferas y las protuberancias en el buffer HDR. Va después de `clear`
+    // (y del fondo) y antes del posprocesado. Primero lo opaco, con escritura
+    // de profundidad; después lo transparente, de atrás hacia adelante, porque
+    // se mezcla con lo que ya hay. Necesita `&mut self` porque reutiliza los
+    // buffers de `context`, ajusta las uniforms a cada cuerpo y guarda sus
+    // matrices para el desenfoque de movimiento.
+    pub fn render(&mut self, framebuffer: &mut Framebuffer) -> RenderStats {
+        let alpha = self.alpha();
+        // Los shaders se ajustaron en "frames" de 1/60 s
+        self.uniforms.time = self.render_time().max(0.0) * UPDATES_PER_SECOND;
+        self.uniforms.view_matrix = create_view_matrix(self.camera.eye, self.camera.center, self.camera.up);
+        self.uniforms.camera_position = self.camera.eye;
+        update_lights(&mut self.uniforms, &self.bodies, alpha);
+
+        let mut stats = render_opaque(
+            framebuffer,
+            &mut self.context,
+            &mut self.uniforms,
+            &mut self.motion_blur,
+            &self.bodies,
+            &self.meshes,
+            alpha,
+        );
+
+        for draw in transparent_draws(&self.bodies, &self.uniforms.view_matrix, alpha) {
+            let body = &self.bodies[draw.body];
+            stats += match draw.part {
+                TransparentPart::Body | TransparentPart::Rings => {
+                    set_body_uniforms(&mut self.uniforms, body, alpha);
+                    self.uniforms.previous_mvp = None;
+                    self.uniforms.depth_write = false;
+                    let stats = if draw.part == TransparentPart::Body {
+                        render(framebuffer, &mut self.context, &self.uniforms, self.meshes.body_mesh(body), &body.shader_type, &self.bodies, draw.body)
+                    } else {
+                        render_rings(framebuffer, &mut self.context, &mut self.uniforms, &self.meshes, &self.bodies, draw.body)
+                    };
+                    self.uniforms.depth_write = true;
+                    stats
+                }
+                TransparentPart::Atmosphere => {
+                    render_body_atmosphere(framebuffer, &mut self.context, &mut self.uniforms, self.meshes.sphere(), &self.bodies, draw.body, alpha)
+                }
+                TransparentPart::Prominences => {
+                    let (position, _) = interpolated_state(body, alpha);
+                    let quads = self.prominences.billboards(&position, body.scale, &self.camera.eye, alpha);
+                    render_prominences(framebuffer, &mut self.context, &mut self.uniforms, &quads)
+                }
+            };
+        }
+        stats
+    }
+
+    // Dibuja la siguiente pasada de `progress` en su buffer de acumulación,
+    // como `render` pero sólo en las filas de esa pasada. Va después de
+    // `RenderProgress::track` (y del fondo, si empezó de cero); cuando ya
+    // están todas no dibuja nada. Todas las pasadas parten de las matrices
+    // del frame anterior a la primera, así el desenfoque de movimiento no
+    // cambia de una a otra.
+    pub fn render_pass(&mut self, progress: &mut RenderProgress) -> RenderStats {
+        let Some(interlace) = progress.next_interlace() else {
+            return RenderStats::default();
+        };
+        if progress.completed == 0 {
+            progress.motion_history = self.motion_blur.history();
+        } else {
+            self.motion_blur.restore_history(&progress.motion_history);
+        }
+        let target = progress.target_mut();
+        target.set_interlace(Some(interlace));
+        let stats = self.render(target);
+        progress.completed += 1;
+        stats
+    }
+
+    // Dibuja la escena en el rectángulo `viewport` del framebuffer con otra
+    // cámara y otra proyección, como en una ventana propia: el viewport y el
+    // aspecto salen del rectángulo y nada se escribe fuera de él. Va después
+    // de `clear_region` (y del fondo de esa vista). La profundidad invertida
+    // la decide el framebuffer. Cada vista pisaría las matrices que guarda la
+    // otra para el desenfoque de movimiento, así que se descartan: una vista
+    // así no tiene estela.
+    pub fn render_viewport(
+        &mut self,
+        framebuffer: &mut Framebuffer,
+        viewport: &Viewport,
+        camera: &Camera,
+        projection: &ProjectionSettings,
+    ) -> RenderStats {
+        if viewport.is_empty() {
+            return RenderStats::default();
+        }
+        let live_camera = mem::replace(&mut self.camera, camera.clone());
+        let live_projection = self.uniforms.projection;
+        let live_matrices = (self.uniforms.projection_matrix, self.uniforms.viewport_matrix);
+
+        self.uniforms.projection = ProjectionSettings { reversed_z: framebuffer.reversed_z(), ..*projection };
+        self.uniforms.projection_matrix = viewport.projection_matrix(&self.uniforms.projection);
+        self.uniforms.viewport_matrix = viewport.matrix();
+        self.motion_blur.reset();
+        framebuffer.set_scissor(Some(*viewport));
+        let stats = self.render(framebuffer);
+        framebuffer.set_scissor(None);
+
+        self.camera = live_camera;
+        self.uniforms.projection = live_projection;
+        (self.uniforms.projection_matrix, self.uniforms.viewport_matrix) = live_matrices;
+        stats
+    }
+
+    // Un cuadro suelto de `width` x `height` para exportar, con los cuerpos de
+    // la escena vistos desde `camera` en el segundo `time` de simulación. La
+    // proyección y el viewport salen del tamaño pedido, así el aspecto es el
+    // de la imagen y no el de la ventana. Devuelve el buffer HDR sin fondo ni
+    // posprocesado (falta `resolve`); la escena queda como estaba, salvo el
+    // desenfoque de movimiento, que se reinicia.
+    pub fn render_still(&mut self, width: usize, height: usize, camera: &Camera, time: f32) -> Framebuffer {
+        let live_bodies = self.bodies.clone();
+        let (live_time, live_accumulator) = (self.sim_time, self.accumulator);
+
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.set_reversed_z(self.uniforms.projection.reversed_z);
+        self.set_sim_time(time);
+        let projection = self.uniforms.projection;
+        self.render_viewport(&mut framebuffer, &Viewport::full(width, height), camera, &projection);
+
+        self.bodies = live_bodies;
+        self.sim_time = live_time;
+        self.accumulator = live_accumulator;
+        // Las matrices guardadas son las del cuadro exportado
+        self.motion_blur.reset();
+        framebuffer
+    }
+
+    // Máscara del contorno del cuerpo `index`. Va justo después de `render`,
+    // mientras el z-buffer sigue tal como quedó la escena.
+    pub fn render_outline_mask(&mut self, framebuffer: &Framebuffer, outline: &mut SelectionOutline, index: usize) {
+        let body = &self.bodies[index];
+        self.uniforms.model_matrix = body_model_matrix(body, self.alpha());
+        self.uniforms.displacement = body.displacement;
+        outline.render_mask(framebuffer, &mut self.context, &self.uniforms, self.meshes.body_mesh(body));
+    }
+}
+
+// El giro en Y se aplica antes que la inclinación en Z, así el eje queda
+// fijo mientras el planeta rota
+pub fn body_model_matrix(body: &CelestialBody, alpha: f32) -> Mat4 {
+    let (position, spin) = interpolated_state(body, alpha);
+    create_model_matrix(position, body.scale, body.rotation + Vec3::new(0.0, spin, body.axial_tilt))
+}
+
+// Parte de la escena que se mezcla o se suma con lo que ya hay debajo:
+// aunque haya z-buffer, el resultado depende del orden
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TransparentPart {
+    // Un cuerpo con `is_transparent` o de un tipo transparente
+    Body,
+    Rings,
+    Atmosphere,
+    // Las del primer Sol de la escena
+    Prominences,
+}
+
+struct TransparentDraw {
+    body: usize,
+    part: TransparentPart,
+}
+
+// Distancia del centro del cuerpo a la cámara, a lo largo de la vista
+fn view_depth(body: &CelestialBody, view_matrix: &Mat4, alpha: f32) -> f32 {
+    let (position, _) = interpolated_state(body, alpha);
+    -(view_matrix * Vec4::new(position.x, position.y, position.z, 1.0)).z
+}
+
+// Índices de los cuerpos del más lejano al más cercano
+fn back_to_front(bodies: &[CelestialBody], view_matrix: &Mat4, alpha: f32) -> Vec<usize> {
+    let depths: Vec<f32> = bodies.iter().map(|body| view_depth(body, view_matrix, alpha)).collect();
+    let mut order: Vec<usize> = (0..bodies.len()).collect();
+    order.sort_by(|&a, &b| depths[b].total_cmp(&depths[a]));
+    order
+}
+
+// Todo lo transparente de la escena, de lo más lejano a lo más cercano. Se
+// ordena por el centro de cada cuerpo: las partes de uno mismo conservan
+// este orden (cuerpo, anillos, atmósfera).
+fn transparent_draws(bodies: &[CelestialBody], view_matrix: &Mat4, alpha: f32) -> Vec<TransparentDraw> {
+    let first_sun = bodies.iter().position(|body| matches!(body.shader_type, PlanetType::Sun));
+    let mut draws = Vec::new();
+    for index in back_to_front(bodies, view_matrix, alpha) {
+        let body = &bodies[index];
+        if !body.visible {
+            continue;
+        }
+        let parts = [
+            (body_is_transparent(body), TransparentPart::Body),
+            (body.rings.is_some(), TransparentPart::Rings),
+            (body.atmosphere.is_some(), TransparentPart::Atmosphere),
+            (first_sun == Some(index), TransparentPart::Prominences),
+        ];
+        draws.extend(parts.into_iter()
+            .filter(|&(present, _)| present)
+            .map(|(_, part)| TransparentDraw { body: index, part }));
+    }
+    draws
+}
+
+fn body_is_transparent(body: &CelestialBody) -> bool {
+    body.is_transparent || body.shader_type.is_transparent()
+}
+
+// Las estrellas de la escena, más la de relleno si está activa. La primera
+// estrella es la que usan los efectos que dependen del Sol.
+fn update_lights(uniforms: &mut Uniforms, bodies: &[CelestialBody], alpha: f32) {
+    uniforms.lights.clear();
+    uniforms.lights.extend(bodies.iter()
+        .filter(|body| body.shader_type.is_star())
+        .map(|body| Light::point(interpolated_state(body, alpha).0, star_light_color(&body.shader_type), 1.0)));
+    if let Some(sun) = uniforms.lights.first().and_then(Light::position) {
+        uniforms.sun_position = sun;
+    }
+    // La de relleno sale de la cámara: aclara justo el lado que se ve
+    if uniforms.fill_light {
+        uniforms.lights.push(Light::point(uniforms.camera_position, FILL_LIGHT_COLOR, FILL_LIGHT_INTENSITY));
+    }
+}
+
+// Matriz de modelo y parámetros propios del cuerpo, para él y sus anillos
+fn set_body_uniforms(uniforms: &mut Uniforms, body: &CelestialBody, alpha: f32) {
+    uniforms.model_matrix = body_model_matrix(body, alpha);
+    uniforms.displacement = body.displacement;
+    uniforms.gas_giant.clone_from(&body.gas_giant);
+    uniforms.terrestrial.clone_from(&body.terrestrial);
+    uniforms.rings.clone_from(&body.rings);
+    uniforms.season = season(&uniforms.model_matrix, body.axial_tilt, &uniforms.sun_position);
+}
+
+// Los cuerpos opacos y el disco de acreción, con escritura de profundidad.
+// Los transparentes y los ocultos sólo guardan su matriz para el desenfoque
+// de movimiento, así no dejan estela al aparecer.
+// El z-buffer resuelve casi todo, pero los bordes antialiasados se mezclan
+// con lo que hay debajo: de atrás hacia adelante, un cuerpo lejano no los
+// tapa al dibujarse después.
+fn render_opaque(
+    framebuffer: &mut Framebuffer,
+    context: &mut RenderContext,
+    uniforms: &mut Uniforms,
+    motion_blur: &mut MotionBlur,
+    bodies: &[CelestialBody],
+    meshes: &SceneMeshes,
+    alpha: f32,
+) -> RenderStats {
+    let mut stats = RenderStats::default();
+    for index in back_to_front(bodies, &uniforms.view_matrix, alpha) {
+        let body = &bodies[index];
+        set_body_uniforms(uniforms, body, alpha);
+        uniforms.previous_mvp = motion_blur.previous_mvp(index);
+
+        if body.visible && !body_is_transparent(body) {
+            stats += render(framebuffer, context, uniforms, meshes.body_mesh(body), &body.shader_type, bodies, index);
+        }
+        // El disco de acreción rodea al agujero negro en su plano ecuatorial
+        if body.visible && matches!(body.shader_type, PlanetType::BlackHole) {
+            stats += render(framebuffer, context, uniforms, &meshes.accretion_disk, &PlanetType::AccretionDisk, bodies, index);
+        }
+
+        motion_blur.store_mvp(index, uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix);
+    }
+    uniforms.previous_mvp = None;
+    stats
+}
+
+// Los anillos del cuerpo `index`, con las uniforms ya puestas para él:
+// parten de su matriz de modelo, así heredan la inclinación del eje
+fn render_rings(
+    framebuffer: &mut Framebuffer,
+    context: &mut RenderContext,
+    uniforms: &mut Uniforms,
+    meshes: &SceneMeshes,
+    bodies: &[CelestialBody],
+    index: usize,
+) -> RenderStats {
+    let Some((rings, mesh)) = bodies[index].rings.as_ref().and_then(|rings| Some((rings, meshes.ring_mesh(rings)?))) else {
+        return RenderStats::default();
+    };
+    let planet_matrix = uniforms.model_matrix;
+    uniforms.model_matrix = planet_matrix * rings.tilt_matrix();
+    uniforms.depth_bias = RING_DEPTH_BIAS;
+    let stats = render(framebuffer, context, uniforms, mesh, &PlanetType::Rings, bodies, index);
+    uniforms.depth_bias = 0.0;
+    uniforms.model_matrix = planet_matrix;
+    stats
+}
+
+// Halo del cuerpo `index`: la misma esfera un poco más grande, sumada sobre
+// lo ya dibujado
+fn render_body_atmosphere(
+    framebuffer: &mut Framebuffer,
+    context: &mut RenderContext,
+    uniforms: &mut Uniforms,
+    vertex_array: &[Vertex],
+    bodies: &[CelestialBody],
+    index: usize,
+    alpha: f32,
+) -> RenderStats {
+    let body = &bodies[index];
+    let Some(atmosphere) = &body.atmosphere else {
+        return RenderStats::default();
+    };
+    let (position, spin) = interpolated_state(body, alpha);
+    uniforms.model_matrix = create_model_matrix(
+        position,
+        body.scale * atmosphere.shell_scale(),
+        body.rotation + Vec3::new(0.0, spin, body.axial_tilt),
+    );
+    // El relieve no debe deformar la cáscara
+    uniforms.displacement = 0.0;
+    render_atmosphere(framebuffer, context, uniforms, vertex_array, atmosphere, bodies, index)
+}
@@ -0,0 +1,53 @@
+use std::fs;
+use std::time::SystemTime;
+
+// Segundos entre dos consultas de la fecha de modificación
+pub const POLL_INTERVAL: f32 = 1.0;
+
+// Vigila el archivo de escena cargado con --scene para recargarlo al
+// guardarlo. Basta consultar su fecha de modificación una vez por segundo,
+// sin depender de las notificaciones del sistema.
+pub struct SceneWatcher {
+    path: String,
+    modified: Option<SystemTime>,
+    since_poll: f32,
+}
+
+impl SceneWatcher {
+    // La versión que hay ahora en disco se da por cargada
+    pub fn new(path: &str) -> Self {
+        SceneWatcher { path: path.to_string(), modified: modified_time(path), since_poll: 0.0 }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // Cuenta `dt` segundos y, cada `POLL_INTERVAL`, dice si el archivo cambió
+    // desde la última vez. Si no se puede leer (un editor lo está
+    // reemplazando) se espera a la siguiente consulta.
+    pub fn poll(&mut self, dt: f32) -> bool {
+        self.since_poll += dt;
+        if self.since_poll < POLL_INTERVAL {
+            return false;
+        }
+        self.since_poll = 0.0;
+        match modified_time(&self.path) {
+            Some(modified) if self.modified != Some(modified) => {
+                self.modified = Some(modified);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Tras una recarga manual: la versión actual ya no cuenta como cambio
+    pub fn sync(&mut self) {
+        self.modified = modified_time(&self.path);
+        self.since_poll = 0.0;
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
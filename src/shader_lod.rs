@@ -0,0 +1,280 @@
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+
+// Diámetro en pantalla (píxeles) desde el que se sombrea en una rejilla
+// más gruesa, de mayor a menor, y su paso en píxeles
+const LATTICE_LEVELS: [(f32, usize); 2] = [(1000.0, 4), (320.0, 2)];
+// Por debajo de este diámetro el cuerpo es un punto: un solo color
+const TINY_BODY_PIXELS: f32 = 6.0;
+// Diferencia máxima entre las esquinas de una celda (por canal, de 0 a 255)
+// para interpolar; con más, el fragmento se sombrea entero
+const MAX_CELL_CONTRAST: u8 = 24;
+const EMPTY: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LodMode {
+    // Cada fragmento pasa por el fragment shader
+    Full,
+    // Se sombrea un punto cada `step` píxeles y se interpola entre ellos
+    Lattice(usize),
+    // El promedio de la cara visible para todo el cuerpo
+    Single,
+}
+
+// Nivel de detalle del fragment shader. En un planeta grande los píxeles
+// vecinos muestrean casi el mismo ruido, así que basta sombrear una rejilla
+// en pantalla e interpolar bilinealmente; en uno de pocos píxeles basta un
+// color. Las celdas con esquinas fuera del cuerpo o con mucho contraste entre
+// ellas se siguen sombreando píxel a píxel.
+pub struct ShaderLod {
+    pub enabled: bool,
+    mode: LodMode,
+    // Rectángulo en pantalla que cubren los fragmentos, ya recortado
+    min_x: usize,
+    min_y: usize,
+    width: usize,
+    height: usize,
+    // Por píxel del rectángulo, el fragmento más cercano que lo cubre entero
+    front: Vec<u32>,
+    // Colores ya calculados de los puntos de la rejilla
+    samples: Vec<Option<Color>>,
+    lattice_width: usize,
+    // Color de todo el cuerpo cuando es diminuto
+    average: Option<Color>,
+    // Llamadas al fragment shader desde el último `prepare`
+    shaded: usize,
+}
+
+impl Default for ShaderLod {
+    fn default() -> Self {
+        ShaderLod {
+            enabled: true,
+            mode: LodMode::Full,
+            min_x: 0,
+            min_y: 0,
+            width: 0,
+            height: 0,
+            front: Vec::new(),
+            samples: Vec::new(),
+            lattice_width: 0,
+            average: None,
+            shaded: 0,
+        }
+    }
+}
+
+impl ShaderLod {
+    // Elige el nivel para los fragmentos de un cuerpo según su tamaño en
+    // pantalla. Con `allowed` en falso (corte, sombreado plano o superficies
+    // con detalle fino) todo se sombrea entero.
+    pub fn prepare(&mut self, fragments: &[Fragment], framebuffer: &Framebuffer, allowed: bool) {
+        self.mode = LodMode::Full;
+        self.average = None;
+        self.shaded = 0;
+        if !self.enabled || !allowed || fragments.is_empty() {
+            return;
+        }
+
+        let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+        let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for fragment in fragments {
+            min_x = min_x.min(fragment.position.x);
+            min_y = min_y.min(fragment.position.y);
+            max_x = max_x.max(fragment.position.x);
+            max_y = max_y.max(fragment.position.y);
+        }
+        // Sin el píxel de borde antialiasado de cada lado
+        let diameter = (max_x - min_x).max(max_y - min_y) - 1.0;
+
+        let tiny = diameter < TINY_BODY_PIXELS;
+        let step = match LATTICE_LEVELS.iter().find(|(pixels, _)| diameter >= *pixels) {
+            Some(&(_, step)) => step,
+            None if tiny => 1,
+            None => return,
+        };
+
+        // Sólo hace falta la parte visible
+        let last_x = max_x.min(framebuffer.width as f32 - 1.0);
+        let last_y = max_y.min(framebuffer.height as f32 - 1.0);
+        if last_x < min_x.max(0.0) || last_y < min_y.max(0.0) {
+            return;
+        }
+        self.min_x = min_x.max(0.0) as usize;
+        self.min_y = min_y.max(0.0) as usize;
+        self.width = last_x as usize + 1 - self.min_x;
+        self.height = last_y as usize + 1 - self.min_y;
+
+        self.front.clear();
+        self.front.resize(self.width * self.height, EMPTY);
+        for (index, fragment) in fragments.iter().enumerate() {
+            let Some(pixel) = self.pixel(fragment) else {
+                continue;
+            };
+            // Un cuerpo diminuto es casi todo borde: ahí también cuenta, pero
+            // por detrás de cualquier fragmento que cubra el píxel entero
+            if fragment.coverage < 1.0 && !tiny {
+                continue;
+            }
+            let replaces = match self.front[pixel] {
+                EMPTY => true,
+                current => {
+                    let other = &fragments[current as usize];
+                    let (covers, other_covers) = (fragment.coverage >= 1.0, other.coverage >= 1.0);
+                    if covers == other_covers { framebuffer.is_nearer(fragment.depth, other.depth) } else { covers }
+                }
+            };
+            if replaces {
+                self.front[pixel] = index as u32;
+            }
+        }
+
+        if tiny {
+            self.mode = LodMode::Single;
+            return;
+        }
+
+        self.lattice_width = self.width.div_ceil(step) + 1;
+        let lattice_height = self.height.div_ceil(step) + 1;
+        self.samples.clear();
+        self.samples.resize(self.lattice_width * lattice_height, None);
+        self.mode = LodMode::Lattice(step);
+    }
+
+    // Color del fragmento `index` según el nivel elegido en `prepare`;
+    // `shade` es el fragment shader completo
+    pub fn shade(&mut self, fragments: &[Fragment], index: usize, mut shade: impl FnMut(&Fragment) -> Color) -> Color {
+        let fragment = &fragments[index];
+        match self.mode {
+            LodMode::Full => self.shade_full(fragment, &mut shade),
+            LodMode::Single => {
+                if let Some(color) = self.average {
+                    return color;
+                }
+                // Un fragmento por píxel, el de delante, pesado por lo que cubre
+                let (mut sum, mut total) = ((0.0, 0.0, 0.0), 0.0);
+                for pixel in 0..self.front.len() {
+                    let Some(sample) = fragments.get(self.front[pixel] as usize) else {
+                        continue;
+                    };
+                    let color = self.shade_full(sample, &mut shade);
+                    sum.0 += color.r as f32 * sample.coverage;
+                    sum.1 += color.g as f32 * sample.coverage;
+                    sum.2 += color.b as f32 * sample.coverage;
+                    total += sample.coverage;
+                }
+                if total == 0.0 {
+                    return self.shade_full(fragment, &mut shade);
+                }
+                let scale = 1.0 / (total * 255.0);
+                let color = Color::from_float(sum.0 * scale, sum.1 * scale, sum.2 * scale);
+                self.average = Some(color);
+                color
+            }
+            LodMode::Lattice(step) => match self.interpolate(fragments, index, step, &mut shade) {
+                Some(color) => color,
+                None => self.shade_full(fragment, &mut shade),
+            },
+        }
+    }
+
+    // Veces que se llamó al fragment shader desde el último `prepare`
+    pub fn shaded(&self) -> usize {
+        self.shaded
+    }
+
+    fn shade_full(&mut self, fragment: &Fragment, shade: &mut impl FnMut(&Fragment) -> Color) -> Color {
+        self.shaded += 1;
+        shade(fragment)
+    }
+
+    // Interpolación bilineal entre las cuatro esquinas de la celda del
+    // fragmento, o nada si no se puede y hay que sombrearlo entero
+    fn interpolate(
+        &mut self,
+        fragments: &[Fragment],
+        index: usize,
+        step: usize,
+        shade: &mut impl FnMut(&Fragment) -> Color,
+    ) -> Option<Color> {
+        let fragment = &fragments[index];
+        let pixel = self.pixel(fragment)?;
+        let front = self.front[pixel];
+        if front == EMPTY {
+            return None;
+        }
+        // Detrás de la cara visible del mismo cuerpo nunca se ve: le sirve
+        // cualquier color, así que no hace falta sombrearlo
+        let hidden = front != index as u32 && fragment.coverage >= 1.0;
+        let (x, y) = (pixel % self.width, pixel / self.width);
+        let (column, row) = (x / step, y / step);
+        let tx = (x % step) as f32 / step as f32;
+        let ty = (y % step) as f32 / step as f32;
+
+        let mut corners = [(0.0, 0.0, 0.0); 4];
+        let (mut darkest, mut brightest) = ([u8::MAX; 3], [0; 3]);
+        for (corner, (dx, dy)) in corners.iter_mut().zip([(0, 0), (1, 0), (0, 1), (1, 1)]) {
+            // Las esquinas que no pesan no necesitan existir (bordes de la rejilla)
+            let weight = if dx == 0 { 1.0 - tx } else { tx } * if dy == 0 { 1.0 - ty } else { ty };
+            if weight == 0.0 {
+                continue;
+            }
+            let color = self.sample(fragments, column + dx, row + dy, step, shade)?;
+            *corner = (color.r as f32, color.g as f32, color.b as f32);
+            for (channel, value) in [color.r, color.g, color.b].into_iter().enumerate() {
+                darkest[channel] = darkest[channel].min(value);
+                brightest[channel] = brightest[channel].max(value);
+            }
+        }
+        // Un borde nítido entre las esquinas (grietas, costas) se perdería
+        let contrast = (0..3).map(|channel| brightest[channel] - darkest[channel]).max().unwrap_or(0);
+        if contrast > MAX_CELL_CONTRAST && !hidden {
+            return None;
+        }
+
+        let lerp = |a: (f32, f32, f32), b: (f32, f32, f32), t: f32| {
+            (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t, a.2 + (b.2 - a.2) * t)
+        };
+        let top = lerp(corners[0], corners[1], tx);
+        let bottom = lerp(corners[2], corners[3], tx);
+        let color = lerp(top, bottom, ty);
+        Some(Color::from_float(color.0 / 255.0, color.1 / 255.0, color.2 / 255.0))
+    }
+
+    // Color del punto (`column`, `row`) de la rejilla, calculado la primera
+    // vez que se pide. No existe si el cuerpo no cubre ese píxel.
+    fn sample(
+        &mut self,
+        fragments: &[Fragment],
+        column: usize,
+        row: usize,
+        step: usize,
+        shade: &mut impl FnMut(&Fragment) -> Color,
+    ) -> Option<Color> {
+        let (x, y) = (column * step, row * step);
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let lattice_index = row * self.lattice_width + column;
+        if let Some(color) = self.samples[lattice_index] {
+            return Some(color);
+        }
+        let front = self.front[y * self.width + x];
+        if front == EMPTY {
+            return None;
+        }
+        let color = self.shade_full(&fragments[front as usize], shade);
+        self.samples[lattice_index] = Some(color);
+        Some(color)
+    }
+
+    // Índice del píxel del fragmento dentro del rectángulo
+    fn pixel(&self, fragment: &Fragment) -> Option<usize> {
+        if fragment.position.x < 0.0 || fragment.position.y < 0.0 {
+            return None;
+        }
+        let x = (fragment.position.x as usize).checked_sub(self.min_x)?;
+        let y = (fragment.position.y as usize).checked_sub(self.min_y)?;
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+}
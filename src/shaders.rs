@@ -1,22 +1,90 @@
-use nalgebra_glm::{Vec3, Vec4, Mat3, mat4_to_mat3};
-use crate::vertex::Vertex;
+use nalgebra_glm::{Vec3, Vec4, Mat3, Mat4, mat4_to_mat3};
+use crate::vertex::{Vertex, any_perpendicular};
 use crate::Uniforms;
+use crate::lighting::{apply_lighting, fragment_lighting, FALLBACK_LIGHT};
 use crate::fragment::Fragment;
 use crate::color::Color;
 use rand::Rng;
 use rand::SeedableRng;
 use rand::rngs::StdRng;
 use crate::planet_type::PlanetType;
+use crate::noise_utils::{fbm, ridged, domain_warp, hash_unit, cellular};
+use crate::rings::{RingSystem, SATURN_RINGS, ACCRETION_DISK_INNER, ACCRETION_DISK_OUTER};
+use crate::gas_giant::{GasGiantParams, GreatSpot, JUPITER};
+use crate::terrestrial::{TerrestrialParams, EARTH};
+use crate::atmosphere::AtmosphereParams;
+use crate::craters::{CALLISTO_CRATERS, MARS_CRATERS, MERCURY_CRATERS};
+use crate::custom_planet::{NoiseFractal, PlanetParams, PlanetStyle};
+
+// Ruido fractal con `uniforms.terrain_octaves` octavas, normalizado a [-1, 1].
+// Con 0 octavas el terreno queda plano en lugar de fallar.
+pub fn fractal_noise(position: &Vec3, zoom: f32, uniforms: &Uniforms) -> f32 {
+  fbm(&uniforms.noise, &(position * zoom), uniforms.terrain_octaves, 2.0, 0.5)
+}
+
+// Ruido 3D desde la caché cuando está activa y el punto cae dentro de ella;
+// si no, se muestrea directamente
+fn noise_3d(uniforms: &Uniforms, x: f32, y: f32, z: f32) -> f32 {
+  match &uniforms.noise_cache {
+    Some(cache) if cache.contains(x, y, z) => cache.sample(x, y, z),
+    _ => uniforms.noise.get_noise_3d(x, y, z),
+  }
+}
+
+// Altura del terreno en espacio de modelo; la comparten el desplazamiento
+// de vértices y el shader de la Tierra para que las montañas coincidan con los continentes
+pub fn terrain_height(position: &Vec3, uniforms: &Uniforms) -> f32 {
+  // El domain warp da costas más orgánicas que el ruido directo
+  let warped = domain_warp(&uniforms.noise, &(position * 250.0), 30.0);
+  fbm(&uniforms.noise, &warped, uniforms.terrain_octaves, 2.0, 0.5).abs()
+}
+
+// Empuja el vértice a lo largo de su normal y recalcula la normal con
+// diferencias finitas sobre dos vecinos desplazados
+fn displace(vertex: &Vertex, uniforms: &Uniforms) -> (Vec3, Vec3) {
+  let amplitude = uniforms.displacement;
+  let normal = vertex.normal.normalize();
+  let displaced = |p: Vec3| p + normal * (amplitude * terrain_height(&p, uniforms));
+
+  let epsilon = 0.01;
+  let tangent = vertex.tangent;
+  let bitangent = normal.cross(&tangent);
+
+  let center = displaced(vertex.position);
+  let along_tangent = displaced(vertex.position + tangent * epsilon);
+  let along_bitangent = displaced(vertex.position + bitangent * epsilon);
+
+  let mut displaced_normal = (along_tangent - center).cross(&(along_bitangent - center));
+  if displaced_normal.dot(&normal) < 0.0 {
+    displaced_normal = -displaced_normal;
+  }
+
+  let displaced_normal = if displaced_normal.norm() > f32::EPSILON {
+    displaced_normal.normalize()
+  } else {
+    normal
+  };
+
+  (center, displaced_normal)
+}
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+  // Displace before any transform so the silhouette becomes bumpy
+  let (model_position, model_normal) = if uniforms.displacement > 0.0 {
+    displace(vertex, uniforms)
+  } else {
+    (vertex.position, vertex.normal)
+  };
+
   // Transform position
   let position = Vec4::new(
-    vertex.position.x,
-    vertex.position.y,
-    vertex.position.z,
+    model_position.x,
+    model_position.y,
+    model_position.z,
     1.0
   );
-  let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
+  let world_position = uniforms.model_matrix * position;
+  let transformed = uniforms.projection_matrix * uniforms.view_matrix * world_position;
 
   // Perform perspective division
   let w = transformed.w;
@@ -32,41 +100,198 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
 
   // Transform normal
   let model_mat3 = mat4_to_mat3(&uniforms.model_matrix); 
-  let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
+  let transformed_normal = normal_matrix(&uniforms.model_matrix) * model_normal;
 
-  let transformed_normal = normal_matrix * vertex.normal;
+  // Tangents lie on the surface, so they follow the model matrix itself
+  let transformed_tangent = model_mat3 * vertex.tangent;
 
   // Create a new Vertex with transformed attributes
   Vertex {
     position: vertex.position,
     normal: vertex.normal,
+    tangent: vertex.tangent,
     tex_coords: vertex.tex_coords,
     color: vertex.color,
     transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
     transformed_normal,
+    transformed_tangent,
+    world_position: world_position.xyz(),
   }
 }
 
+// Inverse-transpose of the model's upper 3x3. Normals must use it instead of
+// the model matrix so they stay perpendicular to the surface (and to its
+// tangents) under non-uniform scale. A singular matrix falls back to identity.
+pub fn normal_matrix(model_matrix: &Mat4) -> Mat3 {
+  mat4_to_mat3(model_matrix)
+    .try_inverse()
+    .map(|inverse| inverse.transpose())
+    .unwrap_or(Mat3::identity())
+}
+
+// Paso de las diferencias finitas de `perturb_normal`, en espacio de modelo.
+// Bastante menor que el detalle más fino del terreno (~0.02 unidades).
+const BUMP_EPSILON: f32 = 2e-4;
+// Cuánto pesa el perfil de los cráteres en la altura usada para el relieve
+const CRATER_RELIEF: f32 = 0.02;
+// Cuánto inclina la normal cada unidad de pendiente del terreno
+const MERCURY_BUMP_STRENGTH: f32 = 0.04;
+const MARS_BUMP_STRENGTH: f32 = 0.03;
+// Oclusión de `ao_factor`: lo más hondo del terreno se queda con este
+// factor y desde esta altura del ruido ya no se oscurece
+const AO_MIN: f32 = 0.7;
+const AO_FULL_HEIGHT: f32 = 0.3;
+
+// Inclina `normal` (la del fragmento) según la pendiente de `height`, un
+// campo de alturas en espacio de modelo, para que el relieve pintado por el
+// shader también reciba luz. El gradiente se mide a lo largo de dos tangentes
+// cualesquiera; su proyección sobre el plano tangente no depende de cuáles
+// se elijan, así que no aparecen costuras donde cambia la base.
+pub fn perturb_normal(
+  normal: &Vec3,
+  fragment: &Fragment,
+  uniforms: &Uniforms,
+  height: impl Fn(&Vec3) -> f32,
+  strength: f32,
+) -> Vec3 {
+  let normal = normal.normalize();
+  // Las normales pasan a mundo con la inversa transpuesta; de vuelta, con la transpuesta
+  let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
+  let model_normal = (model_mat3.transpose() * normal).normalize();
+  let tangent = any_perpendicular(&model_normal);
+  let bitangent = model_normal.cross(&tangent);
+
+  let p = fragment.vertex_position;
+  let center = height(&p);
+  let slope_t = (height(&(p + tangent * BUMP_EPSILON)) - center) / BUMP_EPSILON;
+  let slope_b = (height(&(p + bitangent * BUMP_EPSILON)) - center) / BUMP_EPSILON;
+
+  // Un gradiente se transforma igual que una normal
+  let gradient = normal_matrix(&uniforms.model_matrix) * (tangent * slope_t + bitangent * slope_b);
+  let tangential = gradient - normal * gradient.dot(&normal);
+  let perturbed = normal - tangential * strength;
+  if perturbed.norm() > f32::EPSILON { perturbed.normalize() } else { normal }
+}
+
+// Brillo de la superficie por encima del blanco del shader; sólo las estrellas emiten
+const SUN_EMISSION: f32 = 3.0;
+// Menor que la del Sol para que el tone mapping no lave el color de la temperatura
+const STAR_EMISSION: f32 = 1.5;
+// El disco de acreción brilla por sí mismo, algo menos que una estrella
+const ACCRETION_DISK_EMISSION: f32 = 1.2;
+// Radianes por frame en el radio 1 del disco
+const ACCRETION_DISK_SPIN: f32 = 0.05;
+
+pub fn emission(planet_type: &PlanetType) -> f32 {
+    match planet_type {
+        PlanetType::Sun => SUN_EMISSION,
+        PlanetType::Star { .. } => STAR_EMISSION,
+        PlanetType::AccretionDisk => ACCRETION_DISK_EMISSION,
+        _ => 1.0,
+    }
+}
+
+// Brillo HDR de las protuberancias en su punto más intenso
+const PROMINENCE_EMISSION: f32 = 3.0;
+
+// Resplandor de una protuberancia, ya en HDR lineal. El color interpolado
+// de los billboards cae linealmente hacia las esquinas; elevarlo al cuadrado
+// lo concentra en el centro.
+pub fn prominence_shader(fragment: &Fragment) -> Vec3 {
+    let color = Vec3::new(fragment.color.r as f32, fragment.color.g as f32, fragment.color.b as f32) / 255.0;
+    color * (color.max() * PROMINENCE_EMISSION)
+}
+
+// Color de la luz que emite una estrella, con el canal más alto en 1.
+// El Sol queda blanco para que el sistema por defecto se vea igual que antes.
+pub fn star_light_color(planet_type: &PlanetType) -> Vec3 {
+    match planet_type {
+        PlanetType::Star { temperature } => {
+            let color = Color::from_kelvin(*temperature);
+            let linear = Vec3::new(color.r as f32, color.g as f32, color.b as f32);
+            linear / linear.max().max(1.0)
+        }
+        _ => Vec3::new(1.0, 1.0, 1.0),
+    }
+}
+
 pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, planet_type: &PlanetType) -> Color {
     match planet_type {
-        PlanetType::Sun => lava_shader(fragment, uniforms),
+        PlanetType::Sun => sun_shader(fragment, uniforms),
         PlanetType::Mercury => mercury_shader(fragment, uniforms),
         PlanetType::Venus => venus_shader(fragment, uniforms),
-        PlanetType::Earth => {
-            let earth_color = earth_shader(fragment, uniforms);
-            let cloud_color = cloud_shader(fragment, uniforms);
-            blend_layers(earth_color, cloud_color)
-        },
+        PlanetType::Earth | PlanetType::Terrestrial => {
+            terrestrial_shader(fragment, uniforms, terrestrial_params(uniforms, planet_type))
+        }
         PlanetType::Moon => moon_shader(fragment, uniforms),
         PlanetType::Mars => mars_shader(fragment, uniforms),
-        PlanetType::Jupiter => jupiter_shader(fragment, uniforms),
-        PlanetType::Saturn => saturn_shader(fragment, uniforms),
-        PlanetType::Uranus => uranus_shader(fragment, uniforms),
-        PlanetType::Neptune => neptune_shader(fragment, uniforms),
-        PlanetType::BlackHole => black_hole_shader(fragment, uniforms),
+        PlanetType::Jupiter | PlanetType::Saturn | PlanetType::Uranus | PlanetType::Neptune | PlanetType::GasGiant => {
+            ringed_gas_giant_shader(fragment, uniforms, gas_giant_params(uniforms, planet_type))
+        }
+        PlanetType::Rings => ring_shader(fragment, uniforms),
+        PlanetType::VertexColor => vertex_color_shader(fragment, uniforms),
+        PlanetType::RandomColor => random_color_shader(fragment, uniforms),
+        PlanetType::BlackHole => black_hole_shader(),
+        PlanetType::AccretionDisk => accretion_disk_shader(fragment, uniforms),
+        PlanetType::Star { temperature } => star_shader(fragment, uniforms, *temperature),
+        PlanetType::Custom(params) => custom_planet_shader(fragment, uniforms, params),
+        PlanetType::Io => io_shader(fragment, uniforms),
+        PlanetType::Europa => europa_shader(fragment, uniforms),
+        PlanetType::Ganymede => ganymede_shader(fragment, uniforms),
+        PlanetType::Callisto => callisto_shader(fragment, uniforms),
+    }
+}
+
+// Océanos, tierra por alturas, nubes y aurora, en ese orden, con los colores
+// de `params`. La Tierra es el caso con `TerrestrialParams::earth`.
+pub fn terrestrial_shader(fragment: &Fragment, uniforms: &Uniforms, params: &TerrestrialParams) -> Color {
+    // La misma luz para las tres capas
+    let light = fragment_lighting(fragment, uniforms);
+    let surface_color = terrestrial_surface(fragment, uniforms, params, light);
+    let cloud_color = cloud_shader(fragment, uniforms, params, light);
+    let aurora_color = aurora_shader(fragment, uniforms, light);
+    blend_layers(surface_color, cloud_color).blend_add(&aurora_color)
+}
+
+// Cuánto tapa el fragmento lo que hay detrás: 0 descarta, menos de 1 se
+// mezcla. Sólo los anillos tienen huecos; todo lo demás es opaco.
+pub fn fragment_opacity(fragment: &Fragment, uniforms: &Uniforms, planet_type: &PlanetType) -> f32 {
+    match planet_type {
+        PlanetType::Rings => ring_system(uniforms).sample(ring_radius(&fragment.vertex_position)).1,
+        _ => 1.0,
+    }
+}
+
+// Los anillos del cuerpo; sin ellos, los de Saturno
+fn ring_system(uniforms: &Uniforms) -> &RingSystem {
+    uniforms.rings.as_ref().unwrap_or(&SATURN_RINGS)
+}
+
+fn ring_radius(position: &Vec3) -> f32 {
+    (position.x * position.x + position.z * position.z).sqrt()
+}
+
+// La paleta propia del planeta si la tiene; si no, la de la Tierra
+fn terrestrial_params<'a>(uniforms: &'a Uniforms, planet_type: &PlanetType) -> &'a TerrestrialParams {
+    match &uniforms.terrestrial {
+        Some(params) => params,
+        None => TerrestrialParams::preset(planet_type).unwrap_or(&EARTH),
+    }
+}
+
+// La paleta propia del cuerpo si la tiene; si no, la del tipo
+fn gas_giant_params<'a>(uniforms: &'a Uniforms, planet_type: &PlanetType) -> &'a GasGiantParams {
+    match &uniforms.gas_giant {
+        Some(params) => params,
+        None => GasGiantParams::preset(planet_type).unwrap_or(&JUPITER),
     }
 }
 
+// Color interpolado de los vértices del OBJ, sólo con la iluminación encima
+pub fn vertex_color_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    fragment.color * fragment_lighting(fragment, uniforms)
+}
+
 fn blend_layers(base: Color, clouds: Color) -> Color {
     // Las nubes blancas se mezclan sobre la tierra
     // Si el color de la nube es más oscuro (cielo azul), se ignora
@@ -83,8 +308,50 @@ fn blend_layers(base: Color, clouds: Color) -> Color {
     }
 }
 
-fn random_color_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let seed = uniforms.time as u64;
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Cortinas de aurora en latitudes altas del lado nocturno. Devuelve un color
+// ya multiplicado por su opacidad para sumarlo sobre las capas anteriores.
+fn aurora_shader(fragment: &Fragment, uniforms: &Uniforms, light: f32) -> Color {
+    let green = Color::new(60, 255, 140);
+    let purple = Color::new(170, 80, 255);
+
+    let position = fragment.vertex_position.normalize();
+    let latitude = position.y.clamp(-1.0, 1.0).asin().to_degrees();
+    let longitude = position.z.atan2(position.x);
+
+    // Sólo por encima de ~60 grados, con borde suave
+    let latitude_mask = smoothstep(55.0, 65.0, latitude.abs());
+    // Sólo donde la luz difusa es baja, desvaneciendo en el terminador
+    let night_mask = 1.0 - smoothstep(0.0, 0.3, light);
+    let mask = latitude_mask * night_mask;
+    if mask <= 0.0 {
+        return Color::black();
+    }
+
+    // Ruido estirado: alta frecuencia en longitud, baja en latitud,
+    // advectado con el tiempo para que las cortinas titilen
+    let t = uniforms.time * 0.5;
+    let curtain = uniforms.noise.get_noise_2d(
+        longitude * 600.0 + t,
+        latitude * 2.0 - t * 0.3
+    );
+    let curtain = smoothstep(0.0, 0.8, curtain);
+
+    // Verde en la base y púrpura hacia el polo
+    let height = smoothstep(60.0, 85.0, latitude.abs());
+    let color = green.lerp(&purple, height);
+
+    color * (curtain * mask * 0.8)
+}
+
+// Un solo color para toda la superficie, elegido con `uniforms.seed` y el
+// segundo de `uniforms.time`: la misma semilla repite la secuencia de colores
+pub fn random_color_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+  let seed = (uniforms.time as u64) ^ uniforms.seed;
 
   let mut rng = StdRng::seed_from_u64(seed);
 
@@ -94,118 +361,290 @@ fn random_color_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
 
   let random_color = Color::new(r, g, b);
 
-  random_color * fragment.intensity
+  random_color * fragment_lighting(fragment, uniforms)
 }
 
-fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+// Densidad de nubes en [0, 1] para un punto en espacio de modelo.
+// La comparten la capa de nubes y sus sombras sobre la superficie.
+fn cloud_density(p: &Vec3, uniforms: &Uniforms, params: &TerrestrialParams) -> f32 {
     let zoom = 100.0;  // Reducido para nubes más grandes
     let ox = 100.0;
     let oy = 100.0;
-    let x = fragment.vertex_position.x;
-    let y = fragment.vertex_position.y;
-    let t = uniforms.time as f32 * 0.1;
+    let t = uniforms.time * 0.1;
 
-    let noise_value = uniforms.noise.get_noise_2d(x * zoom + ox + t, y * zoom + oy);
+    let noise_value = uniforms.noise.get_noise_2d(p.x * zoom + ox + t, p.y * zoom + oy);
 
-    // Define cloud threshold and colors
-    let cloud_threshold = 0.1; // Reducido para más cobertura
-    let cloud_color = Color::new(255, 255, 255);
-
-    let cloud_factor = if noise_value > cloud_threshold {
+    let cloud_threshold = params.cloud_threshold();
+    if noise_value > cloud_threshold {
         ((noise_value - cloud_threshold) / (1.0 - cloud_threshold)).min(1.0)
     } else {
         0.0
-    };
+    }
+}
 
-    cloud_color * (cloud_factor * fragment.intensity)
+fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms, params: &TerrestrialParams, light: f32) -> Color {
+    let cloud_factor = cloud_density(&fragment.vertex_position, uniforms, params);
+
+    params.cloud_color * (cloud_factor * light)
+}
+
+// Dirección unitaria hacia el Sol en espacio de modelo. Con rotación y
+// escala uniforme la transpuesta basta, porque luego se normaliza.
+fn sun_direction_model(position: &Vec3, uniforms: &Uniforms) -> Vec3 {
+    let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
+    let world_position = (uniforms.model_matrix * Vec4::new(position.x, position.y, position.z, 1.0)).xyz();
+    let to_sun = model_mat3.transpose() * (uniforms.sun_position - world_position);
+    if to_sun.norm() > f32::EPSILON { to_sun.normalize() } else { Vec3::new(0.0, 1.0, 0.0) }
+}
+
+// Ancho del borde suave de la sombra del planeta sobre los anillos, en unidades de modelo
+const PLANET_SHADOW_SOFTNESS: f32 = 0.03;
+
+// Sombra de los anillos sobre el planeta: el rayo hacia el Sol cruza el
+// plano de la corona y se atenúa con la opacidad de la banda que atraviesa
+fn ring_shadow_on_planet(position: &Vec3, to_sun: &Vec3, rings: &RingSystem) -> f32 {
+    let (position, to_sun) = (rings.to_ring_plane(position), rings.to_ring_plane(to_sun));
+    if to_sun.y.abs() <= f32::EPSILON {
+        return 0.0;
+    }
+    let t = -position.y / to_sun.y;
+    if t <= 0.0 {
+        return 0.0;
+    }
+
+    let hit = position + to_sun * t;
+    rings.sample(ring_radius(&hit)).1
+}
+
+// Sombra del planeta (esfera de radio 1) sobre un punto de los anillos
+fn planet_shadow_on_ring(position: &Vec3, to_sun: &Vec3) -> f32 {
+    let t = -position.dot(to_sun);
+    if t <= 0.0 {
+        // El punto está del lado del Sol
+        return 0.0;
+    }
+    let closest = (position + to_sun * t).norm();
+    let s = PLANET_SHADOW_SOFTNESS;
+    1.0 - smoothstep(1.0 - s, 1.0 + s, closest)
+}
+
+// Cuánto tapan las nubes al Sol en este punto. Se muestrea la densidad
+// desplazada hacia el Sol sobre la superficie, así la sombra queda corrida
+// respecto a la nube que la proyecta.
+fn cloud_shadow(fragment: &Fragment, uniforms: &Uniforms, params: &TerrestrialParams) -> f32 {
+    let shadow_offset = 0.04;
+    let p = fragment.vertex_position;
+    let surface_normal = p.normalize();
+    let to_sun = sun_direction_model(&p, uniforms);
+
+    // Solo la componente tangente a la superficie
+    let tangential = to_sun - surface_normal * to_sun.dot(&surface_normal);
+    if tangential.norm() <= f32::EPSILON {
+        return cloud_density(&p, uniforms, params) * uniforms.cloud_shadow_strength;
+    }
+
+    let sample = p + tangential.normalize() * shadow_offset;
+    cloud_density(&sample, uniforms, params) * uniforms.cloud_shadow_strength
 }
 
 
-fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   // Colores más brillantes y solares
   let bright_color = Color::new(255, 255, 100); // Amarillo brillante casi blanco
   let dark_color = Color::new(255, 140, 0);    // Naranja más brillante
 
-  // Get fragment position
-  let position = Vec3::new(
-    fragment.vertex_position.x,
-    fragment.vertex_position.y,
-    fragment.depth
-  );
+  let color = dark_color.lerp(&bright_color, granulation(fragment, uniforms));
 
-  // Ajustes para movimiento más rápido y dinámico
-  let base_frequency = 0.4;  // Aumentado para más movimiento
-  let pulsate_amplitude = 0.8;  // Aumentado para más contraste
-  let t = uniforms.time as f32 * 0.02;  // Velocidad aumentada
+  // Las estrellas no reciben luz: la fija desde +Z oscurece el borde del
+  // disco. Aumentar la intensidad general (un solo producto, satura una vez)
+  color * (FALLBACK_LIGHT.diffuse(&fragment.world_position, &fragment.normal) * 1.2)
+}
 
-  // Pulsate on the z-axis to change spot size
-  let pulsate = (t * base_frequency).sin() * pulsate_amplitude;
+// Temperatura del Sol, referencia para el contraste de las demás estrellas
+const SOLAR_TEMPERATURE: f32 = 5800.0;
 
-  // Zoom reducido para patrones más grandes
-  let zoom = 800.0;
-  let noise_value1 = uniforms.noise.get_noise_3d(
-    position.x * zoom,
-    position.y * zoom,
-    (position.z + pulsate) * zoom
-  );
-  let noise_value2 = uniforms.noise.get_noise_3d(
-    (position.x + 1000.0) * zoom,
-    (position.y + 1000.0) * zoom,
-    (position.z + 1000.0 + pulsate) * zoom
-  );
-  // Ajuste del contraste del ruido
-  let noise_value = ((noise_value1 + noise_value2) * 0.5 + 0.2).min(1.0);
+// Como `sun_shader`, pero con los colores del cuerpo negro a esa temperatura:
+// las zonas oscuras son más frías. Las estrellas más calientes tienen
+// granulación más marcada.
+fn star_shader(fragment: &Fragment, uniforms: &Uniforms, temperature: f32) -> Color {
+  let bright_color = Color::from_kelvin(temperature);
+  let dark_color = Color::from_kelvin(temperature * 0.7);
 
-  let color = dark_color.lerp(&bright_color, noise_value);
+  let contrast = (temperature / SOLAR_TEMPERATURE).clamp(0.4, 1.6);
+  let cells = (0.5 + (granulation(fragment, uniforms) - 0.5) * contrast).clamp(0.0, 1.0);
 
-  // Aumentar la intensidad general
-  color * fragment.intensity * 1.2
+  dark_color.lerp(&bright_color, cells) * (FALLBACK_LIGHT.diffuse(&fragment.world_position, &fragment.normal) * 1.2)
 }
 
-fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Colores más simples y definidos
-    let ocean_color = Color::new(25, 80, 180);     // Azul más profundo para océanos
-    let land_color = Color::new(50, 160, 80);      // Verde más vivo para continentes
-    
-    let position = Vec3::new(
-        fragment.vertex_position.x,
-        fragment.vertex_position.y,
-        fragment.depth
-    );
+// Gránulos de convección por radio de la estrella
+const GRANULE_SCALE: f32 = 11.0;
+// Ancho de los canales oscuros entre gránulos, en unidades de celda
+const GRANULE_LANE_WIDTH: f32 = 0.15;
+// El flujo que arrastra los gránulos: escala del ruido, desplazamiento
+// máximo en celdas y velocidad en unidades de ruido por frame
+const GRANULE_FLOW_ZOOM: f32 = 20.0;
+const GRANULE_FLOW: f32 = 0.3;
+const GRANULE_FLOW_SPEED: f32 = 0.03;
 
-    // Un solo nivel de ruido para los continentes
-    let zoom = 250.0;  // Ajustado para continentes más grandes
-    let noise_value = uniforms.noise.get_noise_3d(
-        position.x * zoom,
-        position.y * zoom,
-        position.z * zoom
-    ).abs();  // Usar valor absoluto para evitar valores negativos
+// Semilla de 32 bits para los patrones que no usan `uniforms.noise`
+fn pattern_seed(uniforms: &Uniforms) -> u32 {
+  (uniforms.seed ^ (uniforms.seed >> 32)) as u32
+}
 
-    // Umbral más definido para la separación tierra/agua
-    let threshold = 0.5;
-    let transition_width = 0.1;
+// Granulación de la superficie estelar, en [0, 1]: centros de celda
+// brillantes y canales oscuros en los bordes. Las coordenadas se arrastran
+// con un ruido que se desplaza con el tiempo, así las celdas se deforman
+// y se mezclan despacio.
+fn granulation(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+  let cells = fragment.vertex_position * GRANULE_SCALE;
 
-    // Transición suave entre tierra y agua
-    let land_factor = if noise_value < (threshold - transition_width) {
-        0.0  // Océano
-    } else if noise_value > (threshold + transition_width) {
-        1.0  // Tierra
-    } else {
-        // Transición suave en los bordes
-        ((noise_value - (threshold - transition_width)) / (transition_width * 2.0))
-    };
+  let t = uniforms.time * GRANULE_FLOW_SPEED;
+  let flowing = cells * GRANULE_FLOW_ZOOM + Vec3::new(t, t * 0.7, -t * 0.5);
+  let drift = (domain_warp(&uniforms.noise, &flowing, 1.0) - flowing) * GRANULE_FLOW;
+
+  let granule = cellular(&(cells + drift), pattern_seed(uniforms));
+  let lanes = smoothstep(0.0, GRANULE_LANE_WIDTH, granule.f2 - granule.f1);
+  let center = 1.0 - granule.f1.min(1.0) * 0.5;
+  let variation = 0.8 + 0.2 * hash_unit(granule.cell, 3);
+
+  (lanes * center * variation).clamp(0.0, 1.0)
+}
+
+// Latitud (seno) donde empiezan los casquetes en los equinoccios, cuánto se
+// desplaza con la estación y el ancho del borde irregular
+const ICE_CAP_LATITUDE: f32 = 0.82;
+const ICE_CAP_SEASONAL_SWING: f32 = 0.1;
+const ICE_CAP_EDGE: f32 = 0.06;
+// Desde qué latitud (seno) amarillea la vegetación en invierno
+const WINTER_LAND_LATITUDE: (f32, f32) = (0.35, 0.75);
+
+// Altura del terreno que separa océano y tierra, y medio ancho de la costa
+// suave alrededor de ese umbral
+const EARTH_LAND_THRESHOLD: f32 = 0.5;
+const EARTH_COAST_WIDTH: f32 = 0.1;
+
+// Cuánto de tierra firme hay en el fragmento: 0 en el océano, 1 en los
+// continentes y un degradado en la costa. Es la única clasificación
+// tierra/agua de la Tierra; lo que deba distinguirlas (brillo especular del
+// agua, luces de ciudades) la toma de aquí en lugar de repetir el umbral.
+pub fn earth_land_factor(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    land_factor(terrain_height(&fragment.vertex_position, uniforms))
+}
+
+// `earth_land_factor` a partir de una altura ya calculada
+fn land_factor(height: f32) -> f32 {
+    let ocean_edge = EARTH_LAND_THRESHOLD - EARTH_COAST_WIDTH;
+    ((height - ocean_edge) / (EARTH_COAST_WIDTH * 2.0)).clamp(0.0, 1.0)
+}
+
+// Altura sobre la costa en [0, 1]: 0 en el umbral de la tierra, 1 en la
+// cima más alta que da el ruido. Elige la banda de `land_colors`.
+fn land_elevation(height: f32) -> f32 {
+    ((height - EARTH_LAND_THRESHOLD) / (1.0 - EARTH_LAND_THRESHOLD)).clamp(0.0, 1.0)
+}
+
+fn terrestrial_surface(fragment: &Fragment, uniforms: &Uniforms, params: &TerrestrialParams, light: f32) -> Color {
+    // Un solo nivel de ruido para los continentes, el mismo que eleva el relieve.
+    // Las bandas de altura y el borde de los casquetes también lo usan, así
+    // que se calcula una vez.
+    let noise_value = terrain_height(&fragment.vertex_position, uniforms);
+    let land_factor = land_factor(noise_value);
+
+    // Estación del hemisferio de este fragmento: 1 verano, -1 invierno.
+    // La Y del modelo es el eje de rotación.
+    let latitude = fragment.vertex_position.normalize().y;
+    let local_season = uniforms.season * latitude.signum();
+
+    // Llanuras, tierras altas y cumbres, con los valles algo oscurecidos; en
+    // invierno la tierra de latitudes altas vira a `winter_land_color`
+    let elevation = land_elevation(noise_value);
+    let land_color = params.land_color(elevation) * ao_factor(elevation);
+    let winter = (-local_season).max(0.0) * smoothstep(WINTER_LAND_LATITUDE.0, WINTER_LAND_LATITUDE.1, latitude.abs());
+    let land_color = land_color.lerp(&params.winter_land_color, winter);
 
     // Mezclar colores
-    let base_color = ocean_color.lerp(&land_color, land_factor);
+    let base_color = params.ocean_color.lerp(&land_color, land_factor);
+
+    // El casquete del hemisferio en invierno crece y el del verano se encoge;
+    // el ruido del terreno hace irregular su borde
+    let cap_start = ICE_CAP_LATITUDE + ICE_CAP_SEASONAL_SWING * local_season;
+    let cap_edge = latitude.abs() + (noise_value - 0.5) * ICE_CAP_EDGE;
+    let ice = smoothstep(cap_start - ICE_CAP_EDGE, cap_start, cap_edge);
+    let base_color = base_color.lerp(&params.ice_color, ice);
 
     // Efecto simple de atmósfera en los bordes
-    let atmosphere_color = Color::new(150, 200, 255);
     let normal_dot = fragment.normal.dot(&Vec3::new(0.0, 0.0, 1.0));
     let atmosphere_factor = (1.0 - normal_dot.abs()).powf(2.0);
     
-    let final_color = base_color.lerp(&atmosphere_color, atmosphere_factor * 0.4);
-    
-    final_color * fragment.intensity
+    let final_color = base_color.lerp(&params.atmosphere_tint, atmosphere_factor * 0.4);
+
+    // Sombras de las nubes, antes de que las nubes se compongan encima
+    let shadow = cloud_shadow(fragment, uniforms, params).clamp(0.0, 1.0);
+
+    final_color * ((1.0 - shadow) * light)
+}
+
+// Relieve del estilo terrestre de `PlanetType::Custom`, como el de Marte
+const CUSTOM_BUMP_STRENGTH: f32 = 0.03;
+// Por encima de este valor de ruido el terreno pasa a `accent` (cumbres)
+const CUSTOM_PEAK_START: f32 = 0.7;
+// Bandas del estilo gaseoso a lo largo de la latitud y cuánto las tuerce el ruido
+const CUSTOM_BAND_COUNT: f32 = 8.0;
+const CUSTOM_BAND_WARP: f32 = 1.5;
+// Las grietas de lava empiezan donde el ruido ridged pasa de este valor
+const LAVA_CRACK_THRESHOLD: f32 = 0.75;
+// Brillo de las grietas sobre el blanco del shader, igual de día que de noche
+const LAVA_GLOW: f32 = 1.4;
+
+// Shader genérico de `PlanetType::Custom`: el mismo ruido, con la frecuencia
+// y el fractal de `params`, pintado según su estilo
+pub fn custom_planet_shader(fragment: &Fragment, uniforms: &Uniforms, params: &PlanetParams) -> Color {
+    let position = fragment.vertex_position;
+    let accent = params.accent_color();
+    match params.style {
+        PlanetStyle::Terrestrial => {
+            let value = custom_noise(&position, uniforms, params);
+            let peaks = smoothstep(CUSTOM_PEAK_START, 1.0, value);
+            let color = params.primary.lerp(&params.secondary, value).lerp(&accent, peaks);
+            let height = |p: &Vec3| custom_noise(p, uniforms, params);
+            let normal = perturb_normal(&fragment.normal, fragment, uniforms, height, CUSTOM_BUMP_STRENGTH);
+            color * apply_lighting(&fragment.world_position, &normal, uniforms)
+        }
+        PlanetStyle::Gas => {
+            // El ruido se estira a lo largo del paralelo, como el viento
+            let stretched = Vec3::new(position.x, position.y * 4.0, position.z);
+            let turbulence = custom_noise(&stretched, uniforms, params) * 2.0 - 1.0;
+            let latitude = position.normalize().y;
+            let band = ((latitude + turbulence * CUSTOM_BAND_WARP / CUSTOM_BAND_COUNT) * CUSTOM_BAND_COUNT * std::f32::consts::PI).sin() * 0.5 + 0.5;
+            let storms = smoothstep(0.6, 1.0, turbulence.abs());
+            params.primary.lerp(&params.secondary, band).lerp(&accent, storms) * fragment_lighting(fragment, uniforms)
+        }
+        PlanetStyle::Lava => {
+            // La corteza recibe luz; las grietas brillan por sí mismas
+            let crust = params.primary.lerp(&params.secondary, custom_noise(&position, uniforms, params));
+            let cracks = ridged(&uniforms.noise, &(position * params.noise_frequency), uniforms.terrain_octaves, 2.0, 0.5);
+            let glow = smoothstep(LAVA_CRACK_THRESHOLD, 1.0, cracks);
+            (crust * (fragment_lighting(fragment, uniforms) * (1.0 - glow))).blend_add(&(accent * (glow * LAVA_GLOW)))
+        }
+    }
+}
+
+// Ruido de `params` en [0, 1], con `Uniforms::terrain_octaves` octavas
+fn custom_noise(position: &Vec3, uniforms: &Uniforms, params: &PlanetParams) -> f32 {
+    let p = position * params.noise_frequency;
+    match params.fractal {
+        NoiseFractal::None => noise_3d(uniforms, p.x, p.y, p.z) * 0.5 + 0.5,
+        NoiseFractal::FBm => fbm(&uniforms.noise, &p, uniforms.terrain_octaves, 2.0, 0.5) * 0.5 + 0.5,
+        NoiseFractal::Ridged => ridged(&uniforms.noise, &p, uniforms.terrain_octaves, 2.0, 0.5),
+    }
+}
+
+// Oclusión ambiental barata para los planetas rocosos: los valles y el
+// fondo de las grietas reciben menos luz. `noise_value` es la altura que da
+// el mismo ruido que dibuja la superficie, de 0 (lo más hondo) a 1, así la
+// sombra cae sobre el terreno que se ve. Multiplica el color por 0.7..1.
+pub fn ao_factor(noise_value: f32) -> f32 {
+    AO_MIN + (1.0 - AO_MIN) * smoothstep(0.0, AO_FULL_HEIGHT, noise_value)
 }
 
 fn mercury_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
@@ -213,49 +652,64 @@ fn mercury_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let dark_color = Color::new(80, 75, 70);    // Gris oscuro
     let light_color = Color::new(170, 160, 150); // Gris claro
     let crater_color = Color::new(60, 55, 50);   // Gris más oscuro para cráteres
+    let rim_color = Color::new(205, 195, 185);   // Bordes levantados, más claros
     
     let position = fragment.vertex_position;
-    let zoom = 300.0;
     
     // Ruido base para el terreno
-    let terrain = uniforms.noise.get_noise_3d(
-        position.x * zoom,
-        position.y * zoom,
-        position.z * zoom
-    ).abs();
+    let terrain = fractal_noise(&position, 300.0, uniforms).abs();
     
-    // Ruido adicional para cráteres
-    let crater_zoom = 600.0;
-    let craters = uniforms.noise.get_noise_3d(
-        position.x * crater_zoom,
-        position.y * crater_zoom,
-        position.z * crater_zoom
-    ).abs();
+    // Cráteres fijos: fondo oscuro y borde claro
+    let crater = MERCURY_CRATERS.profile(&position);
     
-    let base_color = dark_color.lerp(&light_color, terrain);
-    let final_color = if craters > 0.7 {
-        base_color.lerp(&crater_color, 0.5)
-    } else {
-        base_color
-    };
+    let base_color = dark_color.lerp(&light_color, terrain) * ao_factor(terrain);
+    let final_color = base_color
+        .lerp(&crater_color, crater.floor * 0.6)
+        .lerp(&rim_color, crater.rim * 0.5);
+
+    // Los bordes de los cráteres y las crestas inclinan la normal
+    let height = |p: &Vec3| fractal_noise(p, 300.0, uniforms).abs() + CRATER_RELIEF * MERCURY_CRATERS.profile(p).height();
+    let normal = perturb_normal(&fragment.normal, fragment, uniforms, height, MERCURY_BUMP_STRENGTH);
+    let intensity = apply_lighting(&fragment.world_position, &normal, uniforms);
     
-    final_color * fragment.intensity
+    final_color * intensity
 }
 
+// Las nubes de Venus giran mucho más rápido que el planeta (super-rotación),
+// en el mismo sentido retrógrado. En rad/s.
+const VENUS_SUPER_ROTATION: f32 = -0.9;
+// Giro extra en el ecuador respecto a los polos, que curva las bandas en "V"
+const VENUS_SWIRL: f32 = 1.2;
+// Candidatos a relámpago por ventana de tiempo y radio angular de cada destello
+const MAX_FLASHES_PER_WINDOW: u32 = 4;
+const FLASH_RADIUS: f32 = 0.08;
+
 fn venus_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // Colores amarillentos y naranjas para la densa atmósfera
     let base_color = Color::new(230, 180, 50);    // Amarillo
     let cloud_color = Color::new(255, 198, 88);   // Naranja claro
     
     let position = fragment.vertex_position;
-    let t = uniforms.time as f32 * 0.05;  // Movimiento lento de nubes
-    
+    let seconds = uniforms.time / 60.0;
+
+    // Rotar el punto de muestreo alrededor del eje; más cerca del ecuador gira más
+    let latitude = position.y.clamp(-1.0, 1.0).asin();
+    let angle = seconds * VENUS_SUPER_ROTATION + latitude.cos() * VENUS_SWIRL;
+    let (sin, cos) = angle.sin_cos();
+    let swirl = Mat3::new(
+        cos, 0.0, sin,
+        0.0, 1.0, 0.0,
+        -sin, 0.0, cos,
+    );
+    let sample = swirl * position;
+
     // Patrones de nubes en movimiento
     let cloud_zoom = 150.0;
-    let clouds = uniforms.noise.get_noise_3d(
-        position.x * cloud_zoom + t,
-        position.y * cloud_zoom,
-        position.z * cloud_zoom
+    let clouds = noise_3d(
+        uniforms,
+        sample.x * cloud_zoom,
+        sample.y * cloud_zoom,
+        sample.z * cloud_zoom
     ).abs();
     
     let final_color = base_color.lerp(&cloud_color, clouds);
@@ -264,7 +718,50 @@ fn venus_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let atmosphere_factor = (1.0 - fragment.normal.dot(&Vec3::new(0.0, 0.0, 1.0))).powf(0.5);
     let atmosphere_color = Color::new(255, 220, 150);
     
-    final_color.lerp(&atmosphere_color, atmosphere_factor * 0.3) * fragment.intensity
+    let lit = final_color.lerp(&atmosphere_color, atmosphere_factor * 0.3) * fragment_lighting(fragment, uniforms);
+    lit.blend_add(&venus_lightning(fragment, uniforms))
+}
+
+// Destellos azulados breves en el lado nocturno. Las posiciones salen de un
+// hash de la ventana de tiempo actual, así cada frame es determinista.
+fn venus_lightning(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let settings = &uniforms.lightning;
+    if settings.frequency <= 0.0 || settings.duration_frames <= 0.0 {
+        return Color::black();
+    }
+
+    let direction = fragment.vertex_position.normalize();
+    let to_sun = sun_direction_model(&fragment.vertex_position, uniforms);
+    let night = smoothstep(0.0, -0.2, direction.dot(&to_sun));
+    if night <= 0.0 {
+        return Color::black();
+    }
+
+    let window = (uniforms.time / settings.duration_frames).floor();
+    // El destello se apaga a lo largo de su ventana
+    let fade = 1.0 - (uniforms.time / settings.duration_frames - window);
+    // Probabilidad de cada candidato para que salgan `frequency` destellos por segundo
+    let flashes_per_window = settings.frequency * settings.duration_frames / 60.0;
+    let chance = (flashes_per_window / MAX_FLASHES_PER_WINDOW as f32).min(1.0);
+
+    let mut strength: f32 = 0.0;
+    for k in 0..MAX_FLASHES_PER_WINDOW {
+        let seed = (window as i64 as u32).wrapping_mul(MAX_FLASHES_PER_WINDOW).wrapping_add(k);
+        if hash_unit(seed, 0) >= chance {
+            continue;
+        }
+
+        // Punto uniforme sobre la esfera
+        let z = hash_unit(seed, 1) * 2.0 - 1.0;
+        let phi = hash_unit(seed, 2) * 2.0 * std::f32::consts::PI;
+        let ring = (1.0 - z * z).sqrt();
+        let flash_direction = Vec3::new(ring * phi.cos(), z, ring * phi.sin());
+
+        let distance = (direction - flash_direction).norm();
+        strength = strength.max(1.0 - smoothstep(0.0, FLASH_RADIUS, distance));
+    }
+
+    Color::new(170, 200, 255) * (strength * fade * night * settings.brightness)
 }
 
 fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
@@ -272,163 +769,189 @@ fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let dark_red = Color::new(145, 50, 20);    // Rojo oscuro
     let light_red = Color::new(200, 80, 30);   // Rojo claro
     let dust_color = Color::new(230, 130, 50);  // Color polvo marciano
+    let canyon_color = Color::new(90, 30, 15);  // Fondo oscuro de los cañones
     
     let position = fragment.vertex_position;
-    let zoom = 250.0;
     
     // Terreno base
-    let terrain = uniforms.noise.get_noise_3d(
-        position.x * zoom,
-        position.y * zoom,
-        position.z * zoom
-    ).abs();
+    let terrain = fractal_noise(&position, 250.0, uniforms).abs();
     
     // Patrones de polvo
     let dust_zoom = 400.0;
-    let dust = uniforms.noise.get_noise_3d(
+    let dust = noise_3d(
+        uniforms,
         position.x * dust_zoom,
         position.y * dust_zoom,
         position.z * dust_zoom
     ).abs();
     
+    // Cañones: las crestas del ruido ridged se vuelven grietas oscuras
+    let canyons = ridged(&uniforms.noise, &(position * 150.0), 4, 2.0, 0.5);
+    let canyon_factor = ((canyons - 0.7) / 0.3).clamp(0.0, 1.0);
+    
     let base_color = dark_red.lerp(&light_red, terrain);
-    let final_color = base_color.lerp(&dust_color, dust * 0.3);
+    let dusty_color = base_color.lerp(&dust_color, dust * 0.3);
+    // Tan hondo como el terreno bajo o como el cañón, lo que más se hunda
+    let final_color = dusty_color.lerp(&canyon_color, canyon_factor * 0.6)
+        * ao_factor(terrain.min(1.0 - canyon_factor));
+
+    // Cráteres más escasos y erosionados que los de Mercurio
+    let crater = MARS_CRATERS.profile(&position);
+    let final_color = final_color
+        .lerp(&canyon_color, crater.floor * 0.45)
+        .lerp(&dust_color, crater.rim * 0.6);
+
+    // Los cañones y los cráteres se hunden respecto al terreno y sus paredes reciben luz de lado
+    let height = |p: &Vec3| {
+        fractal_noise(p, 250.0, uniforms).abs() - 0.5 * ridged(&uniforms.noise, &(p * 150.0), 4, 2.0, 0.5)
+            + CRATER_RELIEF * MARS_CRATERS.profile(p).height()
+    };
+    let normal = perturb_normal(&fragment.normal, fragment, uniforms, height, MARS_BUMP_STRENGTH);
+    let intensity = apply_lighting(&fragment.world_position, &normal, uniforms);
     
-    final_color * fragment.intensity
+    final_color * intensity
 }
 
-fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Colores para las bandas de Júpiter
-    let light_band = Color::new(255, 220, 180);  // Banda clara
-    let dark_band = Color::new(180, 140, 100);   // Banda oscura
-    let storm_color = Color::new(255, 160, 120); // Color para la Gran Mancha Roja
-    
+// Bandas, turbulencia y óvalos de tormenta de cualquier gigante gaseoso.
+// Júpiter, Saturno, Urano y Neptuno son sólo juegos de parámetros distintos.
+pub fn gas_giant_shader(fragment: &Fragment, uniforms: &Uniforms, params: &GasGiantParams) -> Color {
     let position = fragment.vertex_position;
-    let t = uniforms.time as f32 * 0.1;
-    
+    let t = uniforms.time * params.drift_speed;
+
     // Bandas horizontales
-    let band_zoom = 100.0;
-    let bands = uniforms.noise.get_noise_2d(
-        position.y * band_zoom,
-        t
-    ).abs();
-    
+    let bands = if params.band_count > 0.0 {
+        uniforms.noise.get_noise_2d(position.y * params.band_count * BAND_ZOOM_PER_COUNT, t).abs()
+    } else {
+        0.0
+    };
+
     // Turbulencia adicional
-    let turb_zoom = 300.0;
-    let turbulence = uniforms.noise.get_noise_3d(
-        position.x * turb_zoom + t,
-        position.y * turb_zoom,
-        position.z * turb_zoom
-    ).abs();
-    
-    let base_color = dark_band.lerp(&light_band, bands);
-    let final_color = base_color.lerp(&storm_color, turbulence * 0.3);
-    
-    final_color * fragment.intensity
+    let turbulence = if params.turbulence > 0.0 {
+        let zoom = params.turbulence_scale;
+        noise_3d(uniforms, position.x * zoom + t, position.y * zoom, position.z * zoom).abs()
+    } else {
+        0.0
+    };
+
+    let base_color = params.band_color(bands);
+    let storm_mix = turbulence * params.turbulence + bands * params.band_storm_mix;
+    let final_color = base_color.lerp(&params.storm_color, storm_mix);
+    let final_color = final_color.lerp(&params.storm_color, storm_ovals(&position, t, params));
+    let final_color = match &params.great_spot {
+        Some(spot) => final_color.lerp(&params.storm_color, great_spot(&position, uniforms.time / 60.0, spot)),
+        None => final_color,
+    };
+
+    final_color * fragment_lighting(fragment, uniforms)
 }
 
-fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Colores para Saturno y sus anillos
-    let planet_light = Color::new(255, 240, 200);  // Color claro del planeta
-    let planet_dark = Color::new(200, 180, 140);   // Color oscuro del planeta
-    let ring_light = Color::new(210, 190, 170);    // Color claro del anillo
-    let ring_dark = Color::new(160, 140, 120);     // Color oscuro del anillo
-    
-    let position = fragment.vertex_position;
-    let normal = fragment.normal;
-    
-    // Calcular distancia desde el centro
-    let radius = (position.x * position.x + position.z * position.z).sqrt();
-    let y_abs = position.y.abs();
-    
-    // Definir los parámetros del anillo
-    let ring_inner = 1.2;    // Donde comienza el anillo (justo fuera del planeta)
-    let ring_outer = 2.5;    // Donde termina el anillo
-    let ring_thickness = 0.1; // Grosor del anillo
-    
-    // Determinar si estamos en el anillo
-    let in_ring = radius >= ring_inner && 
-                  radius <= ring_outer && 
-                  y_abs <= ring_thickness;
-    
-    if in_ring {
-        // Patrón de anillos concéntricos
-        let ring_pattern = ((radius * 20.0).sin() * 0.5 + 0.5).abs();
-        
-        // Variación adicional en los anillos
-        let detail = uniforms.noise.get_noise_2d(
-            radius * 15.0,
-            position.z.atan2(position.x) * 5.0
-        ).abs();
-        
-        // Combinar patrones
-        let ring_factor = ring_pattern * 0.7 + detail * 0.3;
-        
-        // Color final del anillo
-        let ring_color = ring_light.lerp(&ring_dark, ring_factor);
-        
-        // Aplicar sombreado basado en la normal
-        let light_factor = normal.dot(&Vec3::new(0.0, 1.0, 0.0)).abs();
-        ring_color * fragment.intensity * light_factor.max(0.2)
-    } else {
-        // Color del planeta con bandas
-        let t = uniforms.time as f32 * 0.08;
-        let bands = uniforms.noise.get_noise_2d(
-            position.y * 120.0,
-            t
-        ).abs();
-        
-        planet_light.lerp(&planet_dark, bands) * fragment.intensity
+// Zoom del ruido de bandas por unidad de `band_count`
+const BAND_ZOOM_PER_COUNT: f32 = 10.0;
+// Rejilla de celdas (longitud x latitud) donde puede aparecer un óvalo
+const STORM_CELLS: (f32, f32) = (12.0, 6.0);
+// Radio del óvalo en fracción de celda, y su alto relativo al ancho
+const STORM_RADIUS: f32 = 0.4;
+const STORM_FLATTENING: f32 = 0.5;
+// Mezcla máxima con el color de tormenta en el centro del óvalo
+const STORM_OPACITY: f32 = 0.7;
+
+// Cuánto de tormenta hay en este punto: cada celda de la rejilla tiene un
+// óvalo con probabilidad `storm_probability`, que deriva en longitud con el tiempo
+fn storm_ovals(position: &Vec3, t: f32, params: &GasGiantParams) -> f32 {
+    if params.storm_probability <= 0.0 {
+        return 0.0;
+    }
+
+    let direction = position.normalize();
+    let longitude = (direction.z.atan2(direction.x) / (2.0 * std::f32::consts::PI) + 0.5 + t * 0.01).rem_euclid(1.0);
+    let latitude = direction.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI + 0.5;
+
+    let cell_x = longitude * STORM_CELLS.0;
+    let cell_y = latitude * STORM_CELLS.1;
+    let (column, row) = (cell_x.floor(), cell_y.floor());
+    let seed = params.storm_seed
+        .wrapping_add(column as u32)
+        .wrapping_add((row as u32).wrapping_mul(STORM_CELLS.0 as u32));
+    if hash_unit(seed, 0) >= params.storm_probability {
+        return 0.0;
     }
+
+    // Centro desplazado dentro de la celda para que no queden alineados
+    let center_x = column + 0.5 + (hash_unit(seed, 1) - 0.5) * 0.3;
+    let center_y = row + 0.5 + (hash_unit(seed, 2) - 0.5) * 0.3;
+    // Óvalo más ancho en longitud, como las tormentas que estiran los vientos
+    let distance = ((cell_x - center_x).powi(2) + ((cell_y - center_y) / STORM_FLATTENING).powi(2)).sqrt();
+    (1.0 - smoothstep(STORM_RADIUS * 0.3, STORM_RADIUS, distance)) * STORM_OPACITY
 }
 
-fn uranus_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Tonos azul-verdosos característicos
-    let base_color = Color::new(150, 210, 230);  // Azul verdoso claro
-    let cloud_color = Color::new(180, 230, 255); // Azul más claro
-    
+// Mezcla máxima con el color de tormenta en el centro de la mancha, y
+// fracción del óvalo desde la que empieza a desvanecerse
+const GREAT_SPOT_OPACITY: f32 = 0.95;
+const GREAT_SPOT_CORE: f32 = 0.55;
+
+// Cuánto de la mancha hay en `position` (espacio de modelo). La distancia
+// angular al centro se reparte en sus componentes este y norte, así el óvalo
+// conserva su forma a cualquier latitud en lugar de deformarse con la rejilla.
+fn great_spot(position: &Vec3, seconds: f32, spot: &GreatSpot) -> f32 {
+    let Some(direction) = position.try_normalize(f32::EPSILON) else {
+        return 0.0;
+    };
+    let latitude = spot.latitude.to_radians();
+    let longitude = (spot.longitude + spot.drift * seconds).to_radians();
+    // Misma convención de longitud que `storm_ovals`: atan2(z, x)
+    let center = Vec3::new(latitude.cos() * longitude.cos(), latitude.sin(), latitude.cos() * longitude.sin());
+    let east = Vec3::new(-longitude.sin(), 0.0, longitude.cos());
+    let north = east.cross(&center);
+
+    let angle = direction.dot(&center).clamp(-1.0, 1.0).acos();
+    let (x, y) = (direction.dot(&east), direction.dot(&north));
+    let along = (x * x + y * y).sqrt();
+    if along <= f32::EPSILON {
+        // En el centro, o justo en el lado opuesto
+        return if angle < 1.0 { GREAT_SPOT_OPACITY } else { 0.0 };
+    }
+    let east_angle = angle * x / along / spot.half_width.to_radians();
+    let north_angle = angle * y / along / spot.half_height.to_radians();
+    let distance = (east_angle * east_angle + north_angle * north_angle).sqrt();
+    (1.0 - smoothstep(GREAT_SPOT_CORE, 1.0, distance)) * GREAT_SPOT_OPACITY
+}
+
+// Gigante gaseoso con la franja curva que proyectan sus anillos, si tiene
+fn ringed_gas_giant_shader(fragment: &Fragment, uniforms: &Uniforms, params: &GasGiantParams) -> Color {
+    let color = gas_giant_shader(fragment, uniforms, params);
+    let Some(rings) = &uniforms.rings else {
+        return color;
+    };
     let position = fragment.vertex_position;
-    let t = uniforms.time as f32 * 0.03;
-    
-    // Patrones de nubes suaves
-    let cloud_zoom = 200.0;
-    let clouds = uniforms.noise.get_noise_3d(
-        position.x * cloud_zoom + t,
-        position.y * cloud_zoom,
-        position.z * cloud_zoom
-    ).abs();
-    
-    let final_color = base_color.lerp(&cloud_color, clouds * 0.4);
-    
-    final_color * fragment.intensity
+    let ring_shadow = ring_shadow_on_planet(&position, &sun_direction_model(&position, uniforms), rings);
+    color * (1.0 - ring_shadow)
 }
 
-fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Azul intenso característico
-    let base_color = Color::new(30, 100, 200);   // Azul profundo
-    let storm_color = Color::new(100, 160, 255); // Azul más claro para tormentas
-    
+// Color de la banda de los anillos en este radio, con un patrón concéntrico
+// fino encima. La opacidad de la banda la aplica `fragment_opacity`.
+fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let position = fragment.vertex_position;
-    let t = uniforms.time as f32 * 0.06;
-    
-    // Patrones de tormentas
-    let storm_zoom = 250.0;
-    let storms = uniforms.noise.get_noise_3d(
-        position.x * storm_zoom + t,
-        position.y * storm_zoom,
-        position.z * storm_zoom
-    ).abs();
-    
-    // Bandas sutiles
-    let band_zoom = 150.0;
-    let bands = uniforms.noise.get_noise_2d(
-        position.y * band_zoom,
-        t
+    let radius = ring_radius(&position);
+    let (band_color, _) = ring_system(uniforms).sample(radius);
+
+    // Patrón de anillos concéntricos
+    let ring_pattern = ((radius * 20.0).sin() * 0.5 + 0.5).abs();
+
+    // Variación adicional en los anillos
+    let detail = uniforms.noise.get_noise_2d(
+        radius * 15.0,
+        position.z.atan2(position.x) * 5.0
     ).abs();
-    
-    let final_color = base_color.lerp(&storm_color, (storms + bands * 0.5) * 0.4);
-    
-    final_color * fragment.intensity
+
+    // Combinar patrones: oscurecen la banda hasta un cuarto
+    let ring_factor = ring_pattern * 0.7 + detail * 0.3;
+    let ring_color = band_color * (1.0 - ring_factor * 0.25);
+
+    // El planeta deja una banda oscura del lado opuesto al Sol
+    let planet_shadow = planet_shadow_on_ring(&position, &sun_direction_model(&position, uniforms));
+
+    // Los anillos son planos: se ven por ambas caras aunque la luz llegue rasante
+    ring_color * (fragment_lighting(fragment, uniforms).max(0.35) * (1.0 - planet_shadow))
 }
 
 fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
@@ -441,7 +964,8 @@ fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let zoom = 400.0;
     
     // Ruido base para el terreno lunar
-    let terrain = uniforms.noise.get_noise_3d(
+    let terrain = noise_3d(
+        uniforms,
         position.x * zoom,
         position.y * zoom,
         position.z * zoom
@@ -449,7 +973,8 @@ fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     
     // Ruido adicional para cráteres
     let crater_zoom = 800.0;
-    let craters = uniforms.noise.get_noise_3d(
+    let craters = noise_3d(
+        uniforms,
         position.x * crater_zoom,
         position.y * crater_zoom,
         position.z * crater_zoom
@@ -462,60 +987,155 @@ fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         base_color
     };
     
-    final_color * fragment.intensity
+    final_color * fragment_lighting(fragment, uniforms)
+}
+
+// Volcanes de Ío: una celda de Worley de cada tanto tiene uno, y la mancha
+// se extiende hasta esta distancia de su centro (en celdas)
+const IO_VOLCANO_SCALE: f32 = 9.0;
+const IO_VOLCANO_THRESHOLD: f32 = 0.8;
+const IO_VOLCANO_RADIUS: f32 = 0.35;
+// Grietas de Europa: sólo lo más alto del ruido ridged, para que queden finas
+const EUROPA_CRACK_SCALE: f32 = 180.0;
+const EUROPA_CRACK_EDGES: (f32, f32) = (0.93, 0.99);
+
+// Ío: llanuras de azufre amarillo con manchas naranjas de volcanes, oscuras
+// en el centro
+fn io_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let sulfur = Color::new(225, 200, 70);
+    let pale_sulfur = Color::new(240, 225, 150);
+    let lava = Color::new(230, 110, 25);
+    let caldera = Color::new(70, 35, 20);
+
+    let position = fragment.vertex_position;
+    let plains = fractal_noise(&position, 250.0, uniforms).abs();
+    let base_color = sulfur.lerp(&pale_sulfur, plains);
+
+    let cells = cellular(&(position * IO_VOLCANO_SCALE), pattern_seed(uniforms) ^ 0x494F);
+    let volcano = if hash_unit(cells.cell, 3) > IO_VOLCANO_THRESHOLD {
+        1.0 - smoothstep(0.0, IO_VOLCANO_RADIUS, cells.f1)
+    } else {
+        0.0
+    };
+    let final_color = base_color
+        .lerp(&lava, smoothstep(0.0, 0.4, volcano))
+        .lerp(&caldera, smoothstep(0.6, 0.9, volcano));
+
+    final_color * fragment_lighting(fragment, uniforms)
 }
 
-fn black_hole_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+// Europa: hielo liso casi blanco cruzado por grietas largas y finas pardas
+fn europa_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let ice = Color::new(235, 235, 228);
+    let blue_ice = Color::new(205, 215, 225);
+    let crack_color = Color::new(150, 95, 60);
+
+    let position = fragment.vertex_position;
+    let tint = fractal_noise(&position, 120.0, uniforms) * 0.5 + 0.5;
+    let base_color = ice.lerp(&blue_ice, tint * 0.6);
+
+    let ridge = ridged(&uniforms.noise, &(position * EUROPA_CRACK_SCALE), 3, 2.0, 0.5);
+    let crack = smoothstep(EUROPA_CRACK_EDGES.0, EUROPA_CRACK_EDGES.1, ridge);
+
+    base_color.lerp(&crack_color, crack * 0.8) * fragment_lighting(fragment, uniforms)
+}
+
+// Ganímedes: terreno en dos tonos, zonas oscuras y viejas entre franjas
+// claras más jóvenes, con bordes nítidos
+fn ganymede_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let dark_terrain = Color::new(95, 85, 72);
+    let bright_terrain = Color::new(175, 165, 150);
+
+    let position = fragment.vertex_position;
+    let regions = fractal_noise(&position, 90.0, uniforms);
+    let detail = fractal_noise(&position, 400.0, uniforms).abs();
+    let bright = smoothstep(-0.05, 0.05, regions);
+    let base_color = dark_terrain.lerp(&bright_terrain, bright) * (0.9 + 0.1 * detail);
+
+    base_color * fragment_lighting(fragment, uniforms)
+}
+
+// Calisto: corteza oscura cubierta por igual de cráteres de bordes claros
+fn callisto_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let dark_color = Color::new(60, 55, 50);
+    let light_color = Color::new(100, 92, 82);
+    let floor_color = Color::new(45, 42, 38);
+    let rim_color = Color::new(185, 180, 170);
+
+    let position = fragment.vertex_position;
+    let terrain = fractal_noise(&position, 300.0, uniforms).abs();
+    let crater = CALLISTO_CRATERS.profile(&position);
+    let final_color = dark_color.lerp(&light_color, terrain)
+        .lerp(&floor_color, crater.floor * 0.5)
+        .lerp(&rim_color, crater.rim * 0.6);
+
+    final_color * fragment_lighting(fragment, uniforms)
+}
+
+// Horizonte de sucesos: negro puro. Todo el espectáculo está en el disco de
+// acreción y en la lente gravitacional (`lensing.rs`).
+fn black_hole_shader() -> Color {
+    Color::new(0, 0, 0)
+}
+
+// Disco de acreción: blanco junto al horizonte, naranja y luego rojo oscuro
+// hacia fuera, con grumos de gas que giran más rápido cuanto más cerca están
+fn accretion_disk_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let hot_color = Color::new(255, 248, 230);
+    let warm_color = Color::new(255, 150, 50);
+    let cool_color = Color::new(120, 30, 10);
+
     let position = fragment.vertex_position;
-    
-    // Colores psicodélicos
-    let core_color = Color::new(0, 0, 0);           // Centro negro
-    let inner_color = Color::new(255, 0, 255);      // Magenta brillante
-    let outer_color = Color::new(147, 0, 255);      // Morado
-    let space_color = Color::new(75, 0, 130);       // Índigo oscuro
-    
-    // Calcular distancia desde el centro
     let radius = (position.x * position.x + position.z * position.z).sqrt();
-    
-    // Tiempo para animación
-    let t = uniforms.time as f32 * 0.05;
-    
-    // Efecto de vórtice
-    let angle = position.z.atan2(position.x) + t;
-    let spiral = (angle * 5.0 + radius * 10.0 + t).sin() * 0.5 + 0.5;
-    
-    // Efecto de pulso
-    let pulse = (t * 2.0).sin() * 0.5 + 0.5;
-    
-    // Distorsión del espacio
-    let distortion = 1.0 / (radius + 0.5);
-    
-    // Patrones de ruido para más detalle
-    let noise = uniforms.noise.get_noise_3d(
-        position.x * 2.0 + t,
-        position.y * 2.0,
-        position.z * 2.0 - t
-    ).abs();
-    
-    // Combinar efectos
-    let effect = (spiral + noise + pulse) / 3.0;
-    
-    if radius < 0.3 {
-        // Centro del agujero negro (siempre negro)
-        core_color
-    } else if radius < 1.0 {
-        // Región interna psicodélica
-        let factor = ((radius - 0.3) / 0.7).powf(0.5);
-        let base = core_color.lerp(&inner_color, factor * effect);
-        base * (distortion * 0.5)
-    } else if radius < 2.0 {
-        // Región externa con vórtice
-        let factor = ((radius - 1.0) / 1.0).powf(0.5);
-        let base = inner_color.lerp(&outer_color, factor * effect);
-        base * ((3.0 - radius) * 0.5)
+    let t = ((radius - ACCRETION_DISK_INNER) / (ACCRETION_DISK_OUTER - ACCRETION_DISK_INNER)).clamp(0.0, 1.0);
+
+    // Rotación kepleriana: la velocidad angular cae como r^-1.5
+    let angle = position.z.atan2(position.x) + uniforms.time * ACCRETION_DISK_SPIN / radius.max(0.1).powf(1.5);
+    // Coordenadas cartesianas del punto ya girado, para que el ruido no tenga costura
+    let (sin, cos) = angle.sin_cos();
+    let clumps = noise_3d(uniforms, cos * radius * 4.0, sin * radius * 4.0, radius * 6.0) * 0.5 + 0.5;
+
+    let color = if t < 0.4 {
+        hot_color.lerp(&warm_color, t / 0.4)
     } else {
-        // Espacio exterior con distorsión
-        let fade = (1.0 / (radius - 1.5)).min(1.0);
-        outer_color.lerp(&space_color, fade) * (0.5 * fade)
+        warm_color.lerp(&cool_color, (t - 0.4) / 0.6)
+    };
+
+    // Más brillo en el borde interior y un desvanecido suave en el exterior
+    let brightness = (1.0 - t).powf(1.5) * 0.7 + clumps * 0.3;
+    let outer_fade = ((1.0 - t) / 0.2).min(1.0);
+    color * (brightness * outer_fade)
+}
+// Cuánto se concentra el brillo hacia el borde del disco del planeta, con
+// la densidad de la Tierra
+const ATMOSPHERE_FALLOFF: f32 = 4.0;
+const ATMOSPHERE_INTENSITY: f32 = 0.8;
+
+// Halo de la cáscara trasera, tipo Fresnel: transparente frente al centro del
+// disco y cada vez más opaco hacia el borde, donde el camino por la atmósfera
+// es más largo. La distancia al centro se saca del ángulo entre la normal y la
+// vista: crece hasta el borde del planeta y luego se apaga hasta el de la cáscara.
+pub fn atmosphere_shader(fragment: &Fragment, uniforms: &Uniforms, atmosphere: &AtmosphereParams) -> Color {
+    let to_eye = uniforms.camera_position - fragment.world_position;
+    if to_eye.norm() <= f32::EPSILON || atmosphere.density <= 0.0 {
+        return Color::new(0, 0, 0);
     }
-}
\ No newline at end of file
+    let cos_view = fragment.normal.dot(&to_eye.normalize()).abs().min(1.0);
+
+    // Distancia proyectada al centro, en radios del planeta
+    let scale = atmosphere.shell_scale();
+    let height = (1.0 - cos_view * cos_view).sqrt() * scale;
+    let glow = if height < 1.0 {
+        height.powf(ATMOSPHERE_FALLOFF / atmosphere.density)
+    } else {
+        let t = (height - 1.0) / (scale - 1.0).max(f32::EPSILON);
+        (1.0 - t).max(0.0).powi(2)
+    };
+
+    // Sólo brilla el lado de día, con un crepúsculo suave en el terminador
+    let p = fragment.vertex_position;
+    let to_sun = sun_direction_model(&p, uniforms);
+    let daylight = smoothstep(-0.2, 0.4, p.normalize().dot(&to_sun));
+
+    atmosphere.color * (glow * daylight * ATMOSPHERE_INTENSITY * atmosphere.density)
+}
@@ -0,0 +1,60 @@
+use nalgebra_glm::Vec3;
+use crate::CelestialBody;
+
+// Fracción de luz que conserva un fragmento en la umbra
+pub const ECLIPSE_LIGHT: f32 = 0.2;
+// Medio ancho de la penumbra alrededor del borde de la sombra, en radios del
+// cuerpo que la proyecta
+const PENUMBRA_WIDTH: f32 = 0.15;
+
+// Luz que le llega a `world_pos` desde el Sol con una esfera de por medio,
+// de `ECLIPSE_LIGHT` en el centro de la sombra a 1 fuera de ella. La sombra
+// es el cono que la esfera deja detrás de sí vista desde el centro del Sol,
+// con un borde suave: sólo oscurece lo que está más lejos del Sol que el
+// centro de la esfera, nunca lo que queda entre ambos.
+pub fn shadow_attenuation(world_pos: Vec3, sun_pos: Vec3, occluder_center: Vec3, occluder_radius: f32) -> f32 {
+    let to_sun = sun_pos - world_pos;
+    let distance = to_sun.norm();
+    if distance <= f32::EPSILON || occluder_radius <= 0.0 {
+        return 1.0;
+    }
+    let direction = to_sun / distance;
+
+    // Punto del rayo hacia el Sol más cercano al centro de la esfera
+    let to_center = occluder_center - world_pos;
+    let along = to_center.dot(&direction);
+    if along <= 0.0 || along >= distance {
+        return 1.0;
+    }
+    let miss = (to_center - direction * along).norm();
+
+    let lit = smoothstep(
+        occluder_radius * (1.0 - PENUMBRA_WIDTH),
+        occluder_radius * (1.0 + PENUMBRA_WIDTH),
+        miss,
+    );
+    ECLIPSE_LIGHT + (1.0 - ECLIPSE_LIGHT) * lit
+}
+
+// Luz del Sol que le llega a `world_pos` entre todos los demás cuerpos: la
+// de la sombra más oscura, sin acumularse donde dos se solapan. Es
+// aproximado (cada cuerpo cuenta como su esfera envolvente), pero basta para
+// ver eclipses, como la sombra de la luna sobre la Tierra. Las estrellas y
+// los cuerpos ocultos nunca tapan la luz.
+pub fn eclipse_light(world_pos: Vec3, sun_pos: Vec3, bodies: &[CelestialBody], self_index: usize) -> f32 {
+    bodies.iter()
+        .enumerate()
+        .filter(|(i, body)| *i != self_index && body.visible && !body.shader_type.is_star())
+        .map(|(_, body)| shadow_attenuation(world_pos, sun_pos, body.position, body.scale))
+        .fold(1.0, f32::min)
+}
+
+// Si algún otro cuerpo le quita luz a `world_pos`, aunque sea en la penumbra
+pub fn in_shadow(world_pos: Vec3, sun_pos: Vec3, bodies: &[CelestialBody], self_index: usize) -> bool {
+    eclipse_light(world_pos, sun_pos, bodies, self_index) < 1.0
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
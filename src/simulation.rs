@@ -0,0 +1,73 @@
+use std::mem;
+use nalgebra_glm::Vec3;
+use crate::CelestialBody;
+use crate::planet_type::PlanetType;
+
+// Pasos de simulación por segundo, independientes de los FPS
+pub const UPDATES_PER_SECOND: f32 = 60.0;
+pub const FIXED_DT: f32 = 1.0 / UPDATES_PER_SECOND;
+// Evita la "espiral de la muerte" si un frame tarda demasiado
+pub const MAX_FRAME_TIME: f32 = 0.25;
+
+const MOON_ORBIT_RADIUS: f32 = 2.0;
+const MOON_ORBIT_SPEED: f32 = 1.8;
+
+// Avanza el estado de todos los cuerpos hasta `sim_time` (en segundos),
+// guardando el estado anterior para poder interpolar al renderizar.
+pub fn update_simulation(bodies: &mut [CelestialBody], sim_time: f32) {
+    for body in bodies.iter_mut() {
+        body.previous_position = body.position;
+        body.previous_spin = body.spin;
+
+        body.spin = sim_time * body.rotation_speed;
+
+        if body.orbit_speed != 0.0 && body.shader_type.host().is_none() {
+            let angle = body.orbit_phase + sim_time * body.orbit_speed;
+            body.position = Vec3::new(
+                body.orbit_radius * angle.cos(),
+                0.0,
+                body.orbit_radius * angle.sin()
+            );
+        }
+    }
+
+    // Las lunas siguen a su planeta, ya movido; sin él, orbitan el origen
+    for index in 0..bodies.len() {
+        if bodies[index].shader_type.host().is_none() {
+            continue;
+        }
+        let host_position = host_index(bodies, index).map_or(Vec3::zeros(), |host| bodies[host].position);
+        let moon = &mut bodies[index];
+        let (radius, speed) = satellite_orbit(moon);
+        let angle = moon.orbit_phase + sim_time * speed;
+        moon.position = host_position + Vec3::new(radius * angle.cos(), 0.0, radius * angle.sin());
+    }
+}
+
+// Radio y velocidad de la órbita de una luna alrededor de su planeta. La
+// Luna conserva la suya de siempre; las demás usan los de su cuerpo.
+fn satellite_orbit(body: &CelestialBody) -> (f32, f32) {
+    match body.shader_type {
+        PlanetType::Moon => (MOON_ORBIT_RADIUS, MOON_ORBIT_SPEED),
+        _ => (body.orbit_radius, body.orbit_speed),
+    }
+}
+
+// Índice del planeta que orbita el cuerpo `index`, si es una luna y su
+// planeta está en la escena
+pub fn host_index(bodies: &[CelestialBody], index: usize) -> Option<usize> {
+    let host = bodies[index].shader_type.host()?;
+    bodies.iter().position(|body| mem::discriminant(&body.shader_type) == mem::discriminant(&host))
+}
+
+// Índices de las lunas que orbitan el cuerpo `index`
+pub fn satellites(bodies: &[CelestialBody], index: usize) -> Vec<usize> {
+    (0..bodies.len()).filter(|&moon| host_index(bodies, moon) == Some(index)).collect()
+}
+
+// Posición y giro de un cuerpo entre el paso anterior y el actual
+pub fn interpolated_state(body: &CelestialBody, alpha: f32) -> (Vec3, f32) {
+    let position = body.previous_position.lerp(&body.position, alpha);
+    let spin = body.previous_spin + (body.spin - body.previous_spin) * alpha;
+    (position, spin)
+}
@@ -0,0 +1,112 @@
+use std::path::Path;
+use nalgebra_glm::Vec3;
+use crate::background::view_basis;
+use crate::camera::Camera;
+use crate::error::Error;
+use crate::framebuffer::Framebuffer;
+use crate::obj::spherical_uv;
+use crate::viewport::Viewport;
+use crate::ProjectionSettings;
+
+// Factor por el que se multiplica la imagen: un cielo a pleno brillo le
+// quitaría protagonismo a los planetas
+pub const DEFAULT_SKYBOX_BRIGHTNESS: f32 = 0.5;
+
+// Imagen equirectangular del cielo, p. ej. una foto de una nebulosa: la
+// longitud recorre el ancho (con +Z en el centro) y la latitud el alto (+Y
+// arriba), como `obj::spherical_uv`. Se ve a distancia infinita.
+pub struct SkyboxTexture {
+    pub width: usize,
+    pub height: usize,
+    // Colores lineales fila a fila, como los del buffer HDR
+    pub pixels: Vec<Vec3>,
+    pub brightness: f32,
+}
+
+impl SkyboxTexture {
+    // Cualquier formato que lea `image`; los de 8 bits quedan entre 0 y 1
+    // como el color de fondo, y los HDR conservan su rango
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let image = image::open(path)
+            .map_err(|source| Error::Image { path: path.to_path_buf(), source })?
+            .to_rgb32f();
+        let pixels = image.pixels().map(|pixel| Vec3::new(pixel[0], pixel[1], pixel[2])).collect();
+        Ok(SkyboxTexture::from_pixels(image.width() as usize, image.height() as usize, pixels))
+    }
+
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Vec3>) -> Self {
+        assert_eq!(pixels.len(), width * height, "el cielo de {}x{} necesita {} píxeles", width, height, width * height);
+        SkyboxTexture { width, height, pixels, brightness: DEFAULT_SKYBOX_BRIGHTNESS }
+    }
+
+    // Color en `direction`, ya con el brillo. Bilineal; en horizontal la
+    // imagen se cierra sobre sí misma y en vertical se detiene en los polos.
+    pub fn sample(&self, direction: &Vec3) -> Vec3 {
+        if self.width == 0 || self.height == 0 {
+            return Vec3::zeros();
+        }
+        let uv = spherical_uv(direction);
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = (uv.y * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        let column = |offset: isize| (x0 as isize + offset).rem_euclid(self.width as isize) as usize;
+        let row = |offset: usize| (y0 as usize + offset).min(self.height - 1) * self.width;
+        let (left, right) = (column(0), column(1));
+        let (top, bottom) = (row(0), row(1));
+        let upper = self.pixels[top + left].lerp(&self.pixels[top + right], tx);
+        let lower = self.pixels[bottom + left].lerp(&self.pixels[bottom + right], tx);
+        upper.lerp(&lower, ty) * self.brightness
+    }
+}
+
+// Pinta el cielo en los píxeles donde no se dibujó nada. Va después de
+// `Scene::render`, porque lee la profundidad, y en lugar del fondo de
+// `Background`. Como el fondo, gira con la cámara pero no se desplaza al
+// moverla.
+pub fn render_skybox(framebuffer: &mut Framebuffer, camera: &Camera, projection: &ProjectionSettings, texture: &SkyboxTexture) {
+    let viewport = Viewport::full(framebuffer.width, framebuffer.height);
+    render_skybox_viewport(framebuffer, &viewport, camera, projection, texture);
+}
+
+// Como `render_skybox`, pero sólo dentro de `viewport` y con su aspecto.
+// Cada píxel sin profundidad escrita toma el cielo en la dirección de su
+// rayo. Los que sólo tienen brillo sumado (halos, atmósferas) lo tienen
+// encima del color de fondo, así que el cielo se suma en lugar de ese color.
+// Como las superficies, respeta el recorte y las filas de una pasada del
+// render progresivo.
+pub fn render_skybox_viewport(
+    framebuffer: &mut Framebuffer,
+    viewport: &Viewport,
+    camera: &Camera,
+    projection: &ProjectionSettings,
+    texture: &SkyboxTexture,
+) {
+    let (width, height) = (viewport.width, viewport.height);
+    if width == 0 || height == 0 || viewport.x + width > framebuffer.width || viewport.y + height > framebuffer.height {
+        return;
+    }
+
+    let (forward, right, up) = view_basis(camera, projection, width, height);
+    let background = framebuffer.background_linear();
+    let clear_depth = framebuffer.clear_depth();
+    for y in 0..height {
+        let ndc_y = 1.0 - (y as f32 + 0.5) / height as f32 * 2.0;
+        for x in 0..width {
+            let index = (viewport.y + y) * framebuffer.width + viewport.x + x;
+            let depth = framebuffer.zbuffer[index];
+            if framebuffer.is_surface(depth) || !framebuffer.writable(viewport.x + x, viewport.y + y) {
+                continue;
+            }
+            let ndc_x = (x as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+            let sky = texture.sample(&(forward + right * ndc_x + up * ndc_y));
+            if depth == clear_depth {
+                framebuffer.hdr_buffer[index] = sky;
+            } else {
+                framebuffer.hdr_buffer[index] += sky - background;
+            }
+        }
+    }
+    framebuffer.mark_background_drawn();
+}
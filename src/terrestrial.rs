@@ -0,0 +1,81 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::color::Color;
+use crate::planet_type::PlanetType;
+
+// Medio ancho del degradado entre dos bandas de altura, en fracción de la
+// altura sobre la costa
+const LAND_BAND_EDGE: f32 = 0.04;
+
+// Parámetros del shader de los planetas rocosos con océanos. La Tierra es el
+// juego por defecto: en un archivo de escena los campos que falten toman su valor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerrestrialParams {
+    pub ocean_color: Color,
+    // Colores de la tierra por altura, de la costa a las cumbres (de 2 a 4:
+    // llanuras, tierras altas, cumbres). Con uno solo no hay bandas.
+    pub land_colors: Vec<Color>,
+    // Color al que vira la tierra de latitudes altas en invierno
+    pub winter_land_color: Color,
+    pub ice_color: Color,
+    // Fracción del cielo cubierta de nubes, de 0 a 1
+    pub cloud_coverage: f32,
+    pub cloud_color: Color,
+    // Color al que vira el borde del disco en la superficie; el del halo va
+    // en `CelestialBody::atmosphere`
+    pub atmosphere_tint: Color,
+}
+
+impl Default for TerrestrialParams {
+    fn default() -> Self {
+        TerrestrialParams::earth()
+    }
+}
+
+pub static EARTH: Lazy<TerrestrialParams> = Lazy::new(TerrestrialParams::earth);
+
+impl TerrestrialParams {
+    pub fn earth() -> Self {
+        TerrestrialParams {
+            ocean_color: Color::new(25, 80, 180),
+            land_colors: vec![Color::new(50, 160, 80), Color::new(105, 125, 60), Color::new(150, 135, 115)],
+            winter_land_color: Color::new(130, 105, 60),
+            ice_color: Color::new(235, 240, 250),
+            cloud_coverage: 0.45,
+            cloud_color: Color::new(255, 255, 255),
+            atmosphere_tint: Color::new(150, 200, 255),
+        }
+    }
+
+    // Parámetros por defecto de cada tipo; None si no es un planeta terrestre
+    pub fn preset(planet_type: &PlanetType) -> Option<&'static TerrestrialParams> {
+        match planet_type {
+            PlanetType::Earth | PlanetType::Terrestrial => Some(&EARTH),
+            _ => None,
+        }
+    }
+
+    // Color de la tierra a una altura en [0, 1] sobre la costa: bandas
+    // iguales de `land_colors`, con un degradado corto entre una y otra
+    pub fn land_color(&self, elevation: f32) -> Color {
+        match self.land_colors.len() {
+            0 => Color::black(),
+            1 => self.land_colors[0],
+            len => {
+                let scaled = elevation.clamp(0.0, 1.0) * len as f32;
+                // Límite entre bandas más cercano, sin contar los extremos
+                let boundary = scaled.round().clamp(1.0, (len - 1) as f32);
+                let edge = LAND_BAND_EDGE * len as f32;
+                let t = ((scaled - (boundary - edge)) / (2.0 * edge)).clamp(0.0, 1.0);
+                let below = boundary as usize - 1;
+                self.land_colors[below].lerp(&self.land_colors[below + 1], t)
+            }
+        }
+    }
+
+    // Umbral del ruido de nubes, en [-1, 1], que deja esta cobertura
+    pub fn cloud_threshold(&self) -> f32 {
+        1.0 - 2.0 * self.cloud_coverage.clamp(0.0, 1.0)
+    }
+}
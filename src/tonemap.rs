@@ -0,0 +1,182 @@
+use nalgebra_glm::Vec3;
+use serde::{Deserialize, Serialize};
+
+// Paso de exposición de las teclas `[` / `]`, en stops
+pub const EXPOSURE_STEP: f32 = 0.25;
+const MIN_EXPOSURE: f32 = 0.05;
+const MAX_EXPOSURE: f32 = 16.0;
+// Gris medio al que la exposición automática lleva la luminancia media
+const AUTO_EXPOSURE_KEY: f32 = 0.18;
+// Qué tan rápido se adapta la exposición automática (por segundo)
+const AUTO_EXPOSURE_RATE: f32 = 1.5;
+// Valor de canal donde `compress_highlights` empieza a curvarse; por debajo los colores no cambian
+pub const HIGHLIGHT_KNEE: f32 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ToneMapOperator {
+    Reinhard,
+    // Aproximación de Narkowicz a la curva fílmica ACES
+    Aces,
+    // Reinhard sólo por encima de `HIGHLIGHT_KNEE` (ver `compress_highlights`):
+    // los planetas quedan como se dibujaron y sólo se comprimen el Sol y la lava
+    Highlights,
+}
+
+// Tramado ordenado para la cuantización final a 8 bits. Sin él, los
+// degradados suaves y oscuros (bordes de atmósfera, gigantes de hielo) se
+// quedan en unas pocas bandas planas.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Dither {
+    // Redondeo simple
+    Off,
+    // Patrón de Bayer 8x8 fijo: cada píxel tiene el mismo umbral en todos los frames
+    #[default]
+    Ordered,
+    // El patrón se corre en cada frame; la textura se promedia con el tiempo
+    // a cambio de un leve parpadeo
+    Temporal,
+}
+
+impl Dither {
+    pub fn next(self) -> Self {
+        match self {
+            Dither::Off => Dither::Ordered,
+            Dither::Ordered => Dither::Temporal,
+            Dither::Temporal => Dither::Off,
+        }
+    }
+}
+
+// Orden de cada celda de la matriz de Bayer 8x8: los valores consecutivos
+// quedan lo más separados posible, así que cualquier nivel es un patrón parejo
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ToneMapSettings {
+    pub exposure: f32,
+    pub operator: ToneMapOperator,
+    pub auto_exposure: bool,
+    // Los estados guardados antes de que hubiera tramado usan el de por defecto
+    #[serde(default)]
+    pub dither: Dither,
+}
+
+impl Default for ToneMapSettings {
+    fn default() -> Self {
+        ToneMapSettings {
+            exposure: 1.0,
+            operator: ToneMapOperator::Aces,
+            auto_exposure: false,
+            dither: Dither::default(),
+        }
+    }
+}
+
+impl ToneMapSettings {
+    // Exposición y curva sobre un color lineal, empaquetado como 0xRRGGBB
+    pub fn apply(&self, color: &Vec3) -> u32 {
+        quantize(&self.map(color), 0.5)
+    }
+
+    // Como `apply` para el píxel (x, y), con el tramado de `dither`: cada
+    // canal se trunca tras sumarle el umbral de Bayer del píxel en lugar de
+    // un medio, así que nunca se aleja más de un escalón del redondeo simple.
+    // `frame` sólo importa con `Dither::Temporal`.
+    pub fn apply_dithered(&self, color: &Vec3, x: usize, y: usize, frame: u32) -> u32 {
+        self.quantize_dithered(&self.map(color), x, y, frame)
+    }
+
+    // Sólo el tramado y la cuantización de `apply_dithered`, para un color
+    // que ya pasó por la curva (ver `Framebuffer::tonemap`)
+    pub fn quantize_dithered(&self, color: &Vec3, x: usize, y: usize, frame: u32) -> u32 {
+        let threshold = match self.dither {
+            Dither::Off => 0.5,
+            Dither::Ordered => bayer_threshold(x, y),
+            Dither::Temporal => {
+                let shift = frame as usize;
+                bayer_threshold(x + shift * 3, y + shift * 5)
+            }
+        };
+        quantize(color, threshold)
+    }
+
+    // Exposición y curva, todavía como floats en [0, 1]
+    fn map(&self, color: &Vec3) -> Vec3 {
+        let exposed = color * self.exposure;
+        match self.operator {
+            ToneMapOperator::Reinhard => exposed.map(|c| c / (1.0 + c)),
+            ToneMapOperator::Aces => exposed.map(aces),
+            ToneMapOperator::Highlights => compress_highlights(&exposed),
+        }
+    }
+
+    // Multiplica la exposición por 2^stops
+    pub fn adjust_exposure(&mut self, stops: f32) {
+        self.exposure = (self.exposure * stops.exp2()).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+    }
+
+    pub fn cycle_operator(&mut self) {
+        self.operator = match self.operator {
+            ToneMapOperator::Aces => ToneMapOperator::Reinhard,
+            ToneMapOperator::Reinhard => ToneMapOperator::Highlights,
+            ToneMapOperator::Highlights => ToneMapOperator::Aces,
+        };
+    }
+
+    // Acerca la exposición, suavizada, a la que lleva `average_luminance` al gris medio
+    pub fn update_auto_exposure(&mut self, average_luminance: f32, dt: f32) {
+        if !self.auto_exposure || average_luminance <= 0.0 {
+            return;
+        }
+        let target = (AUTO_EXPOSURE_KEY / average_luminance).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+        let t = 1.0 - (-AUTO_EXPOSURE_RATE * dt).exp();
+        self.exposure += (target - self.exposure) * t;
+    }
+}
+
+// Umbral en (0, 1) para el píxel (x, y)
+fn bayer_threshold(x: usize, y: usize) -> f32 {
+    (BAYER_8X8[y % 8][x % 8] as f32 + 0.5) / 64.0
+}
+
+// Empaqueta un color en [0, 1] como 0xRRGGBB, redondeando cada canal hacia
+// arriba desde `threshold` (0.5 es el redondeo simple)
+fn quantize(color: &Vec3, threshold: f32) -> u32 {
+    let to_byte = |c: f32| ((c.clamp(0.0, 1.0) * 255.0 + threshold).floor() as u32).min(255);
+    (to_byte(color.x) << 16) | (to_byte(color.y) << 8) | to_byte(color.z)
+}
+
+// Reinhard sobre el canal más brillante por encima de `HIGHLIGHT_KNEE`, en
+// espacio lineal: el hombro empalma con la identidad con la misma pendiente y
+// tiende a 1, así que los colores LDR se conservan y cualquier valor HDR cae
+// en [0, 1]. Todo el color se escala por el mismo factor, de modo que un Sol
+// brillante conserva su tono y su degradado en lugar de recortarse a blanco.
+pub fn compress_highlights(color: &Vec3) -> Vec3 {
+    let color = color.map(|c| c.max(0.0));
+    let peak = color.max();
+    if peak <= HIGHLIGHT_KNEE {
+        return color;
+    }
+    let over = (peak - HIGHLIGHT_KNEE) / (1.0 - HIGHLIGHT_KNEE);
+    let compressed = HIGHLIGHT_KNEE + (1.0 - HIGHLIGHT_KNEE) * over / (1.0 + over);
+    color * (compressed / peak)
+}
+
+// Luminancia relativa con los pesos de Rec. 709
+pub fn luminance(color: &Vec3) -> f32 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+fn aces(x: f32) -> f32 {
+    let x = x.max(0.0);
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
@@ -0,0 +1,121 @@
+use std::f32::consts::PI;
+use nalgebra_glm::Vec3;
+use crate::bookmarks::interpolate_state;
+use crate::camera::CameraState;
+use crate::simulation::interpolated_state;
+use crate::{system_extent, CelestialBody};
+
+// Duraciones del recorrido por defecto, en segundos: de una vista general
+// al planeta, la pausa junto a él y de ahí a la siguiente vista general
+const APPROACH_SECONDS: f32 = 5.0;
+const PAUSE_SECONDS: f32 = 3.0;
+const DEPART_SECONDS: f32 = 4.0;
+// Altura de las vistas generales respecto a su distancia al centro
+const OVERVIEW_ELEVATION: f32 = 0.35;
+// Distancia de la cámara a cada planeta, en radios del planeta
+const CLOSE_UP_DISTANCE: f32 = 5.0;
+
+// Un punto del recorrido. Con `anchor` el ojo y el centro son relativos a la
+// posición actual de ese cuerpo, así la cámara alcanza a los planetas aunque orbiten.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TourKeyframe {
+    pub eye: Vec3,
+    pub center: Vec3,
+    // Segundos para ir de este punto al siguiente
+    pub duration: f32,
+    pub anchor: Option<usize>,
+}
+
+// Recorrido de cámara en bucle: tras el último punto vuelve al primero
+#[derive(Debug, Clone)]
+pub struct Tour {
+    keyframes: Vec<TourKeyframe>,
+    elapsed: f32,
+}
+
+impl Tour {
+    pub fn new(keyframes: Vec<TourKeyframe>) -> Self {
+        Tour { keyframes, elapsed: 0.0 }
+    }
+
+    // Rodea el sistema entero y se detiene junto a cada planeta, uno por vuelta parcial
+    pub fn around_system(bodies: &[CelestialBody]) -> Self {
+        let radius = system_extent(bodies).max(1.0);
+        let planets: Vec<usize> = (0..bodies.len())
+            .filter(|&index| !bodies[index].shader_type.is_star())
+            .collect();
+        let stops = planets.len().max(1);
+
+        let mut keyframes = Vec::new();
+        for stop in 0..stops {
+            let angle = stop as f32 / stops as f32 * 2.0 * PI;
+            keyframes.push(TourKeyframe {
+                eye: Vec3::new(angle.cos(), OVERVIEW_ELEVATION, angle.sin()) * radius,
+                center: Vec3::zeros(),
+                duration: APPROACH_SECONDS,
+                anchor: None,
+            });
+
+            if let Some(&index) = planets.get(stop) {
+                let offset = Vec3::new(angle.cos(), OVERVIEW_ELEVATION, angle.sin()).normalize()
+                    * bodies[index].scale * CLOSE_UP_DISTANCE;
+                let close_up = TourKeyframe {
+                    eye: offset,
+                    center: Vec3::zeros(),
+                    duration: DEPART_SECONDS,
+                    anchor: Some(index),
+                };
+                // Dos puntos iguales: la cámara se queda quieta durante la pausa
+                keyframes.push(TourKeyframe { duration: PAUSE_SECONDS, ..close_up });
+                keyframes.push(close_up);
+            }
+        }
+
+        Tour::new(keyframes)
+    }
+
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    // Avanza `dt` segundos y devuelve dónde debe estar la cámara. `alpha`
+    // interpola las posiciones de los cuerpos igual que al dibujarlos.
+    pub fn update(&mut self, dt: f32, bodies: &[CelestialBody], alpha: f32) -> Option<CameraState> {
+        let total: f32 = self.keyframes.iter().map(|keyframe| keyframe.duration.max(0.0)).sum();
+        if self.keyframes.is_empty() || total <= 0.0 {
+            return None;
+        }
+        self.elapsed = (self.elapsed + dt).rem_euclid(total);
+
+        let mut remaining = self.elapsed;
+        for (index, keyframe) in self.keyframes.iter().enumerate() {
+            let duration = keyframe.duration.max(0.0);
+            if remaining < duration {
+                let next = &self.keyframes[(index + 1) % self.keyframes.len()];
+                let t = remaining / duration;
+                // Arranca y frena suavemente en cada punto
+                let eased = t * t * (3.0 - 2.0 * t);
+                let from = resolve(keyframe, bodies, alpha);
+                let to = resolve(next, bodies, alpha);
+                return Some(interpolate_state(&from, &to, eased));
+            }
+            remaining -= duration;
+        }
+
+        Some(resolve(&self.keyframes[0], bodies, alpha))
+    }
+}
+
+// Posición absoluta de un punto del recorrido en este instante
+fn resolve(keyframe: &TourKeyframe, bodies: &[CelestialBody], alpha: f32) -> CameraState {
+    let origin = keyframe.anchor
+        .and_then(|index| bodies.get(index))
+        .map(|body| interpolated_state(body, alpha).0)
+        .unwrap_or_else(Vec3::zeros);
+
+    CameraState {
+        eye: origin + keyframe.eye,
+        center: origin + keyframe.center,
+        up: Vec3::new(0.0, 1.0, 0.0),
+    }
+}
@@ -1,60 +1,161 @@
-use nalgebra_glm::{Vec3, dot, Vec2};
+use nalgebra_glm::{Vec3, Vec2};
 use crate::fragment::Fragment;
-use crate::vertex::{self, Vertex};
+use crate::vertex::Vertex;
 use crate::color::Color;
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
-  let mut fragments = Vec::new();
+// Vertices are snapped to 1/256 of a pixel so edge functions are exact integers
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL: i64 = 1 << SUBPIXEL_BITS;
+// Beyond this many pixels from the origin the fixed-point products could
+// overflow; such triangles (vertices almost on the eye plane) are skipped
+const MAX_COORDINATE: f32 = (1 << 20) as f32;
+// Pixels whose center lies within this many pixels outside an edge get a
+// partial-coverage fragment (edge anti-aliasing)
+pub const AA_FRINGE: f32 = 0.5;
+// Most negative barycentric weight a fringe pixel may extrapolate to
+const MAX_EXTRAPOLATION: f32 = 1.0;
+
+// Appends the triangle's fragments to `fragments`, so callers can reuse one
+// buffer across triangles. `flat_normal` overrides the interpolated normal
+// for the whole triangle (flat shading). Only pixels inside a `width` x
+// `height` framebuffer are visited.
+pub fn triangle(
+  v1: &Vertex,
+  v2: &Vertex,
+  v3: &Vertex,
+  flat_normal: Option<Vec3>,
+  width: usize,
+  height: usize,
+  fragments: &mut Vec<Fragment>,
+) {
   let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
 
+  // Also rejects NaN coordinates
+  let in_range = |v: &Vec3| v.x.abs() < MAX_COORDINATE && v.y.abs() < MAX_COORDINATE;
+  if !(in_range(&a) && in_range(&b) && in_range(&c)) {
+    return;
+  }
+
+  // One extra pixel around the box for the anti-aliased fringe, clamped to
+  // the framebuffer: a triangle near the eye plane can span millions of
+  // pixels, almost all of them off screen
   let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+  let (min_x, min_y) = ((min_x - 1).max(0), (min_y - 1).max(0));
+  let max_x = (max_x + 1).min(width as i32 - 1);
+  let max_y = (max_y + 1).min(height as i32 - 1);
+  if min_x > max_x || min_y > max_y {
+    return;
+  }
 
-  let light_dir = Vec3::new(0.0, 0.0, 1.0);
+  let (fa, fb, fc) = (to_fixed(&a), to_fixed(&b), to_fixed(&c));
+  let triangle_area = edge_function(fa, fb, fc);
+  if triangle_area == 0 {
+    return;
+  }
 
-  let triangle_area = edge_function(&a, &b, &c);
+  // Flip the edge values of clockwise triangles so "inside" is always positive
+  let winding = triangle_area.signum();
+  let inv_area = 1.0 / (triangle_area * winding) as f32;
 
-  // Iterate over each pixel in the bounding box
-  for y in min_y..=max_y {
-    for x in min_x..=max_x {
-      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+  // Edge i is opposite vertex i, so its value is that vertex's barycentric weight
+  let edges = [(fb, fc), (fc, fa), (fa, fb)];
 
-      // Calculate barycentric coordinates
-      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
+  // Top-left fill rule: a pixel exactly on an edge belongs to the triangle
+  // only if that edge is a top or left edge, so triangles sharing the edge
+  // never both draw it nor both skip it. With integer edge values this is a
+  // -1 bias on the edges the triangle doesn't own.
+  let bias = edges.map(|(from, to)| if is_top_left(from, to, winding) { 0 } else { -1 });
 
-      // Check if the point is inside the triangle
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
+  // Converts an edge value into a signed distance in pixels from that edge
+  let to_pixels = edges.map(|(from, to)| {
+    let length = (((to.0 - from.0) as f64).powi(2) + ((to.1 - from.1) as f64).powi(2)).sqrt();
+    if length > 0.0 { (1.0 / (length * SUBPIXEL as f64)) as f32 } else { 0.0 }
+  });
 
-        // Interpolate normal
-        let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
-        let normal = normal.normalize();
+  // The edge functions are linear, so after evaluating them at the first
+  // pixel center they only need one addition per pixel and per row
+  let first_center = (min_x as i64 * SUBPIXEL + SUBPIXEL / 2, min_y as i64 * SUBPIXEL + SUBPIXEL / 2);
+  let step_x = edges.map(|(from, to)| (to.1 - from.1) * SUBPIXEL * winding);
+  let step_y = edges.map(|(from, to)| -(to.0 - from.0) * SUBPIXEL * winding);
+  let mut row = edges.map(|(from, to)| edge_function(from, to, first_center) * winding);
 
-        // Calculate lighting intensity
-        let intensity = dot(&normal, &light_dir).max(0.0);
+  for y in min_y..=max_y {
+    let mut e = row;
+    for x in min_x..=max_x {
+      let inside = e[0] + bias[0] >= 0 && e[1] + bias[1] >= 0 && e[2] + bias[2] >= 0;
+
+      // Outside pixels near an edge are partially covered: the closer the
+      // center to the edge, the more coverage (up to half a pixel on it)
+      let coverage = if inside {
+        1.0
+      } else {
+        let distance = (0..3)
+          .map(|i| e[i] as f32 * to_pixels[i])
+          .fold(f32::INFINITY, f32::min);
+        (AA_FRINGE + distance).clamp(0.0, AA_FRINGE)
+      };
+
+      if coverage > 0.0 {
+        // Calculate barycentric coordinates. Fringe pixels lie outside the
+        // triangle and extrapolate, which keeps neighbouring triangles
+        // seamless. On a sliver (an edge-on ring) half a pixel away can be
+        // many times the triangle's own size, far off the surface, so past
+        // that the weights are clamped back onto the triangle; the positive
+        // parts always add up to at least the area.
+        let (w1, w2, w3) = (e[0] as f32 * inv_area, e[1] as f32 * inv_area, e[2] as f32 * inv_area);
+        let (w1, w2, w3) = if w1.min(w2).min(w3) >= -MAX_EXTRAPOLATION {
+          (w1, w2, w3)
+        } else {
+          let clamped = e.map(|value| value.max(0) as f32);
+          let inv_sum = 1.0 / (clamped[0] + clamped[1] + clamped[2]);
+          (clamped[0] * inv_sum, clamped[1] * inv_sum, clamped[2] * inv_sum)
+        };
 
-        // Create a gray color (unchanged)
-        let color = Color::new(100, 100, 100); // Medium gray
+        // Interpolate normal
+        let normal = match flat_normal {
+          Some(normal) => normal,
+          None => (v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3).normalize(),
+        };
+
+        // Interpolate the vertex colors
+        let color = interpolate_color(&v1.color, &v2.color, &v3.color, w1, w2, w3);
 
         // Interpolate depth
         let depth = a.z * w1 + b.z * w2 + c.z * w3;
 
         // Positions of the original vertex
         let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
+        let world_position = v1.world_position * w1 + v2.world_position * w2 + v3.world_position * w3;
 
         fragments.push(Fragment::new(
             Vec2::new(x as f32, y as f32),
             color,
             depth,
             normal,
-            intensity,
             vertex_position,
+            world_position,
+            coverage,
         ));
       }
+
+      for (value, step) in e.iter_mut().zip(step_x) {
+        *value += step;
+      }
+    }
+
+    for (value, step) in row.iter_mut().zip(step_y) {
+      *value += step;
     }
   }
+}
 
-  fragments
+fn interpolate_color(c1: &Color, c2: &Color, c3: &Color, w1: f32, w2: f32, w3: f32) -> Color {
+    let channel = |a: u8, b: u8, c: u8| (a as f32 * w1 + b as f32 * w2 + c as f32 * w3) / 255.0;
+    Color::from_float(
+        channel(c1.r, c2.r, c3.r),
+        channel(c1.g, c2.g, c3.g),
+        channel(c1.b, c2.b, c3.b),
+    )
 }
 
 fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
@@ -66,16 +167,20 @@ fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i3
     (min_x, min_y, max_x, max_y)
 }
 
-fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> (f32, f32, f32) {
-    let w1 = edge_function(b, c, p) / area;
-    let w2 = edge_function(c, a, p) / area;
-    let w3 = edge_function(a, b, p) / area;
-
-    (w1, w2, w3)
+// In screen space (y down), an edge is "left" when the inside lies to its
+// right and "top" when it is horizontal with the inside below it
+fn is_top_left(from: (i64, i64), to: (i64, i64), winding: i64) -> bool {
+    let dx = (to.0 - from.0) * winding;
+    let dy = (to.1 - from.1) * winding;
+    dy > 0 || (dy == 0 && dx < 0)
 }
 
-fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
-    (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
+fn to_fixed(v: &Vec3) -> (i64, i64) {
+    ((v.x * SUBPIXEL as f32).round() as i64, (v.y * SUBPIXEL as f32).round() as i64)
 }
 
-
+// Exact in fixed point, so two triangles walking a shared edge in opposite
+// directions always get exactly opposite values
+fn edge_function(a: (i64, i64), b: (i64, i64), c: (i64, i64)) -> i64 {
+    (c.0 - a.0) * (b.1 - a.1) - (c.1 - a.1) * (b.0 - a.0)
+}
@@ -0,0 +1,126 @@
+use std::f32::consts::TAU;
+use std::fs;
+use std::path::{Path, PathBuf};
+use nalgebra_glm::{Vec3, Vec4};
+use crate::camera::Camera;
+use crate::error::Error;
+use crate::golden::GoldenImage;
+use crate::scene::Scene;
+use crate::simulation::interpolated_state;
+use crate::tonemap::ToneMapSettings;
+use crate::{create_model_matrix, CelestialBody};
+
+// 4 s de animación a 30 FPS
+pub const DEFAULT_TURNTABLE_FRAMES: usize = 120;
+// Tiempo de simulación entre dos frames, el de un GIF a 30 FPS
+pub const TURNTABLE_FRAME_TIME: f32 = 1.0 / 30.0;
+// Distancia de la cámara al centro, en radios del cuerpo (o de sus anillos):
+// con el campo de visión por defecto el cuerpo ocupa algo más de la mitad
+// del alto de la imagen
+const TURNTABLE_DISTANCE: f32 = 4.0;
+// Elevación de la cámara sobre el ecuador del cuerpo, en radianes
+const TURNTABLE_ELEVATION: f32 = 0.25;
+
+// Vuelta completa de la cámara alrededor de un cuerpo, en `frames` cuadros.
+// Ángulos y tiempos salen sólo del número de frame, así dos corridas con la
+// misma escena dan las mismas imágenes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Turntable {
+    // Índice del cuerpo en la escena
+    pub body: usize,
+    pub frames: usize,
+    // Segundo de simulación del primer frame
+    pub start_time: f32,
+}
+
+impl Turntable {
+    pub fn new(body: usize, frames: usize) -> Self {
+        Turntable { body, frames, start_time: 0.0 }
+    }
+
+    // Ángulo de la cámara en el frame `frame`. El último queda a un paso del
+    // primero, así la animación se repite sin un cuadro duplicado.
+    pub fn angle(&self, frame: usize) -> f32 {
+        TAU * (frame % self.frames.max(1)) as f32 / self.frames.max(1) as f32
+    }
+
+    // Segundo de simulación del frame: la animación propia del cuerpo (giro,
+    // nubes, tormentas) sigue avanzando mientras la cámara da la vuelta
+    pub fn time(&self, frame: usize) -> f32 {
+        self.start_time + frame as f32 * TURNTABLE_FRAME_TIME
+    }
+
+    // Cámara del frame `frame` para `body` tal como está en ese momento: en
+    // un círculo del plano ecuatorial del cuerpo (su eje inclinado es la
+    // vertical de la imagen), un poco por encima, mirando a su centro. Gira
+    // en sentido contrario al cuerpo, que así se ve girar en su sentido.
+    pub fn camera(&self, body: &CelestialBody, frame: usize) -> Camera {
+        let (position, _) = interpolated_state(body, 0.0);
+        // El marco del cuerpo sin su giro diario
+        let frame_matrix = create_model_matrix(Vec3::zeros(), 1.0, body.rotation + Vec3::new(0.0, 0.0, body.axial_tilt));
+        let axis = (frame_matrix * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz();
+        let front = (frame_matrix * Vec4::new(0.0, 0.0, 1.0, 0.0)).xyz();
+        let side = (frame_matrix * Vec4::new(-1.0, 0.0, 0.0, 0.0)).xyz();
+
+        let (sin_angle, cos_angle) = self.angle(frame).sin_cos();
+        let (sin_elevation, cos_elevation) = TURNTABLE_ELEVATION.sin_cos();
+        let direction = (front * cos_angle + side * sin_angle) * cos_elevation + axis * sin_elevation;
+        Camera::new(position + direction * turntable_distance(body), position, axis)
+    }
+}
+
+// Distancia de la cámara al centro de `body`, proporcional a su tamaño
+pub fn turntable_distance(body: &CelestialBody) -> f32 {
+    let extent = body.rings.as_ref().map_or(1.0, |rings| rings.outer.max(1.0));
+    body.scale * extent * TURNTABLE_DISTANCE
+}
+
+// `frame_0000.png`, `frame_0001.png`... para que el orden alfabético sea el
+// de la animación
+pub fn frame_file_name(frame: usize) -> String {
+    format!("frame_{:04}.png", frame)
+}
+
+// Índice del cuerpo que nombra `query`: su posición en la escena, o el
+// nombre de su tipo en español o en inglés (`Júpiter` o `Jupiter`), sin
+// distinguir mayúsculas. Con varios del mismo tipo, el primero.
+pub fn find_body(bodies: &[CelestialBody], query: &str) -> Option<usize> {
+    if let Ok(index) = query.parse::<usize>() {
+        return (index < bodies.len()).then_some(index);
+    }
+    let query = query.to_lowercase();
+    bodies.iter().position(|body| {
+        let variant = format!("{:?}", body.shader_type);
+        let variant = variant.split(|c: char| !c.is_alphanumeric()).next().unwrap_or_default();
+        body.shader_type.name().to_lowercase() == query || variant.to_lowercase() == query
+    })
+}
+
+// Dibuja la vuelta y guarda cada frame en `out_dir` (que se crea si no
+// existe) como `frame_NNNN.png`, de `width` x `height`, sin fondo, como F12.
+// Devuelve las rutas en orden. La escena queda en el tiempo del último frame.
+pub fn render_turntable(
+    scene: &mut Scene,
+    turntable: &Turntable,
+    width: usize,
+    height: usize,
+    tone_mapping: &ToneMapSettings,
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>, Error> {
+    fs::create_dir_all(out_dir).map_err(|source| Error::Write { path: out_dir.to_path_buf(), source })?;
+
+    let mut paths = Vec::with_capacity(turntable.frames);
+    for frame in 0..turntable.frames {
+        let time = turntable.time(frame);
+        // La cámara sigue al cuerpo a lo largo de su órbita
+        scene.set_sim_time(time);
+        let camera = turntable.camera(&scene.bodies[turntable.body], frame);
+        let mut framebuffer = scene.render_still(width, height, &camera, time);
+        framebuffer.resolve(tone_mapping);
+
+        let path = out_dir.join(frame_file_name(frame));
+        GoldenImage::save(&framebuffer, &path)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
@@ -5,21 +5,38 @@ use crate::color::Color;
 pub struct Vertex {
   pub position: Vec3,
   pub normal: Vec3,
+  pub tangent: Vec3,
   pub tex_coords: Vec2,
   pub color: Color,
+  // Screen space: x, y in pixels and z = depth after the viewport transform
   pub transformed_position: Vec3,
   pub transformed_normal: Vec3,
+  pub transformed_tangent: Vec3,
+  // World space: the (displaced) position after the model matrix
+  pub world_position: Vec3,
 }
 
 impl Vertex {
   pub fn new(position: Vec3, normal: Vec3, tex_coords: Vec2) -> Self {
+    let tangent = any_perpendicular(&normal);
     Vertex {
       position,
       normal,
+      tangent,
       tex_coords,
       color: Color::black(),
       transformed_position: position,
       transformed_normal: normal,
+      transformed_tangent: tangent,
+      world_position: position,
+    }
+  }
+
+  pub fn new_with_tangent(position: Vec3, normal: Vec3, tangent: Vec3, tex_coords: Vec2) -> Self {
+    Vertex {
+      tangent,
+      transformed_tangent: tangent,
+      ..Vertex::new(position, normal, tex_coords)
     }
   }
 
@@ -27,10 +44,13 @@ impl Vertex {
     Vertex {
       position,
       normal: Vec3::new(0.0, 0.0, 0.0),
+      tangent: Vec3::new(0.0, 0.0, 0.0),
       tex_coords: Vec2::new(0.0, 0.0),
       color,
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 0.0, 0.0),
+      transformed_tangent: Vec3::new(0.0, 0.0, 0.0),
+      world_position: position,
     }
   }
 
@@ -45,10 +65,28 @@ impl Default for Vertex {
     Vertex {
       position: Vec3::new(0.0, 0.0, 0.0),
       normal: Vec3::new(0.0, 1.0, 0.0),
+      tangent: Vec3::new(1.0, 0.0, 0.0),
       tex_coords: Vec2::new(0.0, 0.0),
       color: Color::black(),
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+      transformed_tangent: Vec3::new(1.0, 0.0, 0.0),
+      world_position: Vec3::new(0.0, 0.0, 0.0),
     }
   }
 }
+
+// Any unit vector perpendicular to `normal`, used when the UVs can't define a tangent
+pub fn any_perpendicular(normal: &Vec3) -> Vec3 {
+  let axis = if normal.x.abs() < 0.9 {
+    Vec3::new(1.0, 0.0, 0.0)
+  } else {
+    Vec3::new(0.0, 1.0, 0.0)
+  };
+  let tangent = axis - normal * normal.dot(&axis);
+  if tangent.norm() > f32::EPSILON {
+    tangent.normalize()
+  } else {
+    axis
+  }
+}
@@ -0,0 +1,129 @@
+use nalgebra_glm::{Mat4, Vec3};
+use crate::camera::Camera;
+use crate::{CelestialBody, ProjectionSettings, create_viewport_matrix, system_extent};
+
+// Columnas de píxeles entre las dos vistas de la pantalla dividida
+pub const DIVIDER_WIDTH: usize = 1;
+// La vista general mira desde arriba algo inclinada: el ojo se corre hacia +Z
+// esta fracción de su altura, así se ve el grosor de los planetas y anillos
+const OVERVIEW_TILT: f32 = 0.25;
+
+// Rectángulo de píxeles del framebuffer en el que se dibuja una vista
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Viewport {
+    pub fn full(width: usize, height: usize) -> Self {
+        Viewport { x: 0, y: 0, width, height }
+    }
+
+    // Mitad izquierda y derecha de un framebuffer de `width` x `height`, con
+    // `divider` columnas libres entre ellas. La derecha se queda la columna
+    // impar que sobre.
+    pub fn split(width: usize, height: usize, divider: usize) -> (Viewport, Viewport) {
+        let left_width = width.saturating_sub(divider) / 2;
+        let right_x = (left_width + divider).min(width);
+        (
+            Viewport { x: 0, y: 0, width: left_width, height },
+            Viewport { x: right_x, y: 0, width: width - right_x, height },
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    // Si el punto (en píxeles del framebuffer) cae dentro
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x as f32 && y >= self.y as f32
+            && x < (self.x + self.width) as f32 && y < (self.y + self.height) as f32
+    }
+
+    // Lleva NDC a los píxeles de este rectángulo
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::new_translation(&Vec3::new(self.x as f32, self.y as f32, 0.0))
+            * create_viewport_matrix(self.width as f32, self.height as f32)
+    }
+
+    // Proyección con el aspecto de este rectángulo
+    pub fn projection_matrix(&self, projection: &ProjectionSettings) -> Mat4 {
+        projection.matrix(self.width as f32, self.height as f32)
+    }
+}
+
+// Vista que recibe la entrada con la pantalla dividida
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActiveView {
+    Overview,
+    #[default]
+    CloseUp,
+}
+
+// Pantalla dividida: a la izquierda una vista general de todo el sistema,
+// con su propia cámara y proyección; a la derecha la cámara de la escena, la
+// que sigue al cuerpo seleccionado. Sólo la vista activa se mueve con los
+// controles.
+pub struct SplitScreen {
+    pub enabled: bool,
+    pub active: ActiveView,
+    pub overview_camera: Camera,
+    pub overview_projection: ProjectionSettings,
+}
+
+impl SplitScreen {
+    // `width` y `height` son los del framebuffer entero
+    pub fn new(bodies: &[CelestialBody], width: usize, height: usize) -> Self {
+        let mut split = SplitScreen {
+            enabled: false,
+            active: ActiveView::default(),
+            overview_camera: Camera::new(Vec3::zeros(), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0)),
+            overview_projection: ProjectionSettings::default(),
+        };
+        split.frame_system(bodies, width, height);
+        split
+    }
+
+    // Al activarla la vista general vuelve a encuadrar el sistema, que pudo
+    // cambiar con una recarga o con el tamaño de la ventana
+    pub fn toggle(&mut self, bodies: &[CelestialBody], width: usize, height: usize) {
+        self.enabled = !self.enabled;
+        if self.enabled {
+            self.frame_system(bodies, width, height);
+        }
+    }
+
+    // Pone la cámara general sobre el sistema, lo bastante alta para que
+    // `system_extent` quepa hacia cada lado en el lado más corto de su vista
+    fn frame_system(&mut self, bodies: &[CelestialBody], width: usize, height: usize) {
+        let (overview, _) = self.viewports(width, height);
+        let aspect = if overview.is_empty() { 1.0 } else { overview.width as f32 / overview.height as f32 };
+        let half_fov = (self.overview_projection.fov_degrees.to_radians() / 2.0).tan();
+        let altitude = system_extent(bodies) / (half_fov * aspect.min(1.0));
+        let eye = Vec3::new(0.0, altitude, altitude * OVERVIEW_TILT);
+        self.overview_camera = Camera::new(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+        // Para que el primer giro con las flechas parta de esta orientación
+        self.overview_camera.restore_state(&self.overview_camera.save_state());
+    }
+
+    pub fn toggle_active(&mut self) {
+        self.active = match self.active {
+            ActiveView::Overview => ActiveView::CloseUp,
+            ActiveView::CloseUp => ActiveView::Overview,
+        };
+    }
+
+    // Si la entrada va a la vista general
+    pub fn overview_active(&self) -> bool {
+        self.enabled && self.active == ActiveView::Overview
+    }
+
+    // Rectángulos de la vista general y del primer plano
+    pub fn viewports(&self, width: usize, height: usize) -> (Viewport, Viewport) {
+        Viewport::split(width, height, DIVIDER_WIDTH)
+    }
+}
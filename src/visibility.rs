@@ -0,0 +1,78 @@
+use crate::CelestialBody;
+use crate::planet_type::PlanetType;
+use crate::simulation::satellites;
+
+// Grupos de cuerpos que se pueden ver solos, en el orden en que los recorre U.
+// Sirven para depurar un shader sin dibujar todo el sistema.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BodyGroup {
+    #[default]
+    All,
+    PlanetsOnly,
+    StarsOnly,
+    NoGasGiants,
+}
+
+impl BodyGroup {
+    pub fn next(self) -> Self {
+        match self {
+            BodyGroup::All => BodyGroup::PlanetsOnly,
+            BodyGroup::PlanetsOnly => BodyGroup::StarsOnly,
+            BodyGroup::StarsOnly => BodyGroup::NoGasGiants,
+            BodyGroup::NoGasGiants => BodyGroup::All,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BodyGroup::All => "Todo",
+            BodyGroup::PlanetsOnly => "Sólo planetas",
+            BodyGroup::StarsOnly => "Sólo estrellas",
+            BodyGroup::NoGasGiants => "Sin gigantes gaseosos",
+        }
+    }
+
+    pub fn shows(self, shader_type: PlanetType) -> bool {
+        match self {
+            BodyGroup::All => true,
+            BodyGroup::PlanetsOnly => !shader_type.is_star(),
+            BodyGroup::StarsOnly => shader_type.is_star(),
+            BodyGroup::NoGasGiants => !shader_type.is_gas_giant(),
+        }
+    }
+
+    // Muestra los cuerpos del grupo y oculta el resto; lo que se haya
+    // ocultado a mano antes se pierde. Una luna sólo se ve si su planeta
+    // también es del grupo.
+    pub fn apply(self, bodies: &mut [CelestialBody]) {
+        for index in 0..bodies.len() {
+            let shader_type = bodies[index].shader_type;
+            let visible = self.shows(shader_type) && shader_type.host().is_none_or(|host| self.shows(host));
+            set_visible(bodies, index, visible);
+        }
+    }
+}
+
+// Muestra u oculta un cuerpo. Todo lo que se dibuja con él (anillos,
+// atmósfera, disco de acreción, protuberancias) lo sigue, y también sus lunas.
+pub fn set_visible(bodies: &mut [CelestialBody], index: usize, visible: bool) {
+    bodies[index].visible = visible;
+    for moon in satellites(bodies, index) {
+        bodies[moon].visible = visible;
+    }
+}
+
+// Deja visible sólo el cuerpo `index`, sin sus lunas; las estrellas ocultas
+// siguen iluminándolo
+pub fn isolate(bodies: &mut [CelestialBody], index: usize) {
+    for (other, body) in bodies.iter_mut().enumerate() {
+        body.visible = other == index;
+    }
+}
+
+// Invierte la visibilidad del cuerpo y devuelve la nueva
+pub fn toggle_visible(bodies: &mut [CelestialBody], index: usize) -> bool {
+    let visible = !bodies[index].visible;
+    set_visible(bodies, index, visible);
+    visible
+}
@@ -0,0 +1,114 @@
+// Cáscaras atmosféricas (`CelestialBody::atmosphere`): el halo sobresale del
+// borde del planeta contra el espacio, es transparente frente al centro del
+// disco y, como se suma sin escribir profundidad, nunca oscurece ni tapa la
+// superficie.
+//
+// cargo test --test atmosphere
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{create_uniforms, default_system, CelestialBody};
+use shaders_lab::atmosphere::AtmosphereParams;
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::planet_type::PlanetType;
+
+const SIZE: usize = 96;
+
+// La Tierra de frente, con el Sol a la izquierda fuera del cuadro: el borde
+// izquierdo del disco es el de día
+fn render(atmosphere: Option<AtmosphereParams>) -> Framebuffer {
+    let earth = Vec3::new(20.0, 0.0, 0.0);
+    let bodies = vec![
+        CelestialBody { shader_type: PlanetType::Sun, scale: 2.0, ..Default::default() },
+        CelestialBody {
+            position: earth,
+            shader_type: PlanetType::Earth,
+            rotation_speed: 0.0,
+            atmosphere,
+            ..Default::default()
+        },
+    ];
+    let camera = common::look_at(earth + Vec3::new(0.0, 0.0, 3.5), earth);
+    let mut scene = common::scene(bodies, &SphereMesh::Icosphere(3), camera, create_uniforms(SIZE, SIZE, 7));
+
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.clear();
+    scene.render(&mut framebuffer);
+    framebuffer
+}
+
+#[test]
+fn shell_glows_past_the_limb() {
+    let bare = render(None);
+    let shell = render(Some(AtmosphereParams::earth()));
+    let background = bare.background_linear();
+
+    // Píxeles de espacio vacío sin atmósfera que con ella quedan iluminados
+    let glowing = (0..bare.hdr_buffer.len())
+        .filter(|&index| !bare.is_surface(bare.zbuffer[index]) && bare.hdr_buffer[index] == background)
+        .filter(|&index| (shell.hdr_buffer[index] - background).max() > 0.02)
+        .count();
+    assert!(glowing > 20, "sólo {} píxeles de halo fuera del disco", glowing);
+
+    // Tirando a azul, el color de la atmósfera de la Tierra
+    let added: Vec3 = shell.hdr_buffer.iter().zip(&bare.hdr_buffer).map(|(with, without)| with - without).sum();
+    assert!(added.z > added.x, "{:?}", added);
+}
+
+#[test]
+fn shell_never_darkens_or_hides_the_planet() {
+    let bare = render(None);
+    let shell = render(Some(AtmosphereParams::venus()));
+
+    // Donde no hay superficie sólo queda la marca de brillo de `add_hdr`
+    for (index, (&with, &without)) in shell.zbuffer.iter().zip(&bare.zbuffer).enumerate() {
+        if shell.is_surface(with) || bare.is_surface(without) {
+            assert_eq!(with, without, "píxel {}: la cáscara escribió profundidad", index);
+        }
+    }
+    for (index, (with, without)) in shell.hdr_buffer.iter().zip(&bare.hdr_buffer).enumerate() {
+        assert!((with - without).min() >= -1e-6, "píxel {}: {:?} más oscuro que {:?}", index, with, without);
+    }
+
+    // Frente al centro del disco es casi transparente
+    let center = SIZE / 2 * SIZE + SIZE / 2;
+    let added = shell.hdr_buffer[center] - bare.hdr_buffer[center];
+    assert!(added.max() < 0.02, "{:?} en el centro", added);
+}
+
+#[test]
+fn denser_atmospheres_reach_further_into_the_disk() {
+    let bare = render(None);
+    let added = |atmosphere: AtmosphereParams| -> f32 {
+        let shell = render(Some(atmosphere));
+        (0..bare.hdr_buffer.len())
+            .filter(|&index| bare.is_surface(bare.zbuffer[index]))
+            .map(|index| (shell.hdr_buffer[index] - bare.hdr_buffer[index]).sum())
+            .sum()
+    };
+    let (mars, earth, venus) = (added(AtmosphereParams::mars()), added(AtmosphereParams::earth()), added(AtmosphereParams::venus()));
+    assert!(mars < earth && earth < venus, "Marte {}, Tierra {}, Venus {}", mars, earth, venus);
+}
+
+#[test]
+fn default_system_atmospheres() {
+    let bodies = default_system();
+    let atmosphere = |shader_type: PlanetType| {
+        bodies.iter().find(|body| body.shader_type == shader_type).and_then(|body| body.atmosphere.clone())
+    };
+
+    for shader_type in [PlanetType::Earth, PlanetType::Venus, PlanetType::Mars, PlanetType::Jupiter, PlanetType::Saturn, PlanetType::Uranus, PlanetType::Neptune] {
+        let params = atmosphere(shader_type).unwrap_or_else(|| panic!("{} sin atmósfera", shader_type.name()));
+        assert!((0.03..=0.05).contains(&params.thickness), "{}: {}", shader_type.name(), params.thickness);
+        assert_eq!(Some(params), AtmosphereParams::preset(&shader_type));
+    }
+    for shader_type in [PlanetType::Sun, PlanetType::Mercury, PlanetType::Moon, PlanetType::Io] {
+        assert!(atmosphere(shader_type).is_none(), "{} con atmósfera", shader_type.name());
+    }
+    // Las de los gigantes son una bruma más tenue que la de la Tierra
+    let earth = AtmosphereParams::earth().density;
+    assert!(atmosphere(PlanetType::Jupiter).is_some_and(|params| params.density < earth));
+}
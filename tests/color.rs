@@ -0,0 +1,55 @@
+// Aritmética de `Color`: todo pasa por la misma conversión que satura a
+// 0..=255, así que multiplicar, sumar o saturar un color brillante nunca da
+// la vuelta a oscuro.
+//
+// cargo test --test color
+
+use shaders_lab::color::Color;
+
+const COLORS: [Color; 5] = [
+    Color { r: 255, g: 80, b: 10 },
+    Color { r: 30, g: 200, b: 90 },
+    Color { r: 12, g: 34, b: 250 },
+    Color { r: 128, g: 128, b: 128 },
+    Color { r: 0, g: 0, b: 0 },
+];
+
+#[test]
+fn zero_saturation_is_the_gray_of_equal_luminance() {
+    for color in COLORS {
+        let gray = color.with_saturation(0.0);
+        assert!(gray.r == gray.g && gray.g == gray.b, "{}", gray);
+        assert_eq!(gray.r, (color.luminance() * 255.0).round() as u8, "{}", color);
+    }
+}
+
+#[test]
+fn unit_saturation_is_the_identity() {
+    for color in COLORS {
+        assert_eq!(color.with_saturation(1.0), color);
+    }
+}
+
+#[test]
+fn oversaturated_channels_clamp() {
+    // El rojo ya está al máximo y el azul casi en cero: se topan sin dar la vuelta
+    let vivid = Color::new(255, 80, 10).with_saturation(3.0);
+    assert_eq!((vivid.r, vivid.b), (255, 0));
+    assert!(vivid.g < 80);
+    // Una saturación negativa invierte el tono alrededor del gris, también acotada
+    let inverted = Color::new(255, 80, 10).with_saturation(-4.0);
+    assert_eq!((inverted.r, inverted.b), (0, 255));
+}
+
+#[test]
+fn bright_colors_cap_instead_of_wrapping() {
+    let bright = Color::new(250, 200, 100);
+    assert_eq!(bright * 1.2, Color::new(255, 240, 120));
+    assert_eq!(bright * 10.0, Color::new(255, 255, 255));
+    assert_eq!(bright * -1.0, Color::black());
+    // 200 + 100 daría 44 con aritmética que da la vuelta
+    assert_eq!(bright + Color::new(10, 100, 100), Color::new(255, 255, 200));
+
+    assert_eq!(Color::from_float(1.5, -0.2, f32::NAN), Color::new(255, 0, 0));
+    assert_eq!(bright.lerp(&Color::new(255, 255, 255), 2.0), Color::new(255, 255, 255));
+}
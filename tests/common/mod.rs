@@ -0,0 +1,29 @@
+// Lo que repiten las pruebas que dibujan una `Scene` sin ventana. Cada
+// archivo de tests/ es un crate aparte que declara `mod common;` y no todos
+// usan todo.
+#![allow(dead_code)]
+
+use nalgebra_glm::Vec3;
+
+use shaders_lab::{CelestialBody, Uniforms};
+use shaders_lab::assets::Assets;
+use shaders_lab::camera::Camera;
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::scene::{Scene, SceneMeshes, SPHERE_MODEL};
+
+// La esfera de assets/, la misma que usa el programa
+pub fn model_sphere() -> SphereMesh {
+    SphereMesh::Obj(Assets::locate(None).path(SPHERE_MODEL))
+}
+
+// Cámara en `eye` mirando a `center`, con +Y hacia arriba
+pub fn look_at(eye: Vec3, center: Vec3) -> Camera {
+    Camera::new(eye, center, Vec3::new(0.0, 1.0, 0.0))
+}
+
+// Escena lista para dibujar con `sphere` como malla de todos los cuerpos
+pub fn scene(bodies: Vec<CelestialBody>, sphere: &SphereMesh, camera: Camera, uniforms: Uniforms) -> Scene {
+    let meshes = SceneMeshes::with_sphere(&Assets::locate(None), sphere, &bodies)
+        .unwrap_or_else(|error| panic!("{}", error));
+    Scene::new(bodies, camera, uniforms, meshes)
+}
@@ -0,0 +1,133 @@
+// Consola de comandos (`console::Console`): cada línea se interpreta con
+// `parse_command` y cambia la escena; lo que no se entiende deja un error en
+// la consola sin tocar nada, y sólo se conservan las últimas líneas.
+//
+// cargo test --test console
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{create_uniforms, default_system};
+use shaders_lab::console::{parse_command, Command, CommandError, Console, ConsoleEffect, CONSOLE_SCROLLBACK};
+use shaders_lab::gas_giant::GasGiantParams;
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::scene::Scene;
+use shaders_lab::simulation::FIXED_DT;
+
+fn scene() -> Scene {
+    let bodies = default_system();
+    let camera = common::look_at(Vec3::new(0.0, 10.0, 40.0), Vec3::zeros());
+    common::scene(bodies, &SphereMesh::Icosphere(1), camera, create_uniforms(64, 64, 1))
+}
+
+fn index_of(scene: &Scene, shader_type: PlanetType) -> usize {
+    scene.bodies.iter().position(|body| body.shader_type == shader_type).expect("cuerpo del sistema por defecto")
+}
+
+fn run(console: &mut Console, scene: &mut Scene, line: &str) -> Option<ConsoleEffect> {
+    for c in line.chars() {
+        console.push_char(c);
+    }
+    console.submit(scene)
+}
+
+#[test]
+fn parses_every_command() {
+    assert_eq!(
+        parse_command("set earth.cloud_threshold 0.25"),
+        Ok(Command::Set { body: "earth".into(), param: "cloud_threshold".into(), value: 0.25 }),
+    );
+    assert_eq!(parse_command("  GOTO   marte "), Ok(Command::Goto("marte".into())));
+    assert_eq!(parse_command("timescale 4"), Ok(Command::TimeScale(4.0)));
+    assert_eq!(parse_command("seed 99"), Ok(Command::Seed(99)));
+    assert_eq!(parse_command("screenshot"), Ok(Command::Screenshot));
+    assert_eq!(parse_command("lod off"), Ok(Command::Lod(false)));
+
+    assert_eq!(parse_command("fly mars"), Err(CommandError::Unknown("fly".into())));
+    assert_eq!(parse_command("timescale fast"), Err(CommandError::NotANumber("fast".into())));
+    assert_eq!(parse_command("seed -1"), Err(CommandError::NotANumber("-1".into())));
+    assert!(matches!(parse_command("set earth 0.2"), Err(CommandError::Usage(_))));
+    assert!(matches!(parse_command("lod maybe"), Err(CommandError::Usage(_))));
+}
+
+#[test]
+fn commands_change_the_scene() {
+    let mut scene = scene();
+    let mut console = Console::default();
+    let earth = index_of(&scene, PlanetType::Earth);
+    let jupiter = index_of(&scene, PlanetType::Jupiter);
+
+    // El umbral de nubes se guarda como cobertura: 1 - 2 * cobertura
+    assert_eq!(run(&mut console, &mut scene, "set earth.cloud_threshold 0.25"), None);
+    let terrestrial = scene.bodies[earth].terrestrial.as_ref().expect("paleta de la Tierra");
+    assert!((terrestrial.cloud_threshold() - 0.25).abs() < 1e-6, "{}", terrestrial.cloud_threshold());
+
+    run(&mut console, &mut scene, "set jupiter.band_count 30");
+    let bands = scene.bodies[jupiter].gas_giant.as_ref().map(|params| params.band_count);
+    assert_eq!(bands, Some(30.0));
+    // El resto de la paleta sigue siendo la del tipo
+    let preset = GasGiantParams::preset(&PlanetType::Jupiter).expect("paleta de Júpiter");
+    assert_eq!(scene.bodies[jupiter].gas_giant.as_ref().map(|params| params.turbulence), Some(preset.turbulence));
+
+    run(&mut console, &mut scene, "set tierra.axial_tilt 90");
+    assert!((scene.bodies[earth].axial_tilt - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+    assert_eq!(run(&mut console, &mut scene, "goto tierra"), Some(ConsoleEffect::Select(earth)));
+    assert_eq!(run(&mut console, &mut scene, "seed 99"), Some(ConsoleEffect::Reseeded));
+    assert_eq!(scene.uniforms.seed, 99);
+    assert_eq!(run(&mut console, &mut scene, "screenshot"), Some(ConsoleEffect::Screenshot));
+
+    run(&mut console, &mut scene, "lod off");
+    assert!(!scene.context.shader_lod.enabled);
+    run(&mut console, &mut scene, "timescale 4");
+    assert_eq!(scene.time_scale, 4.0);
+    assert_eq!(console.line(), "");
+}
+
+#[test]
+fn time_scale_speeds_up_the_simulation() {
+    let mut normal = scene();
+    let mut fast = scene();
+    let mut paused = scene();
+    fast.time_scale = 4.0;
+    paused.time_scale = 0.0;
+    for scene in [&mut normal, &mut fast, &mut paused] {
+        scene.update(0.5);
+    }
+    // `render_time` va un paso por detrás del tiempo simulado
+    let elapsed = |scene: &Scene| scene.render_time() + FIXED_DT;
+    assert!((elapsed(&normal) - 0.5).abs() < 1e-3, "{}", elapsed(&normal));
+    assert!((elapsed(&fast) - 2.0).abs() < 1e-3, "{}", elapsed(&fast));
+    assert_eq!(elapsed(&paused), 0.0);
+}
+
+#[test]
+fn errors_leave_the_scene_untouched() {
+    let mut scene = scene();
+    let mut console = Console::default();
+    let mars = index_of(&scene, PlanetType::Mars);
+    let before = scene.bodies[mars].clone();
+
+    let attempts = [
+        "set marte.band_count 12",
+        "set pluto.scale 2",
+        "set marte.scale -1",
+        "set mercurio.atmosphere_density 2",
+        "timescale 1000",
+    ];
+    for line in attempts {
+        assert_eq!(run(&mut console, &mut scene, line), None, "{}", line);
+    }
+    assert_eq!(scene.bodies[mars].gas_giant, before.gas_giant);
+    assert_eq!(scene.bodies[mars].scale, before.scale);
+    assert_eq!(scene.time_scale, 1.0);
+
+    // Cada intento deja su línea y su error; sólo quedan las últimas
+    let lines: Vec<&str> = console.scrollback().collect();
+    assert_eq!(lines.len(), CONSOLE_SCROLLBACK);
+    assert_eq!(lines.last(), Some(&"error: timescale va de 0 a 50"));
+    assert!(lines.contains(&"> timescale 1000"));
+    assert!(!lines.iter().any(|line| line.contains("band_count")), "{:?}", lines);
+}
@@ -0,0 +1,73 @@
+// Sesgo de profundidad (`Uniforms::depth_bias`): de dos triángulos
+// coplanares, sin sesgo gana el que manda la convención de profundidad ante
+// un empate, y con sesgo gana siempre el sesgado, como los anillos sobre el
+// planeta.
+//
+// cargo test --test depth_bias
+
+use nalgebra_glm::{Vec2, Vec3};
+
+use shaders_lab::{create_uniforms, create_view_matrix, render, set_reversed_z, RenderContext};
+use shaders_lab::debug_view::{normal_color, RenderMode};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::rings::RING_DEPTH_BIAS;
+use shaders_lab::vertex::Vertex;
+
+const SIZE: usize = 48;
+
+// Las mismas posiciones para los dos; sólo la normal, que da el color, cambia
+fn triangle(normal: Vec3) -> Vec<Vertex> {
+    [Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0)]
+        .iter()
+        .map(|&position| Vertex::new(position, normal, Vec2::zeros()))
+        .collect()
+}
+
+fn first() -> Vec3 {
+    Vec3::new(1.0, 0.0, 1.0).normalize()
+}
+
+fn second() -> Vec3 {
+    Vec3::new(-1.0, 0.0, 1.0).normalize()
+}
+
+// Normal que queda en el centro tras dibujar el primero y luego el segundo,
+// cada uno con su sesgo
+fn winner(reversed_z: bool, first_bias: f32, second_bias: f32) -> Vec3 {
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    let mut uniforms = create_uniforms(SIZE, SIZE, 0);
+    set_reversed_z(&mut framebuffer, &mut uniforms, reversed_z);
+    uniforms.view_matrix = create_view_matrix(Vec3::new(0.0, 0.0, 3.0), Vec3::zeros(), Vec3::y());
+    let mut context = RenderContext::new();
+    context.render_mode = RenderMode::Normals;
+    framebuffer.clear();
+
+    for (normal, bias) in [(first(), first_bias), (second(), second_bias)] {
+        uniforms.depth_bias = bias;
+        render(&mut framebuffer, &mut context, &uniforms, &triangle(normal), &PlanetType::Mars, &[], 0);
+    }
+    let center = framebuffer.hdr_buffer[SIZE / 2 * SIZE + SIZE / 2];
+    if (center - normal_color(&first())).norm() < 1e-4 {
+        first()
+    } else if (center - normal_color(&second())).norm() < 1e-4 {
+        second()
+    } else {
+        panic!("el centro no es de ninguno de los dos: {:?}", center);
+    }
+}
+
+#[test]
+fn ties_follow_the_depth_convention() {
+    // La profundidad normal se queda con el primero; la invertida, con el último
+    assert_eq!(winner(false, 0.0, 0.0), first());
+    assert_eq!(winner(true, 0.0, 0.0), second());
+}
+
+#[test]
+fn biased_triangle_wins_in_either_convention() {
+    for reversed_z in [false, true] {
+        assert_eq!(winner(reversed_z, RING_DEPTH_BIAS, 0.0), first(), "reversed_z = {}", reversed_z);
+        assert_eq!(winner(reversed_z, 0.0, RING_DEPTH_BIAS), second(), "reversed_z = {}", reversed_z);
+    }
+}
@@ -0,0 +1,118 @@
+// Tramado de la cuantización final: un degradado HDR de poco contraste,
+// como el borde de una atmósfera, se resuelve con y sin tramado.
+//
+// cargo test --test dither
+
+use std::collections::HashSet;
+use nalgebra_glm::Vec3;
+
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::tonemap::{Dither, ToneMapSettings};
+
+const WIDTH: usize = 256;
+const HEIGHT: usize = 64;
+// Lado de los bloques que se promedian, como haría el ojo a cierta distancia
+const BLOCK: usize = 8;
+
+// Gris oscuro que sube muy poco de izquierda a derecha: sin tramado quedan
+// apenas unas bandas de 8 bits
+fn gradient_color(x: usize) -> Vec3 {
+    let t = x as f32 / (WIDTH - 1) as f32;
+    Vec3::repeat(0.02 * (1.0 + 0.3 * t))
+}
+
+fn gradient() -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            framebuffer.point_hdr(x, y, 0.5, gradient_color(x));
+        }
+    }
+    framebuffer
+}
+
+fn resolve(framebuffer: &mut Framebuffer, dither: Dither) -> Vec<u32> {
+    framebuffer.resolve(&ToneMapSettings { dither, ..Default::default() });
+    framebuffer.buffer.clone()
+}
+
+fn channels(pixel: u32) -> [u32; 3] {
+    [(pixel >> 16) & 0xFF, (pixel >> 8) & 0xFF, pixel & 0xFF]
+}
+
+fn unique_colors(pixels: &[u32]) -> usize {
+    pixels.iter().collect::<HashSet<_>>().len()
+}
+
+// Colores distintos entre los promedios de bloques de BLOCK x BLOCK. Se
+// guardan las sumas para no perder la precisión que aporta el tramado.
+fn unique_block_averages(pixels: &[u32]) -> usize {
+    let mut averages = HashSet::new();
+    for block_y in (0..HEIGHT).step_by(BLOCK) {
+        for block_x in (0..WIDTH).step_by(BLOCK) {
+            let mut sum = [0; 3];
+            for y in block_y..block_y + BLOCK {
+                for x in block_x..block_x + BLOCK {
+                    let pixel = channels(pixels[y * WIDTH + x]);
+                    for channel in 0..3 {
+                        sum[channel] += pixel[channel];
+                    }
+                }
+            }
+            averages.insert(sum);
+        }
+    }
+    averages.len()
+}
+
+#[test]
+fn ordered_dither_reduces_banding() {
+    let mut framebuffer = gradient();
+    let plain = resolve(&mut framebuffer, Dither::Off);
+    let dithered = resolve(&mut framebuffer, Dither::Ordered);
+
+    // Cada bloque abarca una franja distinta del degradado: con tramado casi
+    // todos deberían quedar distintos, sin él sólo hay tantos como bandas
+    let plain_blocks = unique_block_averages(&plain);
+    let dithered_blocks = unique_block_averages(&dithered);
+    println!(
+        "colores distintos: {} sin tramado, {} con tramado; en bloques de {}x{}: {} y {}",
+        unique_colors(&plain),
+        unique_colors(&dithered),
+        BLOCK,
+        BLOCK,
+        plain_blocks,
+        dithered_blocks,
+    );
+    assert!(
+        dithered_blocks >= plain_blocks * 3,
+        "el tramado debería dar muchos más niveles ({} frente a {})",
+        dithered_blocks,
+        plain_blocks,
+    );
+}
+
+#[test]
+fn dither_stays_within_one_step() {
+    let mut framebuffer = gradient();
+    let plain = resolve(&mut framebuffer, Dither::Off);
+    let dithered = resolve(&mut framebuffer, Dither::Ordered);
+
+    for (index, (&expected, &actual)) in plain.iter().zip(&dithered).enumerate() {
+        for (a, b) in channels(expected).into_iter().zip(channels(actual)) {
+            assert!(a.abs_diff(b) <= 1, "el píxel {} se alejó más de un escalón: {:06x} frente a {:06x}", index, expected, actual);
+        }
+    }
+}
+
+#[test]
+fn ordered_dither_is_stable_and_temporal_moves() {
+    let mut framebuffer = gradient();
+    let first = resolve(&mut framebuffer, Dither::Ordered);
+    let second = resolve(&mut framebuffer, Dither::Ordered);
+    assert_eq!(first, second, "el tramado ordenado no debe cambiar entre frames");
+
+    let first = resolve(&mut framebuffer, Dither::Temporal);
+    let second = resolve(&mut framebuffer, Dither::Temporal);
+    assert_ne!(first, second, "el tramado temporal debería desplazarse entre frames");
+}
@@ -0,0 +1,47 @@
+// Clasificación tierra/océano de la Tierra (`earth_land_factor`) con una
+// semilla fija: un punto de océano, uno de continente y uno de costa. Si
+// cambia el ruido del terreno o el umbral, esta prueba lo avisa antes de que
+// la superficie y los efectos que la usan se desalineen.
+//
+// cargo test --test earth_land
+
+use nalgebra_glm::{Vec2, Vec3};
+
+use shaders_lab::create_uniforms;
+use shaders_lab::color::Color;
+use shaders_lab::fragment::Fragment;
+use shaders_lab::shaders::earth_land_factor;
+
+const SEED: u64 = 42;
+// Margen para el redondeo de otra máquina en el valor de la costa
+const TOLERANCE: f32 = 1e-4;
+
+// Fragmento sobre la esfera unitaria a una latitud fija (y = 0.3 antes de
+// normalizar) y `longitude` radianes
+fn fragment_at(longitude: f32) -> Fragment {
+    let position = Vec3::new(longitude.cos(), 0.3, longitude.sin()).normalize();
+    Fragment::new(Vec2::zeros(), Color::black(), 0.0, position, position, position, 1.0)
+}
+
+fn land_factor(longitude: f32) -> f32 {
+    let uniforms = create_uniforms(1, 1, SEED);
+    earth_land_factor(&fragment_at(longitude), &uniforms)
+}
+
+#[test]
+fn ocean_land_and_coast_are_pinned() {
+    assert_eq!(land_factor(1.57), 0.0, "debería ser océano");
+    assert_eq!(land_factor(0.628), 1.0, "debería ser continente");
+
+    let coast = land_factor(0.0);
+    assert!((coast - 0.329_846).abs() < TOLERANCE, "la costa dio {}", coast);
+}
+
+#[test]
+fn land_factor_stays_in_unit_range() {
+    let uniforms = create_uniforms(1, 1, SEED);
+    for step in 0..360 {
+        let factor = earth_land_factor(&fragment_at((step as f32).to_radians()), &uniforms);
+        assert!((0.0..=1.0).contains(&factor), "{} grados dio {}", step, factor);
+    }
+}
@@ -0,0 +1,51 @@
+// Errores de carga (`error::Error`): un OBJ que no existe da `MissingAsset`
+// y uno mal formado `Obj`, los dos con la ruta del archivo, también cuando
+// los pide la escena.
+//
+// cargo test --test errors
+
+use std::fs;
+use std::path::PathBuf;
+
+use shaders_lab::CelestialBody;
+use shaders_lab::assets::Assets;
+use shaders_lab::error::Error;
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::obj::Obj;
+use shaders_lab::scene::SceneMeshes;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("shaders_lab_{}_{}.obj", std::process::id(), name))
+}
+
+#[test]
+fn missing_obj_is_a_missing_asset() {
+    let path = temp_path("missing");
+    for result in [Obj::load(&path), Obj::load_with_tobj(&path)] {
+        match result {
+            Err(Error::MissingAsset { path: reported }) => assert_eq!(reported, path),
+            Err(error) => panic!("otro error: {:?}", error),
+            Ok(_) => panic!("cargó un archivo que no existe"),
+        }
+    }
+
+    let bodies = vec![CelestialBody::default()];
+    let error = SceneMeshes::with_sphere(&Assets::locate(None), &SphereMesh::Obj(path.clone()), &bodies).err();
+    assert!(matches!(error, Some(Error::MissingAsset { path: ref reported }) if *reported == path), "{:?}", error);
+    // El mensaje dice dónde se buscó
+    assert!(error.unwrap().to_string().contains(&path.display().to_string()));
+}
+
+#[test]
+fn corrupt_obj_is_an_obj_error() {
+    let path = temp_path("corrupt");
+    fs::write(&path, "v 0 0 0\nv 1 0 0\nf 1 2 x\n").unwrap();
+    for result in [Obj::load(&path), Obj::load_with_tobj(&path)] {
+        match result {
+            Err(Error::Obj { path: reported, .. }) => assert_eq!(reported, path),
+            Err(error) => panic!("otro error: {:?}", error),
+            Ok(_) => panic!("cargó un OBJ mal formado"),
+        }
+    }
+    fs::remove_file(path).unwrap();
+}
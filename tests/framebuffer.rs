@@ -0,0 +1,65 @@
+// Borrado por región sucia (`Framebuffer::clear`): dibujar un frame, borrar y
+// dibujar otro deja los mismos planos que dibujar ese otro sobre un buffer
+// borrado entero, aunque el primero haya tocado píxeles que el segundo no.
+//
+// cargo test --test framebuffer
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{create_uniforms, CelestialBody};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::tonemap::{Dither, ToneMapSettings};
+
+const WIDTH: usize = 96;
+const HEIGHT: usize = 64;
+
+// Marte visto desde `eye`, dibujado y convertido a 8 bits
+fn draw(framebuffer: &mut Framebuffer, eye: Vec3) {
+    let bodies = vec![CelestialBody { shader_type: PlanetType::Mars, rotation_speed: 0.0, ..Default::default() }];
+    let mut uniforms = create_uniforms(WIDTH, HEIGHT, 7);
+    uniforms.fill_light = true;
+    let camera = common::look_at(eye, Vec3::new(eye.x, eye.y, 0.0) * 0.5);
+    let mut scene = common::scene(bodies, &SphereMesh::Icosphere(2), camera, uniforms);
+    scene.render(framebuffer);
+    framebuffer.resolve(&ToneMapSettings { dither: Dither::Off, ..Default::default() });
+}
+
+fn assert_same_planes(a: &Framebuffer, b: &Framebuffer) {
+    assert!(a.buffer == b.buffer, "colores distintos");
+    assert!(a.zbuffer == b.zbuffer, "profundidades distintas");
+    assert!(a.hdr_buffer == b.hdr_buffer, "colores lineales distintos");
+    assert!(a.velocity_buffer == b.velocity_buffer, "velocidades distintas");
+    assert!(a.overdraw_buffer == b.overdraw_buffer, "sobredibujo distinto");
+}
+
+#[test]
+fn dirty_clear_matches_a_full_clear() {
+    // Primero el planeta grande y corrido a un lado, luego chico y al otro
+    let (first, second) = (Vec3::new(1.2, 0.6, 3.0), Vec3::new(-2.0, -1.0, 9.0));
+
+    let mut reused = Framebuffer::new(WIDTH, HEIGHT);
+    reused.clear();
+    draw(&mut reused, first);
+    let first_frame = reused.buffer.clone();
+    reused.clear();
+    draw(&mut reused, second);
+
+    let mut fresh = Framebuffer::new(WIDTH, HEIGHT);
+    fresh.clear();
+    draw(&mut fresh, second);
+
+    assert!(first_frame != fresh.buffer, "los dos frames deberían diferir");
+    assert_same_planes(&reused, &fresh);
+
+    // Lo mismo con `mark_all_dirty`, que obliga a borrar todo el buffer
+    let mut full = Framebuffer::new(WIDTH, HEIGHT);
+    draw(&mut full, first);
+    full.mark_all_dirty();
+    full.clear();
+    draw(&mut full, second);
+    assert_same_planes(&full, &fresh);
+}
@@ -0,0 +1,97 @@
+// Prueba de regresión de los shaders: dibuja la Tierra sola con cámara y
+// tiempo fijos y compara el frame con una imagen de referencia.
+//
+// cargo test --test golden
+//
+// Tras un cambio intencional en los shaders, regenerar la referencia con
+// UPDATE_GOLDEN=1 cargo test --test golden y revisar el PNG antes de subirlo.
+
+use std::path::PathBuf;
+use nalgebra_glm::Vec3;
+
+use shaders_lab::{
+    CelestialBody,
+    RenderContext,
+    create_uniforms,
+    create_model_matrix,
+    create_view_matrix,
+    render,
+    season,
+};
+use shaders_lab::assets::Assets;
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::golden::GoldenImage;
+use shaders_lab::lighting::Light;
+use shaders_lab::obj::Obj;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::tonemap::ToneMapSettings;
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 120;
+const TIME: f32 = 12.5;
+// Diferencia por canal que se acepta en un píxel (redondeo de otra máquina)
+const TOLERANCE: u8 = 6;
+// Píxeles que pueden pasarse de la tolerancia, sobre todo en el borde antialiasado
+const MAX_DIFFERING_PIXELS: usize = WIDTH * HEIGHT / 500;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name)
+}
+
+fn render_earth() -> Framebuffer {
+    let vertices = Obj::load(Assets::locate(None).path("models/esfera.obj"))
+        .unwrap_or_else(|error| panic!("{}", error))
+        .get_vertex_array();
+    let earth = CelestialBody {
+        scale: 1.0,
+        rotation: Vec3::new(0.0, 1.2, 0.0),
+        shader_type: PlanetType::Earth,
+        axial_tilt: 23.4_f32.to_radians(),
+        ..Default::default()
+    };
+
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_background_color(0x000000);
+    let mut uniforms = create_uniforms(WIDTH, HEIGHT, 0);
+    let eye = Vec3::new(0.0, 0.5, 3.0);
+    uniforms.view_matrix = create_view_matrix(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    uniforms.camera_position = eye;
+    uniforms.time = TIME;
+    // Sol a un lado de la cámara, para que se vea el terminador
+    uniforms.sun_position = Vec3::new(-20.0, 5.0, 15.0);
+    uniforms.lights = vec![Light::point(uniforms.sun_position, Vec3::new(1.0, 1.0, 1.0), 1.0)];
+    uniforms.model_matrix = create_model_matrix(earth.position, earth.scale, earth.rotation);
+    uniforms.displacement = earth.displacement;
+    uniforms.season = season(&uniforms.model_matrix, earth.axial_tilt, &uniforms.sun_position);
+
+    let mut context = RenderContext::new();
+    let bodies = [earth];
+    render(&mut framebuffer, &mut context, &uniforms, &vertices, &PlanetType::Earth, &bodies, 0);
+    framebuffer.resolve(&ToneMapSettings::default());
+    framebuffer
+}
+
+#[test]
+fn earth_matches_golden() {
+    let framebuffer = render_earth();
+    let path = golden_path("earth.png");
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        GoldenImage::save(&framebuffer, &path).unwrap_or_else(|error| panic!("{}", error));
+        return;
+    }
+
+    let golden = GoldenImage::load(&path)
+        .unwrap_or_else(|error| panic!("{} (UPDATE_GOLDEN=1 la genera)", error));
+    let diff = golden.compare(&framebuffer, TOLERANCE);
+    assert!(diff.same_size, "la referencia mide {}x{} y el frame {}x{}", golden.width, golden.height, WIDTH, HEIGHT);
+    assert!(
+        diff.differing <= MAX_DIFFERING_PIXELS,
+        "{} píxeles difieren en más de {} (máximo {}, el primero en {:?}); checksum del frame {:016x}",
+        diff.differing,
+        TOLERANCE,
+        diff.max_difference,
+        diff.first_difference,
+        framebuffer.checksum(),
+    );
+}
@@ -0,0 +1,44 @@
+// Luminancia con los pesos de Rec. 709 (`Color::luminance` y
+// `tonemap::luminance`) y el modo monocromo
+// (`Framebuffer::to_grayscale_in_place`), que cambia cada píxel por el gris
+// de su misma luminancia.
+//
+// cargo test --test grayscale
+
+use nalgebra_glm::Vec3;
+
+use shaders_lab::color::Color;
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::tonemap::luminance;
+
+#[test]
+fn primaries_weigh_as_rec_709() {
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+    assert!(close(Color::new(255, 0, 0).luminance(), 0.2126));
+    assert!(close(Color::new(0, 255, 0).luminance(), 0.7152));
+    assert!(close(Color::new(0, 0, 255).luminance(), 0.0722));
+    assert!(close(Color::new(255, 255, 255).luminance(), 1.0));
+    assert_eq!(Color::black().luminance(), 0.0);
+    // Un gris tiene su propio valor como luminancia
+    assert!(close(Color::new(51, 51, 51).luminance(), 0.2));
+
+    // La versión lineal usa los mismos pesos
+    assert!(close(luminance(&Vec3::x()), 0.2126));
+    assert!(close(luminance(&Vec3::y()), 0.7152));
+    assert!(close(luminance(&Vec3::z()), 0.0722));
+    assert!(close(luminance(&Vec3::new(2.0, 1.0, 0.5)), 2.0 * 0.2126 + 0.7152 + 0.5 * 0.0722));
+}
+
+#[test]
+fn grayscale_keeps_each_pixel_luminance() {
+    let pixels = [0xFF0000, 0x00FF00, 0x0000FF, 0xFFFFFF, 0x000000, 0x808080, 0xFF8000];
+    let mut framebuffer = Framebuffer::new(pixels.len(), 1);
+    framebuffer.buffer.copy_from_slice(&pixels);
+    framebuffer.to_grayscale_in_place();
+
+    // round(255 * peso) para cada primario; blanco, negro y gris no cambian
+    let expected = [0x363636, 0xB6B6B6, 0x121212, 0xFFFFFF, 0x000000, 0x808080, 0x929292];
+    for (pixel, (got, want)) in pixels.iter().zip(framebuffer.buffer.iter().zip(expected)) {
+        assert_eq!(*got, want, "{:06X}: {:06X} en lugar de {:06X}", pixel, got, want);
+    }
+}
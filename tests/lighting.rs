@@ -0,0 +1,80 @@
+// Modelo de iluminación de `lighting`: luces puntuales con y sin
+// atenuación, direccionales y el brillo especular.
+//
+// cargo test --test lighting
+
+use nalgebra_glm::Vec3;
+
+use shaders_lab::{Uniforms, create_uniforms};
+use shaders_lab::lighting::{apply_lighting, specular, Attenuation, Light, LightKind};
+
+const WHITE: Vec3 = Vec3::new(1.0, 1.0, 1.0);
+const UP: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+
+fn uniforms(lights: Vec<Light>) -> Uniforms {
+    let mut uniforms = create_uniforms(64, 64, 0);
+    uniforms.lights = lights;
+    uniforms.ambient = 0.0;
+    uniforms
+}
+
+#[test]
+fn point_light_without_attenuation_is_lambert() {
+    let uniforms = uniforms(vec![Light::point(Vec3::new(0.0, 100.0, 0.0), WHITE, 1.0)]);
+    let slanted = Vec3::new(1.0, 1.0, 0.0).normalize();
+
+    assert!((apply_lighting(&Vec3::zeros(), &UP, &uniforms) - 1.0).abs() < 1e-6);
+    assert!((apply_lighting(&Vec3::zeros(), &slanted, &uniforms) - slanted.y).abs() < 1e-6);
+    assert_eq!(apply_lighting(&Vec3::zeros(), &-UP, &uniforms), 0.0);
+}
+
+#[test]
+fn attenuated_point_light_fades_with_distance() {
+    let attenuation = Attenuation { constant: 1.0, linear: 0.0, quadratic: 0.1 };
+    let light = Light {
+        kind: LightKind::Point { position: Vec3::new(0.0, 10.0, 0.0), attenuation },
+        color: WHITE,
+        intensity: 1.0,
+    };
+    let uniforms = uniforms(vec![light]);
+
+    let near = apply_lighting(&Vec3::new(0.0, 8.0, 0.0), &UP, &uniforms);
+    let far = apply_lighting(&Vec3::zeros(), &UP, &uniforms);
+    assert!((near - attenuation.factor(2.0)).abs() < 1e-6);
+    assert!((far - attenuation.factor(10.0)).abs() < 1e-6);
+    assert!(far < near, "lejos {} no es menos que cerca {}", far, near);
+}
+
+#[test]
+fn directional_light_ignores_position() {
+    let uniforms = uniforms(vec![Light::directional(Vec3::new(0.0, 2.0, 0.0), WHITE, 0.8)]);
+    for position in [Vec3::zeros(), Vec3::new(50.0, -30.0, 7.0), Vec3::new(0.0, 1e4, 0.0)] {
+        assert!((apply_lighting(&position, &UP, &uniforms) - 0.8).abs() < 1e-6, "{:?}", position);
+    }
+}
+
+#[test]
+fn ambient_and_lights_never_pass_one() {
+    let mut uniforms = uniforms(vec![
+        Light::point(Vec3::new(0.0, 10.0, 0.0), WHITE, 1.0),
+        Light::directional(UP, WHITE, 0.35),
+    ]);
+    uniforms.ambient = 0.03;
+    assert!(apply_lighting(&Vec3::zeros(), &UP, &uniforms) <= 1.0);
+    // De espaldas a todas sólo queda el ambiente
+    assert!((apply_lighting(&Vec3::zeros(), &-UP, &uniforms) - 0.03).abs() < 1e-6);
+}
+
+#[test]
+fn specular_peaks_at_the_mirror_direction() {
+    let mut uniforms = uniforms(vec![Light::directional(Vec3::new(1.0, 1.0, 0.0), WHITE, 1.0)]);
+    uniforms.camera_position = Vec3::new(-10.0, 10.0, 0.0);
+    let mirror = specular(&Vec3::zeros(), &UP, &uniforms, 32.0);
+
+    uniforms.camera_position = Vec3::new(-10.0, 3.0, 0.0);
+    let off_mirror = specular(&Vec3::zeros(), &UP, &uniforms, 32.0);
+
+    assert!((mirror - 1.0).abs() < 1e-4, "en el reflejo: {}", mirror);
+    assert!(off_mirror < mirror * 0.5, "fuera del reflejo: {}", off_mirror);
+    assert_eq!(specular(&Vec3::zeros(), &-UP, &uniforms, 32.0), 0.0);
+}
@@ -0,0 +1,84 @@
+// Líneas y círculos antialiasados (`Framebuffer::draw_line_aa` y
+// `draw_circle_aa`): las figuras que salen de la pantalla se recortan sin
+// entrar en pánico y lo que queda adentro se sigue dibujando.
+//
+// cargo test --test line_aa
+
+use nalgebra_glm::Vec2;
+
+use shaders_lab::framebuffer::Framebuffer;
+
+const WIDTH: usize = 40;
+const HEIGHT: usize = 30;
+const WHITE: u32 = 0xFFFFFF;
+
+fn framebuffer() -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.clear();
+    framebuffer
+}
+
+fn lit(framebuffer: &Framebuffer) -> usize {
+    framebuffer.buffer.iter().filter(|&&pixel| pixel != 0).count()
+}
+
+#[test]
+fn lines_partly_off_screen_are_clipped() {
+    let (w, h) = (WIDTH as f32, HEIGHT as f32);
+    let lines = [
+        // Cruzan la pantalla de lado a lado y más allá
+        (Vec2::new(-50.0, -20.0), Vec2::new(w + 50.0, h + 20.0)),
+        (Vec2::new(w / 2.0, -1e6), Vec2::new(w / 2.0, 1e6)),
+        // Justo sobre los bordes
+        (Vec2::new(0.0, 0.0), Vec2::new(w, 0.0)),
+        (Vec2::new(w - 0.5, -3.0), Vec2::new(w - 0.5, h + 3.0)),
+        (Vec2::new(-0.4, h - 0.2), Vec2::new(w + 0.4, h - 0.2)),
+        // Una punta adentro y la otra muy lejos
+        (Vec2::new(10.0, 10.0), Vec2::new(-1e7, 3e7)),
+    ];
+    for (from, to) in lines {
+        let mut framebuffer = framebuffer();
+        framebuffer.draw_line_aa(from, to, WHITE, None);
+        assert!(lit(&framebuffer) > 0, "{:?} -> {:?} no dibujó nada", from, to);
+        // Con profundidad pasa por la misma prueba que la geometría
+        framebuffer.draw_line_aa(to, from, WHITE, Some((0.5, 0.5)));
+    }
+}
+
+#[test]
+fn lines_fully_off_screen_draw_nothing() {
+    for (from, to) in [
+        (Vec2::new(-10.0, -10.0), Vec2::new(-2.0, 50.0)),
+        (Vec2::new(WIDTH as f32 + 2.0, 0.0), Vec2::new(WIDTH as f32 + 90.0, 10.0)),
+        (Vec2::new(-1e9, -1e9), Vec2::new(-1e9, -1e9)),
+    ] {
+        let mut framebuffer = framebuffer();
+        framebuffer.draw_line_aa(from, to, WHITE, None);
+        assert_eq!(lit(&framebuffer), 0, "{:?} -> {:?}", from, to);
+    }
+}
+
+#[test]
+fn circles_partly_off_screen_are_clipped() {
+    let circles = [
+        // Centro afuera, arco adentro
+        (-5.0, -5.0, 12.0, 1.0),
+        (WIDTH as f32 + 3.0, HEIGHT as f32 / 2.0, 8.0, 2.5),
+        // Más grande que la pantalla, con el borde cruzándola
+        (WIDTH as f32 / 2.0, HEIGHT as f32 + 100.0, 110.0, 1.0),
+        // En la esquina, más fino que un píxel
+        (0.0, 0.0, 6.0, 0.3),
+    ];
+    for (cx, cy, r, thickness) in circles {
+        let mut framebuffer = framebuffer();
+        framebuffer.draw_circle_aa(cx, cy, r, WHITE, thickness);
+        assert!(lit(&framebuffer) > 0, "({}, {}) r = {}", cx, cy, r);
+    }
+
+    // Del todo afuera, o tan grande que el borde no pasa por la pantalla
+    let mut framebuffer = framebuffer();
+    framebuffer.draw_circle_aa(-100.0, 10.0, 20.0, WHITE, 2.0);
+    framebuffer.draw_circle_aa(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0, 1e4, WHITE, 1.0);
+    framebuffer.draw_circle_aa(f32::MAX, -f32::MAX, 5.0, WHITE, 1.0);
+    assert_eq!(lit(&framebuffer), 0);
+}
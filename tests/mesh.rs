@@ -0,0 +1,104 @@
+// Icosfera de `mesh::icosphere`: en cada nivel de subdivisión tiene que ser
+// una superficie cerrada del género de la esfera, y las coordenadas de
+// textura no pueden cruzar la costura dentro de un triángulo.
+//
+// cargo test --test mesh
+
+use std::collections::HashMap;
+
+use shaders_lab::mesh::{icosphere, uv_sphere, UV_SPHERE_SECTORS, UV_SPHERE_STACKS};
+use shaders_lab::vertex::Vertex;
+
+const LEVELS: std::ops::RangeInclusive<u32> = 0..=4;
+
+// Triángulos con los vértices soldados por posición: los duplicados de la
+// costura y de los polos son el mismo punto de la superficie
+fn welded(vertices: &[Vertex], indices: &[u32]) -> (usize, Vec<[usize; 3]>) {
+    let mut points: HashMap<[u32; 3], usize> = HashMap::new();
+    let ids: Vec<usize> = vertices.iter().map(|vertex| {
+        let key = [vertex.position.x, vertex.position.y, vertex.position.z].map(f32::to_bits);
+        let next = points.len();
+        *points.entry(key).or_insert(next)
+    }).collect();
+    let triangles = indices.chunks_exact(3)
+        .map(|tri| [ids[tri[0] as usize], ids[tri[1] as usize], ids[tri[2] as usize]])
+        .collect();
+    (points.len(), triangles)
+}
+
+// Cuántas veces aparece cada arista orientada
+fn directed_edges(triangles: &[[usize; 3]]) -> HashMap<(usize, usize), usize> {
+    let mut edges = HashMap::new();
+    for tri in triangles {
+        for i in 0..3 {
+            *edges.entry((tri[i], tri[(i + 1) % 3])).or_insert(0) += 1;
+        }
+    }
+    edges
+}
+
+// Cada arista la comparten dos triángulos que la recorren en sentidos
+// opuestos: no hay bordes ni caras dadas vuelta
+fn assert_watertight(triangles: &[[usize; 3]], name: &str) {
+    let edges = directed_edges(triangles);
+    for (&(a, b), &count) in &edges {
+        assert_ne!(a, b, "{}: triángulo degenerado", name);
+        assert_eq!(count, 1, "{}: la arista {}-{} se repite", name, a, b);
+        assert_eq!(edges.get(&(b, a)), Some(&1), "{}: la arista {}-{} es un borde", name, a, b);
+    }
+}
+
+#[test]
+fn icosphere_euler_characteristic_is_two() {
+    for level in LEVELS {
+        let (vertices, indices) = icosphere(level);
+        let (points, triangles) = welded(&vertices, &indices);
+        let faces = triangles.len();
+        let edges = directed_edges(&triangles).len() / 2;
+
+        assert_eq!(faces, 20 * 4usize.pow(level), "nivel {}", level);
+        assert_eq!(points as i64 - edges as i64 + faces as i64, 2, "nivel {}: V={} E={} F={}", level, points, edges, faces);
+    }
+}
+
+#[test]
+fn icosphere_is_watertight() {
+    for level in LEVELS {
+        let (vertices, indices) = icosphere(level);
+        let (_, triangles) = welded(&vertices, &indices);
+        assert_watertight(&triangles, &format!("nivel {}", level));
+    }
+}
+
+#[test]
+fn icosphere_lies_on_the_unit_sphere_facing_out() {
+    for level in LEVELS {
+        let (vertices, indices) = icosphere(level);
+        for vertex in &vertices {
+            assert!((vertex.position.norm() - 1.0).abs() < 1e-5, "nivel {}: {:?}", level, vertex.position);
+            assert_eq!(vertex.normal, vertex.position);
+        }
+        for tri in indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|index| vertices[index as usize].position);
+            assert!((b - a).cross(&(c - a)).dot(&(a + b + c)) > 0.0, "nivel {}: cara hacia adentro", level);
+        }
+    }
+}
+
+#[test]
+fn texture_coordinates_do_not_wrap_inside_a_triangle() {
+    let (vertices, indices) = icosphere(4);
+    for tri in indices.chunks_exact(3) {
+        let us = [tri[0], tri[1], tri[2]].map(|index| vertices[index as usize].tex_coords.x);
+        let span = us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(span < 0.5, "el triángulo cruza la costura: u = {:?}", us);
+    }
+}
+
+#[test]
+fn uv_sphere_is_watertight() {
+    let (vertices, indices) = uv_sphere(UV_SPHERE_SECTORS, UV_SPHERE_STACKS);
+    let (points, triangles) = welded(&vertices, &indices);
+    assert_eq!(points, UV_SPHERE_SECTORS * (UV_SPHERE_STACKS - 1) + 2);
+    assert_watertight(&triangles, "esfera UV");
+}
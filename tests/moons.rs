@@ -0,0 +1,101 @@
+// Lunas galileanas del sistema por defecto: orbitan a Júpiter a radios y
+// velocidades distintos, se ocultan con él, y cada una tiene su propio
+// shader (Ío amarilla, Europa blanca, Ganímedes gris pardo, Calisto oscura).
+//
+// cargo test --test moons
+
+use nalgebra_glm::{Vec2, Vec3};
+
+use shaders_lab::{create_uniforms, default_system, CelestialBody};
+use shaders_lab::color::Color;
+use shaders_lab::fragment::Fragment;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::shaders::fragment_shader;
+use shaders_lab::simulation::{host_index, satellites, update_simulation};
+use shaders_lab::visibility::{self, BodyGroup};
+
+const GALILEAN: [PlanetType; 4] = [PlanetType::Io, PlanetType::Europa, PlanetType::Ganymede, PlanetType::Callisto];
+
+fn index_of(bodies: &[CelestialBody], shader_type: PlanetType) -> usize {
+    bodies.iter()
+        .position(|body| body.shader_type == shader_type)
+        .unwrap_or_else(|| panic!("falta {} en el sistema", shader_type.name()))
+}
+
+// Color medio del shader sobre un anillo de puntos de la esfera unitaria,
+// iluminado de frente
+fn average_color(shader_type: PlanetType) -> Vec3 {
+    let uniforms = create_uniforms(64, 64, 7);
+    let samples = 64;
+    let sum: Vec3 = (0..samples).map(|sample| {
+        let angle = sample as f32 * 0.37;
+        let position = Vec3::new(angle.cos(), (sample as f32 * 0.11).sin() * 0.8, angle.sin()).normalize();
+        let fragment = Fragment::new(Vec2::zeros(), Color::black(), 0.0, position, position, position, 1.0);
+        let color = fragment_shader(&fragment, &uniforms, &shader_type);
+        Vec3::new(color.r as f32, color.g as f32, color.b as f32) / 255.0
+    }).sum();
+    sum / samples as f32
+}
+
+#[test]
+fn moons_orbit_jupiter_at_their_own_radius_and_speed() {
+    let mut bodies = default_system();
+    let jupiter = index_of(&bodies, PlanetType::Jupiter);
+    let moons: Vec<usize> = GALILEAN.iter().map(|&moon| index_of(&bodies, moon)).collect();
+    assert_eq!(satellites(&bodies, jupiter), moons);
+
+    let mut angles = Vec::new();
+    for sim_time in [0.0, 3.0] {
+        update_simulation(&mut bodies, sim_time);
+        angles.push(moons.iter().map(|&moon| {
+            assert_eq!(host_index(&bodies, moon), Some(jupiter));
+            let offset = bodies[moon].position - bodies[jupiter].position;
+            assert!((offset.norm() - bodies[moon].orbit_radius).abs() < 1e-3, "{} a {}", bodies[moon].shader_type.name(), offset.norm());
+            offset.z.atan2(offset.x)
+        }).collect::<Vec<f32>>());
+    }
+
+    let radii: Vec<f32> = moons.iter().map(|&moon| bodies[moon].orbit_radius).collect();
+    assert!(radii.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", radii);
+    // Cada una avanzó lo que dice su velocidad
+    for (slot, &moon) in moons.iter().enumerate() {
+        let advanced = (angles[1][slot] - angles[0][slot]).rem_euclid(std::f32::consts::TAU);
+        let expected = (3.0 * bodies[moon].orbit_speed).rem_euclid(std::f32::consts::TAU);
+        assert!((advanced - expected).abs() < 1e-3, "{}: {} en vez de {}", bodies[moon].shader_type.name(), advanced, expected);
+    }
+}
+
+#[test]
+fn hiding_jupiter_hides_its_moons() {
+    let mut bodies = default_system();
+    let jupiter = index_of(&bodies, PlanetType::Jupiter);
+    let moons = satellites(&bodies, jupiter);
+
+    visibility::set_visible(&mut bodies, jupiter, false);
+    assert!(moons.iter().all(|&moon| !bodies[moon].visible));
+    assert!(bodies[index_of(&bodies, PlanetType::Saturn)].visible);
+
+    visibility::toggle_visible(&mut bodies, jupiter);
+    assert!(moons.iter().all(|&moon| bodies[moon].visible));
+
+    // Sin gigantes gaseosos tampoco quedan sus lunas; la de la Tierra sí
+    BodyGroup::NoGasGiants.apply(&mut bodies);
+    assert!(moons.iter().all(|&moon| !bodies[moon].visible));
+    assert!(bodies[index_of(&bodies, PlanetType::Moon)].visible);
+}
+
+#[test]
+fn each_moon_has_its_own_look() {
+    let colors: Vec<Vec3> = GALILEAN.iter().map(|&moon| average_color(moon)).collect();
+    let [io, europa, ganymede, callisto] = [colors[0], colors[1], colors[2], colors[3]];
+
+    // Ío tira a amarillo: poco azul frente al rojo y el verde
+    assert!(io.z < io.x * 0.8 && io.z < io.y * 0.8, "Ío {:?}", io);
+    // Europa es la más clara y Calisto la más oscura
+    for (other, name) in [(io, "Ío"), (ganymede, "Ganímedes"), (callisto, "Calisto")] {
+        assert!(europa.sum() > other.sum(), "Europa {:?} no es más clara que {} {:?}", europa, name, other);
+    }
+    for (other, name) in [(io, "Ío"), (europa, "Europa"), (ganymede, "Ganímedes")] {
+        assert!(callisto.sum() < other.sum(), "Calisto {:?} no es más oscura que {} {:?}", callisto, name, other);
+    }
+}
@@ -0,0 +1,60 @@
+// Ruido fractal (`noise_utils::ridged` y `domain_warp`): en muchas muestras
+// las crestas quedan en [0, 1] usando todo el rango, y el desplazamiento del
+// warp nunca pasa de `strength` por eje.
+//
+// cargo test --test noise
+
+use fastnoise_lite::{FastNoiseLite, NoiseType};
+use nalgebra_glm::Vec3;
+
+use shaders_lab::noise_utils::{domain_warp, ridged};
+
+fn noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(1337);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise
+}
+
+// Puntos repartidos por un cubo de 400 unidades, varias veces la escala del ruido
+fn samples() -> impl Iterator<Item = Vec3> {
+    (0..4096u32).map(|i| {
+        let cell = Vec3::new((i % 16) as f32, (i / 16 % 16) as f32, (i / 256) as f32);
+        // Corrido de la rejilla para no caer siempre en los mismos puntos del ruido
+        cell * 25.0 + Vec3::new(0.37, 0.71, 0.13) * (i % 7) as f32 - Vec3::repeat(200.0)
+    })
+}
+
+#[test]
+fn ridged_stays_in_unit_range() {
+    let noise = noise();
+    for octaves in [1, 3, 6] {
+        let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+        for p in samples() {
+            let value = ridged(&noise, &p, octaves, 2.0, 0.5);
+            assert!((0.0..=1.0).contains(&value), "{} octavas en {:?}: {}", octaves, p, value);
+            min = min.min(value);
+            max = max.max(value);
+        }
+        // Hay crestas y también valles
+        assert!(min < 0.3 && max > 0.95, "{} octavas: de {} a {}", octaves, min, max);
+    }
+    // Sin octavas el fbm vale 0, que es una cresta
+    assert_eq!(ridged(&noise, &Vec3::new(1.0, 2.0, 3.0), 0, 2.0, 0.5), 1.0);
+}
+
+#[test]
+fn domain_warp_moves_at_most_strength_per_axis() {
+    let noise = noise();
+    for strength in [0.5, 4.0, 30.0] {
+        let mut largest: f32 = 0.0;
+        for p in samples() {
+            let offset = domain_warp(&noise, &p, strength) - p;
+            // Una pizca de margen por el redondeo de p + offset - p
+            assert!(offset.abs().max() <= strength * (1.0 + 1e-4) + 1e-3, "{} en {:?}: {:?}", strength, p, offset);
+            largest = largest.max(offset.abs().max());
+        }
+        assert!(largest > strength * 0.5, "{}: {}", strength, largest);
+    }
+    let p = Vec3::new(12.5, -3.0, 40.0);
+    assert_eq!(domain_warp(&noise, &p, 0.0), p);
+}
@@ -0,0 +1,58 @@
+// Matriz de normales (`shaders::normal_matrix`): con una escala no uniforme
+// las normales siguen perpendiculares a la superficie transformada, cosa que
+// la matriz de modelo sola no consigue.
+//
+// cargo test --test normal_matrix
+
+use nalgebra_glm::{self as glm, Mat4, Vec2, Vec3};
+
+use shaders_lab::create_uniforms;
+use shaders_lab::shaders::{normal_matrix, vertex_shader};
+use shaders_lab::vertex::Vertex;
+
+// Aplastada en Y, estirada en X y girada para que los ejes no coincidan
+fn model_matrix() -> Mat4 {
+    let rotation = glm::rotate(&Mat4::identity(), 0.7, &Vec3::new(1.0, 2.0, 0.5).normalize());
+    let scale = glm::scale(&Mat4::identity(), &Vec3::new(3.0, 0.4, 1.5));
+    glm::translate(&Mat4::identity(), &Vec3::new(5.0, -2.0, 1.0)) * rotation * scale
+}
+
+// Puntos de la esfera unitaria con dos tangentes cada uno
+fn sphere_frames() -> Vec<(Vec3, Vec3, Vec3)> {
+    (0..64).map(|i| {
+        let (theta, phi) = (i as f32 * 0.37 + 0.2, (i % 8) as f32 * 0.35 + 0.15);
+        let normal = Vec3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+        let east = Vec3::new(-theta.sin(), 0.0, theta.cos());
+        (normal, east, normal.cross(&east))
+    }).collect()
+}
+
+#[test]
+fn normals_stay_perpendicular_under_non_uniform_scale() {
+    let model = model_matrix();
+    let linear = model.fixed_view::<3, 3>(0, 0).into_owned();
+    let normals = normal_matrix(&model);
+
+    let mut naive_error: f32 = 0.0;
+    for (normal, east, north) in sphere_frames() {
+        let transformed = (normals * normal).normalize();
+        for tangent in [east, north] {
+            let tangent = (linear * tangent).normalize();
+            assert!(transformed.dot(&tangent).abs() < 1e-5, "{:?} frente a {:?}", transformed, tangent);
+            naive_error = naive_error.max((linear * normal).normalize().dot(&tangent).abs());
+        }
+    }
+    // Sin la inversa traspuesta la normal se inclina bastante
+    assert!(naive_error > 0.3, "{}", naive_error);
+}
+
+#[test]
+fn vertex_shader_uses_the_normal_matrix() {
+    let mut uniforms = create_uniforms(64, 64, 0);
+    uniforms.model_matrix = model_matrix();
+    for (normal, east, _) in sphere_frames() {
+        let vertex = vertex_shader(&Vertex::new_with_tangent(normal, normal, east, Vec2::zeros()), &uniforms);
+        let dot = vertex.transformed_normal.normalize().dot(&vertex.transformed_tangent.normalize());
+        assert!(dot.abs() < 1e-5, "{:?}: {}", normal, dot);
+    }
+}
@@ -0,0 +1,183 @@
+// El lector rápido de OBJ debe dar exactamente los mismos vértices que tobj:
+// se comparan bit a bit con los de `Obj::load_with_tobj` para el modelo del
+// repositorio y para archivos pequeños con los casos raros del formato. Los
+// colores de `v x y z r g b` llegan a cada vértice.
+//
+// cargo test --test obj_loader
+
+use std::fs;
+use std::path::PathBuf;
+
+use nalgebra_glm::{Vec2, Vec3};
+
+use shaders_lab::assets::Assets;
+use shaders_lab::color::Color;
+use shaders_lab::obj::Obj;
+use shaders_lab::vertex::Vertex;
+
+// Cada campo como bits, para que 0.0 y -0.0 o dos NaN distintos no pasen por iguales
+fn vertex_bits(vertices: &[Vertex]) -> Vec<Vec<u32>> {
+    vertices.iter().map(|vertex| {
+        let mut bits: Vec<u32> = [vertex.position, vertex.normal, vertex.tangent]
+            .iter()
+            .flat_map(|v| v.iter().map(|c| c.to_bits()))
+            .chain(vertex.tex_coords.iter().map(|c| c.to_bits()))
+            .collect();
+        bits.extend([vertex.color.r, vertex.color.g, vertex.color.b].map(u32::from));
+        bits
+    }).collect()
+}
+
+fn assert_same_vertices(path: &PathBuf) {
+    let fast = Obj::load(path).unwrap_or_else(|error| panic!("{}", error)).get_vertex_array();
+    let reference = Obj::load_with_tobj(path).unwrap_or_else(|error| panic!("{}", error)).get_vertex_array();
+    assert_eq!(fast.len(), reference.len(), "{}: cantidad de vértices distinta", path.display());
+    assert!(vertex_bits(&fast) == vertex_bits(&reference), "{}: los vértices no coinciden", path.display());
+}
+
+// Escribe el OBJ en el directorio temporal, con un nombre por prueba
+fn write_obj(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("shaders_lab_{}_{}.obj", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn sphere_matches_tobj() {
+    assert_same_vertices(&Assets::locate(None).path("models/esfera.obj"));
+}
+
+#[test]
+fn quads_polygons_and_relative_indices_match_tobj() {
+    let path = write_obj("faces", "\
+# cuadrado, pentágono e índices negativos
+o Cuadrado
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+f -4/-4/-1 -2/-2/-1 -1/-1/-1
+s off
+g Pentagono
+v 2 0 0
+v 3 0.5 0
+v 2.8 1.5 0\r
+v 2 2 0
+v 1.5 1 0
+f 5//1 6//1 7//1 8//1 9//1
+f 5/1 6/2 7/3
+f 5 5 6
+l 1 2
+f 1\u{3000}2 3
+f\u{b}1 3 4
+");
+    assert_same_vertices(&path);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn vertex_colors_and_missing_uvs_match_tobj() {
+    // Sin `vt` se usan coordenadas esféricas; los colores van tras la posición
+    let path = write_obj("colors", "\
+v 0 1 0 1 0 0
+v -1 0 0 0 1 0
+v 1 0 0 0 0 1
+v 0 0 1 1 1 1
+v 0 -1 0 0.5 0.5 0.5
+vn 0 1 0
+f 1//1 2//1 4//1
+f 1//1 4//1 3//1
+f 5 4 2
+f 5 3 4
+");
+    assert_same_vertices(&path);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn vertex_colors_reach_the_vertices() {
+    // Cada vértice lleva el color de su línea `v`; sin colores, blanco
+    let path = write_obj("own_colors", "\
+v 0 0 0 1 0 0
+v 1 0 0 0 1 0
+v 0 1 0 0 0 1
+v 1 1 0 1 0.5 0
+f 1 2 3
+f 2 4 3
+");
+    let expected = |position: Vec3| match (position.x as i32, position.y as i32) {
+        (0, 0) => Color::new(255, 0, 0),
+        (1, 0) => Color::new(0, 255, 0),
+        (0, 1) => Color::new(0, 0, 255),
+        _ => Color::from_float(1.0, 0.5, 0.0),
+    };
+    let vertices = Obj::load(&path).unwrap_or_else(|error| panic!("{}", error)).get_vertex_array();
+    assert_eq!(vertices.len(), 6);
+    for vertex in &vertices {
+        assert_eq!(vertex.color, expected(vertex.position), "{:?}", vertex.position);
+    }
+    fs::remove_file(path).unwrap();
+
+    let path = write_obj("no_colors", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n");
+    let vertices = Obj::load(&path).unwrap_or_else(|error| panic!("{}", error)).get_vertex_array();
+    assert!(vertices.iter().all(|vertex| vertex.color == Color::new(255, 255, 255)));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn material_change_matches_tobj() {
+    // Un cambio de material a mitad de objeto lo resuelve tobj
+    let path = write_obj("materials", "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+usemtl rojo
+f 1 2 3
+usemtl azul
+f 2 4 3
+");
+    assert_same_vertices(&path);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn malformed_files_fail_like_tobj() {
+    for (name, contents) in [
+        ("short_vertex", "v 0 0\nf 1 1 1\n"),
+        ("bad_index", "v 0 0 0\nf 1 2 x\n"),
+        ("out_of_bounds", "v 0 0 0\nv 1 0 0\nf 1 2 3\n"),
+    ] {
+        let path = write_obj(name, contents);
+        assert!(Obj::load(&path).is_err(), "{} debería fallar", name);
+        assert!(Obj::load_with_tobj(&path).is_err(), "{} debería fallar también con tobj", name);
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn texcoords_keep_only_u_and_v() {
+    // La w opcional de `vt u v w` se descarta; v se invierte a propósito
+    // (ver `Obj::load`): 0 es la fila de arriba de la imagen, como en
+    // `spherical_uv`, así que `vt 0.5 0.25` queda en (0.5, 0.75)
+    let path = write_obj("texcoords", "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0.5 0.25
+vt 1 0 0.75
+vt 0 1
+f 1/1 2/2 3/3
+");
+    assert_same_vertices(&path);
+    let vertices = Obj::load(&path).unwrap_or_else(|error| panic!("{}", error)).get_vertex_array();
+    let tex_coords: Vec<Vec2> = vertices.iter().map(|vertex| vertex.tex_coords).collect();
+    assert_eq!(tex_coords, [Vec2::new(0.5, 1.0 - 0.25), Vec2::new(1.0, 1.0), Vec2::new(0.0, 0.0)]);
+    fs::remove_file(path).unwrap();
+}
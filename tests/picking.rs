@@ -0,0 +1,99 @@
+// Selección con el ratón (`picking::pick_body`): el rayo sale de la
+// proyección y el viewport con que se dibuja, así que un cuerpo se elige
+// clicando donde aparece aunque la imagen no sea cuadrada, esté en media
+// pantalla o use reversed-Z, y entre dos alineados gana el más cercano.
+//
+// cargo test --test picking
+
+use nalgebra_glm::Vec3;
+
+use shaders_lab::{CelestialBody, Uniforms, create_uniforms, create_view_matrix, update_projection_matrix};
+use shaders_lab::camera::Camera;
+use shaders_lab::picking::{pick_body, project_to_screen};
+use shaders_lab::viewport::{Viewport, DIVIDER_WIDTH};
+
+const WIDTH: usize = 400;
+const HEIGHT: usize = 100;
+
+fn body(position: Vec3, scale: f32) -> CelestialBody {
+    CelestialBody { position, scale, ..Default::default() }
+}
+
+fn camera() -> Camera {
+    Camera::new(Vec3::new(0.0, 0.0, 20.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0))
+}
+
+// Píxel del framebuffer en el que se dibuja el centro de `body`
+fn center_pixel(body: &CelestialBody, camera: &Camera, uniforms: &Uniforms, viewport: &Viewport) -> (f32, f32) {
+    let view = create_view_matrix(camera.eye, camera.center, camera.up);
+    let (x, y) = project_to_screen(
+        &body.position,
+        viewport.width as f32,
+        viewport.height as f32,
+        &view,
+        &uniforms.projection_matrix,
+    ).expect("el cuerpo queda detrás de la cámara");
+    (viewport.x as f32 + x, viewport.y as f32 + y)
+}
+
+#[test]
+fn nearest_body_wins_whatever_the_order() {
+    let uniforms = create_uniforms(WIDTH, HEIGHT, 0);
+    let far = body(Vec3::new(0.0, 0.0, -10.0), 3.0);
+    let near = body(Vec3::new(0.0, 0.0, 5.0), 1.0);
+    let (x, y) = (WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0);
+
+    assert_eq!(pick_body(x, y, &camera(), &[far.clone(), near.clone()], &uniforms), Some(1));
+    assert_eq!(pick_body(x, y, &camera(), &[near, far], &uniforms), Some(0));
+}
+
+#[test]
+fn wide_image_picks_the_body_where_it_is_drawn() {
+    let uniforms = create_uniforms(WIDTH, HEIGHT, 0);
+    // Lejos del centro en x: con el aspecto mal puesto el rayo no lo toca
+    let bodies = [body(Vec3::new(20.0, 0.0, 0.0), 0.5)];
+    let (x, y) = center_pixel(&bodies[0], &camera(), &uniforms, &Viewport::full(WIDTH, HEIGHT));
+
+    assert!(x > WIDTH as f32 * 0.75, "el cuerpo debería verse cerca del borde derecho, está en x = {}", x);
+    assert_eq!(pick_body(x, y, &camera(), &bodies, &uniforms), Some(0));
+    assert_eq!(pick_body(WIDTH as f32 / 2.0, y, &camera(), &bodies, &uniforms), None);
+}
+
+#[test]
+fn split_view_picks_in_its_own_rectangle() {
+    let (_, right) = Viewport::split(WIDTH, HEIGHT, DIVIDER_WIDTH);
+    let mut uniforms = create_uniforms(WIDTH, HEIGHT, 0);
+    uniforms.projection_matrix = right.projection_matrix(&uniforms.projection);
+    uniforms.viewport_matrix = right.matrix();
+    let bodies = [body(Vec3::new(-4.0, 1.0, 0.0), 0.5)];
+
+    let (x, y) = center_pixel(&bodies[0], &camera(), &uniforms, &right);
+    assert!(right.contains(x, y));
+    assert_eq!(pick_body(x, y, &camera(), &bodies, &uniforms), Some(0));
+}
+
+#[test]
+fn reversed_z_picks_the_same_bodies() {
+    let uniforms = create_uniforms(WIDTH, HEIGHT, 0);
+    let mut reversed = create_uniforms(WIDTH, HEIGHT, 0);
+    reversed.projection.reversed_z = true;
+    update_projection_matrix(&mut reversed, WIDTH, HEIGHT);
+    let bodies = [
+        body(Vec3::new(-6.0, 0.0, 0.0), 2.0),
+        body(Vec3::new(-5.0, 0.5, 8.0), 1.0),
+        body(Vec3::new(10.0, -1.0, -30.0), 6.0),
+        // Justo detrás de la cámara, fuera de la imagen
+        body(Vec3::new(0.0, 0.0, 20.3), 0.25),
+    ];
+
+    let mut hits = 0;
+    for y in (0..HEIGHT).step_by(5) {
+        for x in (0..WIDTH).step_by(5) {
+            let (x, y) = (x as f32 + 0.5, y as f32 + 0.5);
+            let expected = pick_body(x, y, &camera(), &bodies, &uniforms);
+            assert_eq!(pick_body(x, y, &camera(), &bodies, &reversed), expected, "({}, {})", x, y);
+            hits += expected.is_some() as usize;
+        }
+    }
+    assert!(hits > 0, "ningún rayo tocó un cuerpo");
+}
@@ -0,0 +1,137 @@
+// Render progresivo (`progressive::RenderProgress` y `Scene::render_pass`):
+// cada pasada dibuja filas intercaladas, de lo grueso a lo fino, y tras la
+// última el buffer es idéntico al de un `Scene::render` normal, desenfoque de
+// movimiento incluido. Mover la cámara vuelve a empezar.
+//
+// cargo test --test progressive
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{create_uniforms, default_system};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::progressive::{pass_offsets, RenderProgress};
+use shaders_lab::scene::Scene;
+use shaders_lab::simulation::FIXED_DT;
+
+const WIDTH: usize = 96;
+const HEIGHT: usize = 70;
+const PASSES: usize = 4;
+
+// El sistema completo, con anillos, atmósferas, protuberancias y lunas, y el
+// desenfoque de movimiento activo tras un primer frame
+fn scene() -> Scene {
+    let bodies = default_system();
+    let camera = common::look_at(Vec3::new(4.0, 8.0, 30.0), Vec3::zeros());
+    let mut scene = common::scene(bodies, &SphereMesh::UvSphere, camera, create_uniforms(WIDTH, HEIGHT, 7));
+    scene.motion_blur.enabled = true;
+    scene.render(&mut framebuffer());
+    scene.update(FIXED_DT * 3.0);
+    scene
+}
+
+fn framebuffer() -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_background_color(0x020208);
+    framebuffer.clear();
+    framebuffer
+}
+
+fn full_render() -> Framebuffer {
+    let mut framebuffer = framebuffer();
+    scene().render(&mut framebuffer);
+    framebuffer
+}
+
+fn track(progress: &mut RenderProgress, scene: &Scene, framebuffer: &Framebuffer) -> bool {
+    progress.track(&scene.camera, &scene.uniforms.projection, scene.render_time(), framebuffer)
+}
+
+fn row(framebuffer: &Framebuffer, y: usize) -> &[Vec3] {
+    &framebuffer.hdr_buffer[y * WIDTH..(y + 1) * WIDTH]
+}
+
+#[test]
+fn passes_go_from_coarse_to_fine() {
+    assert_eq!(pass_offsets(4), [0, 2, 1, 3]);
+    assert_eq!(pass_offsets(8), [0, 4, 2, 6, 1, 3, 5, 7]);
+    for passes in 1..=9 {
+        let mut offsets = pass_offsets(passes);
+        offsets.sort();
+        assert_eq!(offsets, (0..passes).collect::<Vec<_>>(), "{} pasadas", passes);
+    }
+}
+
+#[test]
+fn last_pass_matches_a_full_render() {
+    let expected = full_render();
+    let mut scene = scene();
+    let mut shown = framebuffer();
+    let mut progress = RenderProgress::new(PASSES);
+
+    assert!(track(&mut progress, &scene, &shown));
+    for pass in 0..PASSES {
+        assert!(!progress.is_complete());
+        scene.render_pass(&mut progress);
+        progress.present(&mut shown);
+
+        // Las filas ya dibujadas son las definitivas; las demás copian una de encima
+        let offsets = &pass_offsets(PASSES)[..=pass];
+        for y in 0..HEIGHT {
+            if offsets.contains(&(y % PASSES)) {
+                assert_eq!(row(&shown, y), row(&expected, y), "pasada {}, fila {}", pass, y);
+            }
+        }
+        if pass == 0 {
+            assert_eq!(row(&shown, 1), row(&expected, 0));
+            assert_eq!(row(&shown, 3), row(&expected, 0));
+        }
+    }
+
+    assert!(progress.is_complete());
+    assert!(shown.hdr_buffer.iter().any(|color| *color != shown.background_linear()), "no se dibujó nada");
+    assert_eq!(shown.hdr_buffer, expected.hdr_buffer);
+    assert_eq!(shown.zbuffer, expected.zbuffer);
+    assert_eq!(shown.velocity_buffer, expected.velocity_buffer);
+    assert!(shown.velocity_buffer.iter().any(|velocity| velocity.norm() > 0.0), "sin velocidades que comparar");
+
+    // Completa, otra pasada no dibuja nada
+    let stats = scene.render_pass(&mut progress);
+    assert_eq!(stats.fragments, 0);
+    progress.present(&mut shown);
+    assert_eq!(shown.hdr_buffer, expected.hdr_buffer);
+}
+
+#[test]
+fn moving_the_camera_restarts() {
+    let mut scene = scene();
+    let shown = framebuffer();
+    let mut progress = RenderProgress::new(PASSES);
+
+    assert!(track(&mut progress, &scene, &shown));
+    scene.render_pass(&mut progress);
+    scene.render_pass(&mut progress);
+    assert!(!track(&mut progress, &scene, &shown));
+    assert_eq!(progress.completed(), 2);
+    // A medias, el tiempo espera a que se complete
+    assert!(progress.holds_time(&scene.camera, &scene.uniforms.projection, &shown));
+
+    scene.camera.eye += Vec3::new(0.5, 0.0, 0.0);
+    assert!(!progress.holds_time(&scene.camera, &scene.uniforms.projection, &shown));
+    assert!(track(&mut progress, &scene, &shown));
+    assert_eq!(progress.completed(), 0);
+
+    // También empieza de nuevo si avanzó el tiempo
+    scene.render_pass(&mut progress);
+    scene.update(FIXED_DT);
+    assert!(track(&mut progress, &scene, &shown));
+    assert_eq!(progress.completed(), 0);
+}
+
+#[test]
+fn progress_can_move_to_another_thread() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<RenderProgress>();
+}
@@ -0,0 +1,42 @@
+// Shader de color aleatorio (`PlanetType::RandomColor`): el color sale de
+// `uniforms.seed` y del segundo de `uniforms.time`, así que la misma semilla
+// en el mismo instante da siempre el mismo color y otra semilla, otro.
+//
+// cargo test --test random_color
+
+use nalgebra_glm::{Vec2, Vec3};
+
+use shaders_lab::create_uniforms;
+use shaders_lab::color::Color;
+use shaders_lab::fragment::Fragment;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::shaders::fragment_shader;
+
+fn shade(seed: u64, time: f32) -> Color {
+    let mut uniforms = create_uniforms(64, 64, seed);
+    uniforms.time = time;
+    let position = Vec3::new(0.0, 0.0, 1.0);
+    let fragment = Fragment::new(Vec2::zeros(), Color::black(), 0.0, position, position, position, 1.0);
+    fragment_shader(&fragment, &uniforms, &PlanetType::RandomColor)
+}
+
+#[test]
+fn same_seed_gives_the_same_color() {
+    for seed in [0, 7, 42, u64::MAX] {
+        assert_eq!(shade(seed, 3.5), shade(seed, 3.5), "semilla {}", seed);
+        // Dentro del mismo segundo el color no cambia
+        assert_eq!(shade(seed, 3.0), shade(seed, 3.9), "semilla {}", seed);
+    }
+}
+
+#[test]
+fn different_seeds_give_different_colors() {
+    let colors: Vec<Color> = (1..=4).map(|seed| shade(seed, 3.5)).collect();
+    for (i, a) in colors.iter().enumerate() {
+        for b in &colors[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+    // Tampoco es negro: la luz ambiente deja ver el color
+    assert!(colors.iter().all(|color| *color != Color::black()));
+}
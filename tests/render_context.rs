@@ -0,0 +1,31 @@
+// Buffers del pipeline (`RenderContext`): se reservan en el primer frame y
+// los siguientes los reutilizan sin volver a pedir memoria.
+//
+// cargo test --test render_context
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{create_uniforms, default_system};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::mesh::SphereMesh;
+
+const SIZE: usize = 96;
+
+#[test]
+fn frames_reuse_the_same_buffers() {
+    // El sistema completo, con atmósferas y anillos, para que se usen todos
+    let camera = common::look_at(Vec3::new(4.0, 8.0, 30.0), Vec3::zeros());
+    let mut scene = common::scene(default_system(), &SphereMesh::Icosphere(2), camera, create_uniforms(SIZE, SIZE, 7));
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+
+    framebuffer.clear();
+    scene.render(&mut framebuffer);
+    let first = scene.context.allocations();
+    assert!(first.iter().all(|&(_, capacity)| capacity > 0), "{:?}", first);
+
+    framebuffer.clear();
+    scene.render(&mut framebuffer);
+    assert_eq!(scene.context.allocations(), first);
+}
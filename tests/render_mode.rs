@@ -0,0 +1,93 @@
+// Modos de render de F2 (`debug_view::RenderMode`) sobre un planeta solo:
+// las normales van de [-1, 1] a RGB, el alambre dibuja sólo las aristas del
+// lado de la cámara (la superficie tapa las de atrás) y el alambre encima
+// deja intacto todo lo que no es arista.
+//
+// cargo test --test render_mode
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{CelestialBody, create_uniforms};
+use shaders_lab::debug_view::{wireframe_color, RenderMode, WIREFRAME_COLOR};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::planet_type::PlanetType;
+
+const SIZE: usize = 64;
+
+fn render(mode: RenderMode) -> Framebuffer {
+    let bodies = vec![CelestialBody { shader_type: PlanetType::Mars, rotation_speed: 0.0, ..Default::default() }];
+    let camera = common::look_at(Vec3::new(0.0, 0.0, 4.0), Vec3::zeros());
+    let mut scene = common::scene(bodies, &SphereMesh::Icosphere(2), camera, create_uniforms(SIZE, SIZE, 0));
+    scene.context.render_mode = mode;
+
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.clear();
+    scene.render(&mut framebuffer);
+    framebuffer
+}
+
+// Píxeles donde quedó una arista
+fn edges(framebuffer: &Framebuffer) -> Vec<usize> {
+    let is_edge = |color: &Vec3| color.y > 0.1 && color.y > color.x * 2.0;
+    (0..framebuffer.hdr_buffer.len()).filter(|&index| is_edge(&framebuffer.hdr_buffer[index])).collect()
+}
+
+#[test]
+fn normals_map_to_rgb() {
+    let framebuffer = render(RenderMode::Normals);
+    let at = |x: usize, y: usize| framebuffer.hdr_buffer[y * SIZE + x];
+
+    // El centro mira a la cámara (+Z), la izquierda a -X y arriba a +Y
+    let center = at(SIZE / 2, SIZE / 2);
+    assert!((center - Vec3::new(0.5, 0.5, 1.0)).norm() < 0.1, "{:?}", center);
+    let (left, top) = (at(SIZE / 2 - 10, SIZE / 2), at(SIZE / 2, SIZE / 2 - 10));
+    assert!(left.x < 0.35 && (left.y - 0.5).abs() < 0.05, "{:?}", left);
+    assert!(top.y > 0.65 && (top.x - 0.5).abs() < 0.05, "{:?}", top);
+    assert!(framebuffer.hdr_buffer.iter().all(|color| color.max() <= 1.0 && color.min() >= 0.0));
+}
+
+#[test]
+fn wireframe_hides_far_side_edges() {
+    let wireframe = render(RenderMode::Wireframe);
+    let shaded = render(RenderMode::Shaded);
+    let edge_pixels = edges(&wireframe);
+    assert!(edge_pixels.len() > 100, "sólo {} píxeles de aristas", edge_pixels.len());
+
+    // Ninguna arista visible queda detrás de la cara de delante. Las del
+    // contorno caen medio píxel fuera de la superficie.
+    for index in edge_pixels.iter().copied().filter(|&index| shaded.is_surface(shaded.zbuffer[index])) {
+        let (edge, surface) = (wireframe.zbuffer[index], shaded.zbuffer[index]);
+        assert!(edge < surface + 1e-4, "píxel {}: {} detrás de {}", index, edge, surface);
+    }
+    // Fuera de las aristas no hay color de superficie
+    let background = wireframe.background_linear();
+    let filled = (0..wireframe.hdr_buffer.len())
+        .filter(|index| !edge_pixels.contains(index))
+        .filter(|&index| (wireframe.hdr_buffer[index] - background).norm() > 0.2)
+        .count();
+    assert!(filled < edge_pixels.len() / 4, "{} píxeles con color fuera de las aristas", filled);
+}
+
+#[test]
+fn overlay_keeps_the_shaded_image_between_edges() {
+    let shaded = render(RenderMode::Shaded);
+    let overlay = render(RenderMode::ShadedWithWireframe);
+    let edge_pixels = edges(&overlay);
+    assert!(!edge_pixels.is_empty());
+
+    let unchanged = (0..shaded.hdr_buffer.len())
+        .filter(|index| !edge_pixels.contains(index))
+        .filter(|&index| shaded.hdr_buffer[index] == overlay.hdr_buffer[index])
+        .count();
+    assert_eq!(unchanged + edge_pixels.len(), shaded.hdr_buffer.len());
+}
+
+#[test]
+fn edges_fade_with_distance() {
+    assert_eq!(wireframe_color(0.0), WIREFRAME_COLOR);
+    let (near, far) = (wireframe_color(3.0), wireframe_color(60.0));
+    assert!(near.y > far.y && near.z < far.z, "{:?} / {:?}", near, far);
+}
@@ -0,0 +1,108 @@
+// Profundidad invertida (`Framebuffer::is_nearer` con `set_reversed_z`): en
+// las dos convenciones lo que está más cerca de la cámara pasa la prueba de
+// profundidad, y cualquier superficie le gana a un píxel recién borrado.
+// Cerca del plano lejano sólo la invertida distingue superficies muy juntas.
+//
+// cargo test --test reversed_z
+
+use nalgebra_glm::{Vec2, Vec3};
+
+use shaders_lab::{create_uniforms, create_view_matrix, set_reversed_z};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::shaders::vertex_shader;
+use shaders_lab::vertex::Vertex;
+
+const SIZE: usize = 16;
+
+fn framebuffer(reversed_z: bool) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    let mut uniforms = create_uniforms(SIZE, SIZE, 0);
+    set_reversed_z(&mut framebuffer, &mut uniforms, reversed_z);
+    framebuffer
+}
+
+// Profundidad en pantalla de un punto a `distance` de la cámara
+fn depth(reversed_z: bool, distance: f32) -> f32 {
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    let mut uniforms = create_uniforms(SIZE, SIZE, 0);
+    set_reversed_z(&mut framebuffer, &mut uniforms, reversed_z);
+    uniforms.view_matrix = create_view_matrix(Vec3::zeros(), Vec3::new(0.0, 0.0, -1.0), Vec3::y());
+    let vertex = Vertex::new(Vec3::new(0.1, 0.0, -distance), Vec3::z(), Vec2::zeros());
+    vertex_shader(&vertex, &uniforms).transformed_position.z
+}
+
+#[test]
+fn nearer_geometry_wins_in_both_conventions() {
+    for reversed_z in [false, true] {
+        let framebuffer = framebuffer(reversed_z);
+        for (near, far) in [(0.5, 2.0), (1.0, 50.0), (20.0, 900.0)] {
+            let (near, far) = (depth(reversed_z, near), depth(reversed_z, far));
+            assert!(framebuffer.is_nearer(near, far), "reversed_z = {}: {} frente a {}", reversed_z, near, far);
+            assert!(!framebuffer.is_nearer(far, near), "reversed_z = {}: {} frente a {}", reversed_z, far, near);
+        }
+    }
+    // Invertida, lo cercano tiene más profundidad
+    assert!(depth(true, 1.0) > depth(true, 10.0));
+    assert!(depth(false, 1.0) < depth(false, 10.0));
+}
+
+#[test]
+fn ties_keep_the_first_or_the_last_write() {
+    assert!(!framebuffer(false).is_nearer(0.5, 0.5));
+    assert!(framebuffer(true).is_nearer(0.5, 0.5));
+}
+
+#[test]
+fn cleared_depth_loses_to_any_surface() {
+    for reversed_z in [false, true] {
+        let mut framebuffer = framebuffer(reversed_z);
+        framebuffer.clear();
+        let cleared = framebuffer.clear_depth();
+        assert!(framebuffer.zbuffer.iter().all(|&depth| depth == cleared));
+        assert!(!framebuffer.is_surface(cleared));
+
+        for distance in [0.5, 10.0, 900.0] {
+            assert!(framebuffer.is_nearer(depth(reversed_z, distance), cleared), "reversed_z = {}", reversed_z);
+        }
+        assert!(framebuffer.point_hdr(3, 4, depth(reversed_z, 900.0), Vec3::repeat(1.0)));
+        assert!(framebuffer.is_surface(framebuffer.zbuffer[4 * SIZE + 3]));
+    }
+}
+
+// Dos superficies a 0.05 unidades cerca del plano lejano (far = 1000). Con
+// la profundidad estándar las dos caen en el mismo valor de f32 junto a 1, o
+// en el orden equivocado, y la que se dibuja después puede ganar: z-fighting.
+// Invertida, la profundidad lejana queda junto a 0, donde a los floats les
+// sobra precisión, y el orden se mantiene.
+#[test]
+fn close_surfaces_near_the_far_plane_only_stay_ordered_when_reversed() {
+    const GAP: f32 = 0.05;
+    let (near_color, far_color) = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+
+    for distance in [900.0, 930.0, 950.0, 980.0, 990.0] {
+        let (near, far) = (depth(false, distance), depth(false, distance + GAP));
+        assert!(near >= far, "a {}: estándar {} frente a {}", distance, near, far);
+
+        let (near, far) = (depth(true, distance), depth(true, distance + GAP));
+        assert!(near > far, "a {}: invertida {} frente a {}", distance, near, far);
+
+        // Se dibujen en el orden que sea, el píxel se queda con la cercana
+        for reversed_z in [false, true] {
+            let (near, far) = (depth(reversed_z, distance), depth(reversed_z, distance + GAP));
+            let mut near_first = framebuffer(reversed_z);
+            near_first.point_hdr(0, 0, near, near_color);
+            near_first.point_hdr(0, 0, far, far_color);
+            let mut far_first = framebuffer(reversed_z);
+            far_first.point_hdr(0, 0, far, far_color);
+            far_first.point_hdr(0, 0, near, near_color);
+
+            let kept = [near_first.hdr_buffer[0], far_first.hdr_buffer[0]];
+            if reversed_z {
+                assert_eq!(kept, [near_color, near_color], "a {}", distance);
+            } else {
+                // En al menos uno de los dos órdenes gana la lejana
+                assert!(kept.contains(&far_color), "a {}", distance);
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+// `Scene` sin ventana: se arma con el sistema por defecto, se avanza con
+// `update` y se dibuja en un `Framebuffer` como cualquier biblioteca.
+//
+// cargo test --test scene
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{create_uniforms, default_system};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::tonemap::ToneMapSettings;
+
+const WIDTH: usize = 120;
+const HEIGHT: usize = 80;
+const FRAMES: usize = 5;
+const BACKGROUND: u32 = 0x020208;
+
+#[test]
+fn renders_frames_without_a_window() {
+    let camera = common::look_at(Vec3::new(0.0, 12.0, 35.0), Vec3::zeros());
+    let mut scene = common::scene(default_system(), &SphereMesh::Icosphere(2), camera, create_uniforms(WIDTH, HEIGHT, 7));
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_background_color(BACKGROUND);
+
+    let mut frames = Vec::new();
+    for _ in 0..FRAMES {
+        scene.update(0.1);
+        framebuffer.clear();
+        scene.render(&mut framebuffer);
+        framebuffer.resolve(&ToneMapSettings::default());
+
+        let drawn = framebuffer.buffer.iter().filter(|&&pixel| pixel != BACKGROUND).count();
+        assert!(drawn > 200, "sólo {} píxeles dibujados", drawn);
+        assert!(framebuffer.zbuffer.iter().any(|&depth| framebuffer.is_surface(depth)));
+        frames.push(framebuffer.buffer.clone());
+    }
+    // Los cuerpos se mueven entre frames
+    assert!(frames.windows(2).all(|pair| pair[0] != pair[1]));
+}
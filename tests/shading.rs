@@ -0,0 +1,74 @@
+// Sombreado plano y suave (`ShadingMode`), visto con el modo de normales de
+// F2: en plano todo el triángulo tiene la normal de su cara y en suave la
+// normal se interpola entre las de los vértices.
+//
+// cargo test --test shading
+
+use nalgebra_glm::{Vec2, Vec3};
+
+use shaders_lab::{create_uniforms, create_view_matrix, render, RenderContext, ShadingMode};
+use shaders_lab::debug_view::{normal_color, RenderMode};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::vertex::Vertex;
+
+const SIZE: usize = 64;
+
+// Un triángulo de frente a la cámara con las normales de los vértices
+// abiertas hacia afuera, como en una esfera
+fn triangle() -> Vec<Vertex> {
+    [Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0)]
+        .iter()
+        .map(|&position| Vertex::new(position, (position * 0.6 + Vec3::z()).normalize(), Vec2::zeros()))
+        .collect()
+}
+
+// Colores de los píxeles con sus ocho vecinos también dentro del triángulo,
+// lejos del borde antialiasado que se mezcla con el fondo
+fn interior_colors(shading: ShadingMode) -> Vec<Vec3> {
+    let mut uniforms = create_uniforms(SIZE, SIZE, 0);
+    uniforms.view_matrix = create_view_matrix(Vec3::new(0.0, 0.0, 3.0), Vec3::zeros(), Vec3::y());
+    uniforms.shading = shading;
+    let mut context = RenderContext::new();
+    context.render_mode = RenderMode::Normals;
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.clear();
+    render(&mut framebuffer, &mut context, &uniforms, &triangle(), &PlanetType::Mars, &[], 0);
+
+    let surface = |x: usize, y: usize| framebuffer.is_surface(framebuffer.zbuffer[y * SIZE + x]);
+    let mut colors = Vec::new();
+    for y in 1..SIZE - 1 {
+        for x in 1..SIZE - 1 {
+            if (y - 1..=y + 1).all(|ny| (x - 1..=x + 1).all(|nx| surface(nx, ny))) {
+                colors.push(framebuffer.hdr_buffer[y * SIZE + x]);
+            }
+        }
+    }
+    assert!(colors.len() > 100, "sólo {} píxeles dentro", colors.len());
+    colors
+}
+
+#[test]
+fn flat_fragments_share_the_face_normal() {
+    let face = normal_color(&Vec3::z());
+    for color in interior_colors(ShadingMode::Flat) {
+        assert!((color - face).abs().max() < 1e-5, "{:?}", color);
+    }
+}
+
+#[test]
+fn smooth_fragments_interpolate_the_vertex_normals() {
+    let colors = interior_colors(ShadingMode::Smooth);
+    // Hacia la izquierda la normal se inclina a -X y hacia la derecha a +X
+    let (min_red, max_red) = colors.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), color| (min.min(color.x), max.max(color.x)));
+    assert!(min_red < 0.4 && max_red > 0.6, "rojo de {} a {}", min_red, max_red);
+
+    // La normal cambia de un píxel al siguiente y queda normalizada
+    let mut distinct = colors.clone();
+    distinct.dedup();
+    assert!(distinct.len() > colors.len() / 2, "{} de {}", distinct.len(), colors.len());
+    for color in &colors {
+        let normal = color * 2.0 - Vec3::repeat(1.0);
+        assert!((normal.norm() - 1.0).abs() < 1e-4, "{:?}", normal);
+    }
+}
@@ -0,0 +1,128 @@
+// Sombras de eclipse (`shadows::shadow_attenuation`): un cuerpo entre el Sol
+// y otro le quita la luz, con un borde de penumbra, y nunca oscurece lo que
+// queda entre él y el Sol. `in_shadow` responde si hay algo de sombra.
+//
+// cargo test --test shadows
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{create_uniforms, CelestialBody};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::shadows::{eclipse_light, in_shadow, shadow_attenuation, ECLIPSE_LIGHT};
+
+const SIZE: usize = 64;
+const SUN: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+const OCCLUDER: Vec3 = Vec3::new(10.0, 0.0, 0.0);
+
+#[test]
+fn only_points_behind_the_occluder_are_darkened() {
+    let attenuation = |point: Vec3| shadow_attenuation(point, SUN, OCCLUDER, 1.0);
+
+    // Detrás, en el eje: umbra
+    assert_eq!(attenuation(Vec3::new(20.0, 0.0, 0.0)), ECLIPSE_LIGHT);
+    assert_eq!(attenuation(Vec3::new(12.0, 0.5, 0.0)), ECLIPSE_LIGHT);
+    assert_eq!(attenuation(Vec3::new(20.0, 1.5, 0.0)), ECLIPSE_LIGHT);
+    // Entre el Sol y el cuerpo, o a un costado: luz plena
+    assert_eq!(attenuation(Vec3::new(5.0, 0.0, 0.0)), 1.0);
+    assert_eq!(attenuation(Vec3::new(20.0, 3.0, 0.0)), 1.0);
+    assert_eq!(attenuation(Vec3::new(-5.0, 0.0, 0.0)), 1.0);
+
+    // La sombra se abre en cono desde el Sol: al doble de distancia, el
+    // doble de ancha. La penumbra aclara de a poco hacia afuera.
+    let edge: Vec<f32> = [1.75, 1.9, 2.0, 2.1, 2.25].iter().map(|&y| attenuation(Vec3::new(20.0, y, 0.0))).collect();
+    assert!(edge.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", edge);
+    assert!(edge[0] > ECLIPSE_LIGHT && edge[4] < 1.0, "{:?}", edge);
+}
+
+#[test]
+fn in_shadow_matches_any_eclipse_light() {
+    let bodies = vec![
+        CelestialBody { position: SUN, shader_type: PlanetType::Sun, ..Default::default() },
+        CelestialBody { position: OCCLUDER, shader_type: PlanetType::Moon, scale: 1.0, ..Default::default() },
+    ];
+    // En la umbra, en la penumbra y fuera de la sombra
+    for point in [Vec3::new(20.0, 0.0, 0.0), Vec3::new(20.0, 2.1, 0.0), Vec3::new(20.0, 3.0, 0.0)] {
+        let light = eclipse_light(point, SUN, &bodies, usize::MAX);
+        assert_eq!(in_shadow(point, SUN, &bodies, usize::MAX), light < 1.0, "{:?}: {}", point, light);
+    }
+    assert!(in_shadow(Vec3::new(20.0, 2.1, 0.0), SUN, &bodies, usize::MAX));
+    assert!(!in_shadow(Vec3::new(20.0, 3.0, 0.0), SUN, &bodies, usize::MAX));
+}
+
+#[test]
+fn a_body_between_another_and_the_sun_shadows_it() {
+    // Sol, luna y planeta alineados sobre el eje X
+    let bodies = vec![
+        CelestialBody { position: SUN, shader_type: PlanetType::Sun, ..Default::default() },
+        CelestialBody { position: OCCLUDER, shader_type: PlanetType::Moon, scale: 1.0, ..Default::default() },
+        CelestialBody { position: Vec3::new(20.0, 0.0, 0.0), shader_type: PlanetType::Earth, scale: 1.0, ..Default::default() },
+    ];
+    // La cara del planeta que mira al Sol queda detrás de la luna
+    let day_side = Vec3::new(19.0, 0.0, 0.0);
+    assert!(in_shadow(day_side, SUN, &bodies, 2));
+    // La luna no está en sombra: el planeta queda detrás de ella
+    assert!(!in_shadow(Vec3::new(9.0, 0.0, 0.0), SUN, &bodies, 1));
+
+    // Con la luna oculta o corrida a un costado vuelve la luz
+    let mut hidden = bodies.clone();
+    hidden[1].visible = false;
+    assert!(!in_shadow(day_side, SUN, &hidden, 2));
+    let mut aside = bodies.clone();
+    aside[1].position = Vec3::new(10.0, 5.0, 0.0);
+    assert!(!in_shadow(day_side, SUN, &aside, 2));
+}
+
+#[test]
+fn a_body_never_shadows_itself() {
+    let bodies = vec![
+        CelestialBody { position: SUN, shader_type: PlanetType::Sun, ..Default::default() },
+        CelestialBody { position: OCCLUDER, shader_type: PlanetType::Earth, scale: 1.0, ..Default::default() },
+    ];
+    // El lado de noche tiene al propio cuerpo entre él y el Sol, pero su
+    // oscuridad la pone la iluminación, no la sombra
+    let night_side = Vec3::new(11.0, 0.0, 0.0);
+    assert!(!in_shadow(night_side, SUN, &bodies, 1));
+    // Visto como punto de otro cuerpo, ahí sí hay sombra
+    assert!(in_shadow(night_side, SUN, &bodies, usize::MAX));
+}
+
+// Brillo medio del planeta en `target`, visto de costado (la mitad de día a
+// la izquierda), con el cuerpo de `OCCLUDER` visible o no
+fn planet_brightness(target: Vec3, occluder_visible: bool) -> f32 {
+    let bodies = vec![
+        CelestialBody { position: SUN, shader_type: PlanetType::Sun, scale: 1.0, ..Default::default() },
+        CelestialBody { position: OCCLUDER, shader_type: PlanetType::Jupiter, scale: 1.5, visible: occluder_visible, ..Default::default() },
+        CelestialBody { position: target, shader_type: PlanetType::Mercury, scale: 0.5, rotation_speed: 0.0, ..Default::default() },
+    ];
+    // El Sol y el otro cuerpo quedan delante de la cámara pero fuera del cuadro
+    let camera = common::look_at(target + Vec3::new(0.0, 0.0, 3.0), target);
+    let mut scene = common::scene(bodies, &SphereMesh::Icosphere(2), camera, create_uniforms(SIZE, SIZE, 7));
+
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    framebuffer.clear();
+    scene.render(&mut framebuffer);
+    let surface: Vec<Vec3> = (0..framebuffer.hdr_buffer.len())
+        .filter(|&index| framebuffer.is_surface(framebuffer.zbuffer[index]))
+        .map(|index| framebuffer.hdr_buffer[index])
+        .collect();
+    assert!(surface.len() > 100, "sólo {} píxeles del planeta", surface.len());
+    surface.iter().map(|color| color.sum()).sum::<f32>() / surface.len() as f32
+}
+
+#[test]
+fn a_planet_in_the_shadow_goes_dark() {
+    let behind = Vec3::new(20.0, 0.0, 0.0);
+    let lit = planet_brightness(behind, false);
+    let eclipsed = planet_brightness(behind, true);
+    assert!(eclipsed < lit * 0.5, "{} eclipsado frente a {}", eclipsed, lit);
+}
+
+#[test]
+fn a_planet_between_the_sun_and_the_occluder_stays_lit() {
+    let between = Vec3::new(5.0, 0.0, 0.0);
+    assert_eq!(planet_brightness(between, true), planet_brightness(between, false));
+}
@@ -0,0 +1,104 @@
+// Paso fijo de simulación (`Scene::update`): con frames largos y desparejos,
+// recortados a `MAX_FRAME_TIME` como en el bucle principal, se dan los mismos
+// pasos y se llega al mismo estado que avanzando de a `FIXED_DT`. Un bucle
+// lento y uno sin límite de FPS dibujan lo mismo tras diez segundos.
+//
+// cargo test --test simulation
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{create_uniforms, default_system};
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::scene::Scene;
+use shaders_lab::simulation::{host_index, interpolated_state, FIXED_DT, MAX_FRAME_TIME, UPDATES_PER_SECOND};
+
+fn scene() -> Scene {
+    let camera = common::look_at(Vec3::new(0.0, 10.0, 40.0), Vec3::zeros());
+    common::scene(default_system(), &SphereMesh::Icosphere(1), camera, create_uniforms(64, 64, 7))
+}
+
+fn steps(scene: &Scene) -> usize {
+    (scene.sim_time() / FIXED_DT).round() as usize
+}
+
+#[test]
+fn uneven_frames_match_fixed_steps() {
+    // Tirones de más de un segundo, frames de varios pasos y otros de menos de uno
+    let frame_times = [0.1, 1.5, 0.004, 0.23, 0.0071, 3.0, 0.05, 0.249, 0.012, 0.3, 0.0833, 0.002, 0.19];
+    let mut uneven = scene();
+    let mut elapsed = 0.0;
+    for frame_time in frame_times {
+        let frame_time = f32::min(frame_time, MAX_FRAME_TIME);
+        uneven.update(frame_time);
+        elapsed += frame_time;
+    }
+
+    let expected = (elapsed / FIXED_DT).floor() as usize;
+    assert_eq!(steps(&uneven), expected, "{} s simulados", elapsed);
+    assert!(expected > UPDATES_PER_SECOND as usize);
+
+    let mut fixed = scene();
+    for _ in 0..expected {
+        fixed.update(FIXED_DT);
+    }
+    assert_eq!(steps(&fixed), expected);
+    assert_eq!(uneven.sim_time(), fixed.sim_time());
+    for (a, b) in uneven.bodies.iter().zip(&fixed.bodies) {
+        assert_eq!((a.position, a.spin), (b.position, b.spin), "{:?}", a.shader_type);
+        assert_eq!((a.previous_position, a.previous_spin), (b.previous_position, b.previous_spin));
+    }
+    // Lo que no llegó a un paso queda para el frame siguiente
+    assert!((0.0..1.0).contains(&uneven.alpha()));
+}
+
+// Segundos que simulan los dos bucles de `throttled_and_unthrottled_loops_agree`
+const STRESS_SECONDS: f32 = 10.0;
+
+// Corre el bucle principal con los frames de `frame_times`, repetidos y
+// recortados como en la ventana, hasta `STRESS_SECONDS`. Devuelve, por
+// cuerpo, el ángulo de su órbita (alrededor de su planeta si es una luna) y
+// su giro tal como se dibujarían.
+fn run_loop(frame_times: &[f32]) -> (Scene, Vec<(f32, f32)>) {
+    let mut scene = scene();
+    let mut elapsed = 0.0;
+    for &frame_time in frame_times.iter().cycle() {
+        if elapsed >= STRESS_SECONDS {
+            break;
+        }
+        let frame_time = f32::min(frame_time, MAX_FRAME_TIME).min(STRESS_SECONDS - elapsed);
+        scene.update(frame_time);
+        elapsed += frame_time;
+    }
+
+    let alpha = scene.alpha();
+    let states: Vec<(Vec3, f32)> = scene.bodies.iter().map(|body| interpolated_state(body, alpha)).collect();
+    let angles = (0..scene.bodies.len()).map(|index| {
+        let center = host_index(&scene.bodies, index).map_or(Vec3::zeros(), |host| states[host].0);
+        let offset = states[index].0 - center;
+        (offset.z.atan2(offset.x), states[index].1)
+    }).collect();
+    (scene, angles)
+}
+
+#[test]
+fn throttled_and_unthrottled_loops_agree() {
+    // A unos 10 FPS con tirones, uno de ellos más largo que `MAX_FRAME_TIME`,
+    // frente a 1000 FPS parejos
+    let (throttled, slow) = run_loop(&[0.1, 0.05, 0.2, 0.4, 0.125, 0.0833, 0.15]);
+    let (unthrottled, fast) = run_loop(&[0.001]);
+
+    assert!(throttled.sim_time() >= STRESS_SECONDS - FIXED_DT, "{} s", throttled.sim_time());
+    assert!((throttled.render_time() - unthrottled.render_time()).abs() < 1e-3,
+        "{} s frente a {} s", throttled.render_time(), unthrottled.render_time());
+
+    for (body, ((slow_orbit, slow_spin), (fast_orbit, fast_spin))) in throttled.bodies.iter().zip(slow.iter().zip(&fast)) {
+        // Distancia entre los dos ángulos sobre el círculo unidad, sin saltos en ±π
+        let orbit = (slow_orbit.cos() - fast_orbit.cos()).hypot(slow_orbit.sin() - fast_orbit.sin());
+        assert!(orbit < 1e-3, "{:?}: órbita {} frente a {}", body.shader_type, slow_orbit, fast_orbit);
+        assert!((slow_spin - fast_spin).abs() < 1e-3, "{:?}: giro {} frente a {}", body.shader_type, slow_spin, fast_spin);
+    }
+    // Algún cuerpo se movió de verdad en esos diez segundos
+    assert!(fast.iter().any(|(_, spin)| spin.abs() > 1.0));
+}
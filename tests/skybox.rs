@@ -0,0 +1,95 @@
+// Cielo de `--skybox` (`skybox::render_skybox`): cada píxel vacío toma la
+// imagen equirectangular en la dirección de su rayo, así que girar la cámara
+// muestra otra parte y moverla no cambia nada. Lo que ya tiene una
+// superficie no se toca.
+//
+// cargo test --test skybox
+
+use nalgebra_glm::Vec3;
+
+use shaders_lab::ProjectionSettings;
+use shaders_lab::camera::Camera;
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::skybox::{render_skybox, SkyboxTexture};
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 48;
+
+// Cada píxel de la imagen con un color distinto: rojo según la columna,
+// verde según la fila
+fn gradient_sky() -> SkyboxTexture {
+    let (width, height) = (16, 8);
+    let pixels = (0..width * height)
+        .map(|index| Vec3::new((index % width) as f32 / width as f32, (index / width) as f32 / height as f32, 0.5))
+        .collect();
+    SkyboxTexture { brightness: 1.0, ..SkyboxTexture::from_pixels(width, height, pixels) }
+}
+
+fn camera(eye: Vec3, forward: Vec3) -> Camera {
+    Camera::new(eye, eye + forward, Vec3::new(0.0, 1.0, 0.0))
+}
+
+fn sky_frame(camera: &Camera, reversed_z: bool) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_reversed_z(reversed_z);
+    framebuffer.clear();
+    render_skybox(&mut framebuffer, camera, &ProjectionSettings::default(), &gradient_sky());
+    framebuffer
+}
+
+#[test]
+fn moving_the_camera_does_not_move_the_sky() {
+    let forward = Vec3::new(0.3, 0.2, -1.0);
+    let here = sky_frame(&camera(Vec3::zeros(), forward), false);
+    let there = sky_frame(&camera(Vec3::new(120.0, -40.0, 75.0), forward), false);
+    // Sólo el redondeo de `center - eye` puede separarlos
+    let largest = here.hdr_buffer.iter().zip(&there.hdr_buffer).map(|(a, b)| (a - b).abs().max()).fold(0.0, f32::max);
+    assert!(largest < 1e-4, "el cielo cambió hasta {}", largest);
+}
+
+#[test]
+fn turning_the_camera_shows_another_part() {
+    let ahead = sky_frame(&camera(Vec3::zeros(), Vec3::new(0.0, 0.0, 1.0)), false);
+    let behind = sky_frame(&camera(Vec3::zeros(), Vec3::new(0.0, 0.0, -1.0)), false);
+    let center = (HEIGHT / 2) * WIDTH + WIDTH / 2;
+
+    // +Z cae en el centro de la imagen y -Z en la costura
+    assert!((ahead.hdr_buffer[center].x - 0.5).abs() < 0.1, "{:?}", ahead.hdr_buffer[center]);
+    assert_ne!(ahead.hdr_buffer, behind.hdr_buffer);
+    // Arriba en la imagen es arriba en el cielo
+    assert!(ahead.hdr_buffer[0].y < ahead.hdr_buffer[(HEIGHT - 1) * WIDTH].y);
+}
+
+#[test]
+fn seam_blends_both_edges() {
+    let sky = gradient_sky();
+    // Justo en la costura se mezclan la primera y la última columna
+    let seam = sky.sample(&Vec3::new(0.0, 0.0, -1.0));
+    let first = sky.pixels[3 * sky.width].x;
+    let last = sky.pixels[4 * sky.width - 1].x;
+    assert!(seam.x > first.min(last) && seam.x < first.max(last), "{} no está entre {} y {}", seam.x, first, last);
+}
+
+#[test]
+fn only_empty_pixels_get_the_sky() {
+    for reversed_z in [false, true] {
+        let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+        framebuffer.set_reversed_z(reversed_z);
+        framebuffer.clear();
+        let surface = Vec3::new(9.0, 8.0, 7.0);
+        let glow = Vec3::new(0.25, 0.0, 0.0);
+        assert!(framebuffer.point_hdr(10, 10, 0.5, surface));
+        framebuffer.add_hdr(20, 20, glow);
+
+        let camera = camera(Vec3::zeros(), Vec3::new(0.0, 0.0, -1.0));
+        render_skybox(&mut framebuffer, &camera, &ProjectionSettings::default(), &gradient_sky());
+        let empty = sky_frame(&camera, reversed_z);
+
+        let index = |x: usize, y: usize| y * WIDTH + x;
+        assert_eq!(framebuffer.hdr_buffer[index(10, 10)], surface);
+        // El halo queda sumado sobre el cielo en vez de sobre el color de fondo
+        let halo = framebuffer.hdr_buffer[index(20, 20)] - empty.hdr_buffer[index(20, 20)];
+        assert!((halo - glow).norm() < 1e-5, "{:?}", halo);
+        assert_eq!(framebuffer.hdr_buffer[index(30, 30)], empty.hdr_buffer[index(30, 30)]);
+    }
+}
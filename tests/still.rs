@@ -0,0 +1,98 @@
+// Imagen fija con `Scene::render_still`: la proyección sale del tamaño
+// pedido y no del de la ventana. Un planeta en el centro debe verse redondo
+// en una imagen apaisada y en una vertical, y el resultado no puede depender
+// del tamaño con que se crearon las uniforms.
+//
+// cargo test --test still
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{CelestialBody, create_uniforms};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::golden::GoldenImage;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::scene::Scene;
+use shaders_lab::tonemap::{Dither, ToneMapSettings};
+
+// Píxeles de diferencia admitidos entre el ancho y el alto del disco
+const ROUNDNESS_TOLERANCE: usize = 2;
+
+fn scene(window_width: usize, window_height: usize) -> Scene {
+    let bodies = vec![CelestialBody {
+        shader_type: PlanetType::Mars,
+        rotation_speed: 0.0,
+        ..Default::default()
+    }];
+    let camera = common::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zeros());
+    let mut uniforms = create_uniforms(window_width, window_height, 0);
+    uniforms.fill_light = true;
+    common::scene(bodies, &common::model_sphere(), camera, uniforms)
+}
+
+fn still(scene: &mut Scene, width: usize, height: usize) -> Framebuffer {
+    let camera = common::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zeros());
+    let mut framebuffer = scene.render_still(width, height, &camera, 0.0);
+    framebuffer.resolve(&ToneMapSettings { dither: Dither::Off, ..Default::default() });
+    framebuffer
+}
+
+// Ancho y alto del rectángulo que ocupan los píxeles que no son fondo
+fn disk_extent(framebuffer: &Framebuffer) -> (usize, usize) {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (usize::MAX, usize::MAX, 0, 0);
+    for y in 0..framebuffer.height {
+        for x in 0..framebuffer.width {
+            if framebuffer.get_pixel(x, y) != 0 {
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+            }
+        }
+    }
+    assert!(min_x <= max_x, "no se dibujó nada");
+    (max_x - min_x + 1, max_y - min_y + 1)
+}
+
+#[test]
+fn aspect_ratio_follows_the_image() {
+    let mut scene = scene(800, 600);
+    for (width, height) in [(240, 120), (120, 240)] {
+        let framebuffer = still(&mut scene, width, height);
+        assert_eq!((framebuffer.width, framebuffer.height), (width, height));
+        let (disk_width, disk_height) = disk_extent(&framebuffer);
+        assert!(
+            disk_width.abs_diff(disk_height) <= ROUNDNESS_TOLERANCE,
+            "{}x{}: el disco mide {}x{}",
+            width,
+            height,
+            disk_width,
+            disk_height,
+        );
+    }
+}
+
+#[test]
+fn window_size_does_not_matter() {
+    let reference = still(&mut scene(800, 600), 200, 150);
+    let other = still(&mut scene(333, 777), 200, 150);
+
+    let reference = GoldenImage { width: reference.width, height: reference.height, pixels: reference.buffer.clone() };
+    let diff = reference.compare(&other, 0);
+    assert!(diff.matches(), "{} píxeles cambian con el tamaño de la ventana", diff.differing);
+}
+
+#[test]
+fn live_scene_is_left_untouched() {
+    let mut scene = scene(800, 600);
+    scene.update(0.5);
+    let (projection, viewport) = (scene.uniforms.projection_matrix, scene.uniforms.viewport_matrix);
+    let (sim_time, alpha, rotation) = (scene.sim_time(), scene.alpha(), scene.bodies[0].rotation);
+
+    still(&mut scene, 64, 64);
+
+    assert_eq!(scene.uniforms.projection_matrix, projection);
+    assert_eq!(scene.uniforms.viewport_matrix, viewport);
+    assert_eq!((scene.sim_time(), scene.alpha(), scene.bodies[0].rotation), (sim_time, alpha, rotation));
+}
@@ -0,0 +1,52 @@
+// Tangentes por vértice (`obj::compute_tangents`): sobre un cuadrilátero de
+// UV conocidas apuntan hacia donde crece u y quedan perpendiculares a las
+// normales, aunque éstas no lo sean a la cara.
+//
+// cargo test --test tangents
+
+use nalgebra_glm::{Vec2, Vec3};
+
+use shaders_lab::obj::compute_tangents;
+
+// Dos triángulos que comparten la diagonal 0-2
+const INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+fn quad_uvs() -> Vec<Vec2> {
+    vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 1.0)]
+}
+
+#[test]
+fn flat_quad_tangents_point_along_u() {
+    // Rectángulo en el plano XY, más ancho que alto, con u creciendo en +X
+    let vertices = [Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0)];
+    let normals = [Vec3::z(); 4];
+    let tangents = compute_tangents(&vertices, &normals, &quad_uvs(), &INDICES);
+    for tangent in &tangents {
+        assert!((tangent - Vec3::x()).norm() < 1e-6, "{:?}", tangent);
+    }
+
+    // Con la textura espejada, u crece hacia -X
+    let mirrored: Vec<Vec2> = quad_uvs().iter().map(|uv| Vec2::new(1.0 - uv.x, uv.y)).collect();
+    for tangent in compute_tangents(&vertices, &normals, &mirrored, &INDICES) {
+        assert!((tangent + Vec3::x()).norm() < 1e-6, "{:?}", tangent);
+    }
+}
+
+#[test]
+fn tangents_are_orthogonal_to_bent_normals() {
+    // Cuadrado inclinado, con normales suavizadas que se abren como en una esfera
+    let (u_axis, v_axis) = (Vec3::new(1.0, 0.0, 1.0).normalize(), Vec3::y());
+    let face_normal = u_axis.cross(&v_axis);
+    let vertices: Vec<Vec3> = quad_uvs().iter().map(|uv| u_axis * uv.x * 3.0 + v_axis * uv.y * 3.0).collect();
+    let normals: Vec<Vec3> = quad_uvs().iter()
+        .map(|uv| (face_normal + u_axis * (uv.x - 0.5) * 0.6 + v_axis * (uv.y - 0.5) * 0.6).normalize())
+        .collect();
+
+    let tangents = compute_tangents(&vertices, &normals, &quad_uvs(), &INDICES);
+    assert_eq!(tangents.len(), 4);
+    for (tangent, normal) in tangents.iter().zip(&normals) {
+        assert!((tangent.norm() - 1.0).abs() < 1e-5, "{:?}", tangent);
+        assert!(tangent.dot(normal).abs() < 1e-5, "{:?} frente a {:?}", tangent, normal);
+        assert!(tangent.dot(&u_axis) > 0.9, "{:?}", tangent);
+    }
+}
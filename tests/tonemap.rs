@@ -0,0 +1,114 @@
+// Curva de luces altas (`tonemap::compress_highlights`): en espacio lineal
+// deja igual lo que ya cabía en pantalla y acerca a 1 sin recortar lo que
+// se pasa, como el Sol, conservando su tono y su degradado.
+// `Framebuffer::tonemap` la aplica con la exposición sobre el buffer HDR, y
+// `resolve` la usa así con `ToneMapOperator::Highlights`.
+//
+// cargo test --test tonemap
+
+use nalgebra_glm::Vec3;
+
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::tonemap::{compress_highlights, luminance, Dither, ToneMapOperator, ToneMapSettings, HIGHLIGHT_KNEE};
+
+// Color del Sol dibujado, muy por encima de 1 en rojo y verde
+const SUN: Vec3 = Vec3::new(6.0, 3.5, 1.2);
+
+#[test]
+fn ldr_colors_are_near_identity_at_exposure_one() {
+    let settings = ToneMapSettings { operator: ToneMapOperator::Highlights, dither: Dither::Off, ..Default::default() };
+    for step in 0..=20 {
+        let value = step as f32 / 20.0;
+        for color in [Vec3::repeat(value), Vec3::new(value, value * 0.5, value * 0.2), Vec3::new(0.1, 0.3, value)] {
+            let mapped = compress_highlights(&color);
+            if color.max() <= HIGHLIGHT_KNEE {
+                assert_eq!(mapped, color);
+            }
+            // Arriba de la rodilla sólo se comprime un poco
+            assert!((mapped - color).abs().max() < 0.11, "{:?} -> {:?}", color, mapped);
+
+            let expected = color.map(|c| (c * 255.0).round() as u32);
+            let packed = settings.apply(&color);
+            let byte = [(packed >> 16) & 0xFF, (packed >> 8) & 0xFF, packed & 0xFF];
+            if color.max() <= HIGHLIGHT_KNEE {
+                assert_eq!(byte, [expected.x, expected.y, expected.z], "{:?}", color);
+            }
+        }
+    }
+}
+
+#[test]
+fn highlights_keep_gradient_and_hue() {
+    // Más brillo siempre da más luminancia, sin llegar a 1
+    let mut previous = 0.0;
+    for step in 1..=40 {
+        let mapped = compress_highlights(&(SUN * (step as f32 * 0.25)));
+        let luminance = luminance(&mapped);
+        assert!(luminance > previous && luminance < 1.0, "paso {}: {}", step, luminance);
+        assert!(mapped.max() <= 1.0 && mapped.min() >= 0.0, "{:?}", mapped);
+        previous = luminance;
+    }
+
+    // El Sol sigue más rojo que verde y más verde que azul, en la misma proporción
+    let sun = compress_highlights(&SUN);
+    assert!(sun.x > sun.y && sun.y > sun.z, "{:?}", sun);
+    assert!((sun.y / sun.x - SUN.y / SUN.x).abs() < 1e-5, "{:?}", sun);
+    assert!(sun.x - sun.z > 0.5, "{:?}", sun);
+}
+
+#[test]
+fn resolve_applies_highlights_in_linear_space() {
+    let mut framebuffer = Framebuffer::new(4, 1);
+    let colors = [Vec3::new(0.25, 0.4, 0.1), Vec3::repeat(0.5), SUN, Vec3::new(0.0, 0.0, 4.0)];
+    for (x, color) in colors.iter().enumerate() {
+        framebuffer.point_hdr(x, 0, 0.5, *color);
+    }
+    let settings = ToneMapSettings { exposure: 2.0, operator: ToneMapOperator::Highlights, dither: Dither::Off, ..Default::default() };
+    framebuffer.resolve(&settings);
+
+    for (x, color) in colors.iter().enumerate() {
+        let byte = compress_highlights(&(color * 2.0)).map(|c| (c * 255.0).round() as u32);
+        assert_eq!(framebuffer.buffer[x], (byte.x << 16) | (byte.y << 8) | byte.z, "{:?}", color);
+    }
+    // La exposición se aplica antes de la curva, sobre valores lineales
+    assert_eq!(framebuffer.buffer[0], 0x80CC33);
+}
+
+#[test]
+fn framebuffer_tonemap_passes_the_knee_through_and_rolls_off_above() {
+    // Una rampa de grises de 0 a 4 veces el blanco, un píxel por valor
+    let values: Vec<f32> = (0..=80).map(|step| step as f32 * 0.05).collect();
+    let mut framebuffer = Framebuffer::new(values.len() + 1, 1);
+    for (x, value) in values.iter().enumerate() {
+        framebuffer.point_hdr(x, 0, 0.5, Vec3::repeat(*value));
+    }
+    framebuffer.tonemap(1.0);
+
+    let mut previous = -1.0;
+    for (x, value) in values.iter().enumerate() {
+        let mapped = framebuffer.hdr_buffer[x];
+        assert_eq!(mapped.x, mapped.y);
+        if *value <= HIGHLIGHT_KNEE {
+            assert!((mapped.x - value).abs() < 1e-6, "{} -> {}", value, mapped.x);
+        } else {
+            assert!(mapped.x < *value && mapped.x < 1.0, "{} -> {}", value, mapped.x);
+        }
+        assert!(mapped.x > previous, "{} -> {} no sube", value, mapped.x);
+        previous = mapped.x;
+    }
+
+    // El píxel sin geometría queda como estaba
+    assert_eq!(framebuffer.hdr_buffer[values.len()], Vec3::zeros());
+}
+
+#[test]
+fn framebuffer_tonemap_applies_exposure_before_the_curve() {
+    let mut framebuffer = Framebuffer::new(2, 1);
+    framebuffer.point_hdr(0, 0, 0.5, Vec3::new(0.2, 0.3, 0.1));
+    framebuffer.point_hdr(1, 0, 0.5, SUN);
+    framebuffer.tonemap(2.0);
+
+    // Por debajo de la rodilla sólo cuenta la exposición
+    assert!((framebuffer.hdr_buffer[0] - Vec3::new(0.4, 0.6, 0.2)).abs().max() < 1e-6);
+    assert_eq!(framebuffer.hdr_buffer[1], compress_highlights(&(SUN * 2.0)));
+}
@@ -0,0 +1,118 @@
+// Orden de dibujo con transparencias: Júpiter y Saturno con sus anillos,
+// con los anillos delante de Júpiter y detrás de él. El resultado no debe
+// depender del orden de los cuerpos en la escena y debe coincidir con las
+// imágenes de referencia.
+//
+// cargo test --test transparency
+//
+// Tras un cambio intencional, regenerar las referencias con
+// UPDATE_GOLDEN=1 cargo test --test transparency y revisar los PNG.
+
+use std::path::PathBuf;
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{CelestialBody, create_uniforms};
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::golden::GoldenImage;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::rings::RingSystem;
+use shaders_lab::tonemap::{Dither, ToneMapSettings};
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 120;
+// Como en la prueba de la Tierra: redondeo de otra máquina
+const TOLERANCE: u8 = 6;
+const MAX_DIFFERING_PIXELS: usize = WIDTH * HEIGHT / 500;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name)
+}
+
+// Sin giro ni órbitas, para que el frame no dependa del tiempo
+fn bodies() -> Vec<CelestialBody> {
+    vec![
+        CelestialBody {
+            scale: 1.5,
+            shader_type: PlanetType::Jupiter,
+            rotation_speed: 0.0,
+            ..Default::default()
+        },
+        CelestialBody {
+            position: Vec3::new(6.0, 0.0, 0.0),
+            scale: 1.3,
+            shader_type: PlanetType::Saturn,
+            rotation_speed: 0.0,
+            axial_tilt: 0.45,
+            rings: Some(RingSystem::saturn()),
+            ..Default::default()
+        },
+    ]
+}
+
+fn render(bodies: Vec<CelestialBody>, eye: Vec3, center: Vec3) -> Framebuffer {
+    let camera = common::look_at(eye, center);
+    let mut uniforms = create_uniforms(WIDTH, HEIGHT, 0);
+    // Sin estrellas en la escena: una luz de relleno fija desde la cámara, y
+    // el "sol" de las sombras también ahí (en el origen Júpiter taparía a Saturno)
+    uniforms.fill_light = true;
+    uniforms.sun_position = eye;
+    let mut scene = common::scene(bodies, &common::model_sphere(), camera, uniforms);
+
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_background_color(0x000000);
+    scene.render(&mut framebuffer);
+    // Sin tramado, para comparar colores sin el patrón encima
+    framebuffer.resolve(&ToneMapSettings { dither: Dither::Off, ..Default::default() });
+    framebuffer
+}
+
+fn check_view(name: &str, eye: Vec3, center: Vec3) {
+    let in_order = render(bodies(), eye, center);
+    let mut reversed = bodies();
+    reversed.reverse();
+    let reversed = render(reversed, eye, center);
+
+    // Los dos órdenes deben dar el mismo frame
+    let reference = GoldenImage { width: WIDTH, height: HEIGHT, pixels: in_order.buffer.clone() };
+    let diff = reference.compare(&reversed, 0);
+    assert!(
+        diff.matches(),
+        "{}: el orden de los cuerpos cambia {} píxeles (hasta {}, el primero en {:?})",
+        name,
+        diff.differing,
+        diff.max_difference,
+        diff.first_difference,
+    );
+
+    let path = golden_path(&format!("{}.png", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        GoldenImage::save(&in_order, &path).unwrap_or_else(|error| panic!("{}", error));
+        return;
+    }
+    let golden = GoldenImage::load(&path)
+        .unwrap_or_else(|error| panic!("{} (UPDATE_GOLDEN=1 la genera)", error));
+    let diff = golden.compare(&in_order, TOLERANCE);
+    assert!(
+        diff.same_size && diff.differing <= MAX_DIFFERING_PIXELS,
+        "{}: {} píxeles difieren de la referencia en más de {} (máximo {}, el primero en {:?})",
+        name,
+        diff.differing,
+        TOLERANCE,
+        diff.max_difference,
+        diff.first_difference,
+    );
+}
+
+#[test]
+fn rings_in_front_of_jupiter() {
+    // Saturno queda entre la cámara y Júpiter
+    check_view("rings_front", Vec3::new(16.0, 2.5, 5.0), Vec3::new(3.0, 0.0, 0.0));
+}
+
+#[test]
+fn rings_behind_jupiter() {
+    // Del otro lado: Júpiter tapa parte de Saturno y de sus anillos
+    check_view("rings_behind", Vec3::new(-10.0, 2.5, 4.0), Vec3::new(3.0, 0.0, 0.0));
+}
@@ -0,0 +1,167 @@
+// Rasterizador (`triangle::triangle`): la regla de relleno arriba-izquierda
+// reparte cada píxel de una arista compartida a un solo triángulo, y las
+// funciones de arista incrementales en punto fijo dan los mismos fragmentos
+// que evaluarlas desde cero en cada píxel. Fuera de las aristas queda un
+// borde de cobertura parcial de `AA_FRINGE` píxeles.
+//
+// cargo test --test triangle
+
+use std::collections::HashMap;
+use nalgebra_glm::{Vec2, Vec3};
+
+use shaders_lab::fragment::Fragment;
+use shaders_lab::triangle::{triangle, AA_FRINGE};
+use shaders_lab::vertex::Vertex;
+
+const SIZE: usize = 32;
+
+fn screen_vertex(x: f32, y: f32) -> Vertex {
+    let mut vertex = Vertex::new(Vec3::zeros(), Vec3::new(0.0, 0.0, 1.0), Vec2::zeros());
+    vertex.transformed_position = Vec3::new(x, y, 0.5);
+    vertex
+}
+
+fn rasterize(corners: [(f32, f32); 3]) -> Vec<Fragment> {
+    let [mut a, mut b, mut c] = corners.map(|(x, y)| screen_vertex(x, y));
+    // Con los vértices en los ejes, la posición interpolada son los pesos baricéntricos
+    (a.position, b.position, c.position) = (Vec3::x(), Vec3::y(), Vec3::z());
+    let mut fragments = Vec::new();
+    triangle(&a, &b, &c, None, SIZE, SIZE, &mut fragments);
+    fragments
+}
+
+// Cuántas veces se dibuja cada píxel entre todos los triángulos, contando
+// sólo los fragmentos con cobertura completa (el borde antialiasado se mezcla)
+fn draw_counts(triangles: &[[(f32, f32); 3]]) -> HashMap<(usize, usize), usize> {
+    let mut counts = HashMap::new();
+    for corners in triangles {
+        for fragment in rasterize(*corners).iter().filter(|fragment| fragment.coverage == 1.0) {
+            *counts.entry((fragment.position.x as usize, fragment.position.y as usize)).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+#[test]
+fn quad_split_on_its_diagonal_draws_every_pixel_once() {
+    // Los centros de los píxeles de la diagonal caen justo sobre la arista compartida
+    let (a, b, c, d) = ((2.0, 2.0), (14.0, 2.0), (14.0, 14.0), (2.0, 14.0));
+    for triangles in [[[a, b, c], [a, c, d]], [[a, c, b], [a, d, c]]] {
+        let counts = draw_counts(&triangles);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let expected = usize::from((2..14).contains(&x) && (2..14).contains(&y));
+                assert_eq!(counts.get(&(x, y)).copied().unwrap_or(0), expected, "píxel ({}, {})", x, y);
+            }
+        }
+    }
+}
+
+#[test]
+fn irregular_quad_has_no_gaps_or_double_draws() {
+    // Aristas con pendientes cualesquiera y esquinas fuera de la rejilla
+    let (a, b, c, d) = ((3.25, 1.5), (20.5, 4.0), (17.75, 19.5), (1.5, 12.25));
+    let counts = draw_counts(&[[a, b, c], [a, c, d]]);
+    assert!(counts.values().all(|&count| count == 1), "píxeles dibujados dos veces");
+
+    // Todo centro de píxel estrictamente dentro del cuadrilátero se dibuja
+    let corners = [a, b, c, d];
+    let inside = |x: f32, y: f32| {
+        (0..4).all(|i| {
+            let (from, to) = (corners[i], corners[(i + 1) % 4]);
+            (x - from.0) * (to.1 - from.1) - (y - from.1) * (to.0 - from.0) < 0.0
+        })
+    };
+    let centers: Vec<(usize, usize)> = (0..SIZE * SIZE)
+        .map(|index| (index % SIZE, index / SIZE))
+        .filter(|&(x, y)| inside(x as f32 + 0.5, y as f32 + 0.5))
+        .collect();
+    assert!(centers.len() > 200, "{} centros dentro", centers.len());
+    for (x, y) in centers {
+        assert!(counts.contains_key(&(x, y)), "hueco en ({}, {})", x, y);
+    }
+}
+
+// El recorrido anterior a las funciones incrementales: las tres funciones de
+// arista en coma flotante desde cero para cada píxel de la caja. Devuelve
+// cada píxel cubierto con sus pesos baricéntricos.
+fn per_pixel_reference(corners: [(f32, f32); 3]) -> Vec<((usize, usize), Vec3)> {
+    let [a, b, c] = corners.map(|(x, y)| Vec2::new(x, y));
+    let edge = |from: Vec2, to: Vec2, p: Vec2| (p.x - from.x) * (to.y - from.y) - (p.y - from.y) * (to.x - from.x);
+    let area = edge(a, b, c);
+    let winding = area.signum();
+    let top_left = |from: Vec2, to: Vec2| {
+        let (dx, dy) = ((to.x - from.x) * winding, (to.y - from.y) * winding);
+        dy > 0.0 || (dy == 0.0 && dx < 0.0)
+    };
+    let owns = [top_left(b, c), top_left(c, a), top_left(a, b)];
+
+    let (min_x, max_x) = (a.x.min(b.x).min(c.x).floor() as usize, a.x.max(b.x).max(c.x).ceil() as usize);
+    let (min_y, max_y) = (a.y.min(b.y).min(c.y).floor() as usize, a.y.max(b.y).max(c.y).ceil() as usize);
+    let mut covered = Vec::new();
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let edges = [edge(b, c, p) * winding, edge(c, a, p) * winding, edge(a, b, p) * winding];
+            let inside = edges.iter().zip(owns).all(|(&e, owned)| e > 0.0 || (e == 0.0 && owned));
+            if inside {
+                let weights = Vec3::new(edges[0], edges[1], edges[2]) / (area * winding);
+                covered.push(((x, y), weights));
+            }
+        }
+    }
+    covered
+}
+
+#[test]
+fn incremental_edges_match_the_per_pixel_path() {
+    // Vértices en la rejilla de 1/256 de píxel, para que el punto fijo no los mueva
+    let triangles = [
+        // Grande, en los dos sentidos de giro
+        [(4.0, 3.0), (27.0, 6.5), (10.25, 28.75)],
+        [(4.0, 3.0), (10.25, 28.75), (27.0, 6.5)],
+        // Astilla casi horizontal
+        [(1.5, 1.5), (30.5, 2.0), (2.0, 3.25)],
+        // Chico, de unos pocos píxeles
+        [(12.125, 12.5), (15.875, 13.0), (13.5, 16.375)],
+        // Con la hipotenusa pasando por centros de píxel
+        [(0.0, 0.0), (31.0, 0.0), (0.0, 31.0)],
+    ];
+    for corners in triangles {
+        let reference = per_pixel_reference(corners);
+        let fragments: Vec<((usize, usize), Vec3)> = rasterize(corners)
+            .iter()
+            .filter(|fragment| fragment.coverage == 1.0)
+            .map(|fragment| ((fragment.position.x as usize, fragment.position.y as usize), fragment.vertex_position))
+            .collect();
+
+        assert!(!reference.is_empty());
+        let positions = |list: &[((usize, usize), Vec3)]| list.iter().map(|(position, _)| *position).collect::<Vec<_>>();
+        assert_eq!(positions(&fragments), positions(&reference), "{:?}", corners);
+        for ((position, weights), (_, expected)) in fragments.iter().zip(&reference) {
+            assert!((weights - expected).abs().max() <= 1e-6, "{:?} en {:?}: {:?} frente a {:?}", corners, position, weights, expected);
+        }
+    }
+}
+
+#[test]
+fn edge_pixels_get_partial_coverage() {
+    // Arista derecha vertical en x = 20.5: los centros de la columna 20 caen
+    // justo sobre ella, y una arista derecha no es dueña de esos píxeles
+    let fragments = rasterize([(20.5, 1.0), (20.5, 30.0), (2.0, 15.0)]);
+    let coverage = |x: usize, y: usize| {
+        fragments.iter()
+            .find(|fragment| fragment.position == Vec2::new(x as f32, y as f32))
+            .map(|fragment| fragment.coverage)
+    };
+
+    assert_eq!(coverage(12, 15), Some(1.0));
+    let on_edge = coverage(20, 15).expect("fragmento sobre la arista");
+    assert!((on_edge - 0.5).abs() < 0.01, "{}", on_edge);
+    // El centro de la columna 21 queda un píxel entero afuera, más que el borde
+    assert_eq!(coverage(21, 15), None);
+
+    // Ningún fragmento queda más de `AA_FRINGE` a la derecha de la arista
+    assert!(fragments.iter().all(|fragment| fragment.position.x + 0.5 <= 20.5 + AA_FRINGE));
+    assert!(fragments.iter().all(|fragment| fragment.coverage > 0.0 && fragment.coverage <= 1.0));
+}
@@ -0,0 +1,48 @@
+// Triangulación de caras (`Obj::load`): los modelos de assets/ con caras de
+// cuatro o más lados se parten en abanico sin perder superficie, y las caras
+// degeneradas y las líneas no dejan triángulos.
+//
+// cargo test --test triangulation
+
+use nalgebra_glm::Vec3;
+
+use shaders_lab::assets::Assets;
+use shaders_lab::obj::Obj;
+use shaders_lab::vertex::Vertex;
+
+fn load(model: &str) -> Vec<Vertex> {
+    let path = Assets::locate(None).path(model);
+    Obj::load(&path).unwrap_or_else(|error| panic!("{}", error)).get_vertex_array()
+}
+
+// Normal geométrica (sin normalizar, su largo es el doble del área)
+fn cross(triangle: &[Vertex]) -> Vec3 {
+    (triangle[1].position - triangle[0].position).cross(&(triangle[2].position - triangle[0].position))
+}
+
+#[test]
+fn quad_cube_has_two_triangles_per_face() {
+    let vertices = load("models/cubo.obj");
+    assert_eq!(vertices.len() / 3, 12);
+
+    let mut area = 0.0;
+    for triangle in vertices.chunks_exact(3) {
+        // Cada triángulo queda en su cara y con el mismo giro que ella
+        let normal = cross(triangle);
+        assert!(triangle.iter().all(|vertex| vertex.normal == triangle[0].normal));
+        assert!(normal.normalize().dot(&triangle[0].normal) > 0.999, "{:?}", normal);
+        area += normal.norm() / 2.0;
+    }
+    assert!((area - 24.0).abs() < 1e-4, "{}", area);
+}
+
+#[test]
+fn mixed_faces_fan_into_n_minus_two_triangles() {
+    // 1 + 2 + 3 + 4: la cara con un vértice repetido y la línea no cuentan
+    let vertices = load("models/caras_mixtas.obj");
+    assert_eq!(vertices.len() / 3, 10);
+
+    let area: f32 = vertices.chunks_exact(3).map(|triangle| cross(triangle).norm() / 2.0).sum();
+    assert!((area - 5.55).abs() < 1e-4, "{}", area);
+    assert!(vertices.chunks_exact(3).all(|triangle| cross(triangle).z > 0.0));
+}
@@ -0,0 +1,128 @@
+// Vuelta de cámara de `--turntable` (`turntable::Turntable`): la cámara
+// recorre un círculo alrededor del cuerpo en su plano ecuatorial, a una
+// distancia proporcional a su tamaño, y todo sale del número de frame, así
+// que dibujar la vuelta dos veces da los mismos archivos.
+//
+// cargo test --test turntable
+
+use std::f32::consts::TAU;
+use std::fs;
+use std::path::PathBuf;
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{CelestialBody, create_uniforms};
+use shaders_lab::mesh::SphereMesh;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::scene::Scene;
+use shaders_lab::tonemap::{Dither, ToneMapSettings};
+use shaders_lab::turntable::{find_body, frame_file_name, render_turntable, turntable_distance, Turntable};
+use shaders_lab::visibility;
+
+const FRAMES: usize = 12;
+
+fn tilted_body(scale: f32) -> CelestialBody {
+    CelestialBody {
+        position: Vec3::new(3.0, 0.0, -2.0),
+        previous_position: Vec3::new(3.0, 0.0, -2.0),
+        scale,
+        axial_tilt: 0.4,
+        ..Default::default()
+    }
+}
+
+fn scene() -> Scene {
+    let bodies = vec![
+        CelestialBody { shader_type: PlanetType::Sun, scale: 2.0, ..Default::default() },
+        CelestialBody { shader_type: PlanetType::Mars, orbit_radius: 6.0, orbit_speed: 0.5, ..Default::default() },
+    ];
+    let camera = common::look_at(Vec3::new(0.0, 0.0, 20.0), Vec3::zeros());
+    common::scene(bodies, &SphereMesh::UvSphere, camera, create_uniforms(800, 600, 0))
+}
+
+fn out_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("shaders_lab_turntable_{}_{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn camera_circles_the_equator_at_a_distance_proportional_to_the_scale() {
+    let turntable = Turntable::new(0, FRAMES);
+    let small = tilted_body(1.0);
+    let large = tilted_body(2.5);
+    let axis = turntable.camera(&small, 0).up;
+    let first_height = (turntable.camera(&small, 0).eye - small.position).dot(&axis);
+
+    assert!((turntable_distance(&large) - 2.5 * turntable_distance(&small)).abs() < 1e-4);
+    let offsets: Vec<Vec3> = (0..FRAMES).map(|frame| {
+        let camera = turntable.camera(&small, frame);
+        assert_eq!(camera.center, small.position);
+        assert_eq!(camera.up, axis);
+        let offset = camera.eye - small.position;
+        assert!((offset.norm() - turntable_distance(&small)).abs() < 1e-4, "frame {}", frame);
+        // Siempre a la misma altura sobre el ecuador inclinado, y algo por encima
+        assert!(first_height > 0.0 && (offset.dot(&axis) - first_height).abs() < 1e-4, "frame {}", frame);
+        offset
+    }).collect();
+
+    // Pasos iguales que cierran la vuelta justo después del último frame
+    let step = TAU / FRAMES as f32;
+    for frame in 0..FRAMES {
+        let flat = |offset: &Vec3| offset - axis * offset.dot(&axis);
+        let (a, b) = (flat(&offsets[frame]), flat(&offsets[(frame + 1) % FRAMES]));
+        let angle = a.angle(&b);
+        assert!((angle - step).abs() < 1e-3, "frame {}: {} en vez de {}", frame, angle, step);
+    }
+    assert_eq!(turntable.camera(&small, FRAMES).eye, turntable.camera(&small, 0).eye);
+}
+
+#[test]
+fn frames_are_numbered_in_order() {
+    assert_eq!(frame_file_name(0), "frame_0000.png");
+    assert_eq!(frame_file_name(42), "frame_0042.png");
+    let names: Vec<String> = [3, 120, 9, 1000].into_iter().map(frame_file_name).collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(sorted, ["frame_0003.png", "frame_0009.png", "frame_0120.png", "frame_1000.png"]);
+}
+
+#[test]
+fn bodies_are_found_by_index_or_name() {
+    let scene = scene();
+    assert_eq!(find_body(&scene.bodies, "1"), Some(1));
+    assert_eq!(find_body(&scene.bodies, "marte"), Some(1));
+    assert_eq!(find_body(&scene.bodies, "Mars"), Some(1));
+    assert_eq!(find_body(&scene.bodies, "SOL"), Some(0));
+    assert_eq!(find_body(&scene.bodies, "2"), None);
+    assert_eq!(find_body(&scene.bodies, "Plutón"), None);
+}
+
+#[test]
+fn rendering_twice_writes_the_same_frames() {
+    let tone_mapping = ToneMapSettings { dither: Dither::Off, ..Default::default() };
+    let turntable = Turntable::new(1, 3);
+    let dirs = [out_dir("a"), out_dir("b")];
+    let mut runs = Vec::new();
+    for dir in &dirs {
+        let mut scene = scene();
+        visibility::isolate(&mut scene.bodies, 1);
+        let paths = render_turntable(&mut scene, &turntable, 48, 36, &tone_mapping, dir)
+            .unwrap_or_else(|error| panic!("{}", error));
+        assert!(!scene.bodies[0].visible && scene.bodies[1].visible);
+        runs.push(paths.iter().map(|path| fs::read(path).expect("falta un frame")).collect::<Vec<_>>());
+    }
+
+    let names: Vec<String> = fs::read_dir(&dirs[0]).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names.len(), 3);
+    assert_eq!(runs[0], runs[1]);
+    // La cámara gira: cada frame es distinto
+    assert_ne!(runs[0][0], runs[0][1]);
+    for dir in dirs {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
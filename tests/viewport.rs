@@ -0,0 +1,88 @@
+// Vistas en parte del framebuffer (`Scene::render_viewport`), como las de
+// la pantalla dividida: lo que se dibuja en un rectángulo debe ser igual a
+// dibujarlo solo en un framebuffer de ese tamaño, y no debe salirse de él.
+//
+// cargo test --test viewport
+
+use nalgebra_glm::Vec3;
+
+mod common;
+
+use shaders_lab::{CelestialBody, ProjectionSettings, create_uniforms};
+use shaders_lab::camera::Camera;
+use shaders_lab::framebuffer::Framebuffer;
+use shaders_lab::planet_type::PlanetType;
+use shaders_lab::scene::Scene;
+use shaders_lab::tonemap::{Dither, ToneMapSettings};
+use shaders_lab::viewport::{Viewport, DIVIDER_WIDTH};
+
+const WIDTH: usize = 161;
+const HEIGHT: usize = 90;
+const BACKGROUND: u32 = 0x000000;
+
+fn scene() -> Scene {
+    let bodies = vec![CelestialBody {
+        scale: 1.5,
+        shader_type: PlanetType::Jupiter,
+        rotation_speed: 0.0,
+        ..Default::default()
+    }];
+    let camera = common::look_at(Vec3::new(0.0, 0.0, 8.0), Vec3::zeros());
+    let mut uniforms = create_uniforms(WIDTH, HEIGHT, 0);
+    uniforms.fill_light = true;
+    common::scene(bodies, &common::model_sphere(), camera, uniforms)
+}
+
+// Cámara corrida a la derecha: el planeta cruza el borde izquierdo de la
+// vista, del lado de la otra
+fn camera() -> Camera {
+    common::look_at(Vec3::new(2.0, 0.0, 6.0), Vec3::new(2.0, 0.0, 0.0))
+}
+
+fn resolve(framebuffer: &mut Framebuffer) {
+    framebuffer.resolve(&ToneMapSettings { dither: Dither::Off, ..Default::default() });
+}
+
+#[test]
+fn split_halves_cover_the_framebuffer() {
+    let (left, right) = Viewport::split(WIDTH, HEIGHT, DIVIDER_WIDTH);
+    assert_eq!(left.x, 0);
+    assert_eq!(right.x, left.width + DIVIDER_WIDTH);
+    assert_eq!(right.x + right.width, WIDTH);
+    assert_eq!((left.height, right.height), (HEIGHT, HEIGHT));
+}
+
+#[test]
+fn viewport_matches_a_framebuffer_of_its_size() {
+    let (_, right) = Viewport::split(WIDTH, HEIGHT, DIVIDER_WIDTH);
+    let projection = ProjectionSettings::default();
+    let mut scene = scene();
+
+    let mut split = Framebuffer::new(WIDTH, HEIGHT);
+    split.set_background_color(BACKGROUND);
+    split.clear_region(&right);
+    scene.render_viewport(&mut split, &right, &camera(), &projection);
+    resolve(&mut split);
+
+    let mut alone = Framebuffer::new(right.width, right.height);
+    alone.set_background_color(BACKGROUND);
+    scene.render_viewport(&mut alone, &Viewport::full(right.width, right.height), &camera(), &projection);
+    resolve(&mut alone);
+
+    let mut drawn = 0;
+    let mut on_edge = false;
+    for y in 0..HEIGHT {
+        for x in 0..right.width {
+            let pixel = split.get_pixel(right.x + x, y);
+            assert_eq!(pixel, alone.get_pixel(x, y), "({}, {}) de la vista derecha", x, y);
+            drawn += (pixel != BACKGROUND) as usize;
+        }
+        on_edge |= alone.get_pixel(0, y) != BACKGROUND;
+        // La vista derecha no se sale hacia la izquierda ni a la línea
+        for x in 0..right.x {
+            assert_eq!(split.get_pixel(x, y), BACKGROUND, "({}, {}) quedó fuera de la vista", x, y);
+        }
+    }
+    assert!(drawn > 0, "no se dibujó nada");
+    assert!(on_edge, "el planeta no llega al borde de la vista");
+}